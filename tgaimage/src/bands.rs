@@ -0,0 +1,172 @@
+use crate::{write_pixel, TGAColor, TGAImage, TGAImageFormat};
+
+/// Mutable band of consecutive image rows
+///
+/// Bands returned by [`TGAImage::split_rows_mut`] do not overlap, so they can be
+/// processed from different threads.
+pub struct RowBandMut<'a> {
+    data: &'a mut [u8],
+    y_offset: u32,
+    width: u32,
+    height: u32,
+    bytespp: TGAImageFormat,
+}
+
+impl<'a> RowBandMut<'a> {
+    /// Image `y` coordinate of the first band row
+    pub fn y_offset(&self) -> u32 {
+        self.y_offset
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Number of rows in the band
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn bytespp(&self) -> TGAImageFormat {
+        self.bytespp
+    }
+
+    /// Set a pixel at `x` of the band row `y_local`
+    ///
+    /// Colors are stored the same way as with [`TGAImage::set`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are outside of the band.
+    pub fn set(&mut self, x: u32, y_local: u32, color: &TGAColor) {
+        assert!(x < self.width, "x {} is outside of the band", x);
+        let bytespp = self.bytespp as usize;
+        let offset = x as usize * bytespp;
+
+        write_pixel(color, &mut self.row_mut(y_local)[offset..offset + bytespp]);
+    }
+
+    /// Pixel data of the band row `y_local`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the row is outside of the band.
+    pub fn row_mut(&mut self, y_local: u32) -> &mut [u8] {
+        assert!(
+            y_local < self.height,
+            "row {} is outside of the band",
+            y_local
+        );
+        let row_length = self.width as usize * self.bytespp as usize;
+        let offset = y_local as usize * row_length;
+
+        &mut self.data[offset..offset + row_length]
+    }
+}
+
+impl TGAImage {
+    /// Split an image into at most `bands` disjoint bands of rows
+    ///
+    /// Rows are distributed evenly with the last band being shorter if needed.
+    /// Fewer bands are returned when the image has less rows than requested.
+    pub fn split_rows_mut(&mut self, bands: usize) -> Vec<RowBandMut<'_>> {
+        let rows_per_band = (self.height as usize).div_ceil(bands.max(1));
+        let row_length = self.width as usize * self.bytespp as usize;
+        let (width, bytespp) = (self.width, self.bytespp);
+
+        self.data
+            .chunks_mut(rows_per_band * row_length)
+            .enumerate()
+            .map(|(i, data)| RowBandMut {
+                y_offset: (i * rows_per_band) as u32,
+                height: (data.len() / row_length) as u32,
+                data,
+                width,
+                bytespp,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_bands {
+    use std::thread;
+
+    use crate::{ColorChannel, TGAColor, TGAImage, TGAImageFormat};
+
+    #[test]
+    fn split_rows_mut_threads() {
+        let mut image = TGAImage::try_new(7, 10, TGAImageFormat::RGB).unwrap();
+        let colors = [
+            TGAColor::new_rgb(255, 0, 0),
+            TGAColor::new_rgb(0, 255, 0),
+            TGAColor::new_rgb(0, 0, 255),
+            TGAColor::new_rgb(255, 255, 0),
+        ];
+
+        thread::scope(|scope| {
+            for (band, color) in image.split_rows_mut(4).into_iter().zip(colors.iter()) {
+                scope.spawn(move || {
+                    let mut band = band;
+
+                    for y in 0..band.height() {
+                        for x in 0..band.width() {
+                            band.set(x, y, color);
+                        }
+                    }
+                });
+            }
+        });
+
+        // 10 rows in 4 bands are split as 3, 3, 3 and 1
+        for (x, y, pixel) in image.pixels() {
+            let expected = colors[y as usize / 3];
+
+            assert_eq!(pixel[ColorChannel::R], expected.r(), "pixel ({}, {})", x, y);
+            assert_eq!(pixel[ColorChannel::G], expected.g(), "pixel ({}, {})", x, y);
+            assert_eq!(pixel[ColorChannel::B], expected.b(), "pixel ({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn split_rows_mut_layout() {
+        let mut image = TGAImage::try_new(2, 3, TGAImageFormat::Grayscale).unwrap();
+        let mut bands = image.split_rows_mut(5);
+
+        assert_eq!(bands.len(), 3);
+        assert_eq!(
+            bands.iter().map(|b| b.y_offset()).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+        bands[1].row_mut(0).copy_from_slice(&[4, 5]);
+        drop(bands);
+        assert_eq!(image.as_bytes(), &[0, 0, 4, 5, 0, 0]);
+        assert_eq!(image.split_rows_mut(0).len(), 1);
+        assert_eq!(image.split_rows_mut(2)[1].height(), 1);
+    }
+
+    #[test]
+    fn split_rows_mut_gray16_matches_set() {
+        let color = TGAColor::new_rgb(10, 200, 30);
+        let mut image = TGAImage::try_new(2, 2, TGAImageFormat::Grayscale16).unwrap();
+        let mut expected = TGAImage::try_new(2, 2, TGAImageFormat::Grayscale16).unwrap();
+
+        for mut band in image.split_rows_mut(2) {
+            band.set(1, 0, &color);
+        }
+
+        expected.set(1, 0, &color);
+        expected.set(1, 1, &color);
+
+        assert_eq!(image.as_bytes(), expected.as_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the band")]
+    fn split_rows_mut_out_of_band() {
+        let mut image = TGAImage::try_new(2, 4, TGAImageFormat::RGB).unwrap();
+        let mut bands = image.split_rows_mut(2);
+
+        bands[0].set(0, 2, &TGAColor::new_rgb(1, 1, 1));
+    }
+}
@@ -0,0 +1,132 @@
+use std::io::Write;
+
+use crate::{convert_pixel, TGAError, TGAImage, TGAImageFormat};
+
+const FILE_HEADER_SIZE: u32 = 14;
+const INFO_HEADER_SIZE: u32 = 40;
+/// 72 DPI in pixels per meter
+const PIXELS_PER_METER: u32 = 2835;
+
+impl TGAImage {
+    /// Write an image as an uncompressed bottom-up BMP file
+    ///
+    /// RGBA images are written with 32 bits per pixel, RGB and grayscale images with 24.
+    pub fn write_bmp<W: Write>(&self, out: &mut W) -> Result<(), TGAError> {
+        let bitsperpixel: u16 = if self.bytespp == TGAImageFormat::RGBA {
+            32
+        } else {
+            24
+        };
+        let pixel_size = bitsperpixel as usize / 8;
+        let row_size = (self.width as usize * pixel_size + 3) & !3;
+        let image_size = row_size * self.height as usize;
+        let data_offset = FILE_HEADER_SIZE + INFO_HEADER_SIZE;
+        let mut header = Vec::with_capacity(data_offset as usize);
+
+        header.extend_from_slice(b"BM");
+        header.extend_from_slice(&(data_offset + image_size as u32).to_le_bytes());
+        header.extend_from_slice(&[0; 4]);
+        header.extend_from_slice(&data_offset.to_le_bytes());
+        header.extend_from_slice(&INFO_HEADER_SIZE.to_le_bytes());
+        header.extend_from_slice(&(self.width as i32).to_le_bytes());
+        header.extend_from_slice(&(self.height as i32).to_le_bytes());
+        header.extend_from_slice(&1u16.to_le_bytes());
+        header.extend_from_slice(&bitsperpixel.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&(image_size as u32).to_le_bytes());
+        header.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        header.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        header.extend_from_slice(&[0; 8]);
+        out.write_all(&header)?;
+
+        let mut line = vec![0u8; row_size];
+
+        // BMP rows go from bottom to top, the same as the image rows
+        for row in self.rows() {
+            if self.bytespp.is_grayscale() {
+                line.chunks_exact_mut(pixel_size)
+                    .zip(row.chunks_exact(self.bytespp as usize))
+                    .for_each(|(bgr, v)| convert_pixel(v, bgr));
+            } else {
+                line[..row.len()].copy_from_slice(row);
+            }
+
+            out.write_all(&line)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_bmp {
+    use crate::{TGAColor, TGAImage, TGAImageFormat};
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    }
+
+    #[test]
+    fn bmp_golden_2x2() {
+        let mut image = TGAImage::try_new(2, 2, TGAImageFormat::RGB).unwrap();
+
+        image.set(0, 0, &TGAColor::new_rgb(1, 2, 3));
+        image.set(1, 1, &TGAColor::new_rgb(4, 5, 6));
+
+        let mut encoded = Vec::new();
+
+        image.write_bmp(&mut encoded).unwrap();
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            // file header
+            b'B', b'M', 70, 0, 0, 0, 0, 0, 0, 0, 54, 0, 0, 0,
+            // info header
+            40, 0, 0, 0, 2, 0, 0, 0, 2, 0, 0, 0, 1, 0, 24, 0,
+            0, 0, 0, 0, 16, 0, 0, 0, 0x13, 0x0b, 0, 0, 0x13, 0x0b, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0,
+            // bottom row, then top row, padded to 8 bytes
+            3, 2, 1, 0, 0, 0, 0, 0,
+            0, 0, 0, 6, 5, 4, 0, 0,
+        ];
+
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn bmp_row_padding() {
+        for width in 1..=4u32 {
+            for &(format, bitsperpixel) in &[
+                (TGAImageFormat::Grayscale, 24),
+                (TGAImageFormat::RGB, 24),
+                (TGAImageFormat::RGBA, 32),
+            ] {
+                let mut image = TGAImage::try_new(width, 3, format).unwrap();
+
+                image.clear_color(&TGAColor::new_rgba(0xaa, 0xaa, 0xaa, 0xaa));
+
+                let mut encoded = Vec::new();
+
+                image.write_bmp(&mut encoded).unwrap();
+
+                let row_size = ((width * bitsperpixel / 8).div_ceil(4) * 4) as usize;
+                let pixels = width as usize * bitsperpixel as usize / 8;
+
+                assert_eq!(row_size % 4, 0);
+                assert_eq!(encoded[28], bitsperpixel as u8);
+                assert_eq!(u32_at(&encoded, 2) as usize, encoded.len());
+                assert_eq!(u32_at(&encoded, 34) as usize, row_size * 3);
+
+                for row in encoded[54..].chunks_exact(row_size) {
+                    assert!(row[..pixels].iter().all(|b| *b == 0xaa));
+                    assert!(row[pixels..].iter().all(|b| *b == 0));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Named color constants
+
+use crate::TGAColor;
+
+pub const BLACK: TGAColor = TGAColor::new_rgb(0, 0, 0);
+pub const WHITE: TGAColor = TGAColor::new_rgb(255, 255, 255);
+pub const RED: TGAColor = TGAColor::new_rgb(255, 0, 0);
+pub const GREEN: TGAColor = TGAColor::new_rgb(0, 255, 0);
+pub const BLUE: TGAColor = TGAColor::new_rgb(0, 0, 255);
+pub const YELLOW: TGAColor = TGAColor::new_rgb(255, 255, 0);
+pub const CYAN: TGAColor = TGAColor::new_rgb(0, 255, 255);
+pub const MAGENTA: TGAColor = TGAColor::new_rgb(255, 0, 255);
+pub const DARK_GREEN: TGAColor = TGAColor::new_rgb(0, 128, 0);
+pub const NAVY: TGAColor = TGAColor::new_rgb(0, 0, 128);
+pub const DARK_GRAY: TGAColor = TGAColor::new_rgb(64, 64, 64);
+pub const GRAY: TGAColor = TGAColor::new_rgb(128, 128, 128);
+pub const LIGHT_GRAY: TGAColor = TGAColor::new_rgb(192, 192, 192);
+/// Fully transparent black
+pub const TRANSPARENT: TGAColor = TGAColor::new_rgba(0, 0, 0, 0);
+
+#[cfg(test)]
+mod tests_colors {
+    use crate::colors::*;
+    use crate::TGAColor;
+
+    #[test]
+    fn colors_round_trip() {
+        let colors = [
+            (BLACK, 0x0000_00ff),
+            (WHITE, 0xffff_ffff),
+            (RED, 0xff00_00ff),
+            (GREEN, 0x00ff_00ff),
+            (BLUE, 0x0000_ffff),
+            (YELLOW, 0xffff_00ff),
+            (CYAN, 0x00ff_ffff),
+            (MAGENTA, 0xff00_ffff),
+            (DARK_GREEN, 0x0080_00ff),
+            (NAVY, 0x0000_80ff),
+            (DARK_GRAY, 0x4040_40ff),
+            (GRAY, 0x8080_80ff),
+            (LIGHT_GRAY, 0xc0c0_c0ff),
+            (TRANSPARENT, 0x0000_0000),
+        ];
+
+        for (color, packed) in &colors {
+            assert_eq!(color.to_rgba_u32(), *packed);
+            assert_eq!(TGAColor::from_rgba_u32(*packed), *color);
+        }
+    }
+
+    #[test]
+    fn colors_derived() {
+        const HALF_RED: TGAColor = RED.with_alpha(128);
+
+        assert_eq!(HALF_RED.to_rgba_u32(), 0xff00_0080);
+        assert_eq!(WHITE.scaled(0.5, 1.0, 0.0).to_rgba_u32(), 0x80ff_00ff);
+        assert_eq!(GRAY.scaled(3.0, -1.0, 1.0).to_rgba_u32(), 0xff00_80ff);
+    }
+}
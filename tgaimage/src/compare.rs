@@ -0,0 +1,264 @@
+//! Pixel level comparison of images
+
+use crate::{TGAError, TGAImage, TGAImageFormat};
+
+/// Summary of differences between two images
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DiffReport {
+    /// Number of pixels exceeding the tolerated channel delta
+    pub differing_pixels: usize,
+    /// Largest per-channel absolute difference among the differing pixels
+    pub max_delta: u8,
+    /// First differing pixel in row-major order
+    pub first_difference: (u32, u32),
+}
+
+impl TGAImage {
+    /// Compare pixels of two images, `None` means the images are identical
+    ///
+    /// See [`TGAImage::diff_with_tolerance`] for images of different shapes.
+    pub fn diff(&self, other: &TGAImage) -> Option<DiffReport> {
+        self.diff_with_tolerance(other, 0)
+    }
+
+    /// Compare pixels of two images ignoring channel differences up to `max_delta`
+    ///
+    /// `other` is converted into the format of `self` before comparison. Pixels outside
+    /// of the area shared by both images are counted as differing by 255.
+    pub fn diff_with_tolerance(&self, other: &TGAImage, max_delta: u8) -> Option<DiffReport> {
+        let converted;
+        let other = if other.bytespp == self.bytespp {
+            other
+        } else {
+            converted = other.converted(self.bytespp);
+            &converted
+        };
+        let width = self.width.max(other.width);
+        let height = self.height.max(other.height);
+        let mut report: Option<DiffReport> = None;
+
+        for y in 0..height {
+            for x in 0..width {
+                let delta = if self.contains(x, y) && other.contains(x, y) {
+                    self[(x, y)]
+                        .iter()
+                        .zip(other[(x, y)].iter())
+                        .map(|(a, b)| (*a as i16 - *b as i16).unsigned_abs() as u8)
+                        .max()
+                        .unwrap_or(0)
+                } else {
+                    u8::MAX
+                };
+
+                if delta <= max_delta {
+                    continue;
+                }
+
+                let report = report.get_or_insert(DiffReport {
+                    differing_pixels: 0,
+                    max_delta: 0,
+                    first_difference: (x, y),
+                });
+
+                report.differing_pixels += 1;
+                report.max_delta = report.max_delta.max(delta);
+            }
+        }
+
+        report
+    }
+}
+
+/// Mean squared error over channels of two images
+///
+/// Images must have the same dimensions and format, except that RGB and RGBA images
+/// can be compared by their color channels ignoring alpha.
+pub fn mse(a: &TGAImage, b: &TGAImage) -> Result<f64, TGAError> {
+    if (a.width, a.height) != (b.width, b.height) {
+        return Err(TGAError::DimensionMismatch {
+            expected: (a.width, a.height),
+            got: (b.width, b.height),
+        });
+    }
+
+    let channels = match (a.bytespp, b.bytespp) {
+        (TGAImageFormat::RGB, TGAImageFormat::RGBA)
+        | (TGAImageFormat::RGBA, TGAImageFormat::RGB) => 3,
+        (x, y) if x == y => x as usize,
+        (expected, got) => return Err(TGAError::FormatMismatch { expected, got }),
+    };
+    let sum: u64 = a
+        .data
+        .chunks_exact(a.bytespp as usize)
+        .zip(b.data.chunks_exact(b.bytespp as usize))
+        .flat_map(|(pa, pb)| pa[..channels].iter().zip(pb[..channels].iter()))
+        .map(|(x, y)| {
+            let d = *x as i64 - *y as i64;
+
+            (d * d) as u64
+        })
+        .sum();
+
+    Ok(sum as f64 / (a.width as f64 * a.height as f64 * channels as f64))
+}
+
+/// Peak signal-to-noise ratio in decibels, infinite for identical images
+///
+/// See [`mse`] for the images requirements.
+pub fn psnr(a: &TGAImage, b: &TGAImage) -> Result<f64, TGAError> {
+    let mse = mse(a, b)?;
+
+    if mse == 0.0 {
+        Ok(f64::INFINITY)
+    } else {
+        Ok(10.0 * (255.0 * 255.0 / mse).log10())
+    }
+}
+
+/// Assert that the PSNR of two images is at least `min_psnr_db`
+///
+/// # Panics
+///
+/// Panics with the computed PSNR if the images are not similar enough, or if they
+/// cannot be compared.
+pub fn assert_similar(a: &TGAImage, b: &TGAImage, min_psnr_db: f64) {
+    match psnr(a, b) {
+        Ok(value) if value >= min_psnr_db => (),
+        Ok(value) => panic!(
+            "Images are not similar: PSNR {:.3} dB is below {:.3} dB",
+            value, min_psnr_db
+        ),
+        Err(e) => panic!("Images cannot be compared: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests_compare {
+    use crate::compare::{assert_similar, mse, psnr, DiffReport};
+    use crate::{TGAColor, TGAError, TGAImage, TGAImageFormat};
+
+    fn gradient(width: u32, height: u32) -> TGAImage {
+        let mut image = TGAImage::try_new(width, height, TGAImageFormat::RGB).unwrap();
+
+        image.for_each_pixel(|x, y, pixel| pixel.iter_mut().for_each(|c| *c = (x ^ y) as u8));
+
+        image
+    }
+
+    #[test]
+    fn diff_identical() {
+        let image = gradient(64, 64);
+
+        assert_eq!(image.diff(&gradient(64, 64)), None);
+        assert_eq!(image, gradient(64, 64));
+    }
+
+    #[test]
+    fn diff_single_pixel() {
+        let image = gradient(512, 512);
+        let mut other = gradient(512, 512);
+
+        other.pixel_mut(300, 200).unwrap()[1] ^= 0x10;
+
+        assert_ne!(image, other);
+        assert_eq!(
+            image.diff(&other),
+            Some(DiffReport {
+                differing_pixels: 1,
+                max_delta: 16,
+                first_difference: (300, 200),
+            })
+        );
+    }
+
+    #[test]
+    fn diff_with_tolerance() {
+        let image = gradient(8, 8);
+        let mut other = gradient(8, 8);
+
+        other.pixel_mut(1, 2).unwrap()[0] += 1;
+        other.pixel_mut(3, 4).unwrap()[2] -= 1;
+
+        assert_eq!(image.diff_with_tolerance(&other, 1), None);
+        assert_eq!(image.diff(&other).unwrap().differing_pixels, 2);
+        assert_eq!(image.diff(&other).unwrap().first_difference, (1, 2));
+    }
+
+    #[test]
+    fn diff_mismatched_shapes() {
+        let mut image = TGAImage::try_new(2, 2, TGAImageFormat::RGB).unwrap();
+
+        image.set(0, 0, &TGAColor::new_rgb(10, 20, 30));
+
+        let report = image
+            .diff(&TGAImage::try_new(3, 2, TGAImageFormat::RGB).unwrap())
+            .unwrap();
+
+        assert_eq!(report.differing_pixels, 3);
+        assert_eq!(report.max_delta, 255);
+        assert_eq!(report.first_difference, (0, 0));
+        assert_eq!(
+            image.diff(&image.convert(TGAImageFormat::RGBA).unwrap()),
+            None
+        );
+        assert_ne!(image, image.convert(TGAImageFormat::RGBA).unwrap());
+    }
+
+    #[test]
+    fn psnr_identical() {
+        let image = gradient(16, 16);
+
+        assert_eq!(mse(&image, &image).unwrap(), 0.0);
+        assert_eq!(psnr(&image, &image).unwrap(), f64::INFINITY);
+        assert_eq!(
+            psnr(&image, &image.convert(TGAImageFormat::RGBA).unwrap()).unwrap(),
+            f64::INFINITY
+        );
+        assert_similar(&image, &image, 100.0);
+    }
+
+    #[test]
+    fn psnr_flipped_fraction() {
+        let black = TGAImage::try_new(16, 16, TGAImageFormat::RGB).unwrap();
+
+        for &(flipped, expected) in &[(64u32, 6.0206), (16, 12.0412), (1, 24.0824)] {
+            let mut image = black.convert(TGAImageFormat::RGBA).unwrap();
+
+            for i in 0..flipped {
+                image.set(i % 16, i / 16, &TGAColor::new_rgba(255, 255, 255, 0));
+            }
+
+            let value = psnr(&black, &image).unwrap();
+
+            assert!((value - expected).abs() < 1e-3, "{} != {}", value, expected);
+            assert!((mse(&black, &image).unwrap() - 65025.0 * flipped as f64 / 256.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn mse_mismatch() {
+        let image = gradient(4, 4);
+
+        assert!(matches!(
+            mse(&image, &gradient(4, 5)),
+            Err(TGAError::DimensionMismatch { .. })
+        ));
+        assert!(matches!(
+            psnr(&image, &image.convert(TGAImageFormat::Grayscale).unwrap()),
+            Err(TGAError::FormatMismatch {
+                expected: TGAImageFormat::RGB,
+                got: TGAImageFormat::Grayscale
+            })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "PSNR 6.021 dB is below 30.000 dB")]
+    fn assert_similar_reports_psnr() {
+        let black = TGAImage::try_new(2, 2, TGAImageFormat::RGB).unwrap();
+        let mut image = TGAImage::try_new(2, 2, TGAImageFormat::RGB).unwrap();
+
+        image.set(0, 0, &TGAColor::new_rgb(255, 255, 255));
+        assert_similar(&black, &image, 30.0);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{TGAImage, TGAImageFormat};
+
+/// Quantization method of [`TGAImage::to_grayscale_dithered`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DitherMethod {
+    /// Round luma to the nearest level
+    None,
+    /// Diffuse the rounding error into neighbor pixels with Floyd-Steinberg weights
+    FloydSteinberg,
+}
+
+impl TGAImage {
+    /// Convert an image into grayscale quantizing Rec.601 luma with a dithering method
+    pub fn to_grayscale_dithered(&self, method: DitherMethod) -> TGAImage {
+        let width = self.width as usize;
+        let mut luma: Vec<f32> = self
+            .data
+            .chunks_exact(self.bytespp as usize)
+            .map(|pixel| match *pixel {
+                [v] => v as f32,
+                [lo, hi] => u16::from_le_bytes([lo, hi]) as f32 / 257.0,
+                [b, g, r, ..] => 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32,
+                _ => 0.0,
+            })
+            .collect();
+        let mut image = TGAImage::blank(self.width, self.height, TGAImageFormat::Grayscale);
+
+        image.copy_properties(self);
+
+        for i in 0..luma.len() {
+            let value = luma[i].round().clamp(0.0, 255.0);
+
+            image.data[i] = value as u8;
+
+            if method == DitherMethod::None {
+                continue;
+            }
+
+            let error = luma[i] - value;
+            let x = i % width;
+            let mut spread = |offset: usize, weight: f32| {
+                if let Some(v) = luma.get_mut(i + offset) {
+                    *v += error * weight;
+                }
+            };
+
+            if x + 1 < width {
+                spread(1, 7.0 / 16.0);
+                spread(width + 1, 1.0 / 16.0);
+            }
+
+            if x > 0 {
+                spread(width - 1, 3.0 / 16.0);
+            }
+
+            spread(width, 5.0 / 16.0);
+        }
+
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests_dither {
+    use crate::{DitherMethod, Origin, TGAImage, TGAImageFormat};
+
+    fn smooth_gradient() -> TGAImage {
+        let mut image = TGAImage::try_new(64, 16, TGAImageFormat::RGB).unwrap();
+
+        image.for_each_pixel(|x, y, pixel| {
+            pixel.copy_from_slice(&[(x / 3) as u8, (x / 4 + y) as u8, (x / 5) as u8]);
+        });
+
+        image
+    }
+
+    fn mean(image: &TGAImage) -> f32 {
+        image.as_bytes().iter().map(|v| *v as f32).sum::<f32>() / image.as_bytes().len() as f32
+    }
+
+    #[test]
+    fn dither_preserves_mean() {
+        let image = smooth_gradient();
+        let exact: f32 = image
+            .as_bytes()
+            .chunks_exact(3)
+            .map(|p| 0.299 * p[2] as f32 + 0.587 * p[1] as f32 + 0.114 * p[0] as f32)
+            .sum::<f32>()
+            / (64.0 * 16.0);
+        let plain = image.to_grayscale_dithered(DitherMethod::None);
+        let dithered = image.to_grayscale_dithered(DitherMethod::FloydSteinberg);
+
+        assert_eq!(dithered.get_bytespp(), TGAImageFormat::Grayscale);
+        assert!((mean(&dithered) - exact).abs() <= 1.0);
+        assert_ne!(plain, dithered);
+        assert!(dithered
+            .encode_to_vec_with_origin(Origin::BottomLeft, true)
+            .is_ok());
+    }
+
+    #[test]
+    fn dither_none_rounds_luma() {
+        let image = smooth_gradient();
+        let plain = image.to_grayscale_dithered(DitherMethod::None);
+
+        for (x, y, color) in image.pixels() {
+            let luma =
+                0.299 * color.r() as f32 + 0.587 * color.g() as f32 + 0.114 * color.b() as f32;
+
+            assert_eq!(plain[(x, y)][0], luma.round() as u8);
+        }
+    }
+}
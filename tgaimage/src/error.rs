@@ -0,0 +1,172 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use crate::TGAImageFormat;
+
+/// Errors reported while decoding or encoding TGA images
+#[derive(Debug)]
+pub enum TGAError {
+    /// Underlying I/O failure
+    Io(io::Error),
+    /// Header contains values that cannot describe a valid image
+    InvalidHeader { reason: &'static str },
+    /// Image type (datatype code) is not supported by the decoder
+    UnsupportedImageType(u8),
+    /// Bits per pixel value is not supported by the decoder
+    UnsupportedBitDepth(u8),
+    /// Input ended before all expected bytes were read
+    TruncatedData { expected: usize, got: usize },
+    /// RLE packet describes more pixels than the image holds
+    RleOverrun,
+    /// Color-mapped pixel refers to an entry outside of the color map
+    ColorMapIndexOutOfRange(u16),
+    /// Pixel buffer length does not match the image dimensions and format
+    InvalidBufferSize { expected: usize, got: usize },
+    /// Images taking part in an operation have different dimensions
+    DimensionMismatch {
+        expected: (u32, u32),
+        got: (u32, u32),
+    },
+    /// Requested rectangle does not cover any pixel of an image
+    InvalidRegion {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    /// Pixel buffer of an image would exceed the allowed size
+    ImageTooLarge {
+        width: u32,
+        height: u32,
+        limit: usize,
+    },
+    /// Images taking part in an operation have incompatible formats
+    FormatMismatch {
+        expected: TGAImageFormat,
+        got: TGAImageFormat,
+    },
+    /// Number of rows written to a stream does not match the image height
+    RowCountMismatch { expected: u32, got: u32 },
+    /// A color must have from 1 to 4 bytes
+    InvalidColorLength(usize),
+}
+
+impl Display for TGAError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TGAError::Io(e) => write!(f, "I/O error: {}", e),
+            TGAError::InvalidHeader { reason } => write!(f, "Invalid TGA header: {}", reason),
+            TGAError::UnsupportedImageType(t) => write!(f, "Unsupported TGA image type {}", t),
+            TGAError::UnsupportedBitDepth(bpp) => {
+                write!(f, "Unsupported TGA bit depth {}", bpp)
+            }
+            TGAError::TruncatedData { expected, got } => write!(
+                f,
+                "Truncated TGA data: expected {} bytes, got {}",
+                expected, got
+            ),
+            TGAError::RleOverrun => write!(f, "RLE packet overruns the image buffer"),
+            TGAError::ColorMapIndexOutOfRange(index) => {
+                write!(f, "Color map index {} is out of range", index)
+            }
+            TGAError::InvalidBufferSize { expected, got } => write!(
+                f,
+                "Invalid pixel buffer size: expected {} bytes, got {}",
+                expected, got
+            ),
+            TGAError::DimensionMismatch { expected, got } => write!(
+                f,
+                "Image dimensions mismatch: expected {}x{}, got {}x{}",
+                expected.0, expected.1, got.0, got.1
+            ),
+            TGAError::InvalidRegion {
+                x,
+                y,
+                width,
+                height,
+            } => write!(
+                f,
+                "Region {}x{} at ({}, {}) does not cover any pixel",
+                width, height, x, y
+            ),
+            TGAError::ImageTooLarge {
+                width,
+                height,
+                limit,
+            } => write!(
+                f,
+                "Image {}x{} exceeds the limit of {} bytes",
+                width, height, limit
+            ),
+            TGAError::FormatMismatch { expected, got } => write!(
+                f,
+                "Image formats mismatch: expected {:?}, got {:?}",
+                expected, got
+            ),
+            TGAError::RowCountMismatch { expected, got } => {
+                write!(f, "Expected {} image rows, got {}", expected, got)
+            }
+            TGAError::InvalidColorLength(len) => {
+                write!(f, "Color must have 1 to 4 bytes, got {}", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TGAError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TGAError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TGAError {
+    fn from(e: io::Error) -> Self {
+        TGAError::Io(e)
+    }
+}
+
+/// Pixel coordinates that lie outside of an image
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OutOfBounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Display for OutOfBounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Pixel ({}, {}) is outside of the {}x{} image",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+impl std::error::Error for OutOfBounds {}
+
+/// Error returned when a color cannot be parsed from a hex string
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ParseColorError {
+    /// Number of hex digits is neither 6 nor 8
+    InvalidLength(usize),
+    /// String contains a character that is not a hex digit
+    InvalidDigit,
+}
+
+impl Display for ParseColorError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseColorError::InvalidLength(len) => {
+                write!(f, "Expected 6 or 8 hex digits, got {}", len)
+            }
+            ParseColorError::InvalidDigit => write!(f, "Invalid hex digit in color"),
+        }
+    }
+}
+
+impl std::error::Error for ParseColorError {}
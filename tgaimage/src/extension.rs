@@ -0,0 +1,347 @@
+//! TGA 2.0 extension area support
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Size of the extension area defined by the TGA 2.0 specification
+pub(crate) const EXTENSION_AREA_SIZE: usize = 495;
+/// Size of the file footer including developer and extension area offsets
+pub(crate) const FOOTER_SIZE: usize = 26;
+/// Signature closing a TGA 2.0 file
+pub(crate) const FOOTER_SIGNATURE: [u8; 18] = *b"TRUEVISION-XFILE.\0";
+
+const AUTHOR_NAME: (usize, usize) = (2, 41);
+const AUTHOR_COMMENTS: (usize, usize) = (43, 324);
+const TIMESTAMP: usize = 367;
+const SOFTWARE_ID: (usize, usize) = (426, 41);
+const COMMENT_LINE_SIZE: usize = 81;
+
+/// Date and time an image was saved
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct TGATimestamp {
+    pub year: u16,
+    /// Month in `1..=12`
+    pub month: u16,
+    /// Day in `1..=31`
+    pub day: u16,
+    /// Hour in `0..=23`
+    pub hour: u16,
+    pub minute: u16,
+    pub second: u16,
+}
+
+/// Image metadata stored in the TGA 2.0 extension area
+///
+/// Strings are limited by the format: author name and software ID to 40 bytes,
+/// comments to 4 lines of 80 bytes. Longer values are truncated on write.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TGAMetadata {
+    pub author_name: String,
+    /// Author comments, lines are separated with `'\n'`
+    pub comments: String,
+    pub software_id: String,
+    pub timestamp: Option<TGATimestamp>,
+}
+
+impl TGAMetadata {
+    /// Serialize metadata into an extension area
+    pub(crate) fn to_bytes(&self) -> [u8; EXTENSION_AREA_SIZE] {
+        let mut bytes = [0u8; EXTENSION_AREA_SIZE];
+
+        bytes[..2].copy_from_slice(&(EXTENSION_AREA_SIZE as u16).to_le_bytes());
+        put_str(field_mut(&mut bytes, AUTHOR_NAME), &self.author_name);
+        put_str(field_mut(&mut bytes, SOFTWARE_ID), &self.software_id);
+
+        for (line, text) in field_mut(&mut bytes, AUTHOR_COMMENTS)
+            .chunks_exact_mut(COMMENT_LINE_SIZE)
+            .zip(self.comments.lines())
+        {
+            put_str(line, text);
+        }
+
+        if let Some(ts) = self.timestamp {
+            let values = [ts.month, ts.day, ts.year, ts.hour, ts.minute, ts.second];
+
+            for (i, value) in values.iter().enumerate() {
+                let offset = TIMESTAMP + i * 2;
+
+                bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    /// Parse an extension area, `None` if the size field does not match the format
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < EXTENSION_AREA_SIZE
+            || u16::from_le_bytes([bytes[0], bytes[1]]) as usize != EXTENSION_AREA_SIZE
+        {
+            return None;
+        }
+
+        let value = |i: usize| {
+            let offset = TIMESTAMP + i * 2;
+
+            u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+        };
+        let timestamp = TGATimestamp {
+            month: value(0),
+            day: value(1),
+            year: value(2),
+            hour: value(3),
+            minute: value(4),
+            second: value(5),
+        };
+        let comments: Vec<_> = field(bytes, AUTHOR_COMMENTS)
+            .chunks_exact(COMMENT_LINE_SIZE)
+            .map(get_str)
+            .collect();
+        let comments_len = comments
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(0, |i| i + 1);
+
+        Some(TGAMetadata {
+            author_name: get_str(field(bytes, AUTHOR_NAME)),
+            comments: comments[..comments_len].join("\n"),
+            software_id: get_str(field(bytes, SOFTWARE_ID)),
+            timestamp: if timestamp == TGATimestamp::default() {
+                None
+            } else {
+                Some(timestamp)
+            },
+        })
+    }
+
+    /// Find the extension area through the footer at the end of a seekable stream
+    ///
+    /// `start` is the stream position of the file start, which offsets stored in the
+    /// footer are relative to, and `data_end` the position right after the pixel data.
+    pub(crate) fn read_from_end<R: Read + Seek>(
+        reader: &mut R,
+        start: u64,
+        data_end: u64,
+    ) -> io::Result<Option<Self>> {
+        let end = reader.seek(SeekFrom::End(0))?;
+        let footer = match end.checked_sub(FOOTER_SIZE as u64) {
+            Some(footer) if footer >= data_end => footer,
+            _ => return Ok(None),
+        };
+        let mut footer_bytes = [0u8; FOOTER_SIZE];
+
+        reader.seek(SeekFrom::Start(footer))?;
+        reader.read_exact(&mut footer_bytes)?;
+
+        if footer_bytes[8..] != FOOTER_SIGNATURE {
+            return Ok(None);
+        }
+
+        let offset = start
+            + u32::from_le_bytes([
+                footer_bytes[0],
+                footer_bytes[1],
+                footer_bytes[2],
+                footer_bytes[3],
+            ]) as u64;
+
+        if offset < data_end || offset + EXTENSION_AREA_SIZE as u64 > footer {
+            return Ok(None);
+        }
+
+        let mut bytes = [0u8; EXTENSION_AREA_SIZE];
+
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut bytes)?;
+
+        Ok(TGAMetadata::from_bytes(&bytes))
+    }
+}
+
+fn field(bytes: &[u8], (offset, size): (usize, usize)) -> &[u8] {
+    &bytes[offset..offset + size]
+}
+
+fn field_mut(bytes: &mut [u8], (offset, size): (usize, usize)) -> &mut [u8] {
+    &mut bytes[offset..offset + size]
+}
+
+/// Copy a string into a null-terminated field truncating it on a char boundary
+fn put_str(field: &mut [u8], s: &str) {
+    let mut len = s.len().min(field.len() - 1);
+
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+
+    field[..len].copy_from_slice(&s.as_bytes()[..len]);
+}
+
+fn get_str(field: &[u8]) -> String {
+    let len = field.iter().position(|c| *c == 0).unwrap_or(field.len());
+
+    String::from_utf8_lossy(&field[..len]).into_owned()
+}
+
+/// Writer keeping track of the number of bytes written
+pub(crate) struct CountingWriter<W> {
+    inner: W,
+    pub(crate) count: usize,
+}
+
+impl<W: Write> CountingWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        CountingWriter { inner, count: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+
+        self.count += n;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests_extension {
+    use std::io::Cursor;
+
+    use crate::extension::{EXTENSION_AREA_SIZE, FOOTER_SIZE};
+    use crate::{Origin, TGAImage, TGAImageFormat, TGAMetadata, TGATimestamp};
+
+    fn metadata() -> TGAMetadata {
+        TGAMetadata {
+            author_name: "Jane Doe".to_string(),
+            comments: "first line\nsecond line".to_string(),
+            software_id: "tinyrenderer".to_string(),
+            timestamp: Some(TGATimestamp {
+                year: 2021,
+                month: 3,
+                day: 14,
+                hour: 15,
+                minute: 9,
+                second: 26,
+            }),
+        }
+    }
+
+    #[test]
+    fn extension_area_round_trip() {
+        for &rle in &[false, true] {
+            let mut image = TGAImage::try_new(3, 2, TGAImageFormat::RGB).unwrap();
+
+            image.set_metadata(Some(metadata()));
+
+            let encoded = image
+                .encode_to_vec_with_origin(Origin::BottomLeft, rle)
+                .unwrap();
+            let footer = encoded.len() - FOOTER_SIZE;
+            let offset = u32::from_le_bytes([
+                encoded[footer],
+                encoded[footer + 1],
+                encoded[footer + 2],
+                encoded[footer + 3],
+            ]) as usize;
+
+            assert_eq!(offset + EXTENSION_AREA_SIZE, footer);
+            assert_eq!(&encoded[offset..offset + 2], &[0xef, 0x01]);
+
+            let decoded = TGAImage::read_from_seekable(&mut Cursor::new(encoded)).unwrap();
+
+            assert_eq!(decoded.metadata(), Some(&metadata()));
+        }
+    }
+
+    #[test]
+    fn extension_area_stream_position() {
+        let mut image = TGAImage::try_new(3, 2, TGAImageFormat::RGB).unwrap();
+
+        image.set_metadata(Some(metadata()));
+
+        let mut encoded = image
+            .encode_to_vec_with_origin(Origin::BottomLeft, false)
+            .unwrap();
+        let data_end = 18 + 3 * 2 * 3;
+
+        // a plain reader stops after the pixel data and leaves the rest unread
+        let mut reader = Cursor::new(&encoded);
+        let decoded = TGAImage::read_from(&mut reader).unwrap();
+
+        assert_eq!(reader.position(), data_end);
+        assert_eq!(decoded.metadata(), None);
+
+        let mut reader = Cursor::new(&encoded);
+        let decoded = TGAImage::read_from_seekable(&mut reader).unwrap();
+
+        assert_eq!(reader.position(), data_end);
+        assert_eq!(decoded.metadata(), Some(&metadata()));
+
+        // the footer is not at the end of the stream anymore
+        encoded.extend_from_slice(b"trailing data");
+
+        let decoded = TGAImage::read_from_seekable(&mut Cursor::new(&encoded)).unwrap();
+
+        assert_eq!(decoded.metadata(), None);
+    }
+
+    #[test]
+    fn extension_area_truncated_strings() {
+        let long = TGAMetadata {
+            author_name: "a".repeat(50),
+            comments: format!("{}\n\n{}\n4\n5", "b".repeat(90), "c"),
+            software_id: "é".repeat(25),
+            timestamp: None,
+        };
+        let decoded = TGAMetadata::from_bytes(&long.to_bytes()).unwrap();
+
+        assert_eq!(decoded.author_name, "a".repeat(40));
+        assert_eq!(decoded.comments, format!("{}\n\nc\n4", "b".repeat(80)));
+        assert_eq!(decoded.software_id, "é".repeat(20));
+        assert_eq!(decoded.timestamp, None);
+    }
+
+    #[test]
+    fn extension_area_absent() {
+        let image = TGAImage::try_new(3, 2, TGAImageFormat::Grayscale).unwrap();
+        let encoded = image
+            .encode_to_vec_with_origin(Origin::BottomLeft, false)
+            .unwrap();
+
+        assert_eq!(&encoded[encoded.len() - 22..encoded.len() - 18], &[0; 4]);
+
+        let decoded = TGAImage::read_from_seekable(&mut Cursor::new(&encoded)).unwrap();
+
+        assert_eq!(decoded.metadata(), None);
+
+        // files without a footer are read as before
+        let without_footer = &encoded[..encoded.len() - FOOTER_SIZE];
+        let decoded = TGAImage::read_from_seekable(&mut Cursor::new(without_footer)).unwrap();
+
+        assert_eq!(decoded.metadata(), None);
+        assert_eq!(decoded, image);
+    }
+
+    #[test]
+    fn extension_area_invalid_offset() {
+        let mut image = TGAImage::try_new(2, 2, TGAImageFormat::RGB).unwrap();
+
+        image.set_metadata(Some(metadata()));
+
+        let mut encoded = image
+            .encode_to_vec_with_origin(Origin::BottomLeft, false)
+            .unwrap();
+        let footer = encoded.len() - FOOTER_SIZE;
+
+        encoded[footer..footer + 4].copy_from_slice(&3u32.to_le_bytes());
+
+        let decoded = TGAImage::read_from_seekable(&mut Cursor::new(encoded)).unwrap();
+
+        assert_eq!(decoded.metadata(), None);
+    }
+}
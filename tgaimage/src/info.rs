@@ -0,0 +1,207 @@
+use std::convert::TryFrom;
+use std::io::Read;
+
+use crate::{TGAError, TGAHeader, TGAImage, TGAImageFormat, TGAImageType};
+
+/// Image properties available from a TGA header
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TGAInfo {
+    pub width: u32,
+    pub height: u32,
+    /// Pixel format of the decoded image
+    pub format: TGAImageFormat,
+    pub image_type: TGAImageType,
+    /// Rows are stored from top to bottom
+    pub top_to_bottom: bool,
+    /// Pixels are stored from right to left
+    pub right_to_left: bool,
+    pub has_colormap: bool,
+}
+
+/// Read image properties without decoding pixel data
+///
+/// Only the header and the image ID field are consumed from the reader.
+pub fn probe<R: Read>(reader: &mut R) -> Result<TGAInfo, TGAError> {
+    let mut header_bytes = [0u8; TGAHeader::SIZE];
+
+    TGAImage::read_data(reader, &mut header_bytes)?;
+
+    let header = TGAHeader::from_bytes(&header_bytes);
+
+    if header.width == 0 || header.height == 0 {
+        return Err(TGAError::InvalidHeader {
+            reason: "image dimensions must be non-zero",
+        });
+    }
+
+    let mut image_id = [0u8; u8::MAX as usize];
+
+    TGAImage::read_data(reader, &mut image_id[..header.idlength as usize])?;
+
+    let image_type = TGAImageType::from_u8(header.datatypecode)
+        .ok_or(TGAError::UnsupportedImageType(header.datatypecode))?;
+    let format = match (image_type, header.bitsperpixel) {
+        (TGAImageType::UncompressedColor, _) | (TGAImageType::RLEColor, _) => {
+            match header.colormapdepth {
+                15 | 16 | 24 => TGAImageFormat::RGB,
+                32 => TGAImageFormat::RGBA,
+                depth => return Err(TGAError::UnsupportedBitDepth(depth)),
+            }
+        }
+        (TGAImageType::UncompressedTrueColor, 15) | (TGAImageType::RLETrueColor, 15) => {
+            TGAImageFormat::RGB
+        }
+        (TGAImageType::UncompressedTrueColor, 16) | (TGAImageType::RLETrueColor, 16) => {
+            if header.imagedescriptor & 0x0f != 0 {
+                TGAImageFormat::RGBA
+            } else {
+                TGAImageFormat::RGB
+            }
+        }
+        (TGAImageType::Unknown, _) => {
+            return Err(TGAError::UnsupportedImageType(header.datatypecode))
+        }
+        (_, bitsperpixel) => match TGAImageFormat::try_from(bitsperpixel >> 3) {
+            Ok(TGAImageFormat::Unknown) | Err(_) => {
+                return Err(TGAError::UnsupportedBitDepth(bitsperpixel))
+            }
+            Ok(_) if bitsperpixel & 0b111 != 0 => {
+                return Err(TGAError::UnsupportedBitDepth(bitsperpixel))
+            }
+            Ok(format) => format,
+        },
+    };
+
+    Ok(TGAInfo {
+        width: header.width as u32,
+        height: header.height as u32,
+        format,
+        image_type,
+        top_to_bottom: header.imagedescriptor & 0b10_0000 != 0,
+        right_to_left: header.imagedescriptor & 0b1_0000 != 0,
+        has_colormap: header.colormaptype == 1,
+    })
+}
+
+impl TGAImage {
+    /// Properties of the image as it would be written without RLE compression
+    pub fn info(&self) -> TGAInfo {
+        let image_type = if self.bytespp.is_grayscale() {
+            TGAImageType::UncompressedBW
+        } else {
+            TGAImageType::UncompressedTrueColor
+        };
+
+        TGAInfo {
+            width: self.width,
+            height: self.height,
+            format: self.bytespp,
+            image_type,
+            top_to_bottom: false,
+            right_to_left: false,
+            has_colormap: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_info {
+    use std::io::Cursor;
+
+    use crate::{probe, Origin, TGAError, TGAImage, TGAImageFormat, TGAImageType};
+
+    #[test]
+    fn probe_reads_header_only() {
+        for &(format, rle, image_type) in &[
+            (
+                TGAImageFormat::RGB,
+                false,
+                TGAImageType::UncompressedTrueColor,
+            ),
+            (TGAImageFormat::RGBA, true, TGAImageType::RLETrueColor),
+            (TGAImageFormat::Grayscale, true, TGAImageType::RLEBW),
+        ] {
+            let mut image = TGAImage::try_new(13, 7, format).unwrap();
+
+            image.set_image_id(Some(b"probe".to_vec())).unwrap();
+
+            let encoded = image
+                .encode_to_vec_with_origin(Origin::TopLeft, rle)
+                .unwrap();
+            let mut reader = Cursor::new(&encoded);
+            let info = probe(&mut reader).unwrap();
+
+            assert_eq!((info.width, info.height), (13, 7));
+            assert_eq!(info.format, format);
+            assert_eq!(info.image_type, image_type);
+            assert!(info.top_to_bottom);
+            assert!(!info.right_to_left);
+            assert!(!info.has_colormap);
+            assert_eq!(reader.position(), 18 + 5);
+            assert_eq!(
+                TGAImage::read_from(&mut Cursor::new(&encoded))
+                    .unwrap()
+                    .get_bytespp(),
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn probe_color_mapped() {
+        #[rustfmt::skip]
+        let header = [
+            0, 1, 1, 0, 0, 2, 0, 32, 0, 0, 0, 0, 4, 0, 2, 0, 8, 0,
+        ];
+        let info = probe(&mut Cursor::new(&header)).unwrap();
+
+        assert_eq!(info.format, TGAImageFormat::RGBA);
+        assert_eq!(info.image_type, TGAImageType::UncompressedColor);
+        assert!(info.has_colormap);
+        assert!(!info.top_to_bottom);
+    }
+
+    #[test]
+    fn probe_malformed() {
+        let image = TGAImage::try_new(2, 2, TGAImageFormat::RGB).unwrap();
+        let mut encoded = image
+            .encode_to_vec_with_origin(Origin::BottomLeft, false)
+            .unwrap();
+
+        assert!(matches!(
+            probe(&mut Cursor::new(&encoded[..10])),
+            Err(TGAError::TruncatedData {
+                expected: 18,
+                got: 10
+            })
+        ));
+
+        encoded[16] = 20;
+        assert!(matches!(
+            probe(&mut Cursor::new(&encoded)),
+            Err(TGAError::UnsupportedBitDepth(20))
+        ));
+
+        encoded[2] = 7;
+        assert!(matches!(
+            probe(&mut Cursor::new(&encoded)),
+            Err(TGAError::UnsupportedImageType(7))
+        ));
+
+        encoded[12] = 0;
+        assert!(matches!(
+            probe(&mut Cursor::new(&encoded)),
+            Err(TGAError::InvalidHeader { .. })
+        ));
+    }
+
+    #[test]
+    fn info_matches_probe() {
+        let image = TGAImage::try_new(3, 5, TGAImageFormat::Grayscale).unwrap();
+        let encoded = image
+            .encode_to_vec_with_origin(Origin::BottomLeft, false)
+            .unwrap();
+
+        assert_eq!(probe(&mut Cursor::new(&encoded)).unwrap(), image.info());
+    }
+}
@@ -1,13 +1,36 @@
 use std::convert::TryFrom;
-use std::io::{Read, Write};
-use std::mem::size_of;
-use std::ops::{Index, IndexMut, Mul};
-use std::ptr;
-use std::ptr::{slice_from_raw_parts, slice_from_raw_parts_mut};
+use std::fmt::{self, Debug, Formatter};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::{Add, Index, IndexMut, Mul};
+use std::str::FromStr;
+
+pub use crate::bands::RowBandMut;
+pub use crate::dither::DitherMethod;
+pub use crate::error::{OutOfBounds, ParseColorError, TGAError};
+use crate::extension::{CountingWriter, FOOTER_SIGNATURE};
+pub use crate::extension::{TGAMetadata, TGATimestamp};
+pub use crate::info::{probe, TGAInfo};
+pub use crate::mipmap::MipmapSampler;
+pub use crate::sampler::{Sampler, TextureFilter, WrapMode};
+pub use crate::stats::ChannelHistograms;
+pub use crate::stream::TGAStreamWriter;
+
+mod bands;
+mod bmp;
+pub mod colors;
+pub mod compare;
+mod dither;
+mod error;
+mod extension;
+mod info;
+mod mipmap;
+mod ppm;
+mod sampler;
+mod stats;
+mod stream;
 
 /// TGA image header
-#[derive(Default)]
-#[repr(packed)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 pub struct TGAHeader {
     pub idlength: u8,
     pub colormaptype: u8,
@@ -23,6 +46,54 @@ pub struct TGAHeader {
     pub imagedescriptor: u8,
 }
 
+impl TGAHeader {
+    /// Size of an encoded TGA header in bytes
+    pub const SIZE: usize = 18;
+
+    /// Decode a header from its on-disk little-endian representation
+    pub fn from_bytes(bytes: &[u8; TGAHeader::SIZE]) -> Self {
+        let read_u16 = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+
+        TGAHeader {
+            idlength: bytes[0],
+            colormaptype: bytes[1],
+            datatypecode: bytes[2],
+            colormaporigin: read_u16(3),
+            colormaplength: read_u16(5),
+            colormapdepth: bytes[7],
+            x_origin: read_u16(8),
+            y_origin: read_u16(10),
+            width: read_u16(12),
+            height: read_u16(14),
+            bitsperpixel: bytes[16],
+            imagedescriptor: bytes[17],
+        }
+    }
+
+    /// Encode a header into its on-disk little-endian representation
+    pub fn to_bytes(&self) -> [u8; TGAHeader::SIZE] {
+        let mut bytes = [0u8; TGAHeader::SIZE];
+        let mut write_u16 = |offset: usize, value: u16| {
+            bytes[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+        };
+
+        write_u16(3, self.colormaporigin);
+        write_u16(5, self.colormaplength);
+        write_u16(8, self.x_origin);
+        write_u16(10, self.y_origin);
+        write_u16(12, self.width);
+        write_u16(14, self.height);
+        bytes[0] = self.idlength;
+        bytes[1] = self.colormaptype;
+        bytes[2] = self.datatypecode;
+        bytes[7] = self.colormapdepth;
+        bytes[16] = self.bitsperpixel;
+        bytes[17] = self.imagedescriptor;
+
+        bytes
+    }
+}
+
 /// Color channel indexes representation of a TGA image
 #[derive(Debug, Copy, Clone)]
 pub enum ColorChannel {
@@ -36,7 +107,7 @@ pub enum ColorChannel {
     A = 3,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum TGAImageType {
     Unknown = 0,
     UncompressedColor = 1,
@@ -63,36 +134,117 @@ impl TGAImageType {
 }
 
 /// TGA image format
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
 pub enum TGAImageFormat {
+    #[default]
     Unknown = 0,
     Grayscale = 1,
+    /// 16-bit grayscale stored little-endian
+    ///
+    /// Conversions, accessors such as [`TGAImage::get_gray16`], resizing, mipmaps
+    /// and statistics work on the 16-bit values, while [`TGAImage::diff`] and
+    /// [`compare::mse`] compare the individual bytes.
+    Grayscale16 = 2,
     RGB = 3,
     RGBA = 4,
 }
 
+/// Corner of a picture the first row of a TGA file starts from
+///
+/// Pixel data in memory always starts from the bottom row, the origin only defines
+/// the row order of the file.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Origin {
+    /// Rows are stored from top to bottom
+    TopLeft,
+    /// Rows are stored from bottom to top, the TGA default
+    #[default]
+    BottomLeft,
+}
+
+impl Origin {
+    const TOP_TO_BOTTOM: u8 = 0b10_0000;
+
+    fn from_descriptor(descriptor: u8) -> Self {
+        if descriptor & Origin::TOP_TO_BOTTOM != 0 {
+            Origin::TopLeft
+        } else {
+            Origin::BottomLeft
+        }
+    }
+}
+
+/// Image descriptor orientation bits of a written file and whether rows have to be
+/// reversed to match them
+#[derive(Copy, Clone)]
+struct RowLayout {
+    descriptor: u8,
+    reverse_rows: bool,
+}
+
+impl RowLayout {
+    /// Layout of the legacy `vflip` flag, which only toggles the descriptor bit
+    fn from_vflip(vflip: bool) -> Self {
+        RowLayout {
+            descriptor: if vflip { 0 } else { Origin::TOP_TO_BOTTOM },
+            reverse_rows: false,
+        }
+    }
+}
+
+impl From<Origin> for RowLayout {
+    fn from(origin: Origin) -> Self {
+        match origin {
+            Origin::TopLeft => RowLayout {
+                descriptor: Origin::TOP_TO_BOTTOM,
+                reverse_rows: true,
+            },
+            Origin::BottomLeft => RowLayout {
+                descriptor: 0,
+                reverse_rows: false,
+            },
+        }
+    }
+}
+
+/// Pixel filter used by [`TGAImage::resize`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ResizeFilter {
+    /// Take the source pixel closest to the sample point
+    Nearest,
+    /// Interpolate the four source pixels around the sample point
+    Bilinear,
+}
+
+impl TGAImageFormat {
+    fn is_grayscale(self) -> bool {
+        matches!(
+            self,
+            TGAImageFormat::Grayscale | TGAImageFormat::Grayscale16
+        )
+    }
+}
+
 impl TryFrom<u8> for TGAImageFormat {
-    type Error = &'static str;
+    type Error = TGAError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(TGAImageFormat::Unknown),
             1 => Ok(TGAImageFormat::Grayscale),
+            2 => Ok(TGAImageFormat::Grayscale16),
             3 => Ok(TGAImageFormat::RGB),
             4 => Ok(TGAImageFormat::RGBA),
-            _ => Err("Invalid TGA Image format number"),
+            _ => Err(TGAError::InvalidHeader {
+                reason: "invalid TGA image format number",
+            }),
         }
     }
 }
 
-impl Default for TGAImageFormat {
-    fn default() -> Self {
-        TGAImageFormat::Unknown
-    }
-}
-
 /// TGA image color representation
 #[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TGAColor {
     /// BGRA array
     bgra: [u8; 4],
@@ -132,6 +284,30 @@ impl Mul<f64> for TGAColor {
     }
 }
 
+/// Colors are equal when they have the same number of channels with the same values
+impl PartialEq for TGAColor {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytespp == other.bytespp && self.channels() == other.channels()
+    }
+}
+
+impl Add for TGAColor {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut color = self;
+
+        color
+            .bgra
+            .iter_mut()
+            .zip(rhs.bgra.iter())
+            .for_each(|(elem, rhs)| *elem = elem.saturating_add(*rhs));
+        color.bytespp = self.bytespp.max(rhs.bytespp);
+
+        color
+    }
+}
+
 impl Index<ColorChannel> for TGAColor {
     type Output = u8;
 
@@ -158,536 +334,3739 @@ impl TGAColor {
         Self::new_rgba(r, g, b, 255)
     }
 
+    /// Same color with another alpha value
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self::new_rgba(self.bgra[2], self.bgra[1], self.bgra[0], a)
+    }
+
+    /// Scale color channels by separate factors, results are clamped to `0..=255`
+    #[inline]
+    pub fn scaled(self, r: f32, g: f32, b: f32) -> Self {
+        let mut color = self;
+        let scale =
+            |value: u8, factor: f32| (value as f32 * factor).round().clamp(0.0, 255.0) as u8;
+
+        color[ColorChannel::R] = scale(self.r(), r);
+        color[ColorChannel::G] = scale(self.g(), g);
+        color[ColorChannel::B] = scale(self.b(), b);
+
+        color
+    }
+
+    /// Build a color from 1 to 4 raw bytes in BGRA order
+    pub fn from_slice(bytes: &[u8]) -> Result<Self, TGAError> {
+        match bytes.len() {
+            1..=4 => Ok(Self::from_slice_unchecked(bytes)),
+            len => Err(TGAError::InvalidColorLength(len)),
+        }
+    }
+
+    /// Color of a pixel of any image format, 16-bit gray levels are scaled down to 8 bits
+    fn from_pixel(pixel: &[u8]) -> Self {
+        match *pixel {
+            [lo, hi] => {
+                Self::from_slice_unchecked(&[gray16_to_gray8(u16::from_le_bytes([lo, hi]))])
+            }
+            _ => Self::from_slice_unchecked(pixel),
+        }
+    }
+
+    /// Same as [`TGAColor::from_slice`] for slices already known to be 1 to 4 bytes long
+    fn from_slice_unchecked(bytes: &[u8]) -> Self {
+        let mut bgra = [0u8; 4];
+
+        bgra[..bytes.len()].copy_from_slice(bytes);
+
+        TGAColor {
+            bgra,
+            bytespp: bytes.len() as u8,
+        }
+    }
+
+    #[deprecated(note = "use `TGAColor::from_slice`")]
     pub fn new_from_iter<'a, I>(values: I, bytespp: u8) -> Self
     where
         I: Iterator<Item = &'a u8> + Clone,
     {
         assert!(bytespp <= 4);
-        let mut it = values.clone();
-        let size = values.count();
+        let size = values.clone().count();
         assert_eq!(size as u8, bytespp);
         let mut bgra = [u8::default(); 4];
 
-        for i in 0..size {
-            bgra[i] = *it.next().unwrap();
-        }
+        bgra.iter_mut().zip(values).for_each(|(c, v)| *c = *v);
 
         TGAColor { bgra, bytespp }
     }
-}
-
-/// TGA image representation
-pub struct TGAImage {
-    data: Vec<u8>,
-    /// Width of an image
-    width: u32,
-    /// Height of an image
-    height: u32,
-    /// TGA image color format
-    bytespp: TGAImageFormat,
-}
 
-impl TGAImage {
-    pub fn new(width: u32, height: u32, bytespp: TGAImageFormat) -> Self {
-        TGAImage {
-            data: vec![0; (width * height * bytespp as u32) as usize],
-            width,
-            height,
-            bytespp,
-        }
+    /// Number of meaningful channels of a color
+    pub fn bytespp(&self) -> u8 {
+        self.bytespp
     }
 
-    pub fn clear(&mut self) {
-        self.data.iter_mut().for_each(|e| *e = 0);
+    /// All four channels in BGRA order
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bgra
     }
 
-    pub fn buffer(&mut self) -> &mut Vec<u8> {
-        &mut self.data
+    /// Meaningful channels in BGRA order
+    pub fn channels(&self) -> &[u8] {
+        &self.bgra[..(self.bytespp as usize).min(self.bgra.len())]
     }
 
-    pub fn get_bytespp(&self) -> TGAImageFormat {
-        self.bytespp
+    pub fn r(&self) -> u8 {
+        self[ColorChannel::R]
     }
 
-    pub fn get_width(&self) -> u32 {
-        self.width
+    pub fn g(&self) -> u8 {
+        self[ColorChannel::G]
     }
 
-    pub fn get_height(&self) -> u32 {
-        self.height
+    pub fn b(&self) -> u8 {
+        self[ColorChannel::B]
     }
 
-    pub fn set(&mut self, x: u32, y: u32, color: &TGAColor) {
-        if self.data.len() != 0 && x < self.width && y < self.height {
-            let offset = ((x + y * self.width) * self.bytespp as u32) as usize;
-            self.data[offset..(offset + self.bytespp as usize)]
-                .copy_from_slice(&color.bgra[..(self.bytespp as usize)]);
-        }
+    pub fn a(&self) -> u8 {
+        self[ColorChannel::A]
     }
 
-    pub fn get(&self, x: u32, y: u32) -> TGAColor {
-        if self.data.is_empty() || x >= self.width || y >= self.height {
-            return TGAColor::default();
+    /// Perceived brightness using Rec.601 weights, alpha is ignored
+    ///
+    /// Grayscale colors return their single channel unchanged.
+    pub fn luma(&self) -> u8 {
+        match self.bytespp {
+            1 => self.bgra[0],
+            _ => rec601_luma(self.r(), self.g(), self.b()),
         }
-
-        let offset = ((x + y * self.width) * self.bytespp as u32) as usize;
-
-        return TGAColor::new_from_iter(
-            self.data[offset..offset + self.bytespp as usize].iter(),
-            self.bytespp as u8,
-        );
     }
 
-    pub fn flip_vertically(&mut self) {
-        if self.data.len() == 0 {
-            return;
-        }
-
-        let bytes_per_line = (self.width * self.bytespp as u32) as usize;
-        let half = self.height / 2;
-
-        for i in 0..half {
-            let line1 = (i * bytes_per_line as u32) as usize;
-            let line2 = ((self.height - i - 1) * bytes_per_line as u32) as usize;
-            let chunk1 = self.data[line1..(line1 + bytes_per_line)].as_mut_ptr();
-            let chunk2 = self.data[line2..(line2 + bytes_per_line)].as_mut_ptr();
-
-            unsafe {
-                ptr::swap_nonoverlapping(chunk1, chunk2, bytes_per_line);
+    /// Perceived brightness in `0.0..=1.0`, see [`TGAColor::luma`]
+    pub fn luma_f32(&self) -> f32 {
+        match self.bytespp {
+            1 => self.bgra[0] as f32 / 255.0,
+            _ => {
+                (0.299 * self.r() as f32 + 0.587 * self.g() as f32 + 0.114 * self.b() as f32)
+                    / 255.0
             }
         }
     }
 
-    pub fn flip_horizontally(&mut self) {
-        if self.data.len() == 0 {
-            return;
-        }
+    /// Single channel color holding the [`TGAColor::luma`] value
+    pub fn to_grayscale(&self) -> Self {
+        Self::from_slice_unchecked(&[self.luma()])
+    }
 
-        let half = self.width / 2;
+    /// Build a color from a packed `0xRRGGBBAA` value
+    pub const fn from_rgba_u32(rgba: u32) -> Self {
+        let [r, g, b, a] = rgba.to_be_bytes();
 
-        for i in 0..half {
-            for j in 0..self.height {
-                let c1 = self.get(i, j);
-                let c2 = self.get(self.width - i - 1, j);
-                self.set(i, j, &c2);
-                self.set(self.width - i - 1, j, &c1);
-            }
-        }
+        Self::new_rgba(r, g, b, a)
     }
 
-    fn unload_rle_data<T: std::io::Write>(&self, out: &mut T) -> std::io::Result<()> {
-        const MAX_CHUNK_LENGTH: u8 = 128;
-        let npixels: usize = (self.width * self.height) as usize;
-        let mut curpix = 0;
-        let mut writer = std::io::BufWriter::new(out);
+    /// Build an opaque color from a packed `0xRRGGBB` value
+    pub const fn from_rgb_u32(rgb: u32) -> Self {
+        Self::from_rgba_u32(rgb << 8 | 0xff)
+    }
 
-        while curpix < npixels {
-            let chunkstart = curpix * self.bytespp as usize;
-            let mut curbyte = chunkstart;
-            let mut run_length = 1u8;
-            let mut raw = true;
+    /// Pack a color into a `0xRRGGBBAA` value
+    pub fn to_rgba_u32(&self) -> u32 {
+        let [b, g, r, a] = self.bgra;
 
-            while curpix + (run_length as usize) < npixels && run_length < MAX_CHUNK_LENGTH {
-                let mut succ_eq = true;
+        u32::from_be_bytes([r, g, b, a])
+    }
 
-                for i in 0..self.bytespp as usize {
-                    succ_eq =
-                        self.data[curbyte + i] == self.data[curbyte + i + self.bytespp as usize];
+    /// Linearly interpolate all channels between `a` at `t == 0.0` and `b` at `t == 1.0`
+    pub fn lerp(a: &TGAColor, b: &TGAColor, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
 
-                    if !succ_eq {
-                        break;
-                    }
-                }
+        Self::weighted_sum(&[(*a, 1.0 - t), (*b, t)])
+    }
 
-                curbyte += self.bytespp as usize;
+    /// Sum colors multiplied by their weights, every channel is clamped to `0..=255`
+    pub fn weighted_sum(colors: &[(TGAColor, f32)]) -> Self {
+        let mut sum = [0f32; 4];
+        let mut bytespp = 0;
 
-                if run_length == 1 {
-                    raw = !succ_eq;
-                }
+        for (color, weight) in colors {
+            sum.iter_mut()
+                .zip(color.bgra.iter())
+                .for_each(|(s, c)| *s += *c as f32 * weight);
+            bytespp = bytespp.max(color.bytespp);
+        }
 
-                if raw && succ_eq {
-                    run_length -= 1;
-                    break;
-                }
+        let mut bgra = [0u8; 4];
 
-                if !raw && !succ_eq {
-                    break;
-                }
+        bgra.iter_mut()
+            .zip(sum.iter())
+            .for_each(|(c, s)| *c = s.round().clamp(0.0, 255.0) as u8);
 
-                run_length += 1;
-            }
+        TGAColor { bgra, bytespp }
+    }
 
-            curpix += run_length as usize;
+    /// Decode sRGB color channels into linear `[r, g, b, a]` values in `0.0..=1.0`
+    ///
+    /// Alpha is stored linearly and only gets normalized.
+    pub fn to_linear(&self) -> [f32; 4] {
+        [
+            srgb_to_linear(self.bgra[ColorChannel::R as usize]),
+            srgb_to_linear(self.bgra[ColorChannel::G as usize]),
+            srgb_to_linear(self.bgra[ColorChannel::B as usize]),
+            self.bgra[ColorChannel::A as usize] as f32 / 255.0,
+        ]
+    }
 
-            if raw {
-                writer.write(&[run_length - 1])?;
-            } else {
-                writer.write(&[run_length + 127])?;
-            }
+    /// Encode linear `[r, g, b, a]` values into an sRGB color, values are clamped to `0.0..=1.0`
+    pub fn from_linear(rgba: [f32; 4]) -> Self {
+        let [r, g, b, a] = rgba;
 
-            let to_write = if raw {
-                run_length as usize * (self.bytespp as u8) as usize
-            } else {
-                self.bytespp as usize
-            };
+        Self::new_rgba(
+            linear_to_srgb(r),
+            linear_to_srgb(g),
+            linear_to_srgb(b),
+            (a.clamp(0.0, 1.0) * 255.0).round() as u8,
+        )
+    }
 
-            writer.write(self.data[chunkstart..chunkstart + to_write].as_ref())?;
-        }
+    /// Scale color channels by `intensity` in linear space, alpha is kept as is
+    pub fn scale_srgb(&self, intensity: f32) -> Self {
+        let intensity = intensity.clamp(0.0, 1.0);
+        let [r, g, b, a] = self.to_linear();
+        let mut color = Self::from_linear([r * intensity, g * intensity, b * intensity, a]);
 
-        Ok(())
+        color.bgra[ColorChannel::A as usize] = self.bgra[ColorChannel::A as usize];
+        color.bytespp = self.bytespp;
+
+        color
     }
+}
 
-    fn load_rle_data<T: std::io::Read>(
-        input: &mut T,
-        data: &mut Vec<u8>,
-        image_param: &(u16, u16, u8),
-    ) -> std::io::Result<()> {
-        let mut current_pixel = 0usize;
-        let mut current_offset = 0usize;
-        let (height, width, bytespp) = image_param;
-        let pixel_count = *height as usize * *width as usize;
-        let mut header_buf = [0u8; 1];
+/// Parse `"#RRGGBB"` or `"#RRGGBBAA"` hex strings, the leading hash is optional
+impl FromStr for TGAColor {
+    type Err = ParseColorError;
 
-        while current_pixel < pixel_count {
-            input.read_exact(&mut header_buf)?;
-            let header = u8::from_ne_bytes(header_buf);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digits = s.strip_prefix('#').unwrap_or(s);
 
-            if header & 0b1000_0000 == 0 {
-                // raw packet
-                let packet_size = header + 1;
-                let offset_start = current_offset;
-                let offset_end = current_offset + *bytespp as usize * packet_size as usize;
-                input.read_exact(&mut data[offset_start..offset_end])?;
-                current_pixel += packet_size as usize;
-                current_offset += packet_size as usize * *bytespp as usize;
-            } else {
-                // rle packet
-                let packet_size = (header ^ 0b1000_0000) + 1u8;
-                input.read_exact(&mut data[current_offset..current_offset + *bytespp as usize])?;
-                let origin_offset = current_offset;
-                current_offset += *bytespp as usize;
-                current_pixel += 1;
+        if !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseColorError::InvalidDigit);
+        }
 
-                for _ in 0..packet_size - 1 {
-                    data.copy_within(
-                        origin_offset..(origin_offset + *bytespp as usize),
-                        current_offset,
-                    );
+        let value =
+            |digits| u32::from_str_radix(digits, 16).map_err(|_| ParseColorError::InvalidDigit);
 
-                    current_offset += *bytespp as usize;
-                    current_pixel += 1;
-                    assert!(current_pixel <= pixel_count);
-                }
-            }
+        match digits.len() {
+            6 => Ok(Self::from_rgb_u32(value(digits)?)),
+            8 => Ok(Self::from_rgba_u32(value(digits)?)),
+            len => Err(ParseColorError::InvalidLength(len)),
         }
+    }
+}
 
-        assert_eq!(current_pixel, pixel_count);
+fn srgb_to_linear(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
 
-        Ok(())
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
     }
+}
 
-    pub fn read_tga_file(filename: &str) -> std::io::Result<Self> {
-        let file = std::fs::File::open(filename)?;
-        let mut reader = std::io::BufReader::new(file);
-        let mut header: TGAHeader = TGAHeader::default();
-        let header_size = size_of::<TGAHeader>();
-
-        unsafe {
-            let header_slice =
-                slice_from_raw_parts_mut(&mut header as *mut _ as *mut u8, header_size);
-            reader.read_exact(&mut *header_slice)?;
-        }
+fn linear_to_srgb(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let encoded = if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
 
-        let (height, width, bitsperpixel) = unsafe {
-            (
-                ptr::read_unaligned(ptr::addr_of!(header.height)),
-                ptr::read_unaligned(ptr::addr_of!(header.width)),
-                ptr::read_unaligned(ptr::addr_of!(header.bitsperpixel)) >> 3,
-            )
-        };
+    (encoded * 255.0).round() as u8
+}
 
-        let is_valid_bpp = match TGAImageFormat::try_from(bitsperpixel) {
-            Ok(TGAImageFormat::Grayscale) | Ok(TGAImageFormat::RGB) | Ok(TGAImageFormat::RGBA) => {
-                true
-            }
-            _ => false,
-        };
+/// TGA image representation
+pub struct TGAImage {
+    data: Vec<u8>,
+    /// Width of an image
+    width: u32,
+    /// Height of an image
+    height: u32,
+    /// TGA image color format
+    bytespp: TGAImageFormat,
+    /// Horizontal screen position of the lower left corner
+    x_origin: u16,
+    /// Vertical screen position of the lower left corner
+    y_origin: u16,
+    /// Image identification field, empty if absent
+    image_id: Vec<u8>,
+    /// Attribute bits declared by the file the image was read from
+    alpha_bits: Option<u8>,
+    /// Extension area metadata
+    metadata: Option<TGAMetadata>,
+    /// Row order of the file the image was read from
+    origin: Origin,
+}
 
-        if height <= 0 || width <= 0 || !is_valid_bpp {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Invalid TGA header",
-            ));
+impl TGAImage {
+    /// Create a black image
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image is degenerate, see [`TGAImage::try_new`].
+    #[deprecated(note = "use try_new")]
+    pub fn new(width: u32, height: u32, bytespp: TGAImageFormat) -> Self {
+        match TGAImage::try_new(width, height, bytespp) {
+            Ok(image) => image,
+            Err(e) => panic!("Cannot create image: {}", e),
         }
+    }
 
-        let mut data = vec![0u8; height as usize * width as usize * bitsperpixel as usize];
-        let datatype = unsafe { ptr::read_unaligned(ptr::addr_of!(header.datatypecode)) };
+    /// Create a black image, zero dimensions and the `Unknown` format are rejected
+    ///
+    /// The pixel buffer is limited to `isize::MAX` bytes.
+    pub fn try_new(width: u32, height: u32, bytespp: TGAImageFormat) -> Result<Self, TGAError> {
+        TGAImage::try_new_with_limit(width, height, bytespp, isize::MAX as usize)
+    }
 
-        match TGAImageType::from_u8(datatype) {
-            Some(TGAImageType::UncompressedTrueColor) | Some(TGAImageType::UncompressedBW) => {
-                reader.read_exact(&mut data)?;
-            }
-            Some(TGAImageType::RLETrueColor) | Some(TGAImageType::RLEBW) => {
-                TGAImage::load_rle_data(&mut reader, &mut data, &(height, width, bitsperpixel))?;
-            }
-            _ => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::InvalidData,
-                    format!("Unknown file format {}", datatype),
-                ))
-            }
-        };
+    /// Create a black image with a pixel buffer of at most `max_bytes`
+    pub fn try_new_with_limit(
+        width: u32,
+        height: u32,
+        bytespp: TGAImageFormat,
+        max_bytes: usize,
+    ) -> Result<Self, TGAError> {
+        let size = TGAImage::check_shape(width, height, bytespp, max_bytes)?;
+
+        Ok(TGAImage {
+            data: vec![0; size],
+            width,
+            height,
+            bytespp,
+            x_origin: 0,
+            y_origin: 0,
+            image_id: Vec::new(),
+            alpha_bits: None,
+            metadata: None,
+            origin: Origin::default(),
+        })
+    }
 
-        let mut image = TGAImage {
-            data,
-            width: width as u32,
-            height: height as u32,
-            bytespp: TGAImageFormat::try_from(bitsperpixel).unwrap(),
-        };
-        let image_descriptor =
-            unsafe { ptr::read_unaligned(ptr::addr_of!(header.imagedescriptor)) };
+    /// Create a black image of a shape taken from an existing image, so already valid
+    fn blank(width: u32, height: u32, format: TGAImageFormat) -> Self {
+        TGAImage {
+            data: vec![0; width as usize * height as usize * format as usize],
+            width,
+            height,
+            bytespp: format,
+            x_origin: 0,
+            y_origin: 0,
+            image_id: Vec::new(),
+            alpha_bits: None,
+            metadata: None,
+            origin: Origin::default(),
+        }
+    }
 
-        if image_descriptor & 0b10_0000 != 0 {
-            image.flip_vertically();
+    /// Validate image shape and compute its buffer size
+    fn check_shape(
+        width: u32,
+        height: u32,
+        format: TGAImageFormat,
+        max_bytes: usize,
+    ) -> Result<usize, TGAError> {
+        if width == 0 || height == 0 {
+            return Err(TGAError::InvalidHeader {
+                reason: "image dimensions must be non-zero",
+            });
         }
 
-        if image_descriptor & 0b1_0000 != 0 {
-            image.flip_horizontally();
+        if format == TGAImageFormat::Unknown {
+            return Err(TGAError::UnsupportedBitDepth(0));
         }
 
-        Ok(image)
+        (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(format as usize))
+            .filter(|size| *size <= max_bytes.min(isize::MAX as usize))
+            .ok_or(TGAError::ImageTooLarge {
+                width,
+                height,
+                limit: max_bytes.min(isize::MAX as usize),
+            })
     }
 
-    pub fn write_tga_file(&self, filename: &str, vflip: bool, rle: bool) -> std::io::Result<()> {
-        fn get_data_type_code(image_fmt: TGAImageFormat, rle: bool) -> u8 {
-            let rle_val = if rle { 11 } else { 3 };
-            return if image_fmt == TGAImageFormat::Grayscale {
-                rle_val
-            } else {
-                rle_val - 1
-            };
+    /// Build an image from an existing pixel buffer without copying it
+    pub fn from_raw(
+        width: u32,
+        height: u32,
+        format: TGAImageFormat,
+        data: Vec<u8>,
+    ) -> Result<Self, TGAError> {
+        let expected = TGAImage::check_shape(width, height, format, isize::MAX as usize)?;
+
+        if data.len() != expected {
+            return Err(TGAError::InvalidBufferSize {
+                expected,
+                got: data.len(),
+            });
         }
 
-        const DEVELOPER_AREA_REF: [u8; 4] = [0u8; 4];
-        const EXTENSION_AREA_REF: [u8; 4] = [0u8; 4];
-        const FOOTER: [u8; 18] = [
-            b'T', b'R', b'U', b'E', b'V', b'I', b'S', b'I', b'O', b'N', b'-', b'X', b'F', b'I',
-            b'L', b'E', b'.', b'\0',
-        ];
+        Ok(TGAImage {
+            data,
+            width,
+            height,
+            bytespp: format,
+            x_origin: 0,
+            y_origin: 0,
+            image_id: Vec::new(),
+            alpha_bits: None,
+            metadata: None,
+            origin: Origin::default(),
+        })
+    }
+
+    /// Decompose an image into its dimensions, format and pixel buffer
+    pub fn into_raw(self) -> (u32, u32, TGAImageFormat, Vec<u8>) {
+        (self.width, self.height, self.bytespp, self.data)
+    }
 
-        let mut file = std::fs::File::create(filename)?;
-        let mut header = TGAHeader::default();
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
 
-        header.bitsperpixel = (self.bytespp as u8) << 3;
-        header.width = self.width as u16;
-        header.height = self.height as u16;
-        header.datatypecode = get_data_type_code(self.bytespp, rle);
-        header.imagedescriptor = if vflip { 0x0u8 } else { 0x20u8 };
+    pub fn clear(&mut self) {
+        self.data.iter_mut().for_each(|e| *e = 0);
+    }
 
-        let header = slice_from_raw_parts(&header as *const _ as *const u8, size_of::<TGAHeader>());
+    /// Fill the whole image with a color
+    pub fn clear_color(&mut self, color: &TGAColor) {
+        self.fill_rect(0, 0, self.width, self.height, color);
+    }
 
-        unsafe {
-            file.write(header.as_ref().unwrap())?;
-        }
+    /// Fill the rectangle `(x, y, w, h)` clipped to the image bounds with a color
+    pub fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, color: &TGAColor) {
+        let w = self.width.saturating_sub(x).min(w) as usize;
+        let h = self.height.saturating_sub(y).min(h);
+        let bytespp = self.bytespp as usize;
 
-        if !rle {
-            file.write(self.data.as_ref())?;
-        } else {
-            self.unload_rle_data(&mut file)?;
+        if w == 0 || h == 0 || bytespp == 0 {
+            return;
         }
 
-        file.write(&DEVELOPER_AREA_REF)?;
-        file.write(&EXTENSION_AREA_REF)?;
-        file.write(&FOOTER)?;
+        let mut pixel = [0u8; 4];
 
-        Ok(())
-    }
+        write_pixel(color, &mut pixel[..bytespp]);
 
-    pub fn dump(&self) {
-        for b in &self.data {
-            print!("{:02x}", b);
+        let pattern = pixel[..bytespp].repeat(w);
+
+        for row in y..y + h {
+            let offset = self.pixel_offset(x, row);
+
+            self.data[offset..offset + pattern.len()].copy_from_slice(&pattern);
         }
     }
-}
 
-#[cfg(test)]
+    pub fn buffer(&mut self) -> &mut Vec<u8> {
+        &mut self.data
+    }
+
+    pub fn get_bytespp(&self) -> TGAImageFormat {
+        self.bytespp
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn get_x_origin(&self) -> u16 {
+        self.x_origin
+    }
+
+    pub fn get_y_origin(&self) -> u16 {
+        self.y_origin
+    }
+
+    /// Set the screen position of the image lower left corner stored in the TGA header
+    pub fn set_xy_origin(&mut self, x_origin: u16, y_origin: u16) {
+        self.x_origin = x_origin;
+        self.y_origin = y_origin;
+    }
+
+    /// Row order of the file the image was read from, [`Origin::BottomLeft`] for
+    /// images created in memory
+    ///
+    /// Pass it to [`TGAImage::write_to_with_origin`] to keep the layout of the source file.
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    /// Change the recorded file row order, pixel data is not touched
+    pub fn set_origin(&mut self, origin: Origin) {
+        self.origin = origin;
+    }
+
+    /// Number of attribute (alpha) bits per pixel declared in the image descriptor
+    ///
+    /// Images read from a file report the count declared by its descriptor, images
+    /// created in memory or converted to another format report 8 bits for RGBA and
+    /// 0 for other formats. The writers always declare the latter for the pixels
+    /// they write.
+    pub fn alpha_bits(&self) -> u8 {
+        self.alpha_bits
+            .unwrap_or_else(|| TGAImage::default_alpha_bits(self.bytespp))
+    }
+
+    fn default_alpha_bits(format: TGAImageFormat) -> u8 {
+        if format == TGAImageFormat::RGBA {
+            8
+        } else {
+            0
+        }
+    }
+
+    /// Copy origin, identification, metadata and alpha bits of the same format from
+    /// another image
+    fn copy_properties(&mut self, src: &TGAImage) {
+        self.x_origin = src.x_origin;
+        self.y_origin = src.y_origin;
+        self.origin = src.origin;
+        self.image_id = src.image_id.clone();
+        self.metadata = src.metadata.clone();
+
+        if self.bytespp == src.bytespp {
+            self.alpha_bits = src.alpha_bits;
+        }
+    }
+
+    /// Metadata read from or written to the TGA 2.0 extension area
+    pub fn metadata(&self) -> Option<&TGAMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Set metadata to embed as an extension area, `None` writes no extension area
+    pub fn set_metadata(&mut self, metadata: Option<TGAMetadata>) {
+        self.metadata = metadata;
+    }
+
+    /// Image identification field read from or written to the TGA file
+    pub fn image_id(&self) -> Option<Vec<u8>> {
+        if self.image_id.is_empty() {
+            None
+        } else {
+            Some(self.image_id.clone())
+        }
+    }
+
+    /// Set the image identification field, which is limited to 255 bytes by the format
+    pub fn set_image_id(&mut self, image_id: Option<Vec<u8>>) -> Result<(), TGAError> {
+        let image_id = image_id.unwrap_or_default();
+
+        if image_id.len() > u8::MAX as usize {
+            return Err(TGAError::InvalidHeader {
+                reason: "image ID must not exceed 255 bytes",
+            });
+        }
+
+        self.image_id = image_id;
+
+        Ok(())
+    }
+
+    fn contains(&self, x: u32, y: u32) -> bool {
+        !self.data.is_empty() && x < self.width && y < self.height
+    }
+
+    fn pixel_offset(&self, x: u32, y: u32) -> usize {
+        (x as usize + y as usize * self.width as usize) * self.bytespp as usize
+    }
+
+    fn out_of_bounds(&self, x: u32, y: u32) -> OutOfBounds {
+        OutOfBounds {
+            x,
+            y,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// Mutable raw bytes of a pixel, `None` for coordinates outside of the image
+    pub fn pixel_mut(&mut self, x: u32, y: u32) -> Option<&mut [u8]> {
+        if !self.contains(x, y) {
+            return None;
+        }
+
+        let offset = self.pixel_offset(x, y);
+
+        Some(&mut self.data[offset..offset + self.bytespp as usize])
+    }
+
+    /// Set a pixel color, silently ignoring coordinates outside of the image
+    ///
+    /// Pixels of 16-bit grayscale images store the Rec.601 luma of the color scaled to
+    /// 16 bits and are read back by [`TGAImage::get`] as 8-bit gray colors. Use
+    /// [`TGAImage::set_gray16`] and [`TGAImage::get_gray16`] for the full precision.
+    pub fn set(&mut self, x: u32, y: u32, color: &TGAColor) {
+        if self.contains(x, y) {
+            self.set_unchecked(x, y, color);
+        }
+    }
+
+    /// Get a pixel color, returning a default color for coordinates outside of the image
+    pub fn get(&self, x: u32, y: u32) -> TGAColor {
+        if !self.contains(x, y) {
+            return TGAColor::default();
+        }
+
+        self.get_unchecked(x, y)
+    }
+
+    /// Set a 16-bit gray level, coordinates outside of the image are ignored
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image format is not [`TGAImageFormat::Grayscale16`].
+    pub fn set_gray16(&mut self, x: u32, y: u32, v: u16) {
+        assert_eq!(
+            self.bytespp,
+            TGAImageFormat::Grayscale16,
+            "Not a 16-bit grayscale image"
+        );
+
+        if let Some(pixel) = self.pixel_mut(x, y) {
+            pixel.copy_from_slice(&v.to_le_bytes());
+        }
+    }
+
+    /// Get a 16-bit gray level, returning 0 for coordinates outside of the image
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image format is not [`TGAImageFormat::Grayscale16`].
+    pub fn get_gray16(&self, x: u32, y: u32) -> u16 {
+        assert_eq!(
+            self.bytespp,
+            TGAImageFormat::Grayscale16,
+            "Not a 16-bit grayscale image"
+        );
+
+        if !self.contains(x, y) {
+            return 0;
+        }
+
+        let pixel = &self[(x, y)];
+
+        u16::from_le_bytes([pixel[0], pixel[1]])
+    }
+
+    /// Set a pixel color, reporting coordinates outside of the image as an error
+    pub fn try_set(&mut self, x: u32, y: u32, color: &TGAColor) -> Result<(), OutOfBounds> {
+        if !self.contains(x, y) {
+            return Err(self.out_of_bounds(x, y));
+        }
+
+        self.set_unchecked(x, y, color);
+
+        Ok(())
+    }
+
+    /// Get a pixel color, reporting coordinates outside of the image as an error
+    pub fn try_get(&self, x: u32, y: u32) -> Result<TGAColor, OutOfBounds> {
+        if !self.contains(x, y) {
+            return Err(self.out_of_bounds(x, y));
+        }
+
+        Ok(self.get_unchecked(x, y))
+    }
+
+    /// Set a pixel color without the coordinates check
+    ///
+    /// Intended for hot loops where coordinates are already known to be inside of the
+    /// image. Out of range coordinates trip a debug assertion and may panic or write
+    /// into a wrong pixel in release builds.
+    pub fn set_unchecked(&mut self, x: u32, y: u32, color: &TGAColor) {
+        debug_assert!(self.contains(x, y), "{}", self.out_of_bounds(x, y));
+        let offset = self.pixel_offset(x, y);
+        let pixel = &mut self.data[offset..(offset + self.bytespp as usize)];
+
+        write_pixel(color, pixel);
+    }
+
+    /// Get a pixel color without the coordinates check
+    ///
+    /// See [`TGAImage::set_unchecked`] for the caveats.
+    pub fn get_unchecked(&self, x: u32, y: u32) -> TGAColor {
+        debug_assert!(self.contains(x, y), "{}", self.out_of_bounds(x, y));
+        let offset = self.pixel_offset(x, y);
+
+        TGAColor::from_pixel(&self.data[offset..offset + self.bytespp as usize])
+    }
+
+    pub fn flip_vertically(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+
+        let bytes_per_line = self.width as usize * self.bytespp as usize;
+        let height = self.height as usize;
+
+        for i in 0..height / 2 {
+            let line1 = i * bytes_per_line;
+            let line2 = (height - i - 1) * bytes_per_line;
+            let (top, bottom) = self.data.split_at_mut(line2);
+
+            top[line1..line1 + bytes_per_line].swap_with_slice(&mut bottom[..bytes_per_line]);
+        }
+    }
+
+    pub fn flip_horizontally(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+
+        let bytespp = self.bytespp as usize;
+        let width = self.width as usize;
+
+        for row in self.data.chunks_exact_mut(width * bytespp) {
+            for i in 0..width / 2 {
+                let (left, right) = row.split_at_mut((width - i - 1) * bytespp);
+
+                left[i * bytespp..(i + 1) * bytespp].swap_with_slice(&mut right[..bytespp]);
+            }
+        }
+    }
+
+    /// Rotate an image by 90 degrees, pixel `(x, y)` moves to `(height - 1 - y, x)`
+    pub fn rotate90(&self) -> TGAImage {
+        self.rotated(|x, y| (self.height - 1 - y, x))
+    }
+
+    /// Rotate an image by 180 degrees in place
+    pub fn rotate180(&mut self) {
+        self.flip_vertically();
+        self.flip_horizontally();
+    }
+
+    /// Rotate an image by 270 degrees, pixel `(x, y)` moves to `(y, width - 1 - x)`
+    pub fn rotate270(&self) -> TGAImage {
+        self.rotated(|x, y| (y, self.width - 1 - x))
+    }
+
+    fn rotated<F: Fn(u32, u32) -> (u32, u32)>(&self, position: F) -> TGAImage {
+        let mut image = TGAImage::blank(self.height, self.width, self.bytespp);
+
+        image.copy_properties(self);
+
+        if self.data.is_empty() {
+            return image;
+        }
+
+        let bytespp = self.bytespp as usize;
+
+        for (i, pixel) in self.data.chunks_exact(bytespp).enumerate() {
+            let x = i as u32 % self.width;
+            let y = i as u32 / self.width;
+            let (dst_x, dst_y) = position(x, y);
+            let offset = image.pixel_offset(dst_x, dst_y);
+
+            image.data[offset..offset + bytespp].copy_from_slice(pixel);
+        }
+
+        image
+    }
+
+    /// Convert an image into another pixel format
+    ///
+    /// Color to grayscale conversion uses Rec.601 luma, grayscale is replicated into
+    /// every color channel, alpha is filled with 255 when added and dropped when removed.
+    /// The `Unknown` target format is rejected.
+    pub fn convert(&self, target: TGAImageFormat) -> Result<TGAImage, TGAError> {
+        TGAImage::check_shape(self.width, self.height, target, isize::MAX as usize)?;
+
+        Ok(self.converted(target))
+    }
+
+    /// Convert an image into a pixel format known to be valid
+    pub(crate) fn converted(&self, target: TGAImageFormat) -> TGAImage {
+        let mut image = TGAImage::blank(self.width, self.height, target);
+
+        image.copy_properties(self);
+
+        if target == self.bytespp {
+            image.data.copy_from_slice(&self.data);
+
+            return image;
+        }
+
+        for (src, dst) in self
+            .data
+            .chunks_exact(self.bytespp as usize)
+            .zip(image.data.chunks_exact_mut(target as usize))
+        {
+            convert_pixel(src, dst);
+        }
+
+        image
+    }
+
+    /// Copy a rectangle `(x, y, width, height)` of `src` into this image at `(dst_x, dst_y)`
+    ///
+    /// The rectangle is clipped against both images. Pixels are converted on the fly
+    /// when the formats differ.
+    pub fn blit(&mut self, src: &TGAImage, src_rect: (u32, u32, u32, u32), dst_x: u32, dst_y: u32) {
+        let (src_x, src_y, width, height) = src_rect;
+        let clip = |start: u32, len: u32, src_size: u32, dst_start: u32, dst_size: u32| {
+            let src_len = src_size.saturating_sub(start).min(len);
+            let dst_len = dst_size.saturating_sub(dst_start);
+
+            src_len.min(dst_len) as usize
+        };
+        let width = clip(src_x, width, src.width, dst_x, self.width);
+        let height = clip(src_y, height, src.height, dst_y, self.height);
+
+        if width == 0 || height == 0 || src.data.is_empty() || self.data.is_empty() {
+            return;
+        }
+
+        let src_bytespp = src.bytespp as usize;
+        let dst_bytespp = self.bytespp as usize;
+
+        for row in 0..height as u32 {
+            let src_offset = src.pixel_offset(src_x, src_y + row);
+            let dst_offset = self.pixel_offset(dst_x, dst_y + row);
+            let src_row = &src.data[src_offset..src_offset + width * src_bytespp];
+            let dst_row = &mut self.data[dst_offset..dst_offset + width * dst_bytespp];
+
+            if src.bytespp == self.bytespp {
+                dst_row.copy_from_slice(src_row);
+            } else {
+                src_row
+                    .chunks_exact(src_bytespp)
+                    .zip(dst_row.chunks_exact_mut(dst_bytespp))
+                    .for_each(|(s, d)| convert_pixel(s, d));
+            }
+        }
+    }
+
+    /// Copy the whole `src` image into this image of the same dimensions
+    pub fn copy_from(&mut self, src: &TGAImage) -> Result<(), TGAError> {
+        if (self.width, self.height) != (src.width, src.height) {
+            return Err(TGAError::DimensionMismatch {
+                expected: (self.width, self.height),
+                got: (src.width, src.height),
+            });
+        }
+
+        self.blit(src, (0, 0, src.width, src.height), 0, 0);
+
+        Ok(())
+    }
+
+    /// Extract the rectangle `(x, y, width, height)` into a new image
+    ///
+    /// A rectangle extending past the right or bottom edge is clamped to the image
+    /// bounds. An empty rectangle, or one starting outside of the image, is rejected.
+    pub fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Result<TGAImage, TGAError> {
+        let clamped_width = self.width.saturating_sub(x).min(width);
+        let clamped_height = self.height.saturating_sub(y).min(height);
+
+        if clamped_width == 0 || clamped_height == 0 {
+            return Err(TGAError::InvalidRegion {
+                x,
+                y,
+                width,
+                height,
+            });
+        }
+
+        let mut image = TGAImage::try_new(clamped_width, clamped_height, self.bytespp)?;
+
+        image.blit(self, (x, y, clamped_width, clamped_height), 0, 0);
+
+        Ok(image)
+    }
+
+    /// Extract a single channel into a grayscale image
+    ///
+    /// Grayscale images provide their only channel as R, G and B. `None` is returned
+    /// for a channel missing in the image format, e.g. alpha of an RGB image.
+    pub fn channel(&self, ch: ColorChannel) -> Option<TGAImage> {
+        if self.bytespp == TGAImageFormat::Grayscale16 {
+            return self.converted(TGAImageFormat::Grayscale).channel(ch);
+        }
+
+        let bytespp = self.bytespp as usize;
+        let offset = match self.bytespp {
+            TGAImageFormat::Grayscale if !matches!(ch, ColorChannel::A) => 0,
+            _ if (ch as usize) < bytespp && bytespp > 1 => ch as usize,
+            _ => return None,
+        };
+        let mut image = TGAImage::blank(self.width, self.height, TGAImageFormat::Grayscale);
+
+        image.copy_properties(self);
+        image
+            .data
+            .iter_mut()
+            .zip(self.data.iter().skip(offset).step_by(bytespp))
+            .for_each(|(dst, src)| *dst = *src);
+
+        Some(image)
+    }
+
+    /// Split an image into grayscale images of every channel in BGRA order
+    pub fn split_channels(&self) -> Vec<TGAImage> {
+        match self.bytespp {
+            TGAImageFormat::Grayscale | TGAImageFormat::Grayscale16 => {
+                self.channel(ColorChannel::B).into_iter().collect()
+            }
+            _ => [
+                ColorChannel::B,
+                ColorChannel::G,
+                ColorChannel::R,
+                ColorChannel::A,
+            ]
+            .iter()
+            .filter_map(|ch| self.channel(*ch))
+            .collect(),
+        }
+    }
+
+    /// Scale an image to the given dimensions
+    ///
+    /// Sample points are taken at pixel centers, so both upscaling and downscaling keep
+    /// the image aligned. Bilinear filtering interpolates every channel independently,
+    /// 16-bit gray levels are interpolated as a whole. Zero dimensions are rejected.
+    pub fn resize(
+        &self,
+        new_width: u32,
+        new_height: u32,
+        filter: ResizeFilter,
+    ) -> Result<TGAImage, TGAError> {
+        let mut image = TGAImage::try_new(new_width, new_height, self.bytespp)?;
+
+        image.copy_properties(self);
+
+        let bytespp = self.bytespp as usize;
+        let x_scale = self.width as f32 / new_width as f32;
+        let y_scale = self.height as f32 / new_height as f32;
+
+        image.for_each_pixel(|x, y, pixel| {
+            let sx = (x as f32 + 0.5) * x_scale;
+            let sy = (y as f32 + 0.5) * y_scale;
+
+            match filter {
+                ResizeFilter::Nearest => {
+                    let x = (sx as u32).min(self.width - 1);
+                    let y = (sy as u32).min(self.height - 1);
+
+                    pixel.copy_from_slice(&self[(x, y)]);
+                }
+                ResizeFilter::Bilinear => {
+                    let sx = (sx - 0.5).max(0.0).min((self.width - 1) as f32);
+                    let sy = (sy - 0.5).max(0.0).min((self.height - 1) as f32);
+                    let (x0, y0) = (sx as u32, sy as u32);
+                    let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+                    let (fx, fy) = (sx - x0 as f32, sy - y0 as f32);
+                    let (p00, p10) = (&self[(x0, y0)], &self[(x1, y0)]);
+                    let (p01, p11) = (&self[(x0, y1)], &self[(x1, y1)]);
+
+                    let lerp = |v00: f32, v10: f32, v01: f32, v11: f32| {
+                        let top = v00 * (1.0 - fx) + v10 * fx;
+                        let bottom = v01 * (1.0 - fx) + v11 * fx;
+
+                        top * (1.0 - fy) + bottom * fy + 0.5
+                    };
+
+                    if self.bytespp == TGAImageFormat::Grayscale16 {
+                        let v = |p: &[u8]| u16::from_le_bytes([p[0], p[1]]) as f32;
+                        let value = lerp(v(p00), v(p10), v(p01), v(p11)) as u16;
+
+                        pixel.copy_from_slice(&value.to_le_bytes());
+                    } else {
+                        for i in 0..bytespp {
+                            let value =
+                                lerp(p00[i] as f32, p10[i] as f32, p01[i] as f32, p11[i] as f32);
+
+                            pixel[i] = value as u8;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(image)
+    }
+
+    /// Iterate over all pixels in memory order yielding their coordinates and color
+    pub fn pixels(&self) -> impl Iterator<Item = (u32, u32, TGAColor)> + '_ {
+        let bytespp = self.bytespp as usize;
+        let width = self.width;
+
+        self.data
+            .chunks_exact(bytespp.max(1))
+            .enumerate()
+            .map(move |(i, pixel)| {
+                (
+                    i as u32 % width,
+                    i as u32 / width,
+                    TGAColor::from_pixel(pixel),
+                )
+            })
+    }
+
+    /// Iterate over raw scanlines of an image
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        let bytes_per_line = self.width as usize * self.bytespp as usize;
+
+        self.data.chunks_exact(bytes_per_line.max(1))
+    }
+
+    /// Call `f` with the coordinates and raw bytes of every pixel for in-place editing
+    pub fn for_each_pixel<F: FnMut(u32, u32, &mut [u8])>(&mut self, mut f: F) {
+        let bytespp = self.bytespp as usize;
+        let width = self.width;
+
+        self.data
+            .chunks_exact_mut(bytespp.max(1))
+            .enumerate()
+            .for_each(|(i, pixel)| f(i as u32 % width, i as u32 / width, pixel));
+    }
+
+    fn unload_rle_data<T: std::io::Write>(
+        data: &[u8],
+        bytespp: usize,
+        writer: &mut T,
+    ) -> Result<(), TGAError> {
+        const MAX_CHUNK_LENGTH: usize = 128;
+        let npixels = data.len() / bytespp;
+        let pixel = |index: usize| &data[index * bytespp..(index + 1) * bytespp];
+        let mut curpix = 0;
+
+        while curpix < npixels {
+            let pixels_remaining = npixels - curpix;
+            let max_length = pixels_remaining.min(MAX_CHUNK_LENGTH);
+            let mut run_length = 1;
+
+            if max_length > 1 && pixel(curpix) == pixel(curpix + 1) {
+                // rle packet: extend while pixels repeat the first one
+                while run_length < max_length && pixel(curpix + run_length) == pixel(curpix) {
+                    run_length += 1;
+                }
+
+                writer.write_all(&[0b1000_0000 | (run_length - 1) as u8])?;
+                writer.write_all(pixel(curpix))?;
+            } else {
+                // raw packet: extend until the next pixel starts a run
+                while run_length < max_length {
+                    let next = curpix + run_length;
+
+                    if next + 1 < npixels && pixel(next) == pixel(next + 1) {
+                        break;
+                    }
+
+                    run_length += 1;
+                }
+
+                writer.write_all(&[(run_length - 1) as u8])?;
+                writer.write_all(&data[curpix * bytespp..(curpix + run_length) * bytespp])?;
+            }
+
+            curpix += run_length;
+        }
+
+        Ok(())
+    }
+
+    /// Fill `buf` from `input`, reporting how many bytes were available on a short read
+    fn read_data<T: std::io::Read>(input: &mut T, buf: &mut [u8]) -> Result<(), TGAError> {
+        let mut got = 0;
+
+        while got < buf.len() {
+            match input.read(&mut buf[got..]) {
+                Ok(0) => {
+                    return Err(TGAError::TruncatedData {
+                        expected: buf.len(),
+                        got,
+                    })
+                }
+                Ok(n) => got += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(TGAError::Io(e)),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn load_rle_data<T: std::io::Read>(
+        input: &mut T,
+        data: &mut [u8],
+        image_param: &(u16, u16, u8),
+    ) -> Result<(), TGAError> {
+        let mut current_pixel = 0usize;
+        let mut current_offset = 0usize;
+        let (height, width, bytespp) = image_param;
+        let pixel_count = *height as usize * *width as usize;
+        let mut header_buf = [0u8; 1];
+        let expected = data.len();
+        let truncated = |read_offset: usize, e: TGAError| match e {
+            TGAError::TruncatedData { got, .. } => TGAError::TruncatedData {
+                expected,
+                got: read_offset + got,
+            },
+            e => e,
+        };
+
+        while current_pixel < pixel_count {
+            TGAImage::read_data(input, &mut header_buf)
+                .map_err(|e| truncated(current_offset, e))?;
+            let header = u8::from_ne_bytes(header_buf);
+            let packet_size = (header & 0b0111_1111) as usize + 1;
+
+            if current_pixel + packet_size > pixel_count {
+                return Err(TGAError::RleOverrun);
+            }
+
+            if header & 0b1000_0000 == 0 {
+                // raw packet
+                let offset_end = current_offset + *bytespp as usize * packet_size;
+                TGAImage::read_data(input, &mut data[current_offset..offset_end])
+                    .map_err(|e| truncated(current_offset, e))?;
+                current_offset = offset_end;
+            } else {
+                // rle packet
+                let origin_offset = current_offset;
+                current_offset += *bytespp as usize;
+                TGAImage::read_data(input, &mut data[origin_offset..current_offset])
+                    .map_err(|e| truncated(origin_offset, e))?;
+
+                for _ in 1..packet_size {
+                    data.copy_within(
+                        origin_offset..(origin_offset + *bytespp as usize),
+                        current_offset,
+                    );
+                    current_offset += *bytespp as usize;
+                }
+            }
+
+            current_pixel += packet_size;
+        }
+
+        Ok(())
+    }
+
+    /// Read a TGA image along with its extension area metadata from a file
+    pub fn read_tga_file(filename: &str) -> Result<Self, TGAError> {
+        let file = std::fs::File::open(filename)?;
+        let mut reader = std::io::BufReader::new(file);
+
+        TGAImage::read_from_seekable(&mut reader)
+    }
+
+    /// Read a TGA image along with its extension area metadata from a seekable source
+    ///
+    /// The TGA 2.0 footer is looked up at the end of the stream, so the image must be
+    /// the last thing in it for the metadata to be found. The stream is left right
+    /// after the pixel data.
+    pub fn read_from_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, TGAError> {
+        let start = reader.stream_position()?;
+        let mut image = TGAImage::read_from(reader)?;
+        let data_end = reader.stream_position()?;
+
+        image.metadata = TGAMetadata::read_from_end(reader, start, data_end)?;
+        reader.seek(SeekFrom::Start(data_end))?;
+
+        Ok(image)
+    }
+
+    /// Read a TGA image from any `Read` source
+    ///
+    /// Reading stops right after the pixel data, so anything following the image is
+    /// left in the reader and the extension area metadata is not read, see
+    /// [`TGAImage::read_from_seekable`].
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, TGAError> {
+        let mut header_bytes = [0u8; TGAHeader::SIZE];
+
+        TGAImage::read_data(reader, &mut header_bytes)?;
+
+        let header = TGAHeader::from_bytes(&header_bytes);
+        let (height, width) = (header.height, header.width);
+
+        if height == 0 || width == 0 {
+            return Err(TGAError::InvalidHeader {
+                reason: "image dimensions must be non-zero",
+            });
+        }
+
+        let mut image_id = vec![0u8; header.idlength as usize];
+
+        TGAImage::read_data(reader, &mut image_id)?;
+
+        let datatype = header.datatypecode;
+        let (data, bytespp) = match TGAImageType::from_u8(datatype) {
+            Some(TGAImageType::UncompressedColor) | Some(TGAImageType::RLEColor) => {
+                TGAImage::load_color_mapped_data(reader, &header)?
+            }
+            Some(TGAImageType::UncompressedTrueColor) | Some(TGAImageType::RLETrueColor)
+                if header.bitsperpixel == 15 || header.bitsperpixel == 16 =>
+            {
+                TGAImage::skip_color_map(reader, &header)?;
+
+                let format = if header.bitsperpixel == 16 && header.imagedescriptor & 0x0f != 0 {
+                    TGAImageFormat::RGBA
+                } else {
+                    TGAImageFormat::RGB
+                };
+                let mut packed = vec![0u8; height as usize * width as usize * 2];
+
+                TGAImage::load_pixel_data(reader, &header, &mut packed, 2)?;
+
+                (TGAImage::expand_16bit(&packed, format), format)
+            }
+            Some(TGAImageType::UncompressedTrueColor)
+            | Some(TGAImageType::UncompressedBW)
+            | Some(TGAImageType::RLETrueColor)
+            | Some(TGAImageType::RLEBW) => {
+                let bitsperpixel = header.bitsperpixel >> 3;
+                let is_valid_bpp = header.bitsperpixel & 0b111 == 0
+                    && matches!(
+                        TGAImageFormat::try_from(bitsperpixel),
+                        Ok(TGAImageFormat::Grayscale)
+                            | Ok(TGAImageFormat::Grayscale16)
+                            | Ok(TGAImageFormat::RGB)
+                            | Ok(TGAImageFormat::RGBA)
+                    );
+
+                if !is_valid_bpp {
+                    return Err(TGAError::UnsupportedBitDepth(header.bitsperpixel));
+                }
+
+                TGAImage::skip_color_map(reader, &header)?;
+
+                let mut data = vec![0u8; height as usize * width as usize * bitsperpixel as usize];
+
+                TGAImage::load_pixel_data(reader, &header, &mut data, bitsperpixel)?;
+
+                (data, TGAImageFormat::try_from(bitsperpixel)?)
+            }
+            _ => return Err(TGAError::UnsupportedImageType(datatype)),
+        };
+
+        let mut image = TGAImage {
+            data,
+            width: width as u32,
+            height: height as u32,
+            bytespp,
+            x_origin: header.x_origin,
+            y_origin: header.y_origin,
+            image_id,
+            alpha_bits: Some(header.imagedescriptor & 0x0f),
+            metadata: None,
+            origin: Origin::from_descriptor(header.imagedescriptor),
+        };
+
+        let image_descriptor = header.imagedescriptor;
+
+        if image.origin == Origin::TopLeft {
+            image.flip_vertically();
+        }
+
+        if image_descriptor & 0b1_0000 != 0 {
+            image.flip_horizontally();
+        }
+
+        Ok(image)
+    }
+
+    /// Read raw or RLE encoded pixel data depending on the header datatype code
+    fn load_pixel_data<R: Read>(
+        reader: &mut R,
+        header: &TGAHeader,
+        data: &mut [u8],
+        bytespp: u8,
+    ) -> Result<(), TGAError> {
+        const RLE_FLAG: u8 = 0b1000;
+
+        if header.datatypecode & RLE_FLAG == 0 {
+            TGAImage::read_data(reader, data)
+        } else {
+            TGAImage::load_rle_data(reader, data, &(header.height, header.width, bytespp))
+        }
+    }
+
+    /// Expand 16-bit A1R5G5B5 pixels into RGB or RGBA pixels
+    fn expand_16bit(packed: &[u8], format: TGAImageFormat) -> Vec<u8> {
+        let expand = |v: u16| {
+            let v = (v & 0x1f) as u8;
+            (v << 3) | (v >> 2)
+        };
+        let mut data = Vec::with_capacity(packed.len() / 2 * format as usize);
+
+        for pixel in packed.chunks_exact(2) {
+            let v = u16::from_le_bytes([pixel[0], pixel[1]]);
+
+            data.extend_from_slice(&[expand(v), expand(v >> 5), expand(v >> 10)]);
+
+            if format == TGAImageFormat::RGBA {
+                data.push(if v & 0x8000 != 0 { 255 } else { 0 });
+            }
+        }
+
+        data
+    }
+
+    /// Read the color map described by the header and return its entries along with
+    /// the pixel format each entry expands to
+    fn read_color_map<R: Read>(
+        reader: &mut R,
+        header: &TGAHeader,
+    ) -> Result<(Vec<u8>, TGAImageFormat), TGAError> {
+        let format = match header.colormapdepth {
+            15 | 16 => {
+                let mut packed = vec![0u8; header.colormaplength as usize * 2];
+
+                TGAImage::read_data(reader, &mut packed)?;
+
+                return Ok((
+                    TGAImage::expand_16bit(&packed, TGAImageFormat::RGB),
+                    TGAImageFormat::RGB,
+                ));
+            }
+            24 => TGAImageFormat::RGB,
+            32 => TGAImageFormat::RGBA,
+            depth => return Err(TGAError::UnsupportedBitDepth(depth)),
+        };
+        let mut color_map = vec![0u8; header.colormaplength as usize * format as usize];
+
+        TGAImage::read_data(reader, &mut color_map)?;
+
+        Ok((color_map, format))
+    }
+
+    /// Skip the color map of a true-color image
+    ///
+    /// A color map may be present even when the image does not use it. Its entries
+    /// are not decoded, so palettes of any depth are accepted.
+    fn skip_color_map<R: Read>(reader: &mut R, header: &TGAHeader) -> Result<(), TGAError> {
+        if header.colormaptype != 1 {
+            return Ok(());
+        }
+
+        let entry_size = (header.colormapdepth as usize).div_ceil(8);
+        let mut color_map = vec![0u8; header.colormaplength as usize * entry_size];
+
+        TGAImage::read_data(reader, &mut color_map)
+    }
+
+    /// Read color-mapped (palettized) pixel data and expand it to true color
+    fn load_color_mapped_data<R: Read>(
+        reader: &mut R,
+        header: &TGAHeader,
+    ) -> Result<(Vec<u8>, TGAImageFormat), TGAError> {
+        if header.colormaptype != 1 {
+            return Err(TGAError::InvalidHeader {
+                reason: "color-mapped image without a color map",
+            });
+        }
+
+        if header.bitsperpixel != 8 && header.bitsperpixel != 16 {
+            return Err(TGAError::UnsupportedBitDepth(header.bitsperpixel));
+        }
+
+        let (color_map, format) = TGAImage::read_color_map(reader, header)?;
+        let index_size = header.bitsperpixel as usize >> 3;
+        let pixel_count = header.width as usize * header.height as usize;
+        let mut indexes = vec![0u8; pixel_count * index_size];
+
+        TGAImage::load_pixel_data(reader, header, &mut indexes, index_size as u8)?;
+
+        let entry_size = format as usize;
+        let mut data = Vec::with_capacity(pixel_count * entry_size);
+
+        for index in indexes.chunks_exact(index_size) {
+            let index = if index_size == 1 {
+                index[0] as u16
+            } else {
+                u16::from_le_bytes([index[0], index[1]])
+            };
+            let entry = index
+                .checked_sub(header.colormaporigin)
+                .filter(|entry| *entry < header.colormaplength)
+                .ok_or(TGAError::ColorMapIndexOutOfRange(index))? as usize;
+
+            data.extend_from_slice(&color_map[entry * entry_size..(entry + 1) * entry_size]);
+        }
+
+        Ok((data, format))
+    }
+
+    /// Write an image into a TGA file with rows stored from `origin`
+    pub fn write_tga_file_with_origin(
+        &self,
+        filename: &str,
+        origin: Origin,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        TGAImage::write_file(filename, |writer| {
+            self.write_to_with_origin(writer, origin, rle)
+        })
+    }
+
+    /// Write an image into a TGA file
+    ///
+    /// `vflip` stores rows from the bottom. Otherwise the file is marked as stored
+    /// from the top without reordering the rows, so readers see the picture flipped.
+    #[deprecated(note = "use `write_tga_file_with_origin`, `vflip = true` is `Origin::BottomLeft`")]
+    pub fn write_tga_file(&self, filename: &str, vflip: bool, rle: bool) -> Result<(), TGAError> {
+        TGAImage::write_file(filename, |writer| {
+            self.write_layout(writer, RowLayout::from_vflip(vflip), rle)
+        })
+    }
+
+    /// Write an image in TGA format into any `Write` sink with rows stored from `origin`
+    pub fn write_to_with_origin<W: Write>(
+        &self,
+        out: &mut W,
+        origin: Origin,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        self.write_layout(out, RowLayout::from(origin), rle)
+    }
+
+    /// Write an image in TGA format into any `Write` sink, see [`TGAImage::write_tga_file`]
+    /// for the meaning of `vflip`
+    #[deprecated(note = "use `write_to_with_origin`, `vflip = true` is `Origin::BottomLeft`")]
+    pub fn write_to<W: Write>(&self, out: &mut W, vflip: bool, rle: bool) -> Result<(), TGAError> {
+        self.write_layout(out, RowLayout::from_vflip(vflip), rle)
+    }
+
+    fn write_layout<W: Write>(
+        &self,
+        out: &mut W,
+        layout: RowLayout,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        fn get_data_type_code(image_fmt: TGAImageFormat, rle: bool) -> u8 {
+            let rle_val = if rle { 11 } else { 3 };
+            if image_fmt.is_grayscale() {
+                rle_val
+            } else {
+                rle_val - 1
+            }
+        }
+
+        let (width, height) = self.header_dimensions()?;
+        let header = TGAHeader {
+            bitsperpixel: (self.bytespp as u8) << 3,
+            width,
+            height,
+            datatypecode: get_data_type_code(self.bytespp, rle),
+            imagedescriptor: TGAImage::default_alpha_bits(self.bytespp),
+            ..TGAHeader::default()
+        };
+
+        self.write_payload(out, &header, &self.data, self.bytespp as usize, layout, rle)
+    }
+
+    /// Write an image into a TGA file with rows stored from `origin`, using RLE only
+    /// when it is smaller than raw data
+    pub fn write_tga_file_auto_with_origin(
+        &self,
+        filename: &str,
+        origin: Origin,
+    ) -> Result<(), TGAError> {
+        TGAImage::write_file(filename, |writer| {
+            self.write_to_auto_with_origin(writer, origin)
+        })
+    }
+
+    /// Write an image into a TGA file using RLE only when it is smaller than raw data
+    #[deprecated(note = "use `write_tga_file_auto_with_origin`")]
+    pub fn write_tga_file_auto(&self, filename: &str, vflip: bool) -> Result<(), TGAError> {
+        TGAImage::write_file(filename, |writer| {
+            self.write_auto_layout(writer, RowLayout::from_vflip(vflip))
+        })
+    }
+
+    /// Write an image in TGA format into any `Write` sink with rows stored from `origin`,
+    /// using RLE only when it is smaller than raw data
+    pub fn write_to_auto_with_origin<W: Write>(
+        &self,
+        out: &mut W,
+        origin: Origin,
+    ) -> Result<(), TGAError> {
+        self.write_auto_layout(out, RowLayout::from(origin))
+    }
+
+    /// Write an image in TGA format into any `Write` sink using RLE only when it is
+    /// smaller than raw data
+    #[deprecated(note = "use `write_to_auto_with_origin`")]
+    pub fn write_to_auto<W: Write>(&self, out: &mut W, vflip: bool) -> Result<(), TGAError> {
+        self.write_auto_layout(out, RowLayout::from_vflip(vflip))
+    }
+
+    fn write_auto_layout<W: Write>(&self, out: &mut W, layout: RowLayout) -> Result<(), TGAError> {
+        let rle = self.rle_encoded_size() < self.data.len();
+
+        self.write_layout(out, layout, rle)
+    }
+
+    /// Size in bytes of RLE encoded pixel data, excluding the header and footer
+    pub fn rle_encoded_size(&self) -> usize {
+        let mut counter = CountingWriter::new(std::io::sink());
+
+        TGAImage::unload_rle_data(&self.data, self.bytespp as usize, &mut counter)
+            .expect("Writing into a sink cannot fail");
+
+        counter.count
+    }
+
+    /// Write an image into a 16-bit (A1R5G5B5) TGA file with rows stored from `origin`
+    pub fn write_tga_file_16bit_with_origin(
+        &self,
+        filename: &str,
+        origin: Origin,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        TGAImage::write_file(filename, |writer| {
+            self.write_to_16bit_with_origin(writer, origin, rle)
+        })
+    }
+
+    /// Write an image into a 16-bit (A1R5G5B5) TGA file
+    #[deprecated(note = "use `write_tga_file_16bit_with_origin`")]
+    pub fn write_tga_file_16bit(
+        &self,
+        filename: &str,
+        vflip: bool,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        TGAImage::write_file(filename, |writer| {
+            self.write_16bit_layout(writer, RowLayout::from_vflip(vflip), rle)
+        })
+    }
+
+    /// Write an image as 16-bit (A1R5G5B5) TGA into any `Write` sink with rows stored
+    /// from `origin`
+    ///
+    /// Color channels are quantized down to 5 bits. The alpha channel of an RGBA image
+    /// is stored in the attribute bit (set for alpha >= 128).
+    pub fn write_to_16bit_with_origin<W: Write>(
+        &self,
+        out: &mut W,
+        origin: Origin,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        self.write_16bit_layout(out, RowLayout::from(origin), rle)
+    }
+
+    /// Write an image as 16-bit (A1R5G5B5) TGA into any `Write` sink
+    #[deprecated(note = "use `write_to_16bit_with_origin`")]
+    pub fn write_to_16bit<W: Write>(
+        &self,
+        out: &mut W,
+        vflip: bool,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        self.write_16bit_layout(out, RowLayout::from_vflip(vflip), rle)
+    }
+
+    fn write_16bit_layout<W: Write>(
+        &self,
+        out: &mut W,
+        layout: RowLayout,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        let (width, height) = self.header_dimensions()?;
+        let bytespp = self.bytespp as usize;
+        let has_alpha = self.bytespp == TGAImageFormat::RGBA;
+        let data: Vec<u8> = self
+            .data
+            .chunks_exact(bytespp)
+            .flat_map(|pixel| {
+                let mut bgra = [0u8; 4];
+
+                convert_pixel(pixel, &mut bgra);
+
+                let [b, g, r, a] = bgra;
+                let packed = ((has_alpha && a >= 128) as u16) << 15
+                    | ((r >> 3) as u16) << 10
+                    | ((g >> 3) as u16) << 5
+                    | (b >> 3) as u16;
+
+                packed.to_le_bytes()
+            })
+            .collect();
+        let header = TGAHeader {
+            bitsperpixel: 16,
+            width,
+            height,
+            datatypecode: if rle { 10 } else { 2 },
+            imagedescriptor: has_alpha as u8,
+            ..TGAHeader::default()
+        };
+
+        self.write_payload(out, &header, &data, 2, layout, rle)
+    }
+
+    /// Image dimensions for the TGA header, which has 16 bits for each of them
+    fn header_dimensions(&self) -> Result<(u16, u16), TGAError> {
+        match (u16::try_from(self.width), u16::try_from(self.height)) {
+            (Ok(width), Ok(height)) => Ok((width, height)),
+            _ => Err(TGAError::InvalidHeader {
+                reason: "image dimensions must be in 1..=65535",
+            }),
+        }
+    }
+
+    /// Write the header, image ID, raw or RLE encoded pixel data and the TGA 2.0 footer
+    fn write_payload<W: Write>(
+        &self,
+        out: &mut W,
+        header: &TGAHeader,
+        data: &[u8],
+        bytespp: usize,
+        layout: RowLayout,
+        rle: bool,
+    ) -> Result<(), TGAError> {
+        const DEVELOPER_AREA_REF: [u8; 4] = [0u8; 4];
+
+        let out = &mut CountingWriter::new(out);
+        let header = TGAHeader {
+            idlength: self.image_id.len() as u8,
+            x_origin: self.x_origin,
+            y_origin: self.y_origin,
+            imagedescriptor: header.imagedescriptor | layout.descriptor,
+            ..*header
+        };
+        let reversed: Vec<u8>;
+        let data = if layout.reverse_rows {
+            reversed = data
+                .chunks_exact(self.width as usize * bytespp)
+                .rev()
+                .flatten()
+                .copied()
+                .collect();
+            &reversed
+        } else {
+            data
+        };
+
+        out.write_all(&header.to_bytes())?;
+        out.write_all(&self.image_id)?;
+
+        if !rle {
+            out.write_all(data)?;
+        } else {
+            TGAImage::unload_rle_data(data, bytespp, out)?;
+        }
+
+        let extension_area_ref = match &self.metadata {
+            Some(metadata) => {
+                let offset = out.count as u32;
+
+                out.write_all(&metadata.to_bytes())?;
+                offset
+            }
+            None => 0,
+        };
+
+        out.write_all(&extension_area_ref.to_le_bytes())?;
+        out.write_all(&DEVELOPER_AREA_REF)?;
+        out.write_all(&FOOTER_SIGNATURE)?;
+
+        Ok(())
+    }
+
+    /// Create a file and flush everything written into it by `write`
+    fn write_file<F>(filename: &str, write: F) -> Result<(), TGAError>
+    where
+        F: FnOnce(&mut std::io::BufWriter<std::fs::File>) -> Result<(), TGAError>,
+    {
+        let file = std::fs::File::create(filename)?;
+        let mut writer = std::io::BufWriter::new(file);
+
+        write(&mut writer)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Encode an image in TGA format into an in-memory buffer with rows stored from `origin`
+    pub fn encode_to_vec_with_origin(
+        &self,
+        origin: Origin,
+        rle: bool,
+    ) -> Result<Vec<u8>, TGAError> {
+        let mut buffer = Vec::new();
+
+        self.write_to_with_origin(&mut buffer, origin, rle)?;
+
+        Ok(buffer)
+    }
+
+    /// Encode an image in TGA format into an in-memory buffer, see
+    /// [`TGAImage::write_tga_file`] for the meaning of `vflip`
+    #[deprecated(note = "use `encode_to_vec_with_origin`, `vflip = true` is `Origin::BottomLeft`")]
+    pub fn encode_to_vec(&self, vflip: bool, rle: bool) -> Result<Vec<u8>, TGAError> {
+        let mut buffer = Vec::new();
+
+        self.write_layout(&mut buffer, RowLayout::from_vflip(vflip), rle)?;
+
+        Ok(buffer)
+    }
+
+    /// Print pixel data as hex to stdout using [`DumpOptions::default`]
+    pub fn dump(&self) {
+        let stdout = std::io::stdout();
+
+        self.dump_to(&mut stdout.lock(), DumpOptions::default())
+            .expect("Cannot write image dump to stdout");
+    }
+
+    /// Write pixel data as hex, one or more lines per image row
+    pub fn dump_to<W: Write>(&self, out: &mut W, opts: DumpOptions) -> Result<(), TGAError> {
+        let (x, y, width, height) = opts.region.unwrap_or((0, 0, self.width, self.height));
+        let width = self.width.saturating_sub(x).min(width) as usize;
+        let height = self.height.saturating_sub(y).min(height);
+        let bytespp = (self.bytespp as usize).max(1);
+        let unit = if opts.group_pixels { bytespp } else { 1 };
+        let units_per_line = match opts.bytes_per_line {
+            0 => usize::MAX,
+            n => (n / unit).max(1),
+        };
+
+        if width == 0 || self.data.is_empty() {
+            return Ok(());
+        }
+
+        for row in y..y + height {
+            let offset = self.pixel_offset(x, row);
+            let bytes = &self.data[offset..offset + width * bytespp];
+            let units: Vec<_> = bytes.chunks(unit).collect();
+
+            for (i, line) in units.chunks(units_per_line).enumerate() {
+                if opts.row_indices {
+                    if i == 0 {
+                        write!(out, "{:4}: ", row)?;
+                    } else {
+                        write!(out, "      ")?;
+                    }
+                }
+
+                for (j, unit) in line.iter().enumerate() {
+                    if j != 0 {
+                        write!(out, " ")?;
+                    }
+
+                    for b in unit.iter() {
+                        write!(out, "{:02x}", b)?;
+                    }
+                }
+
+                writeln!(out)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Formatting options of [`TGAImage::dump_to`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DumpOptions {
+    /// Maximum number of bytes on a line, `0` puts every image row on a single line
+    pub bytes_per_line: usize,
+    /// Print bytes of a pixel without separators and wrap lines on pixel boundaries
+    pub group_pixels: bool,
+    /// Prefix every image row with its `y` coordinate
+    pub row_indices: bool,
+    /// Rectangle `(x, y, width, height)` to dump, clipped to the image bounds
+    pub region: Option<(u32, u32, u32, u32)>,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        DumpOptions {
+            bytes_per_line: 16,
+            group_pixels: true,
+            row_indices: false,
+            region: None,
+        }
+    }
+}
+
+/// Convert raw bytes of a single pixel between formats given by the slice lengths
+fn convert_pixel(src: &[u8], dst: &mut [u8]) {
+    let bgra = match *src {
+        [v] => [v, v, v, 255],
+        [lo, hi] => {
+            let v = gray16_to_gray8(u16::from_le_bytes([lo, hi]));
+
+            [v, v, v, 255]
+        }
+        [b, g, r] => [b, g, r, 255],
+        [b, g, r, a] => [b, g, r, a],
+        _ => [0, 0, 0, 0],
+    };
+
+    match dst {
+        [v] => *v = rec601_luma(bgra[2], bgra[1], bgra[0]),
+        [lo, hi] => {
+            [*lo, *hi] = (rec601_luma(bgra[2], bgra[1], bgra[0]) as u16 * 257).to_le_bytes();
+        }
+        _ => dst.copy_from_slice(&bgra[..dst.len()]),
+    }
+}
+
+/// Store a color into the bytes of an image pixel
+///
+/// Grayscale16 pixels get the color luminance scaled to 16 bits like
+/// [`TGAImage::set`] does, other formats take the color bytes as they are.
+pub(crate) fn write_pixel(color: &TGAColor, pixel: &mut [u8]) {
+    if pixel.len() == 2 {
+        convert_pixel(color.channels(), pixel);
+    } else {
+        pixel.copy_from_slice(&color.bgra[..pixel.len()]);
+    }
+}
+
+/// Scale a 16-bit gray level down to 8 bits with rounding
+fn gray16_to_gray8(v: u16) -> u8 {
+    ((v as u32 + 128) / 257) as u8
+}
+
+/// Perceived brightness of a color using Rec.601 weights
+fn rec601_luma(r: u8, g: u8, b: u8) -> u8 {
+    ((299 * r as u32 + 587 * g as u32 + 114 * b as u32 + 500) / 1000) as u8
+}
+
+/// Raw bytes of a pixel, panics for coordinates outside of the image
+impl Index<(u32, u32)> for TGAImage {
+    type Output = [u8];
+
+    fn index(&self, (x, y): (u32, u32)) -> &Self::Output {
+        assert!(self.contains(x, y), "{}", self.out_of_bounds(x, y));
+        let offset = self.pixel_offset(x, y);
+
+        &self.data[offset..offset + self.bytespp as usize]
+    }
+}
+
+/// Images are equal when they have the same dimensions, format and pixel data
+impl PartialEq for TGAImage {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width
+            && self.height == other.height
+            && self.bytespp == other.bytespp
+            && self.data == other.data
+    }
+}
+
+impl Debug for TGAImage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TGAImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("bytespp", &self.bytespp)
+            .field("x_origin", &self.x_origin)
+            .field("y_origin", &self.y_origin)
+            .finish()
+    }
+}
+
+#[cfg(test)]
 mod tests_tgacolor {
     use super::*;
 
     #[test]
-    fn tgacolor_default() {
-        let default = TGAColor::default();
-
-        for e in &default.bgra {
-            assert_eq!(*e, 0);
+    fn tgacolor_default() {
+        let default = TGAColor::default();
+
+        for e in &default.bgra {
+            assert_eq!(*e, 0);
+        }
+
+        assert_eq!(default.bytespp, 0);
+    }
+
+    #[test]
+    fn tgacolor_rgb() {
+        let tgacolor = TGAColor::new_rgb(1, 1, 1);
+
+        for e in tgacolor.bgra.iter().take(3) {
+            assert_eq!(*e, 1);
+        }
+
+        assert_eq!(tgacolor.bgra[3], 255);
+        assert_eq!(tgacolor.bytespp, 4);
+
+        let tgacolor = TGAColor::new_rgb(255, 255, 255);
+
+        for e in tgacolor.bgra.iter().take(3) {
+            assert_eq!(*e, 255);
+        }
+
+        assert_eq!(tgacolor.bgra[3], 255);
+        assert_eq!(tgacolor.bytespp, 4);
+    }
+
+    fn index_to_rgba_color_channel(index: usize) -> Result<ColorChannel, &'static str> {
+        match index {
+            0 => Ok(ColorChannel::R),
+            1 => Ok(ColorChannel::G),
+            2 => Ok(ColorChannel::B),
+            3 => Ok(ColorChannel::A),
+            _ => Err("Unknown color channel"),
+        }
+    }
+
+    fn index_to_bgra_color_channel(index: usize) -> Result<ColorChannel, &'static str> {
+        match index {
+            0 => Ok(ColorChannel::B),
+            1 => Ok(ColorChannel::G),
+            2 => Ok(ColorChannel::R),
+            3 => Ok(ColorChannel::A),
+            _ => Err("Unknown color channel"),
+        }
+    }
+
+    #[test]
+    fn tgacolor_rgba() {
+        let tgacolor = TGAColor::new_rgba(1, 2, 3, 4);
+
+        for index in 0..tgacolor.bgra.len() {
+            let color_index = index_to_rgba_color_channel(index).unwrap();
+            assert_eq!(tgacolor[color_index], index as u8 + 1);
+        }
+
+        assert_eq!(tgacolor.bytespp, 4);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn tgacolor_iter() {
+        let bgra1 = [1u8];
+        let bgra2 = [1u8, 2];
+        let bgra3 = [1u8, 2, 3];
+        let bgra4 = [1u8, 2, 3, 4];
+        let checker = |arr: &[u8]| {
+            let tgacolor = TGAColor::new_from_iter(arr.iter(), arr.len() as u8);
+
+            for index in 0..arr.len() {
+                let color_index = index_to_bgra_color_channel(index).unwrap();
+                let val = tgacolor[color_index];
+
+                assert_eq!(val, index as u8 + 1);
+            }
+
+            assert_eq!(tgacolor.bytespp, arr.len() as u8);
+        };
+
+        checker(&bgra1);
+        checker(&bgra2);
+        checker(&bgra3);
+        checker(&bgra4);
+    }
+
+    #[test]
+    fn tgacolor_from_slice() {
+        let bgra = [1u8, 2, 3, 4];
+
+        for len in 1..=4 {
+            let color = TGAColor::from_slice(&bgra[..len]).unwrap();
+
+            assert_eq!(color.bytespp(), len as u8);
+            assert_eq!(color.channels(), &bgra[..len]);
+        }
+
+        assert!(matches!(
+            TGAColor::from_slice(&[]),
+            Err(TGAError::InvalidColorLength(0))
+        ));
+        assert!(matches!(
+            TGAColor::from_slice(&[0; 5]),
+            Err(TGAError::InvalidColorLength(5))
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    #[allow(deprecated)]
+    fn tgacolor_iter_fails() {
+        let bgra5 = [0u8; 4];
+        let bgra6 = [0u8; 6];
+
+        let checker = |arr: &[u8]| {
+            TGAColor::new_from_iter(arr.iter(), arr.len() as u8);
+        };
+
+        checker(&bgra5);
+        checker(&bgra6);
+    }
+
+    #[test]
+    fn tgacolor_index_access() {
+        let tgacolor = TGAColor::new_rgba(128, 128, 128, 128);
+
+        for i in 0..4 {
+            let color_index = index_to_rgba_color_channel(i).unwrap();
+            assert_eq!(tgacolor[color_index], 128);
+        }
+
+        let mut tgacolor = TGAColor::new_rgba(128, 128, 128, 128);
+
+        for i in 0..4 {
+            let color_index = index_to_rgba_color_channel(i).unwrap();
+
+            tgacolor[color_index] += 1;
+            assert_eq!(tgacolor[color_index], 129);
+        }
+    }
+
+    #[test]
+    fn tgacolor_intensity() {
+        let tgacolor = TGAColor::new_rgba(128, 128, 128, 128);
+        let expected = 128.0 * 0.5;
+        let new_tgacolor: TGAColor = tgacolor * 0.5;
+
+        for i in 0..4 {
+            let color_index = index_to_rgba_color_channel(i).unwrap();
+
+            assert_eq!(new_tgacolor[color_index], expected as u8);
+        }
+    }
+    #[test]
+    fn tgacolor_from_u32() {
+        let color = TGAColor::from_rgba_u32(0x1e90_ff80);
+
+        assert_eq!(color.bgra, [0xff, 0x90, 0x1e, 0x80]);
+        assert_eq!(color.to_rgba_u32(), 0x1e90_ff80);
+        assert_eq!(TGAColor::from_rgb_u32(0x1e90ff).to_rgba_u32(), 0x1e90_ffff);
+
+        for &value in &[0u32, 0xffff_ffff, 0x0102_0304, 0xdead_beef] {
+            assert_eq!(TGAColor::from_rgba_u32(value).to_rgba_u32(), value);
+        }
+    }
+
+    #[test]
+    fn tgacolor_from_str() {
+        for s in &["#1E90FF", "#1e90ff", "1e90FF", "#1E90FFff", "1e90ffFF"] {
+            let color: TGAColor = s.parse().unwrap();
+
+            assert_eq!(color.to_rgba_u32(), 0x1e90_ffff, "{}", s);
+        }
+
+        assert_eq!("#00000080".parse::<TGAColor>().unwrap().to_rgba_u32(), 0x80);
+    }
+
+    #[test]
+    fn tgacolor_from_str_invalid() {
+        let cases = [
+            ("", ParseColorError::InvalidLength(0)),
+            ("#", ParseColorError::InvalidLength(0)),
+            ("#fff", ParseColorError::InvalidLength(3)),
+            ("#1e90ff0", ParseColorError::InvalidLength(7)),
+            ("#1e90ff0000", ParseColorError::InvalidLength(10)),
+            ("##1e90ff", ParseColorError::InvalidDigit),
+            ("#1e90fg", ParseColorError::InvalidDigit),
+            ("+1e90ff", ParseColorError::InvalidDigit),
+            ("#1e90fé", ParseColorError::InvalidDigit),
+        ];
+
+        for (s, err) in &cases {
+            assert_eq!(s.parse::<TGAColor>().unwrap_err(), *err, "{}", s);
+        }
+    }
+
+    #[test]
+    fn tgacolor_channel_accessors() {
+        let color = TGAColor::new_rgba(1, 2, 3, 4);
+
+        assert_eq!(color.bytespp(), 4);
+        assert_eq!(color.as_slice(), &[3, 2, 1, 4]);
+        assert_eq!(color.channels(), &[3, 2, 1, 4]);
+        assert_eq!((color.r(), color.g(), color.b(), color.a()), (1, 2, 3, 4));
+
+        let gray = TGAColor::from_slice(&[7]).unwrap();
+
+        assert_eq!(gray.bytespp(), 1);
+        assert_eq!(gray.as_slice(), &[7, 0, 0, 0]);
+        assert_eq!(gray.channels(), &[7]);
+        assert!(TGAColor::default().channels().is_empty());
+    }
+
+    #[test]
+    fn tgacolor_eq() {
+        let mut rgb = TGAColor::from_slice(&[1, 2, 3]).unwrap();
+        let other = TGAColor::from_slice(&[1, 2, 3]).unwrap();
+
+        rgb[ColorChannel::A] = 42;
+
+        assert_eq!(rgb, other);
+        assert_ne!(rgb, TGAColor::from_slice(&[1, 2, 4]).unwrap());
+        assert_ne!(rgb, TGAColor::from_slice(&[1, 2, 3, 0]).unwrap());
+        assert_eq!(TGAColor::new_rgb(3, 2, 1), TGAColor::new_rgba(3, 2, 1, 255));
+    }
+
+    #[test]
+    fn tgacolor_luma() {
+        let cases = [
+            (TGAColor::new_rgb(255, 0, 0), 76),
+            (TGAColor::new_rgb(0, 255, 0), 150),
+            (TGAColor::new_rgb(0, 0, 255), 29),
+            (TGAColor::new_rgba(255, 255, 255, 0), 255),
+            (TGAColor::from_slice(&[42]).unwrap(), 42),
+        ];
+
+        for (color, expected) in &cases {
+            assert!((color.luma() as i16 - *expected as i16).abs() <= 1);
+            assert!((color.luma_f32() - *expected as f32 / 255.0).abs() <= 1.0 / 255.0);
+        }
+
+        assert_eq!(
+            TGAColor::new_rgba(10, 20, 30, 0).luma(),
+            TGAColor::new_rgb(10, 20, 30).luma()
+        );
+    }
+
+    #[test]
+    fn tgacolor_to_grayscale() {
+        for value in (0..=255u32).step_by(5) {
+            let color = TGAColor::from_rgb_u32(value * 0x010203 % 0x100_0000);
+            let gray = color.to_grayscale();
+
+            assert_eq!(gray.bytespp(), 1);
+            assert_eq!(gray.channels(), &[color.luma()]);
+            assert_eq!(gray.to_grayscale(), gray);
+        }
+    }
+
+    #[test]
+    fn tgacolor_add() {
+        let a = TGAColor::new_rgba(10, 200, 0, 255);
+        let b = TGAColor::from_slice(&[5, 100, 7]).unwrap();
+        let sum = a + b;
+
+        assert_eq!(sum.bgra, [5, 255, 17, 255]);
+        assert_eq!(sum.bytespp, 4);
+    }
+
+    #[test]
+    fn tgacolor_lerp() {
+        let a = TGAColor::new_rgba(0, 100, 200, 255);
+        let b = TGAColor::new_rgba(100, 0, 200, 55);
+
+        assert_eq!(TGAColor::lerp(&a, &b, 0.0).bgra, a.bgra);
+        assert_eq!(TGAColor::lerp(&a, &b, 1.0).bgra, b.bgra);
+        assert_eq!(TGAColor::lerp(&a, &b, 0.25).bgra, [200, 75, 25, 205]);
+        assert_eq!(TGAColor::lerp(&a, &b, 2.0).bgra, b.bgra);
+    }
+
+    #[test]
+    fn tgacolor_weighted_sum() {
+        let red = TGAColor::new_rgb(255, 0, 0);
+        let green = TGAColor::new_rgb(0, 255, 0);
+        let gray = TGAColor::from_slice(&[90]).unwrap();
+        let mixed = TGAColor::weighted_sum(&[(red, 0.5), (green, 0.25), (gray, 1.0)]);
+
+        assert_eq!(mixed.bgra, [90, 64, 128, 191]);
+        assert_eq!(mixed.bytespp, 4);
+        assert_eq!(
+            TGAColor::weighted_sum(&[(red, 2.0), (green, -1.0)]).bgra,
+            [0, 0, 255, 255]
+        );
+        assert_eq!(TGAColor::weighted_sum(&[]).bgra, [0; 4]);
+    }
+
+    #[test]
+    fn tgacolor_scale_srgb() {
+        let white = TGAColor::new_rgba(255, 255, 255, 200);
+        let half = white.scale_srgb(0.5);
+
+        for &channel in &[ColorChannel::R, ColorChannel::G, ColorChannel::B] {
+            assert_eq!(half[channel], 188);
+        }
+
+        assert_eq!(half[ColorChannel::A], 200);
+        assert_eq!((white * 0.5f32)[ColorChannel::R], 127);
+    }
+
+    #[test]
+    fn tgacolor_linear_round_trip() {
+        for value in 0..=255u8 {
+            let color = TGAColor::new_rgba(value, value / 2, 255 - value, value);
+            let decoded = TGAColor::from_linear(color.to_linear());
+
+            assert_eq!(decoded.bgra, color.bgra);
+        }
+
+        assert_eq!(TGAColor::new_rgb(0, 0, 0).to_linear(), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(
+            TGAColor::from_linear([2.0, -1.0, 1.0, 0.0]).bgra,
+            [255, 0, 255, 0]
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests_tgaimage {
+    use super::*;
+
+    #[test]
+    fn tgaimage_getters() {
+        let image = TGAImage::try_new(100, 100, TGAImageFormat::Grayscale).unwrap();
+
+        assert_eq!(image.get_width(), 100);
+        assert_eq!(image.get_height(), 100);
+        assert_eq!(image.get_bytespp(), TGAImageFormat::Grayscale);
+    }
+
+    #[test]
+    fn tgaimage_buffer() {
+        let width = 100;
+        let height = 100;
+        let format = TGAImageFormat::Grayscale;
+        let image = TGAImage::try_new(width, height, format).unwrap();
+
+        assert_eq!(image.data.len(), (width * height * format as u32) as usize);
+
+        let format = TGAImageFormat::RGBA;
+        let image = TGAImage::try_new(100, 100, format).unwrap();
+
+        assert_eq!(image.data.len(), (width * height * format as u32) as usize);
+    }
+
+    #[test]
+    fn tgaimage_from_raw() {
+        let data = vec![7u8; 4 * 3 * 3];
+        let ptr = data.as_ptr();
+        let image = TGAImage::from_raw(4, 3, TGAImageFormat::RGB, data).unwrap();
+
+        assert_eq!(image.get_width(), 4);
+        assert_eq!(image.get_height(), 3);
+        assert_eq!(image.get_bytespp(), TGAImageFormat::RGB);
+        assert_eq!(image.as_bytes().as_ptr(), ptr);
+
+        let (width, height, format, data) = image.into_raw();
+
+        assert_eq!((width, height, format), (4, 3, TGAImageFormat::RGB));
+        assert_eq!(data.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn tgaimage_from_raw_size_mismatch() {
+        assert!(matches!(
+            TGAImage::from_raw(4, 3, TGAImageFormat::RGBA, vec![0u8; 4 * 3 * 3]),
+            Err(TGAError::InvalidBufferSize {
+                expected: 48,
+                got: 36
+            })
+        ));
+        assert!(TGAImage::from_raw(4, 3, TGAImageFormat::Grayscale, vec![0u8; 13]).is_err());
+    }
+
+    #[test]
+    fn tgaimage_degenerate_shapes() {
+        for &(width, height) in &[(0, 5), (5, 0), (0, 0)] {
+            assert!(matches!(
+                TGAImage::try_new(width, height, TGAImageFormat::RGB),
+                Err(TGAError::InvalidHeader { .. })
+            ));
+            assert!(matches!(
+                TGAImage::from_raw(width, height, TGAImageFormat::RGB, Vec::new()),
+                Err(TGAError::InvalidHeader { .. })
+            ));
+        }
+
+        assert!(matches!(
+            TGAImage::try_new(2, 2, TGAImageFormat::Unknown),
+            Err(TGAError::UnsupportedBitDepth(0))
+        ));
+        assert!(matches!(
+            TGAImage::from_raw(2, 2, TGAImageFormat::Unknown, Vec::new()),
+            Err(TGAError::UnsupportedBitDepth(0))
+        ));
+        assert_eq!(
+            TGAImage::try_new(2, 3, TGAImageFormat::RGBA)
+                .unwrap()
+                .as_bytes()
+                .len(),
+            24
+        );
+    }
+
+    #[test]
+    fn tgaimage_size_overflow() {
+        // overflows usize
+        assert!(matches!(
+            TGAImage::try_new(u32::MAX, u32::MAX, TGAImageFormat::RGBA),
+            Err(TGAError::ImageTooLarge { .. })
+        ));
+        assert!(matches!(
+            TGAImage::try_new_with_limit(100, 100, TGAImageFormat::RGB, 29_999),
+            Err(TGAError::ImageTooLarge {
+                width: 100,
+                height: 100,
+                limit: 29_999
+            })
+        ));
+        assert!(TGAImage::try_new_with_limit(100, 100, TGAImageFormat::RGB, 30_000).is_ok());
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn tgaimage_size_overflow_64bit() {
+        // fits into usize but exceeds isize::MAX
+        assert!(matches!(
+            TGAImage::try_new(u32::MAX, 1 << 30, TGAImageFormat::RGBA),
+            Err(TGAError::ImageTooLarge { .. })
+        ));
+
+        // exceeds u32 arithmetic, the buffer is zeroed lazily so only touched pages are allocated
+        let mut image = TGAImage::try_new(20_000, 20_000, TGAImageFormat::RGB).unwrap();
+        let color = TGAColor::new_rgb(1, 2, 3);
+
+        assert_eq!(image.as_bytes().len(), 1_200_000_000);
+        image.set(19_999, 19_999, &color);
+        image.set(0, 0, &color);
+        assert_eq!(&image[(19_999, 19_999)], &[3, 2, 1]);
+        assert_eq!(&image.as_bytes()[1_199_999_997..], &[3, 2, 1]);
+        assert_eq!(&image[(19_998, 19_999)], &[0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot create image")]
+    #[allow(deprecated)]
+    fn tgaimage_new_zero_width() {
+        TGAImage::new(0, 5, TGAImageFormat::Grayscale);
+    }
+
+    #[test]
+    fn tgaimage_try_set_get() {
+        let mut image = TGAImage::try_new(4, 3, TGAImageFormat::RGB).unwrap();
+        let color = TGAColor::new_rgb(1, 2, 3);
+
+        assert!(image.try_set(3, 2, &color).is_ok());
+        assert_eq!(image.try_get(3, 2).unwrap()[ColorChannel::B], 3);
+
+        let err = image.try_set(4, 0, &color).unwrap_err();
+
+        assert_eq!(
+            err,
+            OutOfBounds {
+                x: 4,
+                y: 0,
+                width: 4,
+                height: 3
+            }
+        );
+        assert!(image.try_get(0, 3).is_err());
+        assert!(image.as_bytes().iter().take(33).all(|e| *e == 0));
+    }
+
+    #[test]
+    #[should_panic]
+    #[cfg(debug_assertions)]
+    fn tgaimage_set_unchecked_out_of_bounds() {
+        let mut image = TGAImage::try_new(4, 3, TGAImageFormat::RGB).unwrap();
+
+        image.set_unchecked(4, 0, &TGAColor::new_rgb(1, 2, 3));
+    }
+
+    #[test]
+    fn tgaimage_pixels() {
+        let mut image = TGAImage::try_new(3, 2, TGAImageFormat::RGBA).unwrap();
+
+        image.set(2, 1, &TGAColor::new_rgba(1, 2, 3, 4));
+
+        let pixels: Vec<_> = image.pixels().collect();
+
+        assert_eq!(pixels.len(), 6);
+        pixels.iter().enumerate().for_each(|(i, (x, y, _))| {
+            assert_eq!(*x, i as u32 % 3);
+            assert_eq!(*y, i as u32 / 3);
+        });
+
+        let (_, _, color) = pixels[5];
+
+        assert_eq!(color[ColorChannel::R], 1);
+        assert_eq!(color[ColorChannel::A], 4);
+    }
+
+    #[test]
+    fn tgaimage_rows() {
+        for format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::RGB,
+            TGAImageFormat::RGBA,
+        ] {
+            let mut image = TGAImage::try_new(5, 4, *format).unwrap();
+
+            image.set(0, 2, &TGAColor::new_rgba(9, 9, 9, 9));
+
+            let rows: Vec<_> = image.rows().collect();
+
+            assert_eq!(rows.len(), 4);
+            assert!(rows.iter().all(|row| row.len() == 5 * *format as usize));
+            assert_eq!(rows[2][0], 9);
+        }
+    }
+
+    #[test]
+    fn tgaimage_for_each_pixel() {
+        let mut image = TGAImage::try_new(4, 3, TGAImageFormat::RGB).unwrap();
+
+        image.for_each_pixel(|x, y, pixel| {
+            assert_eq!(pixel.len(), 3);
+            pixel[0] = (x + y * 10) as u8;
+        });
+
+        for y in 0..3 {
+            for x in 0..4 {
+                assert_eq!(image.get(x, y)[ColorChannel::B], (x + y * 10) as u8);
+            }
+        }
+
+        // gamma-like remap of every channel through the iterator API
+        image.for_each_pixel(|_, _, pixel| pixel.iter_mut().for_each(|c| *c = 255 - *c));
+
+        assert_eq!(image.get(3, 2)[ColorChannel::B], 255 - 23);
+        assert_eq!(image.get(3, 2)[ColorChannel::G], 255);
+    }
+
+    #[test]
+    fn tgaimage_flip_horizontally() {
+        for format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::RGB,
+            TGAImageFormat::RGBA,
+        ] {
+            for width in &[1u32, 4, 5] {
+                let mut image = TGAImage::try_new(*width, 2, *format).unwrap();
+
+                image.for_each_pixel(|x, y, pixel| {
+                    pixel.iter_mut().for_each(|c| *c = (x + y * 8) as u8)
+                });
+
+                let original: Vec<_> = image.pixels().collect();
+
+                image.flip_horizontally();
+
+                for (x, y, color) in original {
+                    let flipped = image.get(width - x - 1, y);
+
+                    assert_eq!(flipped[ColorChannel::B], color[ColorChannel::B]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tgaimage_index() {
+        for format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::RGB,
+            TGAImageFormat::RGBA,
+        ] {
+            let mut image = TGAImage::try_new(3, 2, *format).unwrap();
+
+            image.set(2, 1, &TGAColor::new_rgba(1, 2, 3, 4));
+
+            assert_eq!(image[(2, 1)].len(), *format as usize);
+            assert_eq!(image[(2, 1)][0], 3);
+            assert!(image[(0, 0)].iter().all(|e| *e == 0));
+
+            image.pixel_mut(0, 0).unwrap()[0] = 42;
+
+            assert_eq!(image[(0, 0)][0], 42);
+            assert_eq!(image.pixel_mut(0, 0).unwrap().len(), *format as usize);
+            assert!(image.pixel_mut(3, 0).is_none());
+            assert!(image.pixel_mut(0, 2).is_none());
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn tgaimage_index_out_of_bounds() {
+        let image = TGAImage::try_new(3, 2, TGAImageFormat::RGB).unwrap();
+
+        let _ = &image[(3, 2)];
+    }
+
+    #[test]
+    #[should_panic]
+    fn tgaimage_index_out_of_bounds_x() {
+        let image = TGAImage::try_new(3, 2, TGAImageFormat::Grayscale).unwrap();
+
+        let _ = &image[(3, 0)];
+    }
+
+    #[test]
+    fn tgaimage_convert() {
+        let mut rgba = TGAImage::try_new(3, 2, TGAImageFormat::RGBA).unwrap();
+
+        rgba.set(0, 0, &TGAColor::new_rgba(255, 0, 0, 10));
+        rgba.set(1, 0, &TGAColor::new_rgba(0, 255, 0, 20));
+        rgba.set(2, 1, &TGAColor::new_rgba(0, 0, 255, 30));
+
+        let rgb = rgba.convert(TGAImageFormat::RGB).unwrap();
+
+        assert_eq!(rgb.get_bytespp(), TGAImageFormat::RGB);
+        assert_eq!((rgb.get_width(), rgb.get_height()), (3, 2));
+        assert_eq!(&rgb[(0, 0)], &[0, 0, 255]);
+        assert_eq!(rgb.as_bytes().len(), 3 * 2 * 3);
+
+        let gray = rgb.convert(TGAImageFormat::Grayscale).unwrap();
+
+        assert_eq!(gray.get_bytespp(), TGAImageFormat::Grayscale);
+        assert_eq!(gray.as_bytes(), &[76, 150, 0, 0, 0, 29]);
+        assert_eq!(
+            rgba.convert(TGAImageFormat::Grayscale).unwrap().as_bytes(),
+            gray.as_bytes()
+        );
+
+        let gray_rgb = gray.convert(TGAImageFormat::RGB).unwrap();
+
+        assert_eq!(&gray_rgb[(1, 0)], &[150, 150, 150]);
+
+        let gray_rgba = gray.convert(TGAImageFormat::RGBA).unwrap();
+
+        assert_eq!(&gray_rgba[(2, 1)], &[29, 29, 29, 255]);
+
+        let rgb_rgba = rgb.convert(TGAImageFormat::RGBA).unwrap();
+
+        assert_eq!(&rgb_rgba[(0, 0)], &[0, 0, 255, 255]);
+        assert_eq!(rgb_rgba.as_bytes().len(), 3 * 2 * 4);
+    }
+
+    fn numbered_image(width: u32, height: u32, format: TGAImageFormat) -> TGAImage {
+        let mut image = TGAImage::try_new(width, height, format).unwrap();
+
+        image.for_each_pixel(|x, y, pixel| {
+            pixel.iter_mut().for_each(|c| *c = (1 + x + y * 16) as u8)
+        });
+
+        image
+    }
+
+    #[test]
+    fn tgaimage_blit_inside() {
+        let src = numbered_image(4, 4, TGAImageFormat::RGB);
+        let mut dst = TGAImage::try_new(6, 6, TGAImageFormat::RGB).unwrap();
+
+        dst.blit(&src, (1, 1, 2, 2), 3, 2);
+
+        for (x, y, color) in dst.pixels() {
+            let expected = if (3..5).contains(&x) && (2..4).contains(&y) {
+                (1 + (x - 2) + (y - 1) * 16) as u8
+            } else {
+                0
+            };
+
+            assert_eq!(color[ColorChannel::G], expected, "pixel ({}, {})", x, y);
+        }
+    }
+
+    #[test]
+    fn tgaimage_blit_clipping() {
+        let src = numbered_image(4, 4, TGAImageFormat::Grayscale);
+
+        // destination right and bottom edges
+        let mut dst = TGAImage::try_new(5, 5, TGAImageFormat::Grayscale).unwrap();
+
+        dst.blit(&src, (0, 0, 4, 4), 3, 3);
+
+        assert_eq!(&dst[(3, 3)], &[1]);
+        assert_eq!(&dst[(4, 4)], &[18]);
+        assert_eq!(dst.as_bytes().iter().filter(|e| **e != 0).count(), 4);
+
+        // source right and bottom edges
+        let mut dst = TGAImage::try_new(5, 5, TGAImageFormat::Grayscale).unwrap();
+
+        dst.blit(&src, (2, 3, 10, 10), 0, 0);
+
+        assert_eq!(&dst[(0, 0)], &[51]);
+        assert_eq!(&dst[(1, 0)], &[52]);
+        assert_eq!(dst.as_bytes().iter().filter(|e| **e != 0).count(), 2);
+
+        // rectangle starting outside of either image
+        let mut dst = TGAImage::try_new(5, 5, TGAImageFormat::Grayscale).unwrap();
+
+        dst.blit(&src, (4, 0, 2, 2), 0, 0);
+        dst.blit(&src, (0, 0, 2, 2), 5, 0);
+        dst.blit(&src, (0, 0, 2, 2), 0, 5);
+
+        assert!(dst.as_bytes().iter().all(|e| *e == 0));
+    }
+
+    #[test]
+    fn tgaimage_blit_zero_area() {
+        let src = numbered_image(4, 4, TGAImageFormat::RGBA);
+        let mut dst = TGAImage::try_new(4, 4, TGAImageFormat::RGBA).unwrap();
+
+        dst.blit(&src, (1, 1, 0, 3), 0, 0);
+        dst.blit(&src, (1, 1, 3, 0), 0, 0);
+
+        assert!(dst.as_bytes().iter().all(|e| *e == 0));
+    }
+
+    #[test]
+    fn tgaimage_blit_mismatched_formats() {
+        let mut src = TGAImage::try_new(2, 1, TGAImageFormat::RGB).unwrap();
+
+        src.set(0, 0, &TGAColor::new_rgb(255, 0, 0));
+        src.set(1, 0, &TGAColor::new_rgb(1, 2, 3));
+
+        let mut gray = TGAImage::try_new(2, 1, TGAImageFormat::Grayscale).unwrap();
+        let mut rgba = TGAImage::try_new(2, 1, TGAImageFormat::RGBA).unwrap();
+
+        gray.blit(&src, (0, 0, 2, 1), 0, 0);
+        rgba.copy_from(&src).unwrap();
+
+        assert_eq!(&gray[(0, 0)], &[76]);
+        assert_eq!(&rgba[(1, 0)], &[3, 2, 1, 255]);
+    }
+
+    #[test]
+    fn tgaimage_copy_from_dimension_mismatch() {
+        let src = numbered_image(4, 4, TGAImageFormat::RGB);
+        let mut dst = TGAImage::try_new(4, 3, TGAImageFormat::RGB).unwrap();
+
+        assert!(matches!(
+            dst.copy_from(&src),
+            Err(TGAError::DimensionMismatch {
+                expected: (4, 3),
+                got: (4, 4)
+            })
+        ));
+    }
+
+    fn checkerboard(width: u32, height: u32) -> TGAImage {
+        let mut image = TGAImage::try_new(width, height, TGAImageFormat::RGB).unwrap();
+
+        image.for_each_pixel(|x, y, pixel| {
+            pixel.copy_from_slice(if (x + y) % 2 == 0 {
+                &[255, 255, 255]
+            } else {
+                &[0, 0, 0]
+            })
+        });
+
+        image
+    }
+
+    #[test]
+    fn tgaimage_resize_downscale() {
+        let image = checkerboard(4, 4);
+        let nearest = image.resize(2, 2, ResizeFilter::Nearest).unwrap();
+        let bilinear = image.resize(2, 2, ResizeFilter::Bilinear).unwrap();
+
+        assert_eq!(nearest.get_width(), 2);
+        assert_eq!(nearest.get_height(), 2);
+        // pixel centers of the 2x2 image map onto source pixels (1, 1), (3, 1), (1, 3), (3, 3)
+        assert!(nearest.as_bytes().iter().all(|e| *e == 255));
+        assert!(bilinear.as_bytes().iter().all(|e| *e == 128));
+    }
+
+    #[test]
+    fn tgaimage_resize_upscale() {
+        let mut image = TGAImage::try_new(2, 1, TGAImageFormat::Grayscale).unwrap();
+
+        image.pixel_mut(1, 0).unwrap()[0] = 200;
+
+        let nearest = image.resize(4, 2, ResizeFilter::Nearest).unwrap();
+        let bilinear = image.resize(4, 1, ResizeFilter::Bilinear).unwrap();
+
+        assert_eq!(nearest.as_bytes(), &[0, 0, 200, 200, 0, 0, 200, 200]);
+        assert_eq!(bilinear.as_bytes(), &[0, 50, 150, 200]);
+    }
+
+    #[test]
+    fn tgaimage_resize_rgba_channels() {
+        let mut image = TGAImage::try_new(2, 2, TGAImageFormat::RGBA).unwrap();
+
+        image.set(0, 0, &TGAColor::new_rgba(100, 0, 40, 255));
+        image.set(1, 1, &TGAColor::new_rgba(0, 100, 0, 55));
+
+        let bilinear = image.resize(1, 1, ResizeFilter::Bilinear).unwrap();
+
+        assert_eq!(&bilinear[(0, 0)], &[10, 25, 25, 78]);
+    }
+
+    #[test]
+    fn tgaimage_resize_gray16() {
+        // The average of 255 and 257 has both bytes different from theirs
+        let mut image = TGAImage::try_new(2, 1, TGAImageFormat::Grayscale16).unwrap();
+
+        image.set_gray16(0, 0, 255);
+        image.set_gray16(1, 0, 257);
+
+        let bilinear = image.resize(1, 1, ResizeFilter::Bilinear).unwrap();
+        let upscaled = image.resize(4, 1, ResizeFilter::Bilinear).unwrap();
+
+        assert_eq!(bilinear.get_gray16(0, 0), 256);
+        assert_eq!(
+            (0..4)
+                .map(|x| upscaled.get_gray16(x, 0))
+                .collect::<Vec<_>>(),
+            [255, 256, 257, 257]
+        );
+    }
+
+    #[test]
+    fn tgaimage_write_oversized() {
+        for &(width, height) in &[(65536, 1), (1, 65536)] {
+            let image = TGAImage::try_new(width, height, TGAImageFormat::RGB).unwrap();
+            let mut out = Vec::new();
+
+            assert!(matches!(
+                image.write_to_with_origin(&mut out, Origin::BottomLeft, false),
+                Err(TGAError::InvalidHeader { .. })
+            ));
+            assert!(matches!(
+                image.write_to_16bit_with_origin(&mut out, Origin::BottomLeft, true),
+                Err(TGAError::InvalidHeader { .. })
+            ));
+            assert!(out.is_empty());
+        }
+    }
+
+    #[test]
+    fn tgaimage_degenerate_targets() {
+        let image = checkerboard(4, 4);
+
+        assert!(matches!(
+            image.resize(0, 2, ResizeFilter::Nearest),
+            Err(TGAError::InvalidHeader { .. })
+        ));
+        assert!(matches!(
+            image.resize(2, 0, ResizeFilter::Bilinear),
+            Err(TGAError::InvalidHeader { .. })
+        ));
+        assert!(matches!(
+            image.convert(TGAImageFormat::Unknown),
+            Err(TGAError::UnsupportedBitDepth(0))
+        ));
+    }
+
+    #[test]
+    fn tgaimage_crop() {
+        let image = numbered_image(4, 3, TGAImageFormat::RGB);
+        let cropped = image.crop(1, 1, 2, 2).unwrap();
+
+        assert_eq!(cropped.get_width(), 2);
+        assert_eq!(cropped.get_height(), 2);
+        assert_eq!(cropped.get_bytespp(), TGAImageFormat::RGB);
+        assert_eq!(
+            cropped.as_bytes(),
+            &[18, 18, 18, 19, 19, 19, 34, 34, 34, 35, 35, 35]
+        );
+    }
+
+    #[test]
+    fn tgaimage_crop_clamped() {
+        let image = numbered_image(4, 3, TGAImageFormat::Grayscale);
+        let cropped = image.crop(2, 1, 10, 10).unwrap();
+
+        assert_eq!(cropped.get_width(), 2);
+        assert_eq!(cropped.get_height(), 2);
+        assert_eq!(cropped.as_bytes(), &[19, 20, 35, 36]);
+    }
+
+    #[test]
+    fn tgaimage_crop_empty() {
+        let image = numbered_image(4, 3, TGAImageFormat::RGBA);
+
+        for &(x, y, w, h) in &[(0, 0, 0, 2), (0, 0, 2, 0), (4, 0, 2, 2), (0, 3, 2, 2)] {
+            assert!(matches!(
+                image.crop(x, y, w, h),
+                Err(TGAError::InvalidRegion { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn tgaimage_rotate90() {
+        let image = numbered_image(3, 2, TGAImageFormat::Grayscale);
+        let rotated = image.rotate90();
+
+        assert_eq!(rotated.get_width(), 2);
+        assert_eq!(rotated.get_height(), 3);
+        assert_eq!(rotated.as_bytes(), &[17, 1, 18, 2, 19, 3]);
+        assert_eq!(image.rotate270().as_bytes(), &[3, 19, 2, 18, 1, 17]);
+    }
+
+    #[test]
+    fn tgaimage_rotate_full_turn() {
+        for &format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::RGB,
+            TGAImageFormat::RGBA,
+        ] {
+            let mut image = numbered_image(3, 2, format);
+
+            image.set(0, 0, &TGAColor::new_rgba(10, 20, 30, 40));
+
+            let rotated = image.rotate90().rotate90().rotate90().rotate90();
+
+            assert_eq!(rotated.get_width(), 3);
+            assert_eq!(rotated.get_height(), 2);
+            assert_eq!(rotated.as_bytes(), image.as_bytes());
+            assert_eq!(image.rotate270().rotate90().as_bytes(), image.as_bytes());
+
+            let mut half_turn = image.rotate90().rotate90();
+
+            half_turn.rotate180();
+            assert_eq!(half_turn.as_bytes(), image.as_bytes());
+        }
+    }
+
+    #[test]
+    fn tgaimage_flip_vertically() {
+        for &format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::RGB,
+            TGAImageFormat::RGBA,
+        ] {
+            for &height in &[1, 2, 3, 4, 5] {
+                let mut image = numbered_image(3, height, format);
+                let original = numbered_image(3, height, format);
+
+                image.flip_vertically();
+
+                for y in 0..height {
+                    for x in 0..3 {
+                        assert_eq!(&image[(x, y)], &original[(x, height - 1 - y)]);
+                    }
+                }
+
+                image.flip_vertically();
+                assert_eq!(image.as_bytes(), original.as_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn tgaimage_clear_color() {
+        let mut image = TGAImage::try_new(3, 2, TGAImageFormat::RGBA).unwrap();
+
+        image.clear_color(&TGAColor::new_rgba(1, 2, 3, 4));
+
+        assert_eq!(image.as_bytes(), [3, 2, 1, 4].repeat(6).as_slice());
+    }
+
+    #[test]
+    fn tgaimage_fill_rect_clipped() {
+        let mut image = TGAImage::try_new(4, 3, TGAImageFormat::RGB).unwrap();
+        let color = TGAColor::new_rgb(10, 20, 30);
+
+        image.fill_rect(2, 1, 5, 5, &color);
+        image.fill_rect(4, 0, 2, 2, &color);
+        image.fill_rect(0, 3, 2, 2, &color);
+
+        for (x, y, pixel) in image.pixels() {
+            let expected = if x >= 2 && y >= 1 {
+                [30, 20, 10]
+            } else {
+                [0, 0, 0]
+            };
+
+            assert_eq!(
+                [
+                    pixel[ColorChannel::B],
+                    pixel[ColorChannel::G],
+                    pixel[ColorChannel::R]
+                ],
+                expected,
+                "pixel ({}, {})",
+                x,
+                y
+            );
+        }
+    }
+
+    fn dump_to_string(image: &TGAImage, opts: DumpOptions) -> String {
+        let mut out = Vec::new();
+
+        image.dump_to(&mut out, opts).unwrap();
+
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn tgaimage_dump_to() {
+        let image = numbered_image(2, 2, TGAImageFormat::RGB);
+        let opts = DumpOptions {
+            row_indices: true,
+            ..DumpOptions::default()
+        };
+
+        assert_eq!(
+            dump_to_string(&image, opts),
+            "   0: 010101 020202\n   1: 111111 121212\n"
+        );
+        assert_eq!(
+            dump_to_string(
+                &image,
+                DumpOptions {
+                    bytes_per_line: 4,
+                    group_pixels: false,
+                    ..opts
+                }
+            ),
+            "   0: 01 01 01 02\n      02 02\n   1: 11 11 11 12\n      12 12\n"
+        );
+        assert_eq!(
+            dump_to_string(
+                &image,
+                DumpOptions {
+                    bytes_per_line: 0,
+                    region: Some((1, 1, 5, 5)),
+                    ..DumpOptions::default()
+                }
+            ),
+            "121212\n"
+        );
+    }
+
+    #[test]
+    fn tgaimage_split_channels() {
+        let mut image = TGAImage::try_new(5, 4, TGAImageFormat::RGBA).unwrap();
+
+        image.for_each_pixel(|x, y, pixel| {
+            for (i, c) in pixel.iter_mut().enumerate() {
+                *c = (x * 7 + y * 13 + i as u32 * 31) as u8;
+            }
+        });
+
+        let channels = image.split_channels();
+
+        assert_eq!(channels.len(), 4);
+
+        for (i, channel) in channels.iter().enumerate() {
+            let source_sum: u32 = image
+                .as_bytes()
+                .iter()
+                .skip(i)
+                .step_by(4)
+                .map(|c| *c as u32)
+                .sum();
+            let channel_sum: u32 = channel.as_bytes().iter().map(|c| *c as u32).sum();
+
+            assert_eq!(channel.get_bytespp(), TGAImageFormat::Grayscale);
+            assert_eq!(channel.get_width(), 5);
+            assert_eq!(channel.get_height(), 4);
+            assert_eq!(channel_sum, source_sum);
+        }
+
+        assert_eq!(image.channel(ColorChannel::R).unwrap(), channels[2]);
+    }
+
+    #[test]
+    fn tgaimage_channel_missing() {
+        let rgb = numbered_image(3, 2, TGAImageFormat::RGB);
+        let gray = numbered_image(3, 2, TGAImageFormat::Grayscale);
+
+        assert!(rgb.channel(ColorChannel::A).is_none());
+        assert_eq!(rgb.split_channels().len(), 3);
+        assert!(gray.channel(ColorChannel::A).is_none());
+        assert_eq!(gray.channel(ColorChannel::R).unwrap(), gray);
+        assert_eq!(
+            gray.split_channels(),
+            vec![gray.channel(ColorChannel::G).unwrap()]
+        );
+    }
+
+    #[test]
+    fn tgaimage_clear() {
+        let width = 100;
+        let height = 100;
+        let format = TGAImageFormat::Grayscale;
+        let mut image = TGAImage::try_new(width, height, format).unwrap();
+
+        image.buffer().iter_mut().for_each(|e| *e = 255);
+        image.buffer().iter().for_each(|e| assert_eq!(*e, 255));
+        image.clear();
+        image.buffer().iter().for_each(|e| assert_eq!(*e, 0));
+    }
+}
+
+#[cfg(test)]
+mod tests_tgaheader {
+    use super::*;
+
+    #[test]
+    fn tgaheader_round_trip() {
+        let header = TGAHeader {
+            idlength: 3,
+            colormaptype: 1,
+            datatypecode: 9,
+            colormaporigin: 0x0102,
+            colormaplength: 0x0304,
+            colormapdepth: 24,
+            x_origin: 0x0506,
+            y_origin: 0x0708,
+            width: 0x090a,
+            height: 0x0b0c,
+            bitsperpixel: 8,
+            imagedescriptor: 0x20,
+        };
+        let bytes = header.to_bytes();
+
+        assert_eq!(
+            bytes,
+            [
+                3, 1, 9, 0x02, 0x01, 0x04, 0x03, 24, 0x06, 0x05, 0x08, 0x07, 0x0a, 0x09, 0x0c,
+                0x0b, 8, 0x20
+            ]
+        );
+        assert_eq!(TGAHeader::from_bytes(&bytes), header);
+    }
+}
+
+#[cfg(test)]
+mod tests_tgaimage_io {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn tga_header_bytes(datatype: u8, width: u16, height: u16, bpp: u8, descriptor: u8) -> Vec<u8> {
+        let mut header = vec![0u8, 0, datatype, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+        header.extend_from_slice(&width.to_le_bytes());
+        header.extend_from_slice(&height.to_le_bytes());
+        header.push(bpp);
+        header.push(descriptor);
+
+        header
+    }
+
+    #[test]
+    fn tgaimage_read_from_uncompressed() {
+        let mut bytes = tga_header_bytes(2, 2, 2, 24, 0);
+        let pixels = [1u8, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12];
+
+        bytes.extend_from_slice(&pixels);
+
+        let image = TGAImage::read_from(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(image.get_width(), 2);
+        assert_eq!(image.get_height(), 2);
+        assert_eq!(image.get_bytespp(), TGAImageFormat::RGB);
+        assert_eq!(image.data, pixels);
+    }
+
+    #[test]
+    fn tgaimage_cursor_round_trip_fixture() {
+        let fixture = include_bytes!("../tests/data/gradient_rle.tga");
+        let image = TGAImage::read_from(&mut Cursor::new(fixture.to_vec())).unwrap();
+
+        assert_eq!((image.get_width(), image.get_height()), (8, 4));
+        assert_eq!(image.get_bytespp(), TGAImageFormat::RGB);
+
+        for y in 0..4 {
+            for x in 0..8 {
+                let bgr = match (y % 2, x < 5) {
+                    (0, true) => [10, 20, 30],
+                    (0, false) => [40, 50, 60],
+                    _ => [x as u8 * 30, y as u8 * 60, 200],
+                };
+
+                assert_eq!(image.get(x, y).channels(), bgr);
+            }
         }
 
-        assert_eq!(default.bytespp, 0);
+        let mut encoded = Cursor::new(Vec::new());
+
+        image
+            .write_to_with_origin(&mut encoded, Origin::BottomLeft, true)
+            .unwrap();
+        encoded.set_position(0);
+
+        assert_eq!(TGAImage::read_from(&mut encoded).unwrap(), image);
     }
 
     #[test]
-    fn tgacolor_rgb() {
-        let tgacolor = TGAColor::new_rgb(1, 1, 1);
+    fn tgaimage_read_from_rle() {
+        let mut bytes = tga_header_bytes(11, 3, 2, 8, 0);
 
-        for e in tgacolor.bgra.iter().take(3) {
-            assert_eq!(*e, 1);
-        }
+        // run of 4 pixels with value 7 followed by a raw packet of 2 pixels
+        bytes.extend_from_slice(&[0b1000_0011, 7, 0b0000_0001, 1, 2]);
 
-        assert_eq!(tgacolor.bgra[3], 255);
-        assert_eq!(tgacolor.bytespp, 4);
+        let image = TGAImage::read_from(&mut Cursor::new(bytes)).unwrap();
 
-        let tgacolor = TGAColor::new_rgb(255, 255, 255);
+        assert_eq!(image.get_bytespp(), TGAImageFormat::Grayscale);
+        assert_eq!(image.data, [7, 7, 7, 7, 1, 2]);
+    }
 
-        for e in tgacolor.bgra.iter().take(3) {
-            assert_eq!(*e, 255);
-        }
+    #[test]
+    fn tgaimage_read_from_rle_raw_overrun() {
+        let mut bytes = tga_header_bytes(11, 2, 2, 8, 0);
 
-        assert_eq!(tgacolor.bgra[3], 255);
-        assert_eq!(tgacolor.bytespp, 4);
+        // raw packet announcing 5 pixels for a 4 pixel image
+        bytes.extend_from_slice(&[0b0000_0100, 1, 2, 3, 4, 5]);
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(bytes)),
+            Err(TGAError::RleOverrun)
+        ));
     }
 
-    fn index_to_rgba_color_channel(index: usize) -> Result<ColorChannel, &'static str> {
-        match index {
-            0 => Ok(ColorChannel::R),
-            1 => Ok(ColorChannel::G),
-            2 => Ok(ColorChannel::B),
-            3 => Ok(ColorChannel::A),
-            _ => Err("Unknown color channel"),
+    #[test]
+    fn tgaimage_read_from_rle_run_overrun() {
+        let mut bytes = tga_header_bytes(10, 2, 2, 24, 0);
+
+        // valid run of 2 pixels, then a run of 128 pixels
+        bytes.extend_from_slice(&[0b1000_0001, 1, 2, 3, 0b1111_1111, 4, 5, 6]);
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(bytes)),
+            Err(TGAError::RleOverrun)
+        ));
+    }
+
+    #[test]
+    fn tgaimage_read_from_rle_truncated() {
+        let mut bytes = tga_header_bytes(10, 2, 2, 24, 0);
+
+        // raw packet of 3 pixels that ends in the middle of the second pixel
+        bytes.extend_from_slice(&[0b0000_0010, 1, 2, 3, 4]);
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(bytes.clone())),
+            Err(TGAError::TruncatedData {
+                expected: 12,
+                got: 4
+            })
+        ));
+
+        // stream ends right before the next packet header
+        let mut bytes = tga_header_bytes(10, 2, 2, 24, 0);
+
+        bytes.extend_from_slice(&[0b1000_0000, 1, 2, 3]);
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(bytes)),
+            Err(TGAError::TruncatedData {
+                expected: 12,
+                got: 3
+            })
+        ));
+    }
+
+    fn color_mapped_fixture(datatype: u8) -> (Vec<u8>, Vec<u8>) {
+        let (width, height) = (16u16, 16u16);
+        let mut color_mapped = vec![0u8, 1, datatype, 0, 0, 0, 1, 24, 0, 0, 0, 0];
+        let mut true_color = tga_header_bytes(2, width, height, 24, 0);
+
+        color_mapped.extend_from_slice(&width.to_le_bytes());
+        color_mapped.extend_from_slice(&height.to_le_bytes());
+        color_mapped.extend_from_slice(&[8, 0]);
+
+        let palette: Vec<[u8; 3]> = (0..=255u8)
+            .map(|i| [i, 255 - i, i.wrapping_mul(7)])
+            .collect();
+
+        palette
+            .iter()
+            .for_each(|entry| color_mapped.extend_from_slice(entry));
+
+        let indexes: Vec<u8> = (0..width * height).map(|i| (i / 3) as u8).collect();
+
+        if datatype == 1 {
+            color_mapped.extend_from_slice(&indexes);
+        } else {
+            indexes.chunks(3).for_each(|run| {
+                color_mapped.extend_from_slice(&[0b1000_0000 | (run.len() as u8 - 1), run[0]])
+            });
         }
+
+        indexes
+            .iter()
+            .for_each(|index| true_color.extend_from_slice(&palette[*index as usize]));
+
+        (color_mapped, true_color)
     }
 
-    fn index_to_bgra_color_channel(index: usize) -> Result<ColorChannel, &'static str> {
-        match index {
-            0 => Ok(ColorChannel::B),
-            1 => Ok(ColorChannel::G),
-            2 => Ok(ColorChannel::R),
-            3 => Ok(ColorChannel::A),
-            _ => Err("Unknown color channel"),
+    #[test]
+    fn tgaimage_read_from_color_mapped() {
+        for datatype in &[1u8, 9] {
+            let (color_mapped, true_color) = color_mapped_fixture(*datatype);
+            let image = TGAImage::read_from(&mut Cursor::new(color_mapped)).unwrap();
+            let expected = TGAImage::read_from(&mut Cursor::new(true_color)).unwrap();
+
+            assert_eq!(image.get_bytespp(), TGAImageFormat::RGB);
+            assert_eq!(image.get_width(), expected.get_width());
+            assert_eq!(image.get_height(), expected.get_height());
+            assert_eq!(image.data, expected.data);
         }
     }
 
     #[test]
-    fn tgacolor_rgba() {
-        let tgacolor = TGAColor::new_rgba(1, 2, 3, 4);
+    fn tgaimage_read_from_color_mapped_origin() {
+        // two 32-bit entries starting at index 10
+        let mut bytes = vec![0u8, 1, 1, 10, 0, 2, 0, 32, 0, 0, 0, 0, 2, 0, 1, 0, 8, 0];
 
-        for index in 0..tgacolor.bgra.len() {
-            let color_index = index_to_rgba_color_channel(index).unwrap();
-            assert_eq!(tgacolor[color_index], index as u8 + 1);
+        bytes.extend_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        bytes.extend_from_slice(&[11, 10]);
+
+        let image = TGAImage::read_from(&mut Cursor::new(bytes.clone())).unwrap();
+
+        assert_eq!(image.get_bytespp(), TGAImageFormat::RGBA);
+        assert_eq!(image.data, [5, 6, 7, 8, 1, 2, 3, 4]);
+
+        *bytes.last_mut().unwrap() = 12;
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(bytes)),
+            Err(TGAError::ColorMapIndexOutOfRange(12))
+        ));
+    }
+
+    #[test]
+    fn tgaimage_read_from_true_color_skips_color_map() {
+        // three unused 12-bit entries take two bytes each
+        let mut bytes = vec![0u8, 1, 2, 0, 0, 3, 0, 12, 0, 0, 0, 0, 2, 0, 1, 0, 24, 0];
+
+        bytes.extend_from_slice(&[0xff; 6]);
+        bytes.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+
+        let image = TGAImage::read_from(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(image.get_bytespp(), TGAImageFormat::RGB);
+        assert_eq!(image.data, [1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn tgaimage_16bit_round_trip() {
+        for rle in &[false, true] {
+            let image = gradient_image(TGAImageFormat::RGB);
+            let mut encoded = Vec::new();
+
+            image
+                .write_to_16bit_with_origin(&mut encoded, Origin::BottomLeft, *rle)
+                .unwrap();
+
+            let decoded = TGAImage::read_from(&mut Cursor::new(encoded)).unwrap();
+
+            assert_eq!(decoded.get_bytespp(), TGAImageFormat::RGB);
+            assert_eq!(decoded.data.len(), image.data.len());
+            decoded
+                .data
+                .iter()
+                .zip(image.data.iter())
+                .for_each(|(d, o)| assert!((*d as i32 - *o as i32).abs() < 8));
         }
+    }
 
-        assert_eq!(tgacolor.bytespp, 4);
+    #[test]
+    fn tgaimage_16bit_attribute_bit() {
+        let mut image = TGAImage::try_new(2, 1, TGAImageFormat::RGBA).unwrap();
+
+        image.set(0, 0, &TGAColor::new_rgba(255, 0, 0, 255));
+        image.set(1, 0, &TGAColor::new_rgba(0, 0, 255, 10));
+
+        let mut encoded = Vec::new();
+
+        image
+            .write_to_16bit_with_origin(&mut encoded, Origin::BottomLeft, false)
+            .unwrap();
+
+        assert_eq!(encoded[16], 16);
+        assert_eq!(encoded[17], 0x01);
+        assert_eq!(&encoded[18..22], &[0x00, 0xfc, 0x1f, 0x00]);
+
+        let decoded = TGAImage::read_from(&mut Cursor::new(encoded)).unwrap();
+
+        assert_eq!(decoded.get_bytespp(), TGAImageFormat::RGBA);
+        assert_eq!(decoded.data, [0, 0, 255, 255, 255, 0, 0, 0]);
     }
 
     #[test]
-    fn tgacolor_iter() {
-        let bgra1 = [1u8];
-        let bgra2 = [1u8, 2];
-        let bgra3 = [1u8, 2, 3];
-        let bgra4 = [1u8, 2, 3, 4];
-        let checker = |arr: &[u8]| {
-            let tgacolor = TGAColor::new_from_iter(arr.iter(), arr.len() as u8);
+    fn tgaimage_read_from_image_id() {
+        // header as written by an editor storing its name in the ID field
+        let id = b"GIMP TGA exporter";
+        let pixels = [10u8, 20, 30, 40, 50, 60];
+        let mut bytes = tga_header_bytes(2, 2, 1, 24, 0);
 
-            for index in 0..arr.len() {
-                let color_index = index_to_bgra_color_channel(index).unwrap();
-                let val = tgacolor[color_index];
+        bytes[0] = id.len() as u8;
+        bytes[8..12].copy_from_slice(&[0x10, 0x00, 0x20, 0x01]);
+        bytes.extend_from_slice(id);
+        bytes.extend_from_slice(&pixels);
 
-                assert_eq!(val, index as u8 + 1);
+        let image = TGAImage::read_from(&mut Cursor::new(bytes.clone())).unwrap();
+
+        assert_eq!(image.data, pixels);
+        assert_eq!(image.image_id(), Some(id.to_vec()));
+        assert_eq!(image.get_x_origin(), 0x10);
+        assert_eq!(image.get_y_origin(), 0x120);
+
+        let encoded = image
+            .encode_to_vec_with_origin(Origin::BottomLeft, false)
+            .unwrap();
+
+        assert_eq!(&encoded[..18 + id.len() + pixels.len()], &bytes[..]);
+    }
+
+    #[test]
+    fn tgaimage_image_id_limit() {
+        let mut image = TGAImage::try_new(1, 1, TGAImageFormat::Grayscale).unwrap();
+
+        assert!(image.image_id().is_none());
+        assert!(image.set_image_id(Some(vec![b'a'; 256])).is_err());
+        assert!(image.set_image_id(Some(vec![b'a'; 255])).is_ok());
+        assert_eq!(image.image_id().unwrap().len(), 255);
+        assert!(image.set_image_id(None).is_ok());
+        assert!(image.image_id().is_none());
+    }
+
+    #[test]
+    fn tgaimage_read_from_flip_bits() {
+        let pixels = [1u8, 2, 3, 4];
+        let mut bytes = tga_header_bytes(3, 2, 2, 8, 0b11_0000);
+
+        bytes.extend_from_slice(&pixels);
+
+        let image = TGAImage::read_from(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(image.data, [4, 3, 2, 1]);
+    }
+
+    fn gradient_image(format: TGAImageFormat) -> TGAImage {
+        let mut image = TGAImage::try_new(5, 3, format).unwrap();
+
+        image
+            .buffer()
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, e)| *e = (i / 4) as u8);
+
+        image
+    }
+
+    #[test]
+    fn tgaimage_alpha_bits_descriptor() {
+        let cases = [
+            (TGAImageFormat::Grayscale, 0x00),
+            (TGAImageFormat::RGB, 0x00),
+            (TGAImageFormat::RGBA, 0x08),
+        ];
+
+        for &(format, alpha_bits) in &cases {
+            let image = gradient_image(format);
+
+            assert_eq!(image.alpha_bits(), alpha_bits);
+
+            for &(origin, descriptor) in &[(Origin::BottomLeft, 0x00), (Origin::TopLeft, 0x20)] {
+                let encoded = image.encode_to_vec_with_origin(origin, false).unwrap();
+
+                assert_eq!(encoded[17], descriptor | alpha_bits);
+
+                let decoded = TGAImage::read_from(&mut Cursor::new(encoded)).unwrap();
+
+                assert_eq!(decoded.alpha_bits(), alpha_bits);
+                assert_eq!(decoded.convert(format).unwrap().alpha_bits(), alpha_bits);
             }
+        }
+    }
 
-            assert_eq!(tgacolor.bytespp, arr.len() as u8);
-        };
+    #[test]
+    fn tgaimage_alpha_bits_read() {
+        let mut bytes = tga_header_bytes(2, 1, 1, 32, 0);
+
+        bytes.extend_from_slice(&[1, 2, 3, 4]);
+
+        let image = TGAImage::read_from(&mut Cursor::new(bytes)).unwrap();
+
+        // 32-bit pixels declaring no attribute bits still decode to RGBA
+        assert_eq!(image.get_bytespp(), TGAImageFormat::RGBA);
+        assert_eq!(image.alpha_bits(), 0);
+        assert_eq!(image.rotate90().alpha_bits(), 0);
+        assert_eq!(image.convert(TGAImageFormat::RGB).unwrap().alpha_bits(), 0);
+        assert_eq!(
+            image
+                .encode_to_vec_with_origin(Origin::BottomLeft, false)
+                .unwrap()[17],
+            8
+        );
 
-        checker(&bgra1);
-        checker(&bgra2);
-        checker(&bgra3);
-        checker(&bgra4);
+        // the single attribute bit of A1R5G5B5 is widened to 8-bit alpha
+        let mut bytes = tga_header_bytes(2, 1, 1, 16, 1);
+
+        bytes.extend_from_slice(&[0x00, 0xfc]);
+
+        let image = TGAImage::read_from(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(image.get_bytespp(), TGAImageFormat::RGBA);
+        assert_eq!(image.alpha_bits(), 1);
+        assert_eq!(
+            image
+                .encode_to_vec_with_origin(Origin::BottomLeft, false)
+                .unwrap()[17],
+            8
+        );
+
+        // the descriptor is reported for formats without alpha too
+        let mut bytes = tga_header_bytes(3, 1, 1, 8, 0x23);
+
+        bytes.push(7);
+
+        let image = TGAImage::read_from(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(image.origin(), Origin::TopLeft);
+        assert_eq!(image.alpha_bits(), 3);
+        assert_eq!(image.convert(TGAImageFormat::RGBA).unwrap().alpha_bits(), 8);
     }
 
     #[test]
-    #[should_panic]
-    fn tgacolor_iter_fails() {
-        let bgra5 = [0u8; 4];
-        let bgra6 = [0u8; 6];
+    fn tgaimage_encode_round_trip() {
+        for format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::RGB,
+            TGAImageFormat::RGBA,
+        ] {
+            for rle in &[false, true] {
+                let image = gradient_image(*format);
+                let encoded = image
+                    .encode_to_vec_with_origin(Origin::BottomLeft, *rle)
+                    .unwrap();
+                let decoded = TGAImage::read_from(&mut Cursor::new(encoded)).unwrap();
+
+                assert_eq!(decoded.get_width(), image.get_width());
+                assert_eq!(decoded.get_height(), image.get_height());
+                assert_eq!(decoded.get_bytespp(), image.get_bytespp());
+                assert_eq!(decoded.data, image.data);
+            }
+        }
+    }
 
-        let checker = |arr: &[u8]| {
-            TGAColor::new_from_iter(arr.iter(), arr.len() as u8);
-        };
+    #[test]
+    fn tgaimage_rle_round_trip_chunk_boundaries() {
+        for format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::RGB,
+            TGAImageFormat::RGBA,
+        ] {
+            for width in &[127u32, 128, 129, 255, 256, 257] {
+                let bytespp = *format as usize;
+                let patterns: [Box<dyn Fn(usize) -> u8>; 4] = [
+                    Box::new(|_| 42),
+                    Box::new(|i| (i / bytespp) as u8),
+                    Box::new(|i| (i / bytespp / 5) as u8),
+                    Box::new(|i| ((i / bytespp) % 7 / 3) as u8),
+                ];
+
+                for pattern in patterns.iter() {
+                    let mut image = TGAImage::try_new(*width, 1, *format).unwrap();
+
+                    image
+                        .buffer()
+                        .iter_mut()
+                        .enumerate()
+                        .for_each(|(i, e)| *e = pattern(i));
+
+                    let encoded = image
+                        .encode_to_vec_with_origin(Origin::BottomLeft, true)
+                        .unwrap();
+                    let decoded = TGAImage::read_from(&mut Cursor::new(encoded)).unwrap();
+
+                    assert_eq!(decoded.data, image.data);
+                }
+            }
+        }
+    }
 
-        checker(&bgra5);
-        checker(&bgra6);
+    #[test]
+    fn tgaimage_rle_packets() {
+        let mut image = TGAImage::try_new(6, 1, TGAImageFormat::Grayscale).unwrap();
+
+        image.buffer().copy_from_slice(&[1, 2, 3, 3, 3, 4]);
+
+        let encoded = image
+            .encode_to_vec_with_origin(Origin::BottomLeft, true)
+            .unwrap();
+
+        assert_eq!(
+            &encoded[18..25],
+            &[0b0000_0001, 1, 2, 0b1000_0010, 3, 0b0000_0000, 4]
+        );
     }
 
     #[test]
-    fn tgacolor_index_access() {
-        let tgacolor = TGAColor::new_rgba(128, 128, 128, 128);
+    fn tgaimage_gray16_round_trip() {
+        let mut image = TGAImage::try_new(257, 3, TGAImageFormat::Grayscale16).unwrap();
 
-        for i in 0..4 {
-            let color_index = index_to_rgba_color_channel(i).unwrap();
-            assert_eq!(tgacolor[color_index], 128);
+        for x in 0..257 {
+            image.set_gray16(x, 0, (x * 255) as u16);
+            image.set_gray16(x, 1, 0x1234);
+            image.set_gray16(x, 2, u16::MAX - x as u16);
         }
 
-        let mut tgacolor = TGAColor::new_rgba(128, 128, 128, 128);
+        for &rle in &[false, true] {
+            let encoded = image
+                .encode_to_vec_with_origin(Origin::BottomLeft, rle)
+                .unwrap();
 
-        for i in 0..4 {
-            let color_index = index_to_rgba_color_channel(i).unwrap();
+            assert_eq!(encoded[2], if rle { 11 } else { 3 });
+            assert_eq!(encoded[16], 16);
 
-            tgacolor[color_index] += 1;
-            assert_eq!(tgacolor[color_index], 129);
+            let decoded = TGAImage::read_from(&mut Cursor::new(encoded)).unwrap();
+
+            assert_eq!(decoded.get_bytespp(), TGAImageFormat::Grayscale16);
+            assert_eq!(decoded, image);
+            assert_eq!(decoded.get_gray16(256, 0), 0xff00);
+            assert_eq!(decoded.get_gray16(5, 2), 0xfffa);
         }
+
+        assert_eq!(image.get_gray16(300, 0), 0);
+        assert_eq!(
+            image.convert(TGAImageFormat::Grayscale).unwrap()[(256, 0)],
+            [254]
+        );
+        assert_eq!(
+            image.convert(TGAImageFormat::RGB).unwrap()[(5, 1)],
+            [18, 18, 18]
+        );
+        assert_eq!(
+            TGAImage::try_new(1, 1, TGAImageFormat::RGB)
+                .unwrap()
+                .convert(TGAImageFormat::Grayscale16)
+                .unwrap()
+                .as_bytes(),
+            &[0, 0]
+        );
     }
 
     #[test]
-    fn tgacolor_intensity() {
-        let tgacolor = TGAColor::new_rgba(128, 128, 128, 128);
-        let expected = 128.0 * 0.5;
-        let new_tgacolor: TGAColor = tgacolor * 0.5;
+    fn tgaimage_gray16_set_get() {
+        let mut image = TGAImage::try_new(3, 1, TGAImageFormat::Grayscale16).unwrap();
+
+        image.set(0, 0, &TGAColor::new_rgb(255, 0, 0));
+        image.set(1, 0, &TGAColor::new_rgb(255, 255, 255));
+        image.set_gray16(2, 0, 0x1234);
+
+        assert_eq!(image.get_gray16(0, 0), 76 * 257);
+        assert_eq!(image.get_gray16(1, 0), u16::MAX);
+        assert_eq!(image.get(0, 0).channels(), [76]);
+        assert_eq!(image.get(1, 0).channels(), [255]);
+        assert_eq!(image.get(2, 0).channels(), [18]);
+        assert_eq!(
+            image
+                .pixels()
+                .map(|(_, _, c)| c.channels()[0])
+                .collect::<Vec<_>>(),
+            [76, 255, 18]
+        );
 
-        for i in 0..4 {
-            let color_index = index_to_rgba_color_channel(i).unwrap();
+        let color = image.get(0, 0);
 
-            assert_eq!(new_tgacolor[color_index], expected as u8);
+        image.set(2, 0, &color);
+
+        assert_eq!(image.get_gray16(2, 0), 76 * 257);
+    }
+
+    #[test]
+    fn tgaimage_gray16_fill_rect_matches_set() {
+        for color in [
+            TGAColor::new_rgb(255, 0, 0),
+            TGAColor::new_rgba(10, 200, 30, 0),
+            TGAColor::from_slice(&[7]).unwrap(),
+        ] {
+            let mut filled = TGAImage::try_new(3, 2, TGAImageFormat::Grayscale16).unwrap();
+            let mut set = TGAImage::try_new(3, 2, TGAImageFormat::Grayscale16).unwrap();
+
+            filled.fill_rect(1, 0, 2, 2, &color);
+
+            for (x, y) in [(1, 0), (2, 0), (1, 1), (2, 1)] {
+                set.set(x, y, &color);
+            }
+
+            assert_eq!(filled.as_bytes(), set.as_bytes(), "{:?}", color);
+
+            filled.clear_color(&color);
+            set.set(0, 0, &color);
+            set.set(0, 1, &color);
+
+            assert_eq!(filled.as_bytes(), set.as_bytes(), "{:?}", color);
         }
     }
-}
 
-#[cfg(test)]
-mod tests_tgaimage {
-    use super::*;
+    #[test]
+    #[should_panic(expected = "Not a 16-bit grayscale image")]
+    fn tgaimage_gray16_format_check() {
+        TGAImage::try_new(1, 1, TGAImageFormat::Grayscale)
+            .unwrap()
+            .set_gray16(0, 0, 1);
+    }
 
     #[test]
-    fn tgaimage_getters() {
-        let image = TGAImage::new(100, 100, TGAImageFormat::Grayscale);
+    fn tgaimage_origin_round_trip() {
+        let mut image = gradient_image(TGAImageFormat::RGB);
 
-        assert_eq!(image.get_width(), 100);
-        assert_eq!(image.get_height(), 100);
-        assert_eq!(image.get_bytespp(), TGAImageFormat::Grayscale);
+        image.set(0, 0, &TGAColor::new_rgb(1, 2, 3));
+        assert_eq!(image.origin(), Origin::BottomLeft);
+
+        for &origin in &[Origin::TopLeft, Origin::BottomLeft] {
+            for &rle in &[false, true] {
+                let encoded = image.encode_to_vec_with_origin(origin, rle).unwrap();
+                let decoded = TGAImage::read_from(&mut Cursor::new(encoded)).unwrap();
+
+                assert_eq!(&decoded[(0, 0)], &[3, 2, 1]);
+                assert_eq!(decoded, image);
+                assert_eq!(decoded.origin(), origin);
+            }
+        }
     }
 
     #[test]
-    fn tgaimage_buffer() {
-        let width = 100;
-        let height = 100;
-        let format = TGAImageFormat::Grayscale;
-        let image = TGAImage::new(width, height, format);
+    fn tgaimage_origin_row_order() {
+        let mut image = gradient_image(TGAImageFormat::Grayscale);
+        let row_length = image.get_width() as usize;
+        let encoded = image
+            .encode_to_vec_with_origin(Origin::TopLeft, false)
+            .unwrap();
+
+        // the top row is the last one in memory
+        assert_eq!(encoded[17], 0x20);
+        assert_eq!(
+            &encoded[18..18 + row_length],
+            &image.data[image.data.len() - row_length..]
+        );
 
-        assert_eq!(image.data.len(), (width * height * format as u32) as usize);
+        image.set_origin(Origin::TopLeft);
 
-        let format = TGAImageFormat::RGBA;
-        let image = TGAImage::new(100, 100, format);
+        let encoded = image
+            .encode_to_vec_with_origin(Origin::BottomLeft, false)
+            .unwrap();
 
-        assert_eq!(image.data.len(), (width * height * format as u32) as usize);
+        assert_eq!(encoded[17], 0x00);
+        assert_eq!(&encoded[18..18 + image.data.len()], &image.data[..]);
+        assert_eq!(
+            TGAImage::read_from(&mut Cursor::new(encoded))
+                .unwrap()
+                .origin(),
+            Origin::BottomLeft
+        );
     }
 
     #[test]
-    fn tgaimage_clear() {
-        let width = 100;
-        let height = 100;
-        let format = TGAImageFormat::Grayscale;
-        let mut image = TGAImage::new(width, height, format);
+    #[allow(deprecated)]
+    fn tgaimage_write_to_layout() {
+        let image = gradient_image(TGAImageFormat::RGB);
+        let mut encoded = Vec::new();
+
+        image.write_to(&mut encoded, false, false).unwrap();
+
+        assert_eq!(encoded.len(), 18 + image.data.len() + 8 + 18);
+        assert_eq!(encoded[2], 2);
+        assert_eq!(encoded[16], 24);
+        assert_eq!(encoded[17], 0x20);
+        assert_eq!(&encoded[18..18 + image.data.len()], &image.data[..]);
+        assert!(encoded.ends_with(b"TRUEVISION-XFILE.\0"));
+    }
 
-        image.buffer().iter_mut().for_each(|e| *e = 255);
-        image.buffer().iter().for_each(|e| assert_eq!(*e, 255));
-        image.clear();
-        image.buffer().iter().for_each(|e| assert_eq!(*e, 0));
+    #[test]
+    fn tgaimage_read_from_invalid_header() {
+        let bytes = tga_header_bytes(2, 0, 2, 24, 0);
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(bytes)),
+            Err(TGAError::InvalidHeader { .. })
+        ));
+    }
+
+    #[test]
+    fn tgaimage_read_from_short_header() {
+        let bytes = tga_header_bytes(2, 2, 2, 24, 0);
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(&bytes[..10])),
+            Err(TGAError::TruncatedData {
+                expected: 18,
+                got: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn tgaimage_read_from_unsupported_type() {
+        let bytes = tga_header_bytes(32, 2, 2, 24, 0);
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(bytes)),
+            Err(TGAError::UnsupportedImageType(32))
+        ));
+    }
+
+    #[test]
+    fn tgaimage_read_from_unsupported_bit_depth() {
+        for bpp in &[0u8, 12, 40] {
+            let bytes = tga_header_bytes(2, 2, 2, *bpp, 0);
+
+            assert!(matches!(
+                TGAImage::read_from(&mut Cursor::new(bytes)),
+                Err(TGAError::UnsupportedBitDepth(b)) if b == *bpp
+            ));
+        }
+    }
+
+    #[test]
+    fn tgaimage_read_from_truncated_pixels() {
+        let mut bytes = tga_header_bytes(2, 2, 2, 24, 0);
+
+        bytes.extend_from_slice(&[0u8; 5]);
+
+        assert!(matches!(
+            TGAImage::read_from(&mut Cursor::new(bytes)),
+            Err(TGAError::TruncatedData {
+                expected: 12,
+                got: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn tgaimage_write_to_auto() {
+        let mut noise = TGAImage::try_new(32, 32, TGAImageFormat::RGB).unwrap();
+        let mut seed = 0x2545_f491u32;
+
+        noise.for_each_pixel(|_, _, pixel| {
+            for v in pixel.iter_mut() {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                *v = seed as u8;
+            }
+        });
+
+        let mut flat = TGAImage::try_new(32, 32, TGAImageFormat::RGB).unwrap();
+
+        flat.clear_color(&TGAColor::new_rgb(10, 20, 30));
+
+        for (image, datatype) in [(&noise, 2u8), (&flat, 10u8)] {
+            let mut encoded = Vec::new();
+
+            image
+                .write_to_auto_with_origin(&mut encoded, Origin::BottomLeft)
+                .unwrap();
+
+            assert_eq!(encoded[2], datatype);
+            assert_eq!(
+                TGAImage::read_from(&mut Cursor::new(encoded)).unwrap(),
+                *image
+            );
+        }
+
+        assert!(noise.rle_encoded_size() > noise.as_bytes().len());
+        // 8 runs of 128 pixels, each a count byte and one pixel
+        assert_eq!(flat.rle_encoded_size(), 8 * 4);
+        assert_eq!(
+            flat.encode_to_vec_with_origin(Origin::BottomLeft, true)
+                .unwrap()
+                .len(),
+            TGAHeader::SIZE + flat.rle_encoded_size() + 26
+        );
     }
 }
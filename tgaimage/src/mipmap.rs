@@ -0,0 +1,210 @@
+use crate::{Sampler, TGAColor, TGAImage, TextureFilter, WrapMode};
+
+impl TGAImage {
+    /// Build a mipmap chain by repeatedly halving the image with a box filter
+    ///
+    /// The image itself is not included, so the first entry is level 1 and the
+    /// last one is 1x1. Odd dimensions are rounded down, with the leftover pixels
+    /// averaged into the last row or column.
+    pub fn generate_mipmaps(&self) -> Vec<TGAImage> {
+        let mut levels: Vec<TGAImage> = Vec::new();
+
+        loop {
+            let src = levels.last().unwrap_or(self);
+
+            if src.width == 1 && src.height == 1 {
+                break;
+            }
+
+            let level = src.box_downsample((src.width / 2).max(1), (src.height / 2).max(1));
+
+            levels.push(level);
+        }
+
+        levels
+    }
+
+    fn box_downsample(&self, width: u32, height: u32) -> TGAImage {
+        let mut image = TGAImage::blank(width, height, self.bytespp);
+        let span = |i: u32, size: u32, src_size: u32| {
+            (i as u64 * src_size as u64 / size as u64) as u32
+                ..((i as u64 + 1) * src_size as u64 / size as u64) as u32
+        };
+
+        image.copy_properties(self);
+        image.for_each_pixel(|x, y, pixel| {
+            let mut sum = [0u32; 4];
+            let mut count = 0;
+
+            for sy in span(y, height, self.height) {
+                for sx in span(x, width, self.width) {
+                    match self[(sx, sy)] {
+                        [lo, hi] => sum[0] += u16::from_le_bytes([lo, hi]) as u32,
+                        ref src => sum
+                            .iter_mut()
+                            .zip(src.iter())
+                            .for_each(|(s, v)| *s += *v as u32),
+                    }
+                    count += 1;
+                }
+            }
+
+            match pixel {
+                [lo, hi] => [*lo, *hi] = (((sum[0] + count / 2) / count) as u16).to_le_bytes(),
+                _ => pixel
+                    .iter_mut()
+                    .zip(sum.iter())
+                    .for_each(|(v, s)| *v = ((s + count / 2) / count) as u8),
+            }
+        });
+
+        image
+    }
+}
+
+/// Texture lookup with level of detail selection over a mipmap chain
+#[derive(Debug, Copy, Clone)]
+pub struct MipmapSampler<'a> {
+    image: &'a TGAImage,
+    mipmaps: &'a [TGAImage],
+    wrap: WrapMode,
+    filter: TextureFilter,
+}
+
+impl<'a> MipmapSampler<'a> {
+    /// Create a sampler over an image and its [`TGAImage::generate_mipmaps`] chain
+    ///
+    /// [`TextureFilter::Nearest`] and [`TextureFilter::Bilinear`] sample the closest
+    /// level, [`TextureFilter::Trilinear`] blends the two closest ones.
+    pub fn new(
+        image: &'a TGAImage,
+        mipmaps: &'a [TGAImage],
+        wrap: WrapMode,
+        filter: TextureFilter,
+    ) -> Self {
+        MipmapSampler {
+            image,
+            mipmaps,
+            wrap,
+            filter,
+        }
+    }
+
+    /// Number of levels including the base image
+    pub fn levels(&self) -> usize {
+        self.mipmaps.len() + 1
+    }
+
+    /// Sample `(u, v)` at a level of detail, where 0 is the base image and every
+    /// next level is half the size of the previous one
+    ///
+    /// The level is clamped to the available mipmap chain.
+    pub fn sample_lod(&self, u: f32, v: f32, level: f32) -> TGAColor {
+        let level = level.clamp(0.0, self.mipmaps.len() as f32);
+
+        match self.filter {
+            TextureFilter::Nearest | TextureFilter::Bilinear => self
+                .sampler(level.round() as usize)
+                .sample_filtered(u, v, self.filter),
+            TextureFilter::Trilinear => {
+                let lower = level.floor() as usize;
+                let upper = (lower + 1).min(self.mipmaps.len());
+
+                TGAColor::lerp(
+                    &self.sampler(lower).sample_bilinear(u, v),
+                    &self.sampler(upper).sample_bilinear(u, v),
+                    level - lower as f32,
+                )
+            }
+        }
+    }
+
+    fn sampler(&self, level: usize) -> Sampler<'a> {
+        let image = match level {
+            0 => self.image,
+            _ => &self.mipmaps[level - 1],
+        };
+
+        Sampler::new(image, self.wrap)
+    }
+}
+
+#[cfg(test)]
+mod tests_mipmap {
+    use crate::{MipmapSampler, TGAColor, TGAImage, TGAImageFormat, TextureFilter, WrapMode};
+
+    fn checkerboard(width: u32, height: u32) -> TGAImage {
+        let mut image = TGAImage::try_new(width, height, TGAImageFormat::RGB).unwrap();
+
+        image.for_each_pixel(|x, y, pixel| {
+            pixel
+                .iter_mut()
+                .for_each(|v| *v = ((x + y) % 2) as u8 * 255)
+        });
+
+        image
+    }
+
+    #[test]
+    fn mipmap_chain_sizes() {
+        let sizes = |width, height| {
+            TGAImage::try_new(width, height, TGAImageFormat::Grayscale)
+                .unwrap()
+                .generate_mipmaps()
+                .iter()
+                .map(|level| (level.get_width(), level.get_height()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sizes(8, 8), vec![(4, 4), (2, 2), (1, 1)]);
+        assert_eq!(sizes(8, 2), vec![(4, 1), (2, 1), (1, 1)]);
+        assert_eq!(sizes(5, 3), vec![(2, 1), (1, 1)]);
+        assert_eq!(sizes(1, 1), vec![]);
+        assert_eq!(sizes(1024, 1).len(), 10);
+    }
+
+    #[test]
+    fn mipmap_odd_dimensions_average_all_pixels() {
+        let mut image = TGAImage::try_new(3, 1, TGAImageFormat::Grayscale).unwrap();
+
+        image.data.copy_from_slice(&[30, 60, 90]);
+
+        let mipmaps = image.generate_mipmaps();
+
+        assert_eq!(mipmaps[0].as_bytes(), &[60]);
+        assert_eq!(mipmaps[0].get_bytespp(), TGAImageFormat::Grayscale);
+    }
+
+    #[test]
+    fn mipmap_checkerboard_converges_to_gray() {
+        let image = checkerboard(16, 8);
+        let mipmaps = image.generate_mipmaps();
+
+        for level in &mipmaps {
+            assert!(level.as_bytes().iter().all(|v| *v == 128));
+        }
+
+        let sampler =
+            MipmapSampler::new(&image, &mipmaps, WrapMode::Repeat, TextureFilter::Nearest);
+        let gray = |color: TGAColor| (color.r(), color.g(), color.b());
+
+        assert_eq!(sampler.levels(), 5);
+        assert_eq!(gray(sampler.sample_lod(0.0, 0.0, 0.0)), (0, 0, 0));
+        assert_eq!(gray(sampler.sample_lod(0.0, 0.0, 0.4)), (0, 0, 0));
+        assert_eq!(gray(sampler.sample_lod(0.3, 0.7, 1.0)), (128, 128, 128));
+        assert_eq!(gray(sampler.sample_lod(0.3, 0.7, 10.0)), (128, 128, 128));
+    }
+
+    #[test]
+    fn mipmap_trilinear_blends_levels() {
+        let image = checkerboard(4, 4);
+        let mipmaps = image.generate_mipmaps();
+        let sampler =
+            MipmapSampler::new(&image, &mipmaps, WrapMode::Clamp, TextureFilter::Trilinear);
+
+        // Texel center of the black base pixel blended halfway with the gray level 1
+        assert_eq!(sampler.sample_lod(0.125, 0.125, 0.5).r(), 64);
+        assert_eq!(sampler.sample_lod(0.125, 0.125, 1.5).r(), 128);
+        assert_eq!(sampler.sample_lod(0.125, 0.125, -1.0).r(), 0);
+    }
+}
@@ -0,0 +1,273 @@
+use std::io::{Read, Write};
+
+use crate::{TGAError, TGAImage, TGAImageFormat};
+
+const MAX_VALUE: u32 = 255;
+
+impl TGAImage {
+    /// Write an image as a binary PPM (P6) or PGM (P5) file
+    ///
+    /// Color images produce P6 with the alpha channel dropped, grayscale images produce P5.
+    /// 16-bit grayscale images are written with maxval 65535 and big-endian samples.
+    /// PPM rows go from top to bottom, so the image bottom row (`y == 0`) is written last.
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> Result<(), TGAError> {
+        let (magic, max_value) = match self.bytespp {
+            TGAImageFormat::Grayscale => ("P5", MAX_VALUE),
+            TGAImageFormat::Grayscale16 => ("P5", u16::MAX as u32),
+            TGAImageFormat::RGB | TGAImageFormat::RGBA => ("P6", MAX_VALUE),
+            TGAImageFormat::Unknown => return Err(TGAError::UnsupportedBitDepth(0)),
+        };
+
+        write!(
+            out,
+            "{}\n{} {}\n{}\n",
+            magic, self.width, self.height, max_value
+        )?;
+
+        let bytes_per_line = self.width as usize * self.bytespp as usize;
+
+        for row in self.data.chunks_exact(bytes_per_line).rev() {
+            let line: Vec<u8> = match self.bytespp {
+                TGAImageFormat::Grayscale => row.to_vec(),
+                TGAImageFormat::Grayscale16 => {
+                    row.chunks_exact(2).flat_map(|v| [v[1], v[0]]).collect()
+                }
+                _ => row
+                    .chunks_exact(self.bytespp as usize)
+                    .flat_map(|bgr| [bgr[2], bgr[1], bgr[0]])
+                    .collect(),
+            };
+
+            out.write_all(&line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Read a binary PPM (P6) or PGM (P5) file
+    ///
+    /// P6 files with maxval 255 produce RGB images. P5 files with maxval 255 produce
+    /// grayscale images, with a maxval up to 65535 their big-endian samples are
+    /// scaled onto the full range of 16-bit grayscale images.
+    pub fn read_ppm<R: Read>(reader: &mut R) -> Result<TGAImage, TGAError> {
+        let mut magic = [0u8; 2];
+
+        TGAImage::read_data(reader, &mut magic)?;
+
+        if &magic != b"P5" && &magic != b"P6" {
+            return Err(TGAError::InvalidHeader {
+                reason: "unsupported PPM magic number",
+            });
+        }
+
+        let width = read_header_value(reader)?;
+        let height = read_header_value(reader)?;
+        let max_value = read_header_value(reader)?;
+
+        if width == 0 || height == 0 {
+            return Err(TGAError::InvalidHeader {
+                reason: "image has zero width or height",
+            });
+        }
+
+        let format = match (&magic, max_value) {
+            (b"P5", MAX_VALUE) => TGAImageFormat::Grayscale,
+            (b"P5", 256..=65535) => TGAImageFormat::Grayscale16,
+            (b"P6", MAX_VALUE) => TGAImageFormat::RGB,
+            _ => {
+                return Err(TGAError::InvalidHeader {
+                    reason: "unsupported PPM maxval",
+                })
+            }
+        };
+
+        let bytespp = format as usize;
+        let mut image = TGAImage::try_new(width, height, format)?;
+        let mut line = vec![0u8; width as usize * bytespp];
+
+        for y in (0..height).rev() {
+            TGAImage::read_data(reader, &mut line)?;
+
+            let offset = image.pixel_offset(0, y);
+            let row = &mut image.data[offset..offset + line.len()];
+
+            match format {
+                TGAImageFormat::Grayscale => row.copy_from_slice(&line),
+                TGAImageFormat::Grayscale16 => row
+                    .chunks_exact_mut(2)
+                    .zip(line.chunks_exact(2))
+                    .for_each(|(le, be)| {
+                        let value = u16::from_be_bytes([be[0], be[1]]) as u32;
+                        let scaled =
+                            (value.min(max_value) * u16::MAX as u32 + max_value / 2) / max_value;
+
+                        le.copy_from_slice(&(scaled as u16).to_le_bytes());
+                    }),
+                _ => row
+                    .chunks_exact_mut(bytespp)
+                    .zip(line.chunks_exact(bytespp))
+                    .for_each(|(bgr, rgb)| bgr.copy_from_slice(&[rgb[2], rgb[1], rgb[0]])),
+            }
+        }
+
+        Ok(image)
+    }
+}
+
+/// Read a decimal header value skipping whitespace and comments before it
+///
+/// Consumes the single whitespace character terminating the value.
+fn read_header_value<R: Read>(reader: &mut R) -> Result<u32, TGAError> {
+    let invalid = TGAError::InvalidHeader {
+        reason: "malformed PPM header",
+    };
+    let mut byte = [0u8; 1];
+    let mut in_comment = false;
+
+    loop {
+        TGAImage::read_data(reader, &mut byte)?;
+
+        match byte[0] {
+            b'\n' | b'\r' => in_comment = false,
+            _ if in_comment => (),
+            b'#' => in_comment = true,
+            b if b.is_ascii_whitespace() => (),
+            b if b.is_ascii_digit() => break,
+            _ => return Err(invalid),
+        }
+    }
+
+    let mut value = 0u32;
+
+    while byte[0].is_ascii_digit() {
+        value = value
+            .checked_mul(10)
+            .and_then(|v| v.checked_add((byte[0] - b'0') as u32))
+            .ok_or(TGAError::InvalidHeader {
+                reason: "PPM header value is too large",
+            })?;
+        TGAImage::read_data(reader, &mut byte)?;
+    }
+
+    if !byte[0].is_ascii_whitespace() {
+        return Err(invalid);
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests_ppm {
+    use crate::{TGAColor, TGAError, TGAImage, TGAImageFormat};
+
+    #[test]
+    fn ppm_write_layout() {
+        let mut image = TGAImage::try_new(2, 2, TGAImageFormat::RGBA).unwrap();
+
+        image.set(0, 0, &TGAColor::new_rgba(1, 2, 3, 4));
+        image.set(1, 1, &TGAColor::new_rgba(5, 6, 7, 8));
+
+        let mut encoded = Vec::new();
+
+        image.write_ppm(&mut encoded).unwrap();
+
+        let mut expected = b"P6\n2 2\n255\n".to_vec();
+
+        expected.extend_from_slice(&[0, 0, 0, 5, 6, 7, 1, 2, 3, 0, 0, 0]);
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn ppm_write_gray16() {
+        let mut image = TGAImage::try_new(2, 1, TGAImageFormat::Grayscale16).unwrap();
+
+        image.set_gray16(0, 0, 0x1234);
+        image.set_gray16(1, 0, 0xfffe);
+
+        let mut encoded = Vec::new();
+
+        image.write_ppm(&mut encoded).unwrap();
+
+        assert_eq!(encoded, b"P5\n2 1\n65535\n\x12\x34\xff\xfe");
+    }
+
+    #[test]
+    fn ppm_round_trip() {
+        for &format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::Grayscale16,
+            TGAImageFormat::RGB,
+        ] {
+            let mut image = TGAImage::try_new(5, 3, format).unwrap();
+
+            image.for_each_pixel(|x, y, pixel| {
+                for (i, c) in pixel.iter_mut().enumerate() {
+                    *c = (x * 40 + y * 7 + i as u32) as u8;
+                }
+            });
+
+            let mut encoded = Vec::new();
+
+            image.write_ppm(&mut encoded).unwrap();
+
+            let decoded = TGAImage::read_ppm(&mut encoded.as_slice()).unwrap();
+
+            assert_eq!(decoded.get_width(), 5);
+            assert_eq!(decoded.get_height(), 3);
+            assert_eq!(decoded.get_bytespp(), format);
+            assert_eq!(decoded.as_bytes(), image.as_bytes());
+        }
+    }
+
+    #[test]
+    fn ppm_read_gray16_maxval() {
+        let mut encoded = b"P5\n3 1\n1023\n".to_vec();
+
+        encoded.extend_from_slice(&[0x03, 0xff, 0x02, 0x00, 0x00, 0x00]);
+
+        let image = TGAImage::read_ppm(&mut encoded.as_slice()).unwrap();
+
+        assert_eq!(image.get_bytespp(), TGAImageFormat::Grayscale16);
+        assert_eq!(
+            (0..3).map(|x| image.get_gray16(x, 0)).collect::<Vec<_>>(),
+            [u16::MAX, 32800, 0]
+        );
+    }
+
+    #[test]
+    fn ppm_read_header_comments() {
+        let mut encoded = b"P5 # grayscale\n# size follows\n3\t# width\n 1\r\n255\n".to_vec();
+
+        encoded.extend_from_slice(&[10, 20, 30]);
+
+        let image = TGAImage::read_ppm(&mut encoded.as_slice()).unwrap();
+
+        assert_eq!(image.get_width(), 3);
+        assert_eq!(image.get_height(), 1);
+        assert_eq!(image.as_bytes(), &[10, 20, 30]);
+    }
+
+    #[test]
+    fn ppm_read_invalid() {
+        let cases: &[&[u8]] = &[
+            b"P3\n1 1\n255\n0 0 0\n",
+            b"P6\n1 1\n65535\n\0\0\0\0\0\0",
+            b"P5\n1 1\n100\n\0",
+            b"P5\n1 1\n65536\n\0\0",
+            b"P6\n0 1\n255\n",
+            b"P6\n1 x\n255\n\0\0\0",
+        ];
+
+        for case in cases {
+            assert!(matches!(
+                TGAImage::read_ppm(&mut &case[..]),
+                Err(TGAError::InvalidHeader { .. })
+            ));
+        }
+
+        assert!(matches!(
+            TGAImage::read_ppm(&mut &b"P6\n2 1\n255\n\0\0\0"[..]),
+            Err(TGAError::TruncatedData { .. })
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{TGAColor, TGAImage};
+
+/// Handling of texture coordinates outside of the `0..1` range
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum WrapMode {
+    /// Use the closest edge texel
+    Clamp,
+    /// Tile the texture
+    Repeat,
+    /// Tile the texture mirroring every other copy
+    MirrorRepeat,
+}
+
+/// Texel interpolation method
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TextureFilter {
+    /// Take the closest texel
+    Nearest,
+    /// Blend four surrounding texels
+    Bilinear,
+    /// Blend bilinear samples of the two closest mipmap levels, see
+    /// [`crate::MipmapSampler`]
+    ///
+    /// Samplers without mipmaps treat it as [`TextureFilter::Bilinear`].
+    Trilinear,
+}
+
+/// Texture lookup with normalized coordinates
+#[derive(Debug, Copy, Clone)]
+pub struct Sampler<'a> {
+    image: &'a TGAImage,
+    wrap: WrapMode,
+}
+
+impl<'a> Sampler<'a> {
+    pub fn new(image: &'a TGAImage, wrap: WrapMode) -> Self {
+        Sampler { image, wrap }
+    }
+
+    pub fn image(&self) -> &'a TGAImage {
+        self.image
+    }
+
+    pub fn wrap_mode(&self) -> WrapMode {
+        self.wrap
+    }
+
+    /// Nearest texel color at `(u, v)`, where `(0, 0)` is the first image pixel
+    /// and `(1, 1)` is the opposite corner
+    pub fn sample(&self, u: f32, v: f32) -> TGAColor {
+        let x = self.wrap(u * self.image.width as f32, self.image.width);
+        let y = self.wrap(v * self.image.height as f32, self.image.height);
+
+        self.image.get_unchecked(x, y)
+    }
+
+    /// Blend of the four texels around `(u, v)` weighted by the distance to their centers
+    pub fn sample_bilinear(&self, u: f32, v: f32) -> TGAColor {
+        let (width, height) = (self.image.width, self.image.height);
+        let fx = u * width as f32 - 0.5;
+        let fy = v * height as f32 - 0.5;
+        let (tx, ty) = (fx - fx.floor(), fy - fy.floor());
+        let (x0, x1) = (self.wrap(fx, width), self.wrap(fx + 1.0, width));
+        let (y0, y1) = (self.wrap(fy, height), self.wrap(fy + 1.0, height));
+
+        TGAColor::weighted_sum(&[
+            (self.image.get_unchecked(x0, y0), (1.0 - tx) * (1.0 - ty)),
+            (self.image.get_unchecked(x1, y0), tx * (1.0 - ty)),
+            (self.image.get_unchecked(x0, y1), (1.0 - tx) * ty),
+            (self.image.get_unchecked(x1, y1), tx * ty),
+        ])
+    }
+
+    /// Sample `(u, v)` with the given filter
+    pub fn sample_filtered(&self, u: f32, v: f32, filter: TextureFilter) -> TGAColor {
+        match filter {
+            TextureFilter::Nearest => self.sample(u, v),
+            TextureFilter::Bilinear | TextureFilter::Trilinear => self.sample_bilinear(u, v),
+        }
+    }
+
+    /// Map a texel coordinate onto `0..size` according to the wrap mode
+    fn wrap(&self, coord: f32, size: u32) -> u32 {
+        // Float to integer casts saturate, so NaN ends up at the first texel
+        let texel = coord.floor() as i64;
+        let size = size as i64;
+
+        let texel = match self.wrap {
+            WrapMode::Clamp => texel.clamp(0, size - 1),
+            WrapMode::Repeat => texel.rem_euclid(size),
+            WrapMode::MirrorRepeat => {
+                let texel = texel.rem_euclid(2 * size);
+
+                if texel < size {
+                    texel
+                } else {
+                    2 * size - 1 - texel
+                }
+            }
+        };
+
+        texel as u32
+    }
+}
+
+#[cfg(test)]
+mod tests_sampler {
+    use crate::{Sampler, TGAColor, TGAImage, TGAImageFormat, TextureFilter, WrapMode};
+
+    fn ramp() -> TGAImage {
+        let mut image = TGAImage::try_new(4, 2, TGAImageFormat::Grayscale).unwrap();
+
+        image.for_each_pixel(|x, y, pixel| pixel[0] = (x + y * 4) as u8);
+
+        image
+    }
+
+    fn sample(image: &TGAImage, wrap: WrapMode, u: f32, v: f32) -> u8 {
+        Sampler::new(image, wrap).sample(u, v).as_slice()[0]
+    }
+
+    #[test]
+    fn sample_nearest_texel() {
+        let image = ramp();
+
+        for &wrap in &[WrapMode::Clamp, WrapMode::Repeat, WrapMode::MirrorRepeat] {
+            assert_eq!(sample(&image, wrap, 0.0, 0.0), 0);
+            assert_eq!(sample(&image, wrap, 0.3, 0.0), 1);
+            assert_eq!(sample(&image, wrap, 0.99, 0.0), 3);
+            assert_eq!(sample(&image, wrap, 0.6, 0.7), 6);
+        }
+    }
+
+    #[test]
+    fn sample_wrap_modes() {
+        let image = ramp();
+
+        assert_eq!(sample(&image, WrapMode::Clamp, 1.0, 1.0), 7);
+        assert_eq!(sample(&image, WrapMode::Repeat, 1.0, 1.0), 0);
+        assert_eq!(sample(&image, WrapMode::MirrorRepeat, 1.0, 1.0), 7);
+
+        assert_eq!(sample(&image, WrapMode::Clamp, -0.1, -3.0), 0);
+        assert_eq!(sample(&image, WrapMode::Repeat, -0.1, 0.0), 3);
+        assert_eq!(sample(&image, WrapMode::Repeat, -0.3, -0.25), 6);
+        assert_eq!(sample(&image, WrapMode::MirrorRepeat, -0.1, 0.0), 0);
+        assert_eq!(sample(&image, WrapMode::MirrorRepeat, -0.3, -0.6), 5);
+        assert_eq!(sample(&image, WrapMode::MirrorRepeat, 1.3, 0.0), 2);
+        assert_eq!(sample(&image, WrapMode::Clamp, f32::NAN, 0.0), 0);
+    }
+
+    #[test]
+    fn sample_color() {
+        let mut image = TGAImage::try_new(2, 1, TGAImageFormat::RGB).unwrap();
+
+        image.set(1, 0, &TGAColor::new_rgb(10, 20, 30));
+
+        let color = Sampler::new(&image, WrapMode::Clamp).sample(0.75, 0.5);
+
+        assert_eq!((color.r(), color.g(), color.b()), (10, 20, 30));
+    }
+
+    #[test]
+    fn sample_bilinear_halfway() {
+        let mut image = TGAImage::try_new(2, 1, TGAImageFormat::RGB).unwrap();
+
+        image.set(1, 0, &TGAColor::new_rgb(255, 255, 255));
+
+        let sampler = Sampler::new(&image, WrapMode::Clamp);
+        let color = sampler.sample_bilinear(0.5, 0.5);
+
+        assert!(color.r() == 127 || color.r() == 128);
+        assert_eq!(color.r(), color.g());
+        assert_eq!(color.r(), color.b());
+        assert_eq!(
+            sampler
+                .sample_filtered(0.5, 0.5, TextureFilter::Nearest)
+                .r(),
+            255
+        );
+        // Texel centers return the texel itself
+        assert_eq!(sampler.sample_bilinear(0.25, 0.5).r(), 0);
+        assert_eq!(sampler.sample_bilinear(0.75, 0.5).r(), 255);
+    }
+
+    #[test]
+    fn sample_bilinear_edges() {
+        let mut image = TGAImage::try_new(2, 1, TGAImageFormat::Grayscale).unwrap();
+
+        image.pixel_mut(0, 0).unwrap()[0] = 200;
+
+        let bilinear = |wrap, u| {
+            Sampler::new(&image, wrap)
+                .sample_bilinear(u, 0.5)
+                .as_slice()[0]
+        };
+
+        // Left of the first texel center the clamped edge keeps its value, while
+        // the repeated texture blends in the last texel
+        assert_eq!(bilinear(WrapMode::Clamp, 0.0), 200);
+        assert_eq!(bilinear(WrapMode::MirrorRepeat, 0.0), 200);
+        assert_eq!(bilinear(WrapMode::Repeat, 0.0), 100);
+        assert_eq!(bilinear(WrapMode::Repeat, 1.0), 100);
+        assert_eq!(bilinear(WrapMode::Clamp, 1.0), 0);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{gray16_to_gray8, rec601_luma, TGAImage, TGAImageFormat};
+
+/// Per-channel histograms of an image
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelHistograms {
+    /// 256-bin counts for every channel present in an image, in BGRA order
+    ///
+    /// Grayscale images have a single histogram, 16-bit gray levels are counted
+    /// scaled down to 8 bits like [`TGAImage::get`] returns them.
+    pub channels: Vec<[u32; 256]>,
+}
+
+impl TGAImage {
+    /// Count channel values of every pixel
+    pub fn histogram(&self) -> ChannelHistograms {
+        let bytespp = self.bytespp as usize;
+
+        if self.bytespp == TGAImageFormat::Grayscale16 {
+            let mut bins = [0u32; 256];
+
+            for value in self.gray16_values() {
+                bins[gray16_to_gray8(value) as usize] += 1;
+            }
+
+            return ChannelHistograms {
+                channels: vec![bins],
+            };
+        }
+
+        let mut channels = vec![[0u32; 256]; bytespp];
+
+        for pixel in self.data.chunks_exact(bytespp) {
+            for (bins, value) in channels.iter_mut().zip(pixel.iter()) {
+                bins[*value as usize] += 1;
+            }
+        }
+
+        ChannelHistograms { channels }
+    }
+
+    /// Darkest and brightest pixel luma, see [`crate::TGAColor::luma`]
+    pub fn min_max_luma(&self) -> (u8, u8) {
+        self.luma_values()
+            .fold((u8::MAX, u8::MIN), |(min, max), luma| {
+                (min.min(luma), max.max(luma))
+            })
+    }
+
+    /// Average pixel luma, see [`crate::TGAColor::luma`]
+    pub fn mean_luma(&self) -> f32 {
+        let sum: u64 = self.luma_values().map(|luma| luma as u64).sum();
+
+        sum as f32 / (self.width as u64 * self.height as u64) as f32
+    }
+
+    /// Remap the `min..=max` range of every color channel onto `0..=255`
+    ///
+    /// 16-bit gray levels are remapped onto `0..=65535`. Alpha is left untouched,
+    /// as are channels holding a single value.
+    pub fn stretch_contrast(&mut self) {
+        if self.bytespp == TGAImageFormat::Grayscale16 {
+            self.stretch_contrast_gray16();
+            return;
+        }
+
+        let bytespp = self.bytespp as usize;
+        let color_channels = if self.bytespp == TGAImageFormat::RGBA {
+            3
+        } else {
+            bytespp
+        };
+        let histograms = self.histogram();
+        let mut lut = vec![[0u8; 256]; color_channels];
+
+        for (lut, bins) in lut.iter_mut().zip(histograms.channels.iter()) {
+            let min = bins.iter().position(|count| *count != 0).unwrap_or(0);
+            let max = bins.iter().rposition(|count| *count != 0).unwrap_or(0);
+
+            for (value, mapped) in lut.iter_mut().enumerate() {
+                *mapped = if min == max {
+                    value as u8
+                } else {
+                    let value = value.max(min).min(max);
+
+                    (((value - min) * 255 + (max - min) / 2) / (max - min)) as u8
+                };
+            }
+        }
+
+        for pixel in self.data.chunks_exact_mut(bytespp) {
+            for (value, lut) in pixel.iter_mut().zip(lut.iter()) {
+                *value = lut[*value as usize];
+            }
+        }
+    }
+
+    fn stretch_contrast_gray16(&mut self) {
+        let (min, max) = self
+            .gray16_values()
+            .fold((u16::MAX, u16::MIN), |(min, max), v| {
+                (min.min(v), max.max(v))
+            });
+
+        if min >= max {
+            return;
+        }
+
+        let range = (max - min) as u32;
+
+        for pixel in self.data.chunks_exact_mut(2) {
+            let value = u16::from_le_bytes([pixel[0], pixel[1]]);
+            let mapped = ((value - min) as u32 * u16::MAX as u32 + range / 2) / range;
+
+            pixel.copy_from_slice(&(mapped as u16).to_le_bytes());
+        }
+    }
+
+    fn gray16_values(&self) -> impl Iterator<Item = u16> + '_ {
+        self.data
+            .chunks_exact(2)
+            .map(|pixel| u16::from_le_bytes([pixel[0], pixel[1]]))
+    }
+
+    fn luma_values(&self) -> impl Iterator<Item = u8> + '_ {
+        self.data
+            .chunks_exact(self.bytespp as usize)
+            .map(|pixel| match *pixel {
+                [v] => v,
+                [lo, hi] => gray16_to_gray8(u16::from_le_bytes([lo, hi])),
+                [b, g, r, ..] => rec601_luma(r, g, b),
+                _ => 0,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests_stats {
+    use crate::{TGAColor, TGAImage, TGAImageFormat};
+
+    fn ramp(format: TGAImageFormat) -> TGAImage {
+        let mut image = TGAImage::try_new(256, 2, format).unwrap();
+
+        image.for_each_pixel(|x, _, pixel| pixel.iter_mut().for_each(|c| *c = x as u8));
+
+        image
+    }
+
+    #[test]
+    fn histogram_ramp_is_flat() {
+        for &format in &[
+            TGAImageFormat::Grayscale,
+            TGAImageFormat::RGB,
+            TGAImageFormat::RGBA,
+        ] {
+            let histograms = ramp(format).histogram();
+
+            assert_eq!(histograms.channels.len(), format as usize);
+
+            for bins in &histograms.channels {
+                assert!(bins.iter().all(|count| *count == 2));
+            }
+        }
+    }
+
+    #[test]
+    fn luma_statistics() {
+        let image = ramp(TGAImageFormat::RGB);
+
+        assert_eq!(image.min_max_luma(), (0, 255));
+        assert!((image.mean_luma() - 127.5).abs() < 0.01);
+
+        let mut image = TGAImage::try_new(2, 1, TGAImageFormat::RGBA).unwrap();
+
+        image.set(0, 0, &TGAColor::new_rgba(255, 0, 0, 0));
+        image.set(1, 0, &TGAColor::new_rgba(0, 0, 255, 255));
+
+        assert_eq!(image.min_max_luma(), (29, 76));
+        assert!((image.mean_luma() - 52.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn stretch_contrast() {
+        let mut image = TGAImage::try_new(51, 1, TGAImageFormat::RGBA).unwrap();
+
+        image.for_each_pixel(|x, _, pixel| {
+            pixel.copy_from_slice(&[50 + x as u8, 10 + x as u8 / 10, 7, 100 + x as u8])
+        });
+        image.stretch_contrast();
+
+        assert_eq!(&image[(0, 0)], &[0, 0, 7, 100]);
+        assert_eq!(&image[(25, 0)], &[128, 102, 7, 125]);
+        assert_eq!(&image[(50, 0)], &[255, 255, 7, 150]);
+    }
+
+    #[test]
+    fn gray16_statistics() {
+        // 255 and 256 differ in both bytes while being next to each other
+        let mut image = TGAImage::try_new(3, 1, TGAImageFormat::Grayscale16).unwrap();
+
+        image.set_gray16(0, 0, 255);
+        image.set_gray16(1, 0, 256);
+        image.set_gray16(2, 0, 257);
+
+        let histograms = image.histogram();
+
+        assert_eq!(histograms.channels.len(), 1);
+        assert_eq!(histograms.channels[0][1], 3);
+
+        image.stretch_contrast();
+
+        assert_eq!(image.get_gray16(0, 0), 0);
+        assert_eq!(image.get_gray16(1, 0), 32768);
+        assert_eq!(image.get_gray16(2, 0), u16::MAX);
+    }
+}
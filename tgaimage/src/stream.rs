@@ -0,0 +1,188 @@
+use std::io::Write;
+
+use crate::extension::FOOTER_SIGNATURE;
+use crate::{TGAError, TGAHeader, TGAImage, TGAImageFormat};
+
+/// Uncompressed TGA encoder accepting pixel rows one at a time, from top to bottom
+///
+/// Only a single row has to be kept in memory, so it suits images too big to be
+/// held in a [`TGAImage`].
+pub struct TGAStreamWriter<W: Write> {
+    out: W,
+    row_length: usize,
+    height: u32,
+    rows_written: u32,
+}
+
+impl<W: Write> TGAStreamWriter<W> {
+    /// Write the header of a `width` x `height` image and prepare to accept rows
+    pub fn new(
+        mut out: W,
+        width: u32,
+        height: u32,
+        format: TGAImageFormat,
+    ) -> Result<Self, TGAError> {
+        if width == 0 || height == 0 || width > u16::MAX as u32 || height > u16::MAX as u32 {
+            return Err(TGAError::InvalidHeader {
+                reason: "image dimensions must be in 1..=65535",
+            });
+        }
+
+        let datatypecode = match format {
+            TGAImageFormat::Grayscale | TGAImageFormat::Grayscale16 => 3,
+            TGAImageFormat::RGB | TGAImageFormat::RGBA => 2,
+            TGAImageFormat::Unknown => return Err(TGAError::UnsupportedBitDepth(0)),
+        };
+        let header = TGAHeader {
+            datatypecode,
+            bitsperpixel: (format as u8) << 3,
+            width: width as u16,
+            height: height as u16,
+            imagedescriptor: 0x20 | TGAImage::default_alpha_bits(format),
+            ..TGAHeader::default()
+        };
+
+        out.write_all(&header.to_bytes())?;
+
+        Ok(TGAStreamWriter {
+            out,
+            row_length: width as usize * format as usize,
+            height,
+            rows_written: 0,
+        })
+    }
+
+    /// Write the next row of pixels in BGRA order
+    pub fn write_row(&mut self, row: &[u8]) -> Result<(), TGAError> {
+        if row.len() != self.row_length {
+            return Err(TGAError::InvalidBufferSize {
+                expected: self.row_length,
+                got: row.len(),
+            });
+        }
+
+        if self.rows_written == self.height {
+            return Err(TGAError::RowCountMismatch {
+                expected: self.height,
+                got: self.rows_written + 1,
+            });
+        }
+
+        self.out.write_all(row)?;
+        self.rows_written += 1;
+
+        Ok(())
+    }
+
+    /// Write the file footer once all rows are written and return the underlying writer
+    pub fn finish(mut self) -> Result<W, TGAError> {
+        if self.rows_written != self.height {
+            return Err(TGAError::RowCountMismatch {
+                expected: self.height,
+                got: self.rows_written,
+            });
+        }
+
+        self.out.write_all(&[0u8; 8])?;
+        self.out.write_all(&FOOTER_SIGNATURE)?;
+        self.out.flush()?;
+
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests_stream {
+    use std::fs::File;
+    use std::io::BufWriter;
+
+    use crate::{TGAError, TGAImage, TGAImageFormat, TGAStreamWriter};
+
+    fn gradient_row(y: u32, width: u32, format: TGAImageFormat) -> Vec<u8> {
+        (0..width * format as u32)
+            .map(|i| (i / format as u32 * 3 + y * 5) as u8)
+            .collect()
+    }
+
+    #[test]
+    fn stream_writer_gradient() {
+        let path = std::env::temp_dir().join("tgaimage_stream_writer_gradient.tga");
+        let (width, height) = (37, 21);
+        let file = BufWriter::new(File::create(&path).unwrap());
+        let mut writer = TGAStreamWriter::new(file, width, height, TGAImageFormat::RGB).unwrap();
+
+        for y in 0..height {
+            writer
+                .write_row(&gradient_row(y, width, TGAImageFormat::RGB))
+                .unwrap();
+        }
+
+        drop(writer.finish().unwrap());
+
+        let image = TGAImage::read_tga_file(path.to_str().unwrap()).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(image.get_width(), width);
+        assert_eq!(image.get_height(), height);
+
+        // rows are streamed from the top, the image bottom row is its last one
+        for (y, row) in image.rows().enumerate() {
+            assert_eq!(
+                row,
+                gradient_row(height - 1 - y as u32, width, TGAImageFormat::RGB).as_slice()
+            );
+        }
+    }
+
+    #[test]
+    fn stream_writer_matches_write_to() {
+        for &format in &[TGAImageFormat::Grayscale, TGAImageFormat::RGBA] {
+            let mut writer = TGAStreamWriter::new(Vec::new(), 3, 2, format).unwrap();
+
+            for y in 0..2 {
+                writer.write_row(&gradient_row(y, 3, format)).unwrap();
+            }
+
+            let encoded = writer.finish().unwrap();
+            let image = TGAImage::read_from(&mut encoded.as_slice()).unwrap();
+
+            assert_eq!(
+                image
+                    .encode_to_vec_with_origin(image.origin(), false)
+                    .unwrap(),
+                encoded
+            );
+        }
+    }
+
+    #[test]
+    fn stream_writer_row_validation() {
+        let mut writer = TGAStreamWriter::new(Vec::new(), 2, 2, TGAImageFormat::RGB).unwrap();
+
+        assert!(matches!(
+            writer.write_row(&[0; 5]),
+            Err(TGAError::InvalidBufferSize {
+                expected: 6,
+                got: 5
+            })
+        ));
+        writer.write_row(&[0; 6]).unwrap();
+        assert!(matches!(
+            writer.finish(),
+            Err(TGAError::RowCountMismatch {
+                expected: 2,
+                got: 1
+            })
+        ));
+
+        let mut writer = TGAStreamWriter::new(Vec::new(), 1, 1, TGAImageFormat::Grayscale).unwrap();
+
+        writer.write_row(&[1]).unwrap();
+        assert!(matches!(
+            writer.write_row(&[2]),
+            Err(TGAError::RowCountMismatch { .. })
+        ));
+        assert!(TGAStreamWriter::new(Vec::new(), 0, 1, TGAImageFormat::RGB).is_err());
+        assert!(TGAStreamWriter::new(Vec::new(), 1, 1, TGAImageFormat::Unknown).is_err());
+    }
+}
@@ -1,37 +1,27 @@
 /// Wireframe rendering
-use tgaimage::{TGAColor, TGAImage, TGAImageFormat};
-use tinyrenderer::{line, model::Model};
-use tinyrenderer::geometry::{XAxis, YAxis};
-
-const WHITE: TGAColor = TGAColor::new_rgba(255, 255, 255, 255);
+use tgaimage::colors::WHITE;
+use tgaimage::{Origin, TGAImage, TGAImageFormat};
+use tinyrenderer::camera::viewport;
+use tinyrenderer::{model::Model, wireframe, CullMode};
 
 fn main() {
     let width = 800u32;
     let height = 800u32;
     let model = Model::new("african_head.obj").unwrap();
-    let mut image = TGAImage::new(width, height, TGAImageFormat::RGB);
+    let mut image =
+        TGAImage::try_new(width, height, TGAImageFormat::RGB).expect("Cannot create image");
 
     println!("v #{} f #{}", model.n_verts(), model.n_faces());
 
-    for i in 0..model.n_faces() {
-        let face = model.face(i);
-
-        for j in 0..3 {
-            let v0 = model.vert(face[j] as usize);
-            let v1 = model.vert(face[(j + 1) % 3] as usize);
-
-            let x0 = (v0.get_x() + 1.) * width as f32 / 2.;
-            let y0 = (v0.get_y() + 1.0) * height as f32 / 2.;
-            let x1 = (v1.get_x() + 1.) * width as f32 / 2.;
-            let y1 = (v1.get_y() + 1.) * height as f32 / 2.;
-
-            line(
-                x0 as i32, y0 as i32, x1 as i32, y1 as i32, &WHITE, &mut image,
-            );
-        }
-    }
+    wireframe(
+        &model,
+        &WHITE,
+        &mut image,
+        &viewport(0, 0, width, height, 255),
+        CullMode::None,
+    );
 
     image
-        .write_tga_file("africa.tga", true, true)
+        .write_tga_file_with_origin("africa.tga", Origin::BottomLeft, true)
         .expect("Cannot write file");
 }
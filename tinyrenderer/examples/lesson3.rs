@@ -1,7 +1,15 @@
-use tgaimage::{TGAImage, TGAImageFormat};
-use tinyrenderer::{TextureDef, triangle_barycentric_zbuf_with_texture, TriangleDef};
-use tinyrenderer::geometry::{Vector3F32, Vector3Int, XAxis, YAxis, ZAxis};
+use tgaimage::{Origin, TGAColor, TGAImageFormat, TextureFilter};
+use tinyrenderer::camera::{lookat, viewport};
+use tinyrenderer::framebuffer::Framebuffer;
+use tinyrenderer::geometry::{Vector3F32, Vector3Int};
 use tinyrenderer::model::Model;
+use tinyrenderer::zbuffer::ZBuffer;
+use tinyrenderer::{
+    render_depth, triangle_gouraud, CullMode, Diffuse, RenderOptions, Shadow, TextureDef,
+    TriangleDef,
+};
+
+const BACKGROUND: TGAColor = TGAColor::new_rgb(0, 0, 64);
 
 fn main() {
     plot_head();
@@ -12,9 +20,11 @@ fn plot_head() {
     let height = 800u32;
     let depth = 255u32;
     let mut model = Model::new("african_head.obj").unwrap();
-    let mut image = TGAImage::new(width, height, TGAImageFormat::RGB);
+    let mut framebuffer = Framebuffer::new(width, height, TGAImageFormat::RGB);
     let light_dir = Vector3F32::new(0., 0., -1.);
-    let mut z_buffer = vec![f32::NEG_INFINITY; width as usize * height as usize];
+    let viewport = viewport(0, 0, width, height, depth);
+
+    framebuffer.clear(&BACKGROUND);
 
     model
         .load_texture("african_head_diffuse.tga")
@@ -27,42 +37,94 @@ fn plot_head() {
         model.n_normals()
     );
     // plot head with light and z-buffer
+    framebuffer.render_model(
+        &model,
+        &RenderOptions {
+            textured: true,
+            srgb: true,
+            cull_mode: CullMode::Back,
+            light_dir,
+            ..RenderOptions::new(width, height, depth)
+        },
+    );
+
+    framebuffer
+        .color()
+        .write_tga_file_with_origin("africa_color.tga", Origin::BottomLeft, true)
+        .expect("Cannot write file");
+    framebuffer
+        .resolve_depth_to_image()
+        .write_tga_file_with_origin("africa_zbuffer.tga", Origin::BottomLeft, true)
+        .expect("Cannot write file");
+    framebuffer.clear(&BACKGROUND);
+    // plot smooth shaded head with per-vertex normals
     for i in 0..model.n_faces() {
+        let (image, z_buffer) = framebuffer.buffers_mut();
         let face = model.face(i);
         let mut screen_coords = [Vector3Int::default(); 3];
-        let mut world_coords = [Vector3F32::default(); 3];
+        let mut intensities = [0.0; 3];
 
         for j in 0..3 {
-            let v0 = model.vert(face[j] as usize);
-            *screen_coords[j].x_as_mut_ref() = ((v0.get_x() + 1.0) * width as f32 / 2.0) as i32;
-            *screen_coords[j].y_as_mut_ref() = ((v0.get_y() + 1.0) * height as f32 / 2.0) as i32;
-            *screen_coords[j].z_as_mut_ref() = ((v0.get_z() + 1.0) * depth as f32 / 2.0) as i32;
-            world_coords[j] = *v0;
-        }
-
-        let mut n = (world_coords[2] - world_coords[0]) ^ (world_coords[1] - world_coords[0]);
+            let screen = viewport.transform_point(*model.vert(face[j] as usize));
 
-        n.normalize_default();
-        let intensity = n * light_dir;
-
-        if intensity > 0.0 {
-            let texture = TextureDef(model.uv(i, 0), model.uv(i, 1), model.uv(i, 2));
-
-            triangle_barycentric_zbuf_with_texture(
-                TriangleDef(screen_coords[0], screen_coords[1], screen_coords[2]),
-                texture,
-                &mut z_buffer,
-                &mut image,
-                &model,
-                intensity,
-            );
+            screen_coords[j] = screen.to_int_round();
+            // normals point out of the model, towards the viewer for the visible faces
+            intensities[j] = -model.normal(i, j).dot(&light_dir);
         }
+
+        triangle_gouraud(
+            TriangleDef(screen_coords[0], screen_coords[1], screen_coords[2]),
+            intensities,
+            z_buffer,
+            Diffuse::Texture {
+                model: &model,
+                texture_def: TextureDef(model.uv_f32(i, 0), model.uv_f32(i, 1), model.uv_f32(i, 2)),
+                filter: TextureFilter::Trilinear,
+            },
+            image,
+        );
     }
 
-    image
-        .write_tga_file("africa_color.tga", true, true)
+    framebuffer
+        .color()
+        .write_tga_file_with_origin("africa_gouraud.tga", Origin::BottomLeft, true)
         .expect("Cannot write file");
-    image.clear();
+    framebuffer.clear(&BACKGROUND);
+    // plot head lit from the top left with hard shadows
+    let light_eye = Vector3F32::new(-1., 1., 1.);
+    let center = Vector3F32::new(0., 0., 0.);
+    let light_transform = viewport * lookat(light_eye, center, Vector3F32::new(0., 1., 0.));
+    let mut shadow_buffer = ZBuffer::new(width, height);
+    let mut light_dir = center - light_eye;
 
-    let _texture_diffuse = TGAImage::read_tga_file("african_head_diffuse.tga").unwrap();
+    light_dir.normalize_default();
+    render_depth(
+        &model,
+        &RenderOptions {
+            transform: light_transform,
+            ..RenderOptions::new(width, height, depth)
+        },
+        &mut shadow_buffer,
+    );
+    framebuffer.render_model(
+        &model,
+        &RenderOptions {
+            textured: true,
+            srgb: true,
+            cull_mode: CullMode::Back,
+            light_dir,
+            shadow: Some(Shadow {
+                depth: &shadow_buffer,
+                transform: light_transform,
+                bias: 5.,
+                intensity: 0.3,
+            }),
+            ..RenderOptions::new(width, height, depth)
+        },
+    );
+
+    framebuffer
+        .color()
+        .write_tga_file_with_origin("africa_shadow.tga", Origin::BottomLeft, true)
+        .expect("Cannot write file");
 }
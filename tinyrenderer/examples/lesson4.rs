@@ -0,0 +1,74 @@
+use tgaimage::colors::NAVY;
+use tgaimage::{Origin, TGAImage, TGAImageFormat, TextureFilter};
+use tinyrenderer::camera::{lookat, projection, viewport};
+use tinyrenderer::geometry::{Vector3F32, Vector4F32};
+use tinyrenderer::model::Model;
+use tinyrenderer::zbuffer::ZBuffer;
+use tinyrenderer::{triangle_perspective_with_texture, TextureDef, TriangleDefF32};
+
+fn main() {
+    plot_head();
+}
+
+fn plot_head() {
+    let width = 800u32;
+    let height = 800u32;
+    let depth = 255u32;
+    let eye = Vector3F32::new(1.0, 1.0, 3.0);
+    let center = Vector3F32::new(0.0, 0.0, 0.0);
+    let up = Vector3F32::new(0.0, 1.0, 0.0);
+    let mut model = Model::new("african_head.obj").unwrap();
+    let mut image =
+        TGAImage::try_new(width, height, TGAImageFormat::RGB).expect("Cannot create image");
+    let light_dir = Vector3F32::new(0., 0., -1.);
+    let mut z_buffer = ZBuffer::new(width, height);
+    let transform = viewport(
+        width as i32 / 8,
+        height as i32 / 8,
+        width * 3 / 4,
+        height * 3 / 4,
+        depth,
+    ) * projection((eye - center).norm_f32())
+        * lookat(eye, center, up);
+
+    image.clear_color(&NAVY);
+
+    model
+        .load_texture("african_head_diffuse.tga")
+        .expect("Cannot load model texture");
+    // plot head as seen from the camera at `eye`
+    for i in 0..model.n_faces() {
+        let face = model.face(i);
+        let mut screen_coords = [Vector4F32::default(); 3];
+        let mut world_coords = [Vector3F32::default(); 3];
+
+        for j in 0..3 {
+            let v0 = model.vert(face[j] as usize);
+            screen_coords[j] = transform * v0.to_homogeneous(1.0);
+            world_coords[j] = *v0;
+        }
+
+        let mut n = (world_coords[2] - world_coords[0]).cross(&(world_coords[1] - world_coords[0]));
+
+        n.normalize_default();
+        let intensity = n.dot(&light_dir);
+
+        if intensity > 0.0 {
+            let texture = TextureDef(model.uv_f32(i, 0), model.uv_f32(i, 1), model.uv_f32(i, 2));
+
+            triangle_perspective_with_texture(
+                TriangleDefF32(screen_coords[0], screen_coords[1], screen_coords[2]),
+                texture,
+                &mut z_buffer,
+                &mut image,
+                &model,
+                TextureFilter::Trilinear,
+                intensity,
+            );
+        }
+    }
+
+    image
+        .write_tga_file_with_origin("africa_camera.tga", Origin::BottomLeft, true)
+        .expect("Cannot write file");
+}
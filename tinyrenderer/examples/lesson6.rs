@@ -0,0 +1,124 @@
+use tgaimage::colors::NAVY;
+use tgaimage::{Origin, TGAColor, TGAImage, TGAImageFormat};
+use tinyrenderer::geometry::Vector3F32;
+use tinyrenderer::lighting::{Light, LightKind, LightingParams};
+use tinyrenderer::model::Model;
+use tinyrenderer::zbuffer::ZBuffer;
+use tinyrenderer::{render_model, CullMode, NormalMapping, RenderOptions};
+
+fn main() {
+    plot_head();
+}
+
+fn load_head(normal_map: &str) -> Model {
+    let mut model = Model::new("african_head.obj").unwrap();
+
+    model
+        .load_texture("african_head_diffuse.tga")
+        .expect("Cannot load model texture");
+    model
+        .load_normal_map(normal_map)
+        .expect("Cannot load model normal map");
+    model
+        .load_specular_map("african_head_spec.tga")
+        .expect("Cannot load model specular map");
+    model.compute_tangents();
+
+    model
+}
+
+fn plot_head() {
+    let width = 800u32;
+    let height = 800u32;
+    let depth = 255u32;
+    let model = load_head("african_head_nm.tga");
+    let tangent_model = load_head("african_head_nm_tangent.tga");
+    let mut image =
+        TGAImage::try_new(width, height, TGAImageFormat::RGB).expect("Cannot create image");
+    let mut z_buffer = ZBuffer::new(width, height);
+    let mut light_dir = Vector3F32::new(-1., -1., -1.);
+
+    light_dir.normalize_default();
+
+    let mut fill_dir = Vector3F32::new(1., 0., -1.);
+
+    fill_dir.normalize_default();
+
+    let phong = LightingParams::new(light_dir);
+    let no_lights: &[Light] = &[];
+    // warm key light at the upper right, cool fill light from the left
+    let key_and_fill = [
+        Light {
+            kind: LightKind::Directional { dir: light_dir },
+            color: TGAColor::new_rgb(255, 215, 160),
+            intensity: 0.9,
+        },
+        Light {
+            kind: LightKind::Directional { dir: fill_dir },
+            color: TGAColor::new_rgb(130, 160, 255),
+            intensity: 0.4,
+        },
+    ];
+
+    // plot head lit with the face normals, then with the model and tangent space
+    // normal maps and finally with specular highlights from one and two lights
+    for &(model, normal_map, phong, lights, filename) in &[
+        (
+            &model,
+            NormalMapping::None,
+            None,
+            no_lights,
+            "africa_face_normals.tga",
+        ),
+        (
+            &model,
+            NormalMapping::Model,
+            None,
+            no_lights,
+            "africa_normal_map.tga",
+        ),
+        (
+            &tangent_model,
+            NormalMapping::Tangent,
+            None,
+            no_lights,
+            "africa_tangent_normal_map.tga",
+        ),
+        (
+            &model,
+            NormalMapping::Model,
+            Some(phong),
+            no_lights,
+            "africa_phong.tga",
+        ),
+        (
+            &model,
+            NormalMapping::Model,
+            Some(phong),
+            &key_and_fill[..],
+            "africa_key_and_fill.tga",
+        ),
+    ] {
+        image.clear_color(&NAVY);
+        z_buffer.clear();
+        render_model(
+            model,
+            &mut image,
+            &mut z_buffer,
+            &RenderOptions {
+                textured: true,
+                srgb: true,
+                cull_mode: CullMode::Back,
+                normal_map,
+                light_dir,
+                phong,
+                lights,
+                ..RenderOptions::new(width, height, depth)
+            },
+        );
+
+        image
+            .write_tga_file_with_origin(filename, Origin::BottomLeft, true)
+            .expect("Cannot write file");
+    }
+}
@@ -0,0 +1,72 @@
+// Compare checked and unchecked pixel access, run with `--release`
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use tgaimage::{TGAColor, TGAImage, TGAImageFormat};
+
+const SIZE: u32 = 800;
+const RUNS: u32 = 50;
+
+fn main() {
+    let color = TGAColor::new_rgb(200, 100, 50);
+    let mut image =
+        TGAImage::try_new(SIZE, SIZE, TGAImageFormat::RGB).expect("Cannot create image");
+
+    let set = measure(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                image.set(black_box(x), black_box(y), &color);
+            }
+        }
+    });
+    let try_set = measure(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                image.try_set(black_box(x), black_box(y), &color).unwrap();
+            }
+        }
+    });
+    let set_unchecked = measure(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                image.set_unchecked(black_box(x), black_box(y), &color);
+            }
+        }
+    });
+
+    println!("set:           {}", format_time(set));
+    println!("try_set:       {}", format_time(try_set));
+    println!("set_unchecked: {}", format_time(set_unchecked));
+
+    let get = measure(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                black_box(image.get(black_box(x), black_box(y)));
+            }
+        }
+    });
+    let get_unchecked = measure(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                black_box(image.get_unchecked(black_box(x), black_box(y)));
+            }
+        }
+    });
+
+    println!("get:           {}", format_time(get));
+    println!("get_unchecked: {}", format_time(get_unchecked));
+}
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..RUNS {
+        f();
+    }
+
+    start.elapsed() / RUNS
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{:.3} ms", time.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,69 @@
+// Read every pixel of an RGBA image through TGAImage::get, TGAColor::from_slice
+// and the deprecated TGAColor::new_from_iter, run with `--release`
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use tgaimage::{TGAColor, TGAImage, TGAImageFormat};
+
+const SIZE: u32 = 800;
+const RUNS: u32 = 50;
+
+fn main() {
+    let mut image =
+        TGAImage::try_new(SIZE, SIZE, TGAImageFormat::RGBA).expect("Cannot create image");
+
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            image.set(
+                x,
+                y,
+                &TGAColor::new_rgba(x as u8, y as u8, (x ^ y) as u8, 255),
+            );
+        }
+    }
+
+    let get = measure(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                black_box(image.get(black_box(x), black_box(y)));
+            }
+        }
+    });
+    let from_slice = measure(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let pixel = &image[(black_box(x), black_box(y))];
+
+                black_box(TGAColor::from_slice(pixel).unwrap());
+            }
+        }
+    });
+    #[allow(deprecated)]
+    let from_iter = measure(|| {
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let pixel = &image[(black_box(x), black_box(y))];
+
+                black_box(TGAColor::new_from_iter(pixel.iter(), pixel.len() as u8));
+            }
+        }
+    });
+
+    println!("get:           {}", format_time(get));
+    println!("from_slice:    {}", format_time(from_slice));
+    println!("new_from_iter: {}", format_time(from_iter));
+}
+
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..RUNS {
+        f();
+    }
+
+    start.elapsed() / RUNS
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{:.3} ms", time.as_secs_f64() * 1000.0)
+}
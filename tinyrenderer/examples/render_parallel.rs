@@ -0,0 +1,57 @@
+use std::time::{Duration, Instant};
+
+use tgaimage::{TGAImage, TGAImageFormat};
+use tinyrenderer::model::Model;
+use tinyrenderer::zbuffer::ZBuffer;
+use tinyrenderer::{render_model, render_model_parallel, CullMode, RenderOptions};
+
+const RUNS: u32 = 20;
+
+fn main() {
+    let width = 800u32;
+    let height = 800u32;
+    let mut model = Model::new("african_head.obj").unwrap();
+
+    model
+        .load_texture("african_head_diffuse.tga")
+        .expect("Cannot load model texture");
+
+    let opts = RenderOptions {
+        textured: true,
+        cull_mode: CullMode::Back,
+        ..RenderOptions::new(width, height, 255)
+    };
+    let render = |parallel: bool| {
+        let mut image =
+            TGAImage::try_new(width, height, TGAImageFormat::RGB).expect("Cannot create image");
+        let mut z_buffer = ZBuffer::new(width, height);
+        let start = Instant::now();
+
+        for _ in 0..RUNS {
+            image.clear();
+            z_buffer.clear();
+
+            if parallel {
+                render_model_parallel(&model, &mut image, &mut z_buffer, &opts);
+            } else {
+                render_model(&model, &mut image, &mut z_buffer, &opts);
+            }
+        }
+
+        (image, start.elapsed() / RUNS)
+    };
+    let (serial, serial_time) = render(false);
+    let (parallel, parallel_time) = render(true);
+
+    assert_eq!(serial.as_bytes(), parallel.as_bytes());
+    println!("serial:   {}", format_time(serial_time));
+    println!(
+        "parallel: {} ({:.1}x)",
+        format_time(parallel_time),
+        serial_time.as_secs_f64() / parallel_time.as_secs_f64()
+    );
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{:.2} ms", time.as_secs_f64() * 1000.0)
+}
@@ -1,10 +1,11 @@
-use tgaimage::{TGAImage, TGAImageFormat};
-use tinyrenderer::model::Model;
+use tgaimage::{Origin, TGAImage};
 
 fn main() {
     let mut texture =
         TGAImage::read_tga_file("african_head_diffuse.tga").expect("Unable to read image");
 
-    texture.write_tga_file("african_head_diffuse_tmp.tga", true, true);
+    texture
+        .write_tga_file_with_origin("african_head_diffuse_tmp.tga", Origin::BottomLeft, true)
+        .expect("Cannot write image");
     texture.clear();
 }
@@ -0,0 +1,41 @@
+use std::time::{Duration, Instant};
+
+use tgaimage::colors::WHITE;
+use tgaimage::{TGAImage, TGAImageFormat};
+use tinyrenderer::geometry::Vector2Int;
+use tinyrenderer::{triangle, triangle_barycentric};
+
+const RUNS: u32 = 200;
+
+fn main() {
+    let width = 4u32;
+    let height = 800u32;
+    // 1 pixel wide and 800 pixels tall
+    let v = [
+        Vector2Int::new(1, 0),
+        Vector2Int::new(2, height as i32 - 1),
+        Vector2Int::new(1, height as i32 - 1),
+    ];
+    let render = |scanline: bool| {
+        let mut image =
+            TGAImage::try_new(width, height, TGAImageFormat::RGB).expect("Cannot create image");
+        let start = Instant::now();
+
+        for _ in 0..RUNS {
+            if scanline {
+                triangle(v[0], v[1], v[2], &WHITE, &mut image);
+            } else {
+                triangle_barycentric(v[0], v[1], v[2], &WHITE, &mut image);
+            }
+        }
+
+        start.elapsed() / RUNS
+    };
+
+    println!("scanline:    {}", format_time(render(true)));
+    println!("barycentric: {}", format_time(render(false)));
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{:.3} ms", time.as_secs_f64() * 1000.0)
+}
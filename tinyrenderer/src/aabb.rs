@@ -0,0 +1,147 @@
+use crate::geometry::{Matrix4F32, Vector3F32, XAxis, YAxis, ZAxis};
+
+/// Axis-aligned bounding box, both corners are inside of it
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Aabb {
+    pub min: Vector3F32,
+    pub max: Vector3F32,
+}
+
+impl Aabb {
+    pub fn new(min: Vector3F32, max: Vector3F32) -> Self {
+        Aabb { min, max }
+    }
+
+    /// Smallest box containing every point, `None` without points
+    pub fn from_points<I: IntoIterator<Item = Vector3F32>>(points: I) -> Option<Self> {
+        points.into_iter().fold(None, |aabb, p| {
+            Some(match aabb {
+                Some(Aabb { min, max }) => Aabb::new(min.min(p), max.max(p)),
+                None => Aabb::new(p, p),
+            })
+        })
+    }
+
+    pub fn from_triangle(triangle: &[Vector3F32; 3]) -> Self {
+        let [a, b, c] = *triangle;
+
+        Aabb::new(a.min(b).min(c), a.max(b).max(c))
+    }
+
+    /// Smallest box containing both boxes
+    pub fn union(&self, other: &Self) -> Self {
+        Aabb::new(self.min.min(other.min), self.max.max(other.max))
+    }
+
+    pub fn center(&self) -> Vector3F32 {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Size of the box along every axis
+    pub fn extent(&self) -> Vector3F32 {
+        self.max - self.min
+    }
+
+    /// Check if the point is inside of the box or on its boundary
+    pub fn contains(&self, point: Vector3F32) -> bool {
+        point.clamp(self.min, self.max).as_array() == point.as_array()
+    }
+
+    /// Move the box center to the origin and scale it uniformly to fit `-1..1`
+    /// along every axis
+    ///
+    /// Used as [`crate::RenderOptions::model_transform`] it frames the whole
+    /// model in the viewport of [`crate::RenderOptions::new`]. A box of a single
+    /// point is only moved.
+    pub fn fit_transform(&self) -> Matrix4F32 {
+        let extent = self.extent();
+        let size = extent.get_x().max(extent.get_y()).max(extent.get_z());
+        let scale = if size > 0.0 { 2.0 / size } else { 1.0 };
+        let center = self.center();
+
+        Matrix4F32::new([
+            [scale, 0.0, 0.0, -center.get_x() * scale],
+            [0.0, scale, 0.0, -center.get_y() * scale],
+            [0.0, 0.0, scale, -center.get_z() * scale],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test_aabb {
+    use crate::aabb::Aabb;
+    use crate::assert_vec_eq;
+    use crate::geometry::Vector3F32;
+
+    #[test]
+    fn from_points_and_triangle() {
+        let triangle = [
+            Vector3F32::new(1.0, -2.0, 0.5),
+            Vector3F32::new(-3.0, 4.0, 0.5),
+            Vector3F32::new(0.0, 0.0, -1.0),
+        ];
+        let aabb = Aabb::from_triangle(&triangle);
+
+        assert_eq!(aabb.min.as_array(), [-3.0, -2.0, -1.0]);
+        assert_eq!(aabb.max.as_array(), [1.0, 4.0, 0.5]);
+        assert_eq!(
+            Aabb::from_points(triangle.iter().copied())
+                .unwrap()
+                .max
+                .as_array(),
+            aabb.max.as_array()
+        );
+        assert!(Aabb::from_points(Vec::new()).is_none());
+        assert_eq!(aabb.center().as_array(), [-1.0, 1.0, -0.25]);
+        assert_eq!(aabb.extent().as_array(), [4.0, 6.0, 1.5]);
+    }
+
+    #[test]
+    fn union_and_contains() {
+        let a = Aabb::new(
+            Vector3F32::new(0.0, 0.0, 0.0),
+            Vector3F32::new(1.0, 1.0, 1.0),
+        );
+        let b = Aabb::new(
+            Vector3F32::new(-1.0, 0.5, 0.5),
+            Vector3F32::new(0.5, 2.0, 0.5),
+        );
+        let union = a.union(&b);
+
+        assert_eq!(union.min.as_array(), [-1.0, 0.0, 0.0]);
+        assert_eq!(union.max.as_array(), [1.0, 2.0, 1.0]);
+        assert!(a.contains(Vector3F32::new(1.0, 0.0, 0.5)));
+        assert!(!a.contains(Vector3F32::new(1.0, 0.0, 1.01)));
+        assert!(union.contains(b.min) && union.contains(a.max));
+    }
+
+    #[test]
+    fn fit_transform_into_unit_cube() {
+        let aabb = Aabb::new(
+            Vector3F32::new(95.0, 40.0, 2.0),
+            Vector3F32::new(105.0, 60.0, 4.0),
+        );
+        let fit = aabb.fit_transform();
+
+        assert_vec_eq!(
+            fit.transform_point(aabb.min),
+            Vector3F32::new(-0.5, -1.0, -0.1)
+        );
+        assert_vec_eq!(
+            fit.transform_point(aabb.max),
+            Vector3F32::new(0.5, 1.0, 0.1)
+        );
+
+        let point = Aabb::new(
+            Vector3F32::new(1.0, 2.0, 3.0),
+            Vector3F32::new(1.0, 2.0, 3.0),
+        );
+
+        assert_vec_eq!(
+            point.fit_transform().transform_point(point.min),
+            Vector3F32::default()
+        );
+    }
+}
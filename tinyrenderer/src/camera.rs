@@ -0,0 +1,367 @@
+use crate::geometry::{Matrix4F32, Vector3F32, XAxis, YAxis, ZAxis};
+use crate::quaternion::Quaternion;
+
+/// Map normalized device coordinates `-1..1` onto the `width` x `height` screen area
+/// with the lower-left corner at `(x, y)` and onto depth `0..depth`
+pub fn viewport(x: i32, y: i32, width: u32, height: u32, depth: u32) -> Matrix4F32 {
+    let (half_width, half_height) = (width as f32 / 2.0, height as f32 / 2.0);
+    let half_depth = depth as f32 / 2.0;
+
+    Matrix4F32::new([
+        [half_width, 0.0, 0.0, x as f32 + half_width],
+        [0.0, half_height, 0.0, y as f32 + half_height],
+        [0.0, 0.0, half_depth, half_depth],
+        [0.0, 0.0, 0.0, 1.0],
+    ])
+}
+
+/// Central projection for a camera on the Z axis at `camera_distance` from the origin
+///
+/// Points on the `z = 0` plane keep their coordinates, closer points get bigger.
+pub fn projection(camera_distance: f32) -> Matrix4F32 {
+    let mut m = Matrix4F32::identity();
+
+    m[(3, 2)] = -1.0 / camera_distance;
+
+    m
+}
+
+/// Camera projection onto normalized device coordinates `-1..1`
+///
+/// Both projections follow the OpenGL conventions: the camera sits in the origin
+/// looking along the negative Z axis, `near` and `far` are distances along it and
+/// map onto the depth `-1` and `1`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Projection {
+    /// Parallel projection of the `left..right` x `bottom..top` x `near..far` box
+    Orthographic {
+        left: f32,
+        right: f32,
+        bottom: f32,
+        top: f32,
+        near: f32,
+        far: f32,
+    },
+    /// Central projection with the vertical field of view `fov_y` in radians and
+    /// the `aspect` ratio of width to height
+    Perspective {
+        fov_y: f32,
+        aspect: f32,
+        near: f32,
+        far: f32,
+    },
+}
+
+impl Projection {
+    pub fn orthographic(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f32) -> Self {
+        Projection::Orthographic {
+            left,
+            right,
+            bottom,
+            top,
+            near,
+            far,
+        }
+    }
+
+    pub fn perspective(fov_y: f32, aspect: f32, near: f32, far: f32) -> Self {
+        Projection::Perspective {
+            fov_y,
+            aspect,
+            near,
+            far,
+        }
+    }
+
+    /// Projection matrix, the orthographic one keeps `w = 1` so the perspective
+    /// divide leaves points as they are
+    pub fn matrix(&self) -> Matrix4F32 {
+        match *self {
+            Projection::Orthographic {
+                left,
+                right,
+                bottom,
+                top,
+                near,
+                far,
+            } => Matrix4F32::new([
+                [
+                    2.0 / (right - left),
+                    0.0,
+                    0.0,
+                    -(right + left) / (right - left),
+                ],
+                [
+                    0.0,
+                    2.0 / (top - bottom),
+                    0.0,
+                    -(top + bottom) / (top - bottom),
+                ],
+                [0.0, 0.0, -2.0 / (far - near), -(far + near) / (far - near)],
+                [0.0, 0.0, 0.0, 1.0],
+            ]),
+            Projection::Perspective {
+                fov_y,
+                aspect,
+                near,
+                far,
+            } => {
+                let f = 1.0 / (fov_y / 2.0).tan();
+
+                Matrix4F32::new([
+                    [f / aspect, 0.0, 0.0, 0.0],
+                    [0.0, f, 0.0, 0.0],
+                    [
+                        0.0,
+                        0.0,
+                        (far + near) / (near - far),
+                        2.0 * far * near / (near - far),
+                    ],
+                    [0.0, 0.0, -1.0, 0.0],
+                ])
+            }
+        }
+    }
+}
+
+/// Model coordinates to screen coordinates through the `view` transform, the
+/// `projection` and the `viewport`
+///
+/// `view` has to put the camera in the origin looking along the negative Z axis.
+/// The projected depth is flipped, so closer points get the bigger depth like the
+/// z-buffer expects.
+pub fn screen_transform(
+    viewport: &Matrix4F32,
+    projection: &Projection,
+    view: &Matrix4F32,
+) -> Matrix4F32 {
+    let mut flip_depth = Matrix4F32::identity();
+
+    flip_depth[(2, 2)] = -1.0;
+
+    *viewport * flip_depth * projection.matrix() * *view
+}
+
+/// Move the scene so that the camera at `eye` looks at `center` along the negative Z axis
+/// with `up` pointing to the positive Y axis
+///
+/// `center` ends up in the origin.
+pub fn lookat(eye: Vector3F32, center: Vector3F32, up: Vector3F32) -> Matrix4F32 {
+    let mut z = eye - center;
+
+    z.normalize_default();
+
+    let mut x = up.cross(&z);
+
+    x.normalize_default();
+
+    let mut y = z.cross(&x);
+
+    y.normalize_default();
+
+    let axes = [x, y, z];
+    let mut rotation = Matrix4F32::identity();
+    let mut translation = Matrix4F32::identity();
+
+    for (i, axis) in axes.iter().enumerate() {
+        rotation[(i, 0)] = axis.get_x();
+        rotation[(i, 1)] = axis.get_y();
+        rotation[(i, 2)] = axis.get_z();
+    }
+
+    translation[(0, 3)] = -center.get_x();
+    translation[(1, 3)] = -center.get_y();
+    translation[(2, 3)] = -center.get_z();
+
+    rotation * translation
+}
+
+/// [`lookat`] with the camera turned around `center` by `rotation`
+///
+/// The camera looks from `center + rotation(+Z)` with `rotation(+Y)` up, so the
+/// identity rotation gives the identity view for `center` in the origin.
+pub fn orbit(center: Vector3F32, rotation: &Quaternion) -> Matrix4F32 {
+    let mut translation = Matrix4F32::identity();
+
+    translation[(0, 3)] = -center.get_x();
+    translation[(1, 3)] = -center.get_y();
+    translation[(2, 3)] = -center.get_z();
+
+    rotation.conjugate().to_matrix4() * translation
+}
+
+#[cfg(test)]
+mod test_camera {
+    use crate::assert_mat_eq;
+    use crate::camera::{lookat, orbit, projection, screen_transform, viewport, Projection};
+    use crate::geometry::{Matrix4F32, Vector3F32, XAxis, YAxis, ZAxis};
+    use crate::quaternion::Quaternion;
+
+    fn assert_point(p: Vector3F32, expected: (f32, f32, f32)) {
+        assert!(
+            (p.get_x() - expected.0).abs() < 1e-5
+                && (p.get_y() - expected.1).abs() < 1e-5
+                && (p.get_z() - expected.2).abs() < 1e-5,
+            "{} != {:?}",
+            p,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_lookat_identity() {
+        let view = lookat(
+            Vector3F32::new(0.0, 0.0, 1.0),
+            Vector3F32::new(0.0, 0.0, 0.0),
+            Vector3F32::new(0.0, 1.0, 0.0),
+        );
+
+        assert_eq!(view, Matrix4F32::identity());
+    }
+
+    #[test]
+    fn test_lookat_moves_eye_onto_z_axis() {
+        let eye = Vector3F32::new(1.0, 1.0, 3.0);
+        let center = Vector3F32::new(0.5, 0.0, -1.0);
+        let view = lookat(eye, center, Vector3F32::new(0.0, 1.0, 0.0));
+
+        assert_point(view.transform_point(center), (0.0, 0.0, 0.0));
+        assert_point(
+            view.transform_point(eye),
+            (0.0, 0.0, (eye - center).norm_f32()),
+        );
+    }
+
+    #[test]
+    fn test_orbit_matches_lookat() {
+        let center = Vector3F32::new(0.5, -1.0, 2.0);
+        let rotation = Quaternion::from_axis_angle(Vector3F32::new(1.0, 2.0, -0.5), 0.8);
+        let view = orbit(center, &rotation);
+        let expected = lookat(
+            center + rotation.rotate_vector(Vector3F32::new(0.0, 0.0, 1.0)),
+            center,
+            rotation.rotate_vector(Vector3F32::new(0.0, 1.0, 0.0)),
+        );
+
+        assert_mat_eq!(view, expected);
+
+        assert_eq!(
+            orbit(Vector3F32::new(0.0, 0.0, 0.0), &Quaternion::identity()),
+            Matrix4F32::identity()
+        );
+    }
+
+    #[test]
+    fn test_viewport() {
+        let m = viewport(10, 20, 800, 600, 255);
+
+        assert_point(
+            m.transform_point(Vector3F32::new(-1.0, -1.0, -1.0)),
+            (10.0, 20.0, 0.0),
+        );
+        assert_point(
+            m.transform_point(Vector3F32::new(1.0, 1.0, 1.0)),
+            (810.0, 620.0, 255.0),
+        );
+        assert_point(
+            m.transform_point(Vector3F32::new(0.0, 0.0, 0.0)),
+            (410.0, 320.0, 127.5),
+        );
+    }
+
+    #[test]
+    fn test_orthographic_box_corners() {
+        let m = Projection::orthographic(-2.0, 4.0, 1.0, 3.0, 0.5, 10.0).matrix();
+
+        assert_point(
+            m.transform_point(Vector3F32::new(-2.0, 1.0, -0.5)),
+            (-1.0, -1.0, -1.0),
+        );
+        assert_point(
+            m.transform_point(Vector3F32::new(4.0, 3.0, -10.0)),
+            (1.0, 1.0, 1.0),
+        );
+        assert_point(
+            m.transform_point(Vector3F32::new(1.0, 2.0, -5.25)),
+            (0.0, 0.0, 0.0),
+        );
+        // No perspective, the distance does not change x and y
+        assert_point(
+            m.transform_point(Vector3F32::new(4.0, 1.0, -7.0)),
+            (
+                1.0,
+                -1.0,
+                m.transform_point(Vector3F32::new(0.0, 0.0, -7.0)).get_z(),
+            ),
+        );
+    }
+
+    #[test]
+    fn test_perspective() {
+        let (near, far) = (0.5, 20.0);
+        let fov_y = std::f32::consts::FRAC_PI_2;
+        let m = Projection::perspective(fov_y, 1.0, near, far).matrix();
+        let p = m.transform_point(Vector3F32::new(0.2, 0.3, -near));
+
+        assert!((p.get_x() / p.get_y() - 0.2 / 0.3).abs() < 1e-5);
+        assert!((p.get_z() + 1.0).abs() < 1e-5);
+        // The top of the frustum at 90 degrees is as high as it is far
+        assert_point(
+            m.transform_point(Vector3F32::new(0.0, far, -far)),
+            (0.0, 1.0, 1.0),
+        );
+
+        let wide = Projection::perspective(fov_y, 2.0, near, far).matrix();
+
+        assert_point(
+            wide.transform_point(Vector3F32::new(2.0, 1.0, -1.0)),
+            (
+                1.0,
+                1.0,
+                wide.transform_point(Vector3F32::new(0.0, 0.0, -1.0))
+                    .get_z(),
+            ),
+        );
+    }
+
+    #[test]
+    fn test_screen_transform() {
+        let vp = viewport(0, 0, 100, 100, 255);
+        let ortho = Projection::orthographic(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
+        let m = screen_transform(&vp, &ortho, &Matrix4F32::identity());
+
+        for &p in &[(-1.0, 0.5, -1.0), (0.25, -1.0, 0.0), (1.0, 1.0, 1.0)] {
+            let p = Vector3F32::new(p.0, p.1, p.2);
+            let expected = vp.transform_point(p);
+
+            assert_point(
+                m.transform_point(p),
+                (expected.get_x(), expected.get_y(), expected.get_z()),
+            );
+        }
+
+        let perspective = Projection::perspective(1.0, 1.0, 0.1, 10.0);
+        let m = screen_transform(&vp, &perspective, &Matrix4F32::identity());
+        let near = m.transform_point(Vector3F32::new(0.0, 0.0, -1.0));
+        let far = m.transform_point(Vector3F32::new(0.0, 0.0, -5.0));
+
+        assert!(near.get_z() > far.get_z());
+        assert!(far.get_z() >= 0.0 && near.get_z() <= 255.0);
+    }
+
+    #[test]
+    fn test_projection() {
+        let m = projection(3.0);
+
+        assert_point(
+            m.transform_point(Vector3F32::new(1.0, -1.0, 0.0)),
+            (1.0, -1.0, 0.0),
+        );
+        // Halfway to the camera everything is twice as big
+        assert_point(
+            m.transform_point(Vector3F32::new(1.0, -1.0, 1.5)),
+            (2.0, -2.0, 3.0),
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::fmt::{Display, Formatter};
+
+use tgaimage::{TGAColor, TGAImage, TGAImageFormat};
+
+use crate::geometry::Vector3Int;
+use crate::model::Model;
+use crate::zbuffer::ZBuffer;
+use crate::{render_model, triangle_barycentric_zbuf, RenderOptions, RenderStats};
+
+/// Color image and depth buffer of the same size rendered together
+#[derive(Debug)]
+pub struct Framebuffer {
+    color: TGAImage,
+    depth: ZBuffer,
+}
+
+/// Depth buffer size that does not match the color image
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SizeMismatch {
+    pub color: (u32, u32),
+    pub depth: (u32, u32),
+}
+
+impl Display for SizeMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}x{} depth buffer does not match the {}x{} color image",
+            self.depth.0, self.depth.1, self.color.0, self.color.1
+        )
+    }
+}
+
+impl std::error::Error for SizeMismatch {}
+
+impl Framebuffer {
+    /// Create a black image with every pixel infinitely far away
+    ///
+    /// # Panics
+    ///
+    /// Panics if the image is degenerate, see [`TGAImage::try_new`].
+    pub fn new(width: u32, height: u32, format: TGAImageFormat) -> Self {
+        let color = TGAImage::try_new(width, height, format)
+            .unwrap_or_else(|e| panic!("Cannot create framebuffer: {}", e));
+
+        Framebuffer {
+            color,
+            depth: ZBuffer::new(width, height),
+        }
+    }
+
+    /// Bundle an existing image with a depth buffer of the same size
+    pub fn from_parts(color: TGAImage, depth: ZBuffer) -> Result<Self, SizeMismatch> {
+        if !depth.matches(&color) {
+            return Err(SizeMismatch {
+                color: (color.get_width(), color.get_height()),
+                depth: (depth.width(), depth.height()),
+            });
+        }
+
+        Ok(Framebuffer { color, depth })
+    }
+
+    pub fn into_parts(self) -> (TGAImage, ZBuffer) {
+        (self.color, self.depth)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.color.get_width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.color.get_height()
+    }
+
+    pub fn color(&self) -> &TGAImage {
+        &self.color
+    }
+
+    pub fn depth(&self) -> &ZBuffer {
+        &self.depth
+    }
+
+    /// Both buffers for the functions taking them separately
+    pub fn buffers_mut(&mut self) -> (&mut TGAImage, &mut ZBuffer) {
+        (&mut self.color, &mut self.depth)
+    }
+
+    /// Fill the image with `color` and move every pixel infinitely far away
+    pub fn clear(&mut self, color: &TGAColor) {
+        self.color.clear_color(color);
+        self.depth.clear();
+    }
+
+    /// Depth buffer as a grayscale image, see [`ZBuffer::to_image`]
+    pub fn resolve_depth_to_image(&self) -> TGAImage {
+        self.depth.to_image()
+    }
+
+    /// Same as [`render_model`]
+    pub fn render_model(&mut self, model: &Model, opts: &RenderOptions) -> RenderStats {
+        render_model(model, &mut self.color, &mut self.depth, opts)
+    }
+
+    /// Same as [`crate::render_model_parallel`]
+    #[cfg(feature = "parallel")]
+    pub fn render_model_parallel(&mut self, model: &Model, opts: &RenderOptions) -> RenderStats {
+        crate::render_model_parallel(model, &mut self.color, &mut self.depth, opts)
+    }
+
+    /// Same as [`triangle_barycentric_zbuf`]
+    pub fn triangle(
+        &mut self,
+        v: [Vector3Int; 3],
+        color: &TGAColor,
+        stats: Option<&mut RenderStats>,
+    ) {
+        triangle_barycentric_zbuf(
+            v[0],
+            v[1],
+            v[2],
+            &mut self.depth,
+            color,
+            &mut self.color,
+            stats,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_framebuffer {
+    use tgaimage::colors::{NAVY, RED};
+    use tgaimage::{TGAImage, TGAImageFormat};
+
+    use crate::framebuffer::{Framebuffer, SizeMismatch};
+    use crate::geometry::Vector3Int;
+    use crate::zbuffer::ZBuffer;
+
+    #[test]
+    fn from_parts_size_mismatch() {
+        let image = TGAImage::try_new(4, 3, TGAImageFormat::RGB).unwrap();
+
+        assert_eq!(
+            Framebuffer::from_parts(image, ZBuffer::new(3, 4)).unwrap_err(),
+            SizeMismatch {
+                color: (4, 3),
+                depth: (3, 4),
+            }
+        );
+
+        let framebuffer = Framebuffer::from_parts(
+            TGAImage::try_new(4, 3, TGAImageFormat::RGB).unwrap(),
+            ZBuffer::new(4, 3),
+        )
+        .unwrap();
+
+        assert_eq!((framebuffer.width(), framebuffer.height()), (4, 3));
+    }
+
+    #[test]
+    fn clear_resets_color_and_depth() {
+        let mut framebuffer = Framebuffer::new(8, 8, TGAImageFormat::RGB);
+
+        framebuffer.triangle(
+            [
+                Vector3Int::new(0, 0, 10),
+                Vector3Int::new(7, 0, 10),
+                Vector3Int::new(0, 7, 10),
+            ],
+            &RED,
+            None,
+        );
+        assert!((framebuffer.depth().get(1, 1) - 10.0).abs() < 1e-5);
+        assert_eq!(framebuffer.color().get(1, 1).r(), RED.r());
+
+        framebuffer.clear(&NAVY);
+
+        assert!(framebuffer
+            .depth()
+            .as_slice()
+            .iter()
+            .all(|z| *z == f32::NEG_INFINITY));
+        assert!(framebuffer
+            .color()
+            .pixels()
+            .all(|(_, _, color)| color.as_slice()[..3] == NAVY.as_slice()[..3]));
+        assert!(framebuffer
+            .resolve_depth_to_image()
+            .as_bytes()
+            .iter()
+            .all(|v| *v == 0));
+    }
+}
@@ -1,13 +1,41 @@
 use core::mem;
 use std::default::Default;
 use std::fmt::{Display, Formatter, Result};
-use std::ops::{Add, BitXor, Mul, MulAssign, Sub};
+use std::ops::{Add, BitXor, Div, Index, IndexMut, Mul, MulAssign, Neg, Sub};
 
 use num;
 use num::cast::AsPrimitive;
 use num::NumCast;
 use num_traits::{Float, Num, ToPrimitive};
 
+/// Assert that two float vectors differ by at most `eps`, `1e-5` by default
+#[cfg(any(test, feature = "test-util"))]
+#[macro_export]
+macro_rules! assert_vec_eq {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::assert_vec_eq!($a, $b, 1e-5)
+    };
+    ($a:expr, $b:expr, $eps:expr $(,)?) => {{
+        let (a, b) = (&$a, &$b);
+
+        assert!(a.approx_eq(b, $eps), "{} != {} within {}", a, b, $eps);
+    }};
+}
+
+/// Assert that two float matrices differ by at most `eps`, `1e-5` by default
+#[cfg(any(test, feature = "test-util"))]
+#[macro_export]
+macro_rules! assert_mat_eq {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::assert_mat_eq!($a, $b, 1e-5)
+    };
+    ($a:expr, $b:expr, $eps:expr $(,)?) => {{
+        let (a, b) = (&$a, &$b);
+
+        assert!(a.approx_eq(b, $eps), "\n{}\n!=\n{}\nwithin {}", a, b, $eps);
+    }};
+}
+
 pub trait VectorTrait<T>: Copy + Clone + Num + NumCast + ToPrimitive + AsPrimitive<T>
 where
     T: Copy + 'static,
@@ -23,6 +51,7 @@ macro_rules! impl_vector_trait {
 impl_vector_trait!(i32 f32);
 
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector2<T: VectorTrait<T>> {
     x: T,
     y: T,
@@ -33,6 +62,14 @@ impl<T: VectorTrait<T>> Vector2<T> {
         Vector2 { x, y }
     }
 
+    pub fn new_from_array(coords: &[T; 2]) -> Self {
+        Self::from(*coords)
+    }
+
+    pub fn as_array(&self) -> [T; 2] {
+        [self.x, self.y]
+    }
+
     pub fn swap(&mut self, rhs: &mut Self) {
         mem::swap(&mut self.x, &mut rhs.x);
         mem::swap(&mut self.y, &mut rhs.y);
@@ -41,6 +78,56 @@ impl<T: VectorTrait<T>> Vector2<T> {
     pub fn swap_xy(&mut self) {
         mem::swap(&mut self.x, &mut self.y);
     }
+
+    pub fn dot(&self, rhs: &Self) -> T {
+        self.x * rhs.x + self.y * rhs.y
+    }
+
+    pub fn norm_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    /// The vector rotated by 90 degrees counter-clockwise
+    pub fn perp(&self) -> Self {
+        Vector2::new(T::zero() - self.y, self.x)
+    }
+}
+
+impl<T: VectorTrait<T> + Float> Vector2<T> {
+    pub fn norm(&self) -> T {
+        self.norm_squared().sqrt()
+    }
+
+    pub fn normalize(&mut self) {
+        *self = self.normalized();
+    }
+
+    /// Unit vector of the same direction
+    pub fn normalized(&self) -> Self {
+        *self / self.norm()
+    }
+
+    /// Every component differs by at most `eps`
+    pub fn approx_eq(&self, other: &Self, eps: T) -> bool {
+        (self.x - other.x).abs() <= eps && (self.y - other.y).abs() <= eps
+    }
+}
+
+impl<T: VectorTrait<T> + NumMinMax<Output = T>> Vector2<T> {
+    /// Componentwise minimum
+    pub fn min(self, other: Self) -> Self {
+        Vector2::new(self.x.minimum(other.x), self.y.minimum(other.y))
+    }
+
+    /// Componentwise maximum
+    pub fn max(self, other: Self) -> Self {
+        Vector2::new(self.x.maximum(other.x), self.y.maximum(other.y))
+    }
+
+    /// Clamp every component to the `lo..=hi` range of the same component
+    pub fn clamp(self, lo: Self, hi: Self) -> Self {
+        self.max(lo).min(hi)
+    }
 }
 
 impl<T: VectorTrait<T> + Default> Default for Vector2<T> {
@@ -60,17 +147,37 @@ impl<T: VectorTrait<T>> Mul for Vector2<T> {
     }
 }
 
-impl<T: VectorTrait<T>> Mul<f32> for Vector2<T>
+impl<T, U> Mul<U> for Vector2<T>
 where
-    f32: AsPrimitive<T>,
+    T: VectorTrait<T> + AsPrimitive<U>,
+    U: Float + AsPrimitive<T>,
 {
     type Output = Self;
 
-    fn mul(self, rhs: f32) -> Self::Output {
-        Self::new(
-            (self.x.to_f32().unwrap() * rhs).as_(),
-            (self.y.to_f32().unwrap() * rhs).as_(),
-        )
+    fn mul(self, rhs: U) -> Self::Output {
+        Self::new((rhs * self.x.as_()).as_(), (rhs * self.y.as_()).as_())
+    }
+}
+
+impl<T, U> Div<U> for Vector2<T>
+where
+    T: VectorTrait<T> + AsPrimitive<U>,
+    U: Float + AsPrimitive<T>,
+{
+    type Output = Self;
+
+    fn div(self, rhs: U) -> Self::Output {
+        let (x, y): (U, U) = (self.x.as_(), self.y.as_());
+
+        Self::new((x / rhs).as_(), (y / rhs).as_())
+    }
+}
+
+impl<T: VectorTrait<T> + Neg<Output = T>> Neg for Vector2<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
     }
 }
 
@@ -101,6 +208,41 @@ impl<T: VectorTrait<T>> Sub for Vector2<T> {
     }
 }
 
+impl<T: VectorTrait<T>> From<[T; 2]> for Vector2<T> {
+    fn from([x, y]: [T; 2]) -> Self {
+        Vector2 { x, y }
+    }
+}
+
+impl<T: VectorTrait<T>> From<Vector2<T>> for [T; 2] {
+    fn from(v: Vector2<T>) -> Self {
+        v.as_array()
+    }
+}
+
+/// Coordinate by its axis number, panics for indices past `y`
+impl<T: VectorTrait<T>> Index<usize> for Vector2<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("index {} is out of range for Vector2", index),
+        }
+    }
+}
+
+impl<T: VectorTrait<T>> IndexMut<usize> for Vector2<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("index {} is out of range for Vector2", index),
+        }
+    }
+}
+
 impl<T: Display + VectorTrait<T>> Display for Vector2<T> {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(f, "({}, {})", self.x, self.y)
@@ -110,6 +252,30 @@ impl<T: Display + VectorTrait<T>> Display for Vector2<T> {
 pub type Vector2F32 = Vector2<f32>;
 pub type Vector2Int = Vector2<i32>;
 
+impl Vector2F32 {
+    /// Nearest pixel, halves are rounded away from zero
+    ///
+    /// This is the default conversion to pixels, `as` casts truncate towards zero
+    /// and shift negative coordinates by a pixel.
+    pub fn to_int_round(&self) -> Vector2Int {
+        Vector2::new(self.x.round() as i32, self.y.round() as i32)
+    }
+
+    pub fn to_int_floor(&self) -> Vector2Int {
+        Vector2::new(self.x.floor() as i32, self.y.floor() as i32)
+    }
+
+    pub fn to_int_ceil(&self) -> Vector2Int {
+        Vector2::new(self.x.ceil() as i32, self.y.ceil() as i32)
+    }
+}
+
+impl Vector2Int {
+    pub fn to_f32(&self) -> Vector2F32 {
+        Vector2::new(self.x as f32, self.y as f32)
+    }
+}
+
 pub trait XAxis<T> {
     fn get_x(&self) -> T;
 
@@ -128,6 +294,12 @@ pub trait ZAxis<T> {
     fn z_as_mut_ref(&mut self) -> &mut T;
 }
 
+pub trait WAxis<T> {
+    fn get_w(&self) -> T;
+
+    fn w_as_mut_ref(&mut self) -> &mut T;
+}
+
 pub trait XYAxis<T>: XAxis<T> + YAxis<T> {}
 
 pub trait XYZAxis<T>: XAxis<T> + YAxis<T> + ZAxis<T> {}
@@ -160,11 +332,14 @@ where
 
 impl<T> XYAxis<T> for Vector2<T> where T: VectorTrait<T> {}
 
-pub(crate) trait NumMinMax {
+/// Extreme values and comparisons shared by the integer and float coordinates
+pub trait NumMinMax {
     type Output;
 
     fn max_value() -> Self::Output;
     fn min_value() -> Self::Output;
+    fn minimum(self, other: Self) -> Self::Output;
+    fn maximum(self, other: Self) -> Self::Output;
 }
 
 macro_rules! impl_num_min_max_trait {
@@ -179,6 +354,14 @@ macro_rules! impl_num_min_max_trait {
             fn min_value() -> Self::Output {
                 <$t>::MIN
             }
+
+            fn minimum(self, other: Self) -> Self::Output {
+                self.min(other)
+            }
+
+            fn maximum(self, other: Self) -> Self::Output {
+                self.max(other)
+            }
         }
     };
 }
@@ -187,6 +370,7 @@ impl_num_min_max_trait!(i32);
 impl_num_min_max_trait!(f32);
 
 #[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector3<T>
 where
     T: VectorTrait<T>,
@@ -205,15 +389,41 @@ where
     }
 
     pub fn new_from_array(coords: &[T; 3]) -> Self {
-        Vector3 {
-            x: coords[0],
-            y: coords[1],
-            z: coords[2],
-        }
+        Self::from(*coords)
+    }
+
+    pub fn as_array(&self) -> [T; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    pub fn swap(&mut self, rhs: &mut Self) {
+        mem::swap(self, rhs);
     }
 
     fn get_sum_of_squared(&self) -> T {
-        self.x * self.x + self.y * self.y + self.z * self.z
+        self.dot(self)
+    }
+
+    /// Squared vector length, cheaper than [`Vector3::norm_f32`] for comparisons
+    pub fn length_squared(&self) -> T {
+        self.get_sum_of_squared()
+    }
+
+    pub fn dot(&self, rhs: &Self) -> T {
+        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn cross(&self, rhs: &Self) -> Self {
+        Vector3::<T>::new(
+            self.y * rhs.z - self.z * rhs.y,
+            self.z * rhs.x - self.x * rhs.z,
+            self.x * rhs.y - self.y * rhs.x,
+        )
+    }
+
+    /// Homogeneous coordinates of the vector, `w = 1` for points and `w = 0` for directions
+    pub fn to_homogeneous(&self, w: T) -> Vector4<T> {
+        Vector4::new(self.x, self.y, self.z, w)
     }
 
     fn get_sum_of_squared_f32(&self) -> f32 {
@@ -247,6 +457,34 @@ where
     }
 }
 
+impl<T> Vector3<T>
+where
+    T: VectorTrait<T> + NumMinMax<Output = T>,
+{
+    /// Componentwise minimum
+    pub fn min(self, other: Self) -> Self {
+        Vector3 {
+            x: self.x.minimum(other.x),
+            y: self.y.minimum(other.y),
+            z: self.z.minimum(other.z),
+        }
+    }
+
+    /// Componentwise maximum
+    pub fn max(self, other: Self) -> Self {
+        Vector3 {
+            x: self.x.maximum(other.x),
+            y: self.y.maximum(other.y),
+            z: self.z.maximum(other.z),
+        }
+    }
+
+    /// Clamp every component to the `lo..=hi` range of the same component
+    pub fn clamp(self, lo: Self, hi: Self) -> Self {
+        self.max(lo).min(hi)
+    }
+}
+
 /// Dot product
 impl<T> Mul for Vector3<T>
 where
@@ -255,7 +493,7 @@ where
     type Output = T;
 
     fn mul(self, rhs: Self) -> Self::Output {
-        self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+        self.dot(&rhs)
     }
 }
 
@@ -317,11 +555,16 @@ where
     type Output = Self;
 
     fn bitxor(self, rhs: Self) -> Self::Output {
-        Vector3::<T>::new(
-            self.y * rhs.z - self.z * rhs.y,
-            self.z * rhs.x - self.x * rhs.z,
-            self.x * rhs.y - self.y * rhs.x,
-        )
+        self.cross(&rhs)
+    }
+}
+
+impl<T: VectorTrait<T> + Float> Vector3<T> {
+    /// Every component differs by at most `eps`
+    pub fn approx_eq(&self, other: &Self, eps: T) -> bool {
+        (self.x - other.x).abs() <= eps
+            && (self.y - other.y).abs() <= eps
+            && (self.z - other.z).abs() <= eps
     }
 }
 
@@ -334,9 +577,112 @@ where
     }
 }
 
+impl<T: VectorTrait<T>> From<[T; 3]> for Vector3<T> {
+    fn from([x, y, z]: [T; 3]) -> Self {
+        Vector3 { x, y, z }
+    }
+}
+
+impl<T: VectorTrait<T>> From<Vector3<T>> for [T; 3] {
+    fn from(v: Vector3<T>) -> Self {
+        [v.x, v.y, v.z]
+    }
+}
+
+/// Coordinate by its axis number, panics for indices past `z`
+impl<T: VectorTrait<T>> Index<usize> for Vector3<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("index {} is out of range for Vector3", index),
+        }
+    }
+}
+
+impl<T: VectorTrait<T>> IndexMut<usize> for Vector3<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("index {} is out of range for Vector3", index),
+        }
+    }
+}
+
 pub type Vector3F32 = Vector3<f32>;
 pub type Vector3Int = Vector3<i32>;
 
+impl Vector3F32 {
+    /// Nearest integer coordinates, halves are rounded away from zero
+    ///
+    /// This is the default conversion of screen coordinates, see
+    /// [`Vector2F32::to_int_round`].
+    pub fn to_int_round(&self) -> Vector3Int {
+        Vector3::new(
+            self.x.round() as i32,
+            self.y.round() as i32,
+            self.z.round() as i32,
+        )
+    }
+
+    pub fn to_int_floor(&self) -> Vector3Int {
+        Vector3::new(
+            self.x.floor() as i32,
+            self.y.floor() as i32,
+            self.z.floor() as i32,
+        )
+    }
+
+    pub fn to_int_ceil(&self) -> Vector3Int {
+        Vector3::new(
+            self.x.ceil() as i32,
+            self.y.ceil() as i32,
+            self.z.ceil() as i32,
+        )
+    }
+
+    /// Reflect the vector off a surface with the unit `normal`
+    ///
+    /// The vector points towards the surface, like the light direction, and the
+    /// result points away from it. A vector pointing away from the surface is
+    /// reflected into one pointing towards it.
+    pub fn reflect(&self, normal: &Self) -> Self {
+        *self - *normal * (2.0 * self.dot(normal))
+    }
+
+    /// Linear interpolation from `self` at `t = 0` to `other` at `t = 1`
+    ///
+    /// `t` outside of `0.0..=1.0` extrapolates, see [`Vector3F32::lerp_clamped`].
+    pub fn lerp(&self, other: &Self, t: f32) -> Self {
+        *self * (1.0 - t) + *other * t
+    }
+
+    pub fn lerp_clamped(&self, other: &Self, t: f32) -> Self {
+        self.lerp(other, t.clamp(0.0, 1.0))
+    }
+
+    /// Component of the vector along `axis`, `axis` does not have to be unit
+    pub fn project_onto(&self, axis: &Self) -> Self {
+        *axis * (self.dot(axis) / axis.dot(axis))
+    }
+
+    /// Component of the vector perpendicular to `axis`
+    pub fn reject_from(&self, axis: &Self) -> Self {
+        *self - self.project_onto(axis)
+    }
+}
+
+impl Vector3Int {
+    pub fn to_f32(&self) -> Vector3F32 {
+        Vector3::new(self.x as f32, self.y as f32, self.z as f32)
+    }
+}
+
 impl<T> XAxis<T> for Vector3<T>
 where
     T: VectorTrait<T>,
@@ -379,35 +725,1280 @@ where
 impl<T> XYAxis<T> for Vector3<T> where T: VectorTrait<T> {}
 impl<T> XYZAxis<T> for Vector3<T> where T: VectorTrait<T> {}
 
-#[derive(Debug, Copy, Clone)]
-pub struct UVMap<T: Num + Copy + Clone> {
-    pub u: T,
-    pub v: T,
-    pub w: T,
+#[derive(Copy, Clone, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Vector4<T>
+where
+    T: VectorTrait<T>,
+{
+    x: T,
+    y: T,
+    z: T,
+    w: T,
 }
 
-pub type UVMapF32 = UVMap<f32>;
+impl<T> Vector4<T>
+where
+    T: VectorTrait<T> + AsPrimitive<f32> + AsPrimitive<f64>,
+{
+    pub fn new(x: T, y: T, z: T, w: T) -> Self {
+        Vector4 { x, y, z, w }
+    }
 
-#[cfg(test)]
-mod test_vector3 {
-    use crate::geometry::{Vector3F32, XAxis, YAxis, ZAxis};
+    /// Divide `x`, `y` and `z` by `w`
+    ///
+    /// Vectors with `w = 0` are directions and keep their coordinates.
+    pub fn project(&self) -> Vector3<T> {
+        if self.w.is_zero() {
+            Vector3::new(self.x, self.y, self.z)
+        } else {
+            Vector3::new(self.x / self.w, self.y / self.w, self.z / self.w)
+        }
+    }
+}
 
-    #[test]
-    fn test_normalization() {
-        let mut v = Vector3F32::new(3.0, 4.0, 5.0);
-        let expected_sqrt = (3.0f32 * 3.0 + 4.0 * 4.0 + 5.0 * 5.0).sqrt();
+impl<T, U> Mul<U> for Vector4<T>
+where
+    T: VectorTrait<T> + AsPrimitive<U> + AsPrimitive<f32> + AsPrimitive<f64>,
+    U: Float + AsPrimitive<T>,
+{
+    type Output = Self;
 
-        assert_eq!(expected_sqrt, v.norm_f32());
+    fn mul(self, rhs: U) -> Self::Output {
+        Vector4::<T>::new(
+            (rhs * self.x.as_()).as_(),
+            (rhs * self.y.as_()).as_(),
+            (rhs * self.z.as_()).as_(),
+            (rhs * self.w.as_()).as_(),
+        )
+    }
+}
 
-        let expected = Vector3F32::new(
-            3.0 as f32 / expected_sqrt,
-            4.0 as f32 / expected_sqrt,
-            5.0 as f32 / expected_sqrt,
-        );
+impl<T> Add for Vector4<T>
+where
+    T: VectorTrait<T> + AsPrimitive<f32> + AsPrimitive<f64>,
+{
+    type Output = Self;
 
-        v.normalize_default();
-        assert!((expected.get_x() - v.get_x()).abs() < 0.05);
-        assert!((expected.get_y() - v.get_y()).abs() < 0.05);
-        assert!((expected.get_z() - v.get_z()).abs() < 0.05);
+    fn add(self, rhs: Self) -> Self::Output {
+        Vector4::<T>::new(
+            self.x + rhs.x,
+            self.y + rhs.y,
+            self.z + rhs.z,
+            self.w + rhs.w,
+        )
+    }
+}
+
+impl<T> Sub for Vector4<T>
+where
+    T: VectorTrait<T> + AsPrimitive<f32> + AsPrimitive<f64>,
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vector4::<T>::new(
+            self.x - rhs.x,
+            self.y - rhs.y,
+            self.z - rhs.z,
+            self.w - rhs.w,
+        )
+    }
+}
+
+impl<T: VectorTrait<T> + Float> Vector4<T> {
+    /// Every component differs by at most `eps`
+    pub fn approx_eq(&self, other: &Self, eps: T) -> bool {
+        (self.x - other.x).abs() <= eps
+            && (self.y - other.y).abs() <= eps
+            && (self.z - other.z).abs() <= eps
+            && (self.w - other.w).abs() <= eps
+    }
+}
+
+impl<T> Display for Vector4<T>
+where
+    T: VectorTrait<T> + AsPrimitive<f32> + AsPrimitive<f64> + Display,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "({}, {}, {}, {})", self.x, self.y, self.z, self.w)
+    }
+}
+
+pub type Vector4F32 = Vector4<f32>;
+pub type Vector4Int = Vector4<i32>;
+
+impl<T> XAxis<T> for Vector4<T>
+where
+    T: VectorTrait<T>,
+{
+    fn get_x(&self) -> T {
+        self.x
+    }
+
+    fn x_as_mut_ref(&mut self) -> &mut T {
+        &mut self.x
+    }
+}
+
+impl<T> YAxis<T> for Vector4<T>
+where
+    T: VectorTrait<T>,
+{
+    fn get_y(&self) -> T {
+        self.y
+    }
+
+    fn y_as_mut_ref(&mut self) -> &mut T {
+        &mut self.y
+    }
+}
+
+impl<T> ZAxis<T> for Vector4<T>
+where
+    T: VectorTrait<T>,
+{
+    fn get_z(&self) -> T {
+        self.z
+    }
+
+    fn z_as_mut_ref(&mut self) -> &mut T {
+        &mut self.z
+    }
+}
+
+impl<T> WAxis<T> for Vector4<T>
+where
+    T: VectorTrait<T>,
+{
+    fn get_w(&self) -> T {
+        self.w
+    }
+
+    fn w_as_mut_ref(&mut self) -> &mut T {
+        &mut self.w
+    }
+}
+
+impl<T> XYAxis<T> for Vector4<T> where T: VectorTrait<T> {}
+impl<T> XYZAxis<T> for Vector4<T> where T: VectorTrait<T> {}
+
+/// Row-major 4x4 matrix
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "[T; 16]", from = "[T; 16]")
+)]
+pub struct Matrix4<T: VectorTrait<T>> {
+    rows: [[T; 4]; 4],
+}
+
+impl<T: VectorTrait<T>> Matrix4<T> {
+    pub fn new(rows: [[T; 4]; 4]) -> Self {
+        Matrix4 { rows }
+    }
+
+    pub fn identity() -> Self {
+        let mut rows = [[T::zero(); 4]; 4];
+
+        rows.iter_mut()
+            .enumerate()
+            .for_each(|(i, row)| row[i] = T::one());
+
+        Matrix4 { rows }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut rows = self.rows;
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+
+        Matrix4 { rows }
+    }
+
+    /// Upper-left 3x3 block, the linear part of an affine transform
+    pub fn upper3x3(&self) -> Matrix3<T> {
+        let mut rows = [[T::zero(); 3]; 3];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            row.copy_from_slice(&self.rows[i][..3]);
+        }
+
+        Matrix3 { rows }
+    }
+}
+
+impl<T: VectorTrait<T> + Float> Matrix4<T> {
+    /// Every element differs by at most `eps`
+    pub fn approx_eq(&self, other: &Self, eps: T) -> bool {
+        rows_approx_eq(&self.rows, &other.rows, eps)
+    }
+
+    /// Inverse matrix computed with Gauss-Jordan elimination, `None` for singular matrices
+    ///
+    /// Pivots are compared against the largest element, so uniformly scaled
+    /// matrices are inverted no matter how small the scale is.
+    pub fn inverse(&self) -> Option<Self> {
+        let mut rows = self.rows;
+        let mut inverse = Self::identity().rows;
+        let largest = self
+            .rows
+            .iter()
+            .flatten()
+            .fold(T::zero(), |largest, v| largest.max(v.abs()));
+
+        for col in 0..4 {
+            let pivot = (col..4).fold(col, |pivot, row| {
+                if rows[row][col].abs() > rows[pivot][col].abs() {
+                    row
+                } else {
+                    pivot
+                }
+            });
+            let pivot_value = rows[pivot][col].abs();
+
+            if !pivot_value.is_finite() || pivot_value <= T::epsilon() * largest {
+                return None;
+            }
+
+            rows.swap(col, pivot);
+            inverse.swap(col, pivot);
+
+            let scale = rows[col][col];
+            let (pivot_row, pivot_inverse) = (
+                rows[col].map(|v| v / scale),
+                inverse[col].map(|v| v / scale),
+            );
+
+            rows[col] = pivot_row;
+            inverse[col] = pivot_inverse;
+
+            for row in (0..4).filter(|row| *row != col) {
+                let factor = rows[row][col];
+
+                for k in 0..4 {
+                    rows[row][k] = rows[row][k] - factor * pivot_row[k];
+                    inverse[row][k] = inverse[row][k] - factor * pivot_inverse[k];
+                }
+            }
+        }
+
+        Some(Matrix4 { rows: inverse })
+    }
+}
+
+impl<T> Matrix4<T>
+where
+    T: VectorTrait<T> + AsPrimitive<f32> + AsPrimitive<f64>,
+{
+    /// Transform a point extended with `w = 1` and divide the result by its `w`
+    ///
+    /// Points mapped to `w = 0` are returned without the division.
+    pub fn transform_point(&self, p: Vector3<T>) -> Vector3<T> {
+        (*self * p.to_homogeneous(T::one())).project()
+    }
+}
+
+impl<T: VectorTrait<T>> From<[T; 16]> for Matrix4<T> {
+    /// Elements listed row by row
+    fn from(elements: [T; 16]) -> Self {
+        let mut rows = [[T::zero(); 4]; 4];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            row.copy_from_slice(&elements[i * 4..(i + 1) * 4]);
+        }
+
+        Matrix4 { rows }
+    }
+}
+
+impl<T: VectorTrait<T>> From<Matrix4<T>> for [T; 16] {
+    fn from(m: Matrix4<T>) -> Self {
+        let mut elements = [T::zero(); 16];
+
+        for (chunk, row) in elements.chunks_mut(4).zip(m.rows.iter()) {
+            chunk.copy_from_slice(row);
+        }
+
+        elements
+    }
+}
+
+impl<T: VectorTrait<T>> Index<(usize, usize)> for Matrix4<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self.rows[row][col]
+    }
+}
+
+impl<T: VectorTrait<T>> IndexMut<(usize, usize)> for Matrix4<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        &mut self.rows[row][col]
+    }
+}
+
+impl<T: VectorTrait<T>> Mul for Matrix4<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut rows = [[T::zero(); 4]; 4];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).fold(T::zero(), |sum, k| sum + self.rows[i][k] * rhs.rows[k][j]);
+            }
+        }
+
+        Matrix4 { rows }
+    }
+}
+
+impl<T> Mul<Vector4<T>> for Matrix4<T>
+where
+    T: VectorTrait<T> + AsPrimitive<f32> + AsPrimitive<f64>,
+{
+    type Output = Vector4<T>;
+
+    fn mul(self, rhs: Vector4<T>) -> Self::Output {
+        let [x, y, z, w] = self
+            .rows
+            .map(|row| row[0] * rhs.x + row[1] * rhs.y + row[2] * rhs.z + row[3] * rhs.w);
+
+        Vector4::new(x, y, z, w)
+    }
+}
+
+/// One bracketed row per line
+impl<T: VectorTrait<T> + Display> Display for Matrix4<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write_rows(f, &self.rows)
+    }
+}
+
+pub type Matrix4F32 = Matrix4<f32>;
+
+fn rows_approx_eq<T: Float, R: AsRef<[T]>>(a: &[R], b: &[R], eps: T) -> bool {
+    a.iter().zip(b).all(|(a, b)| {
+        a.as_ref()
+            .iter()
+            .zip(b.as_ref())
+            .all(|(a, b)| (*a - *b).abs() <= eps)
+    })
+}
+
+fn write_rows<T: Display, R: AsRef<[T]>>(f: &mut Formatter<'_>, rows: &[R]) -> Result {
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+
+        write!(f, "[")?;
+
+        for (j, value) in row.as_ref().iter().enumerate() {
+            if j > 0 {
+                write!(f, ", ")?;
+            }
+
+            write!(f, "{}", value)?;
+        }
+
+        write!(f, "]")?;
+    }
+
+    Ok(())
+}
+
+/// Row-major 3x3 matrix
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "[T; 9]", from = "[T; 9]")
+)]
+pub struct Matrix3<T: VectorTrait<T>> {
+    rows: [[T; 3]; 3],
+}
+
+impl<T: VectorTrait<T>> Matrix3<T> {
+    pub fn new(rows: [[T; 3]; 3]) -> Self {
+        Matrix3 { rows }
+    }
+
+    pub fn identity() -> Self {
+        let mut rows = [[T::zero(); 3]; 3];
+
+        rows.iter_mut()
+            .enumerate()
+            .for_each(|(i, row)| row[i] = T::one());
+
+        Matrix3 { rows }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut rows = self.rows;
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.rows[j][i];
+            }
+        }
+
+        Matrix3 { rows }
+    }
+
+    /// Cofactor of the element in `row` and `col`
+    fn cofactor(&self, row: usize, col: usize) -> T {
+        let (r0, r1) = ((row + 1) % 3, (row + 2) % 3);
+        let (c0, c1) = ((col + 1) % 3, (col + 2) % 3);
+
+        // cyclic minors already carry the cofactor sign
+        self.rows[r0][c0] * self.rows[r1][c1] - self.rows[r0][c1] * self.rows[r1][c0]
+    }
+
+    pub fn determinant(&self) -> T {
+        (0..3).fold(T::zero(), |sum, col| {
+            sum + self.rows[0][col] * self.cofactor(0, col)
+        })
+    }
+}
+
+impl<T: VectorTrait<T> + Float> Matrix3<T> {
+    /// Every element differs by at most `eps`
+    pub fn approx_eq(&self, other: &Self, eps: T) -> bool {
+        rows_approx_eq(&self.rows, &other.rows, eps)
+    }
+
+    /// Inverse matrix computed from the cofactors, `None` for singular matrices
+    ///
+    /// The determinant is compared against the product of the row lengths, its
+    /// largest possible value, so uniformly scaled matrices are inverted no
+    /// matter how small the scale is.
+    pub fn inverse(&self) -> Option<Self> {
+        let det = self.determinant();
+        let bound = self.rows.iter().fold(T::one(), |bound, row| {
+            bound * row.iter().fold(T::zero(), |sum, v| sum + *v * *v).sqrt()
+        });
+
+        if !det.is_finite() || det.abs() <= T::epsilon() * bound {
+            return None;
+        }
+
+        let mut rows = [[T::zero(); 3]; 3];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.cofactor(j, i) / det;
+            }
+        }
+
+        Some(Matrix3 { rows })
+    }
+}
+
+impl<T: VectorTrait<T>> From<[T; 9]> for Matrix3<T> {
+    /// Elements listed row by row
+    fn from(elements: [T; 9]) -> Self {
+        let mut rows = [[T::zero(); 3]; 3];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            row.copy_from_slice(&elements[i * 3..(i + 1) * 3]);
+        }
+
+        Matrix3 { rows }
+    }
+}
+
+impl<T: VectorTrait<T>> From<Matrix3<T>> for [T; 9] {
+    fn from(m: Matrix3<T>) -> Self {
+        let mut elements = [T::zero(); 9];
+
+        for (chunk, row) in elements.chunks_mut(3).zip(m.rows.iter()) {
+            chunk.copy_from_slice(row);
+        }
+
+        elements
+    }
+}
+
+impl<T: VectorTrait<T>> Index<(usize, usize)> for Matrix3<T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &Self::Output {
+        &self.rows[row][col]
+    }
+}
+
+impl<T: VectorTrait<T>> IndexMut<(usize, usize)> for Matrix3<T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut Self::Output {
+        &mut self.rows[row][col]
+    }
+}
+
+impl<T: VectorTrait<T>> Mul for Matrix3<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        let mut rows = [[T::zero(); 3]; 3];
+
+        for (i, row) in rows.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..3).fold(T::zero(), |sum, k| sum + self.rows[i][k] * rhs.rows[k][j]);
+            }
+        }
+
+        Matrix3 { rows }
+    }
+}
+
+impl<T> Mul<Vector3<T>> for Matrix3<T>
+where
+    T: VectorTrait<T> + AsPrimitive<f32> + AsPrimitive<f64>,
+{
+    type Output = Vector3<T>;
+
+    fn mul(self, rhs: Vector3<T>) -> Self::Output {
+        let [x, y, z] = self
+            .rows
+            .map(|row| row[0] * rhs.x + row[1] * rhs.y + row[2] * rhs.z);
+
+        Vector3::new(x, y, z)
+    }
+}
+
+/// One bracketed row per line
+impl<T: VectorTrait<T> + Display> Display for Matrix3<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write_rows(f, &self.rows)
+    }
+}
+
+pub type Matrix3F32 = Matrix3<f32>;
+
+/// Matrix transforming the normals of a model moved by `model`
+///
+/// Normals stay perpendicular to the surface only when transformed by the
+/// inverse transpose of the linear part of `model`, which differs from the
+/// linear part itself for non-uniform scales. The result does not keep the
+/// normal length. `None` for a singular `model`.
+pub fn normal_matrix(model: &Matrix4F32) -> Option<Matrix3F32> {
+    model
+        .upper3x3()
+        .inverse()
+        .map(|inverse| inverse.transpose())
+}
+
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UVMap<T: Num + Copy + Clone> {
+    pub u: T,
+    pub v: T,
+    pub w: T,
+}
+
+impl<T: Num + Copy + Clone + Display> Display for UVMap<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "({}, {}, {})", self.u, self.v, self.w)
+    }
+}
+
+pub type UVMapF32 = UVMap<f32>;
+
+/// Check the winding of a triangle in screen coordinates
+///
+/// Front faces have their vertices ordered counter-clockwise with X to the right
+/// and Y up, i.e. the cross product of their edges points towards the viewer.
+/// Degenerate triangles are not back facing.
+pub fn is_back_facing(screen_tri: &[Vector3F32; 3]) -> bool {
+    let normal = (screen_tri[1] - screen_tri[0]).cross(&(screen_tri[2] - screen_tri[0]));
+
+    normal.z < 0.0
+}
+
+#[cfg(test)]
+mod test_vector2 {
+    use crate::geometry::{Vector2F32, Vector2Int, XAxis, YAxis};
+
+    #[test]
+    fn test_normalization() {
+        let mut v = Vector2F32::new(3.0, 4.0);
+
+        assert_eq!(v.norm_squared(), 25.0);
+        assert_eq!(v.norm(), 5.0);
+
+        let normalized = v.normalized();
+
+        v.normalize();
+        assert_vec_eq!(v, Vector2F32::new(0.6, 0.8), 1e-6);
+        assert_eq!(normalized.as_array(), v.as_array());
+        assert!((v.norm() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_scalar_ops() {
+        let v = Vector2Int::new(3, -4);
+        let scaled = v * 2.5f32;
+        let divided = Vector2F32::new(3.0, -4.0) / 2.0;
+        let negated = -v;
+
+        assert_eq!(v.norm_squared(), 25);
+        assert_eq!(v.dot(&Vector2Int::new(2, 1)), 2);
+        assert_eq!((scaled.get_x(), scaled.get_y()), (7, -10));
+        assert_eq!((divided.get_x(), divided.get_y()), (1.5, -2.0));
+        assert_eq!((negated.get_x(), negated.get_y()), (-3, 4));
+    }
+
+    #[test]
+    fn test_perp() {
+        let v = Vector2Int::new(3, 1);
+        let perp = v.perp();
+
+        assert_eq!((perp.get_x(), perp.get_y()), (-1, 3));
+        assert_eq!(v.dot(&perp), 0);
+
+        let back = perp.perp().perp().perp();
+
+        assert_eq!((back.get_x(), back.get_y()), (3, 1));
+    }
+
+    #[test]
+    fn test_index_and_arrays() {
+        let mut v = Vector2Int::from([7, -2]);
+
+        assert_eq!((v[0], v[1]), (v.get_x(), v.get_y()));
+
+        v[0] += 1;
+        v[1] = 5;
+
+        assert_eq!(v.as_array(), [8, 5]);
+        assert_eq!(<[i32; 2]>::from(v), [8, 5]);
+        assert_eq!(
+            Vector2F32::new_from_array(&[0.5, 1.5]).as_array(),
+            [0.5, 1.5]
+        );
+    }
+
+    #[test]
+    fn test_int_conversions() {
+        let v = Vector2F32::new(-2.5, 1.5);
+        let as_array = |v: Vector2Int| v.as_array();
+
+        assert_eq!(as_array(v.to_int_round()), [-3, 2]);
+        assert_eq!(as_array(v.to_int_floor()), [-3, 1]);
+        assert_eq!(as_array(v.to_int_ceil()), [-2, 2]);
+        assert_eq!(as_array(Vector2F32::new(-0.4, 0.4).to_int_round()), [0, 0]);
+        assert_eq!(Vector2Int::new(-3, 7).to_f32().as_array(), [-3.0, 7.0]);
+    }
+
+    #[test]
+    fn test_min_max_clamp() {
+        let a = Vector2Int::new(-3, 4);
+        let b = Vector2Int::new(2, -5);
+        let (lo, hi) = (Vector2Int::new(-2, -2), Vector2Int::new(2, 2));
+
+        assert_eq!(a.min(b).as_array(), [-3, -5]);
+        assert_eq!(a.max(b).as_array(), [2, 4]);
+        assert_eq!(a.clamp(lo, hi).as_array(), [-2, 2]);
+        assert_eq!(b.clamp(lo, hi).as_array(), [2, -2]);
+        // values at the bounds are kept
+        assert_eq!(lo.clamp(lo, hi).as_array(), [-2, -2]);
+        assert_eq!(hi.clamp(lo, hi).as_array(), [2, 2]);
+        assert_eq!(
+            Vector2F32::new(-0.5, 1.0)
+                .clamp(Vector2F32::new(-0.5, 0.0), Vector2F32::new(0.0, 1.0))
+                .as_array(),
+            [-0.5, 1.0]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "index 2 is out of range for Vector2")]
+    fn test_index_out_of_range() {
+        let _ = Vector2Int::new(1, 2)[2];
+    }
+}
+
+#[cfg(test)]
+mod test_vector3 {
+    use crate::geometry::{Vector2Int, Vector3F32, Vector3Int, XAxis, YAxis, ZAxis};
+
+    #[test]
+    fn test_normalization() {
+        let mut v = Vector3F32::new(3.0, 4.0, 5.0);
+        let expected_sqrt = (3.0f32 * 3.0 + 4.0 * 4.0 + 5.0 * 5.0).sqrt();
+
+        assert_eq!(expected_sqrt, v.norm_f32());
+
+        let expected = Vector3F32::new(
+            3.0f32 / expected_sqrt,
+            4.0f32 / expected_sqrt,
+            5.0f32 / expected_sqrt,
+        );
+
+        v.normalize_default();
+        assert_vec_eq!(v, expected);
+        assert!(!v.approx_eq(&Vector3F32::new(0.43, 0.57, 0.71), 1e-3));
+    }
+
+    #[test]
+    fn test_cross_product() {
+        let a = Vector3Int::new(1, 2, 3);
+        let b = Vector3Int::new(-4, 5, 6);
+        let axb = a.cross(&b);
+        let bxa = b.cross(&a);
+
+        assert_eq!((axb.get_x(), axb.get_y(), axb.get_z()), (-3, -18, 13));
+        assert_eq!((bxa.get_x(), bxa.get_y(), bxa.get_z()), (3, 18, -13));
+        assert_eq!(axb.dot(&a), 0);
+        assert_eq!(axb.dot(&b), 0);
+        assert_eq!((a ^ b).dot(&axb), axb.length_squared());
+        assert_eq!(a.cross(&a).length_squared(), 0);
+    }
+
+    #[test]
+    fn test_index_and_arrays() {
+        let mut v = Vector3Int::from([1, -2, 3]);
+
+        for i in 0..3 {
+            v[i] *= 10;
+        }
+
+        assert_eq!((v[0], v[1], v[2]), (v.get_x(), v.get_y(), v.get_z()));
+        assert_eq!(v.as_array(), [10, -20, 30]);
+
+        let coords: [i32; 3] = v.into();
+
+        assert_eq!(coords, [10, -20, 30]);
+        assert_eq!(
+            Vector3F32::new_from_array(&[0.5, 1.5, 2.5]).as_array(),
+            Vector3F32::from([0.5, 1.5, 2.5]).as_array()
+        );
+    }
+
+    #[test]
+    fn test_int_conversions() {
+        let v = Vector3F32::new(-0.5, 2.5, -1.2);
+
+        assert_eq!(v.to_int_round().as_array(), [-1, 3, -1]);
+        assert_eq!(v.to_int_floor().as_array(), [-1, 2, -2]);
+        assert_eq!(v.to_int_ceil().as_array(), [0, 3, -1]);
+        // truncation would put the negative coordinate a pixel to the right
+        assert_eq!(Vector3F32::new(-1.7, 0.0, 0.0).to_int_round().get_x(), -2);
+        assert_eq!(
+            Vector3Int::new(-4, 0, 9).to_f32().as_array(),
+            [-4.0, 0.0, 9.0]
+        );
+    }
+
+    #[test]
+    fn test_min_max_clamp() {
+        let a = Vector3F32::new(-1.5, 0.0, 7.0);
+        let b = Vector3F32::new(1.5, -0.0, -7.0);
+        let lo = Vector3F32::new(-1.0, -1.0, -7.0);
+        let hi = Vector3F32::new(1.0, 1.0, 7.0);
+
+        assert_eq!(a.min(b).as_array(), [-1.5, 0.0, -7.0]);
+        assert_eq!(a.max(b).as_array(), [1.5, 0.0, 7.0]);
+        assert_eq!(a.clamp(lo, hi).as_array(), [-1.0, 0.0, 7.0]);
+        assert_eq!(b.clamp(lo, hi).as_array(), [1.0, 0.0, -7.0]);
+        assert_eq!(
+            Vector3Int::new(5, -5, 0)
+                .clamp(Vector3Int::new(0, -5, 0), Vector3Int::new(5, 0, 0))
+                .as_array(),
+            [5, -5, 0]
+        );
+    }
+
+    #[test]
+    fn test_reflect_lerp_project() {
+        let up = Vector3F32::new(0.0, 1.0, 0.0);
+        let r = Vector3F32::new(1.0, -1.0, 0.0).reflect(&up);
+
+        assert_eq!(r.as_array(), [1.0, 1.0, 0.0]);
+
+        let a = Vector3F32::new(0.1, -3.7, 1e3);
+        let b = Vector3F32::new(-2.3, 0.7, 1e-3);
+
+        assert_eq!(a.lerp(&b, 0.0).as_array(), a.as_array());
+        assert_eq!(a.lerp(&b, 1.0).as_array(), b.as_array());
+        assert_eq!(
+            Vector3F32::new(0.0, 2.0, 4.0)
+                .lerp(&Vector3F32::new(2.0, 2.0, 0.0), 0.5)
+                .as_array(),
+            [1.0, 2.0, 2.0]
+        );
+        assert_eq!(a.lerp_clamped(&b, 1.5).as_array(), b.as_array());
+        assert_eq!(a.lerp_clamped(&b, -0.5).as_array(), a.as_array());
+
+        let v = Vector3F32::new(3.0, 4.0, 12.0);
+        let mut axis = Vector3F32::new(1.0, 1.0, 0.0);
+
+        axis.normalize_default();
+
+        let projected = v.project_onto(&axis);
+        let rejected = v.reject_from(&axis);
+
+        assert!((projected.norm_f32() - 7.0 / 2f32.sqrt()).abs() < 1e-5);
+        assert!(rejected.dot(&axis).abs() < 1e-5);
+        assert_eq!(
+            Vector3F32::new(3.0, 4.0, 12.0)
+                .project_onto(&Vector3F32::new(0.0, 0.0, 2.0))
+                .as_array(),
+            [0.0, 0.0, 12.0]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 is out of range for Vector3")]
+    fn test_index_out_of_range() {
+        let _ = Vector3Int::new(1, 2, 3)[3];
+    }
+
+    #[test]
+    fn test_dot_product() {
+        let a = Vector3F32::new(1.0, -2.0, 0.5);
+        let b = Vector3F32::new(4.0, 1.0, -2.0);
+
+        assert_eq!(a.dot(&b), 1.0);
+        assert_eq!(a * b, a.dot(&b));
+        assert_eq!(a.length_squared(), 5.25);
+        assert_eq!(Vector2Int::new(2, -3).dot(&Vector2Int::new(4, 5)), -7);
+    }
+}
+
+#[cfg(test)]
+mod test_winding {
+    use crate::geometry::{is_back_facing, Vector3F32};
+
+    #[test]
+    fn test_back_facing() {
+        let ccw = [
+            Vector3F32::new(0.0, 0.0, 0.0),
+            Vector3F32::new(10.0, 0.0, 5.0),
+            Vector3F32::new(0.0, 10.0, -5.0),
+        ];
+        let cw = [ccw[0], ccw[2], ccw[1]];
+        let degenerate = [ccw[0], ccw[1], ccw[1] * 2.0];
+
+        assert!(!is_back_facing(&ccw));
+        assert!(is_back_facing(&cw));
+        assert!(!is_back_facing(&[ccw[1], ccw[2], ccw[0]]));
+        assert!(!is_back_facing(&degenerate));
+    }
+}
+
+#[cfg(test)]
+mod test_vector4 {
+    use crate::geometry::{Vector3F32, Vector4F32, WAxis, XAxis, YAxis, ZAxis};
+
+    #[test]
+    fn test_homogeneous_round_trip() {
+        let p = Vector3F32::new(1.5, -2.0, 3.25);
+        let h = p.to_homogeneous(1.0);
+        let round_trip = h.project();
+
+        assert_eq!(h.get_w(), 1.0);
+        assert_eq!(
+            (round_trip.get_x(), round_trip.get_y(), round_trip.get_z()),
+            (1.5, -2.0, 3.25)
+        );
+
+        let scaled = (h * 4.0).project();
+
+        assert_eq!(
+            (scaled.get_x(), scaled.get_y(), scaled.get_z()),
+            (1.5, -2.0, 3.25)
+        );
+    }
+
+    #[test]
+    fn test_project_direction() {
+        let direction = Vector4F32::new(1.0, 2.0, -3.0, 0.0).project();
+
+        assert_eq!(
+            (direction.get_x(), direction.get_y(), direction.get_z()),
+            (1.0, 2.0, -3.0)
+        );
+    }
+
+    #[test]
+    fn test_vector4_arithmetic() {
+        let a = Vector4F32::new(1.0, 2.0, 3.0, 4.0);
+        let b = Vector4F32::new(0.5, 0.5, 0.5, 0.5);
+        let sum = a + b;
+        let difference = a - b;
+
+        assert_eq!(
+            (sum.get_x(), sum.get_y(), sum.get_z(), sum.get_w()),
+            (1.5, 2.5, 3.5, 4.5)
+        );
+        assert_eq!(
+            (
+                difference.get_x(),
+                difference.get_y(),
+                difference.get_z(),
+                difference.get_w()
+            ),
+            (0.5, 1.5, 2.5, 3.5)
+        );
+        assert_eq!(format!("{}", a * 2.0), "(2, 4, 6, 8)");
+    }
+}
+
+#[cfg(test)]
+mod test_matrix3 {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::geometry::{normal_matrix, Matrix3F32, Matrix4F32, Vector3F32};
+
+    fn scale_rotate_z(scale: [f32; 3], angle: f32) -> Matrix4F32 {
+        let (sin, cos) = angle.sin_cos();
+
+        Matrix4F32::new([
+            [cos, -sin, 0.0, 0.0],
+            [sin, cos, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]) * Matrix4F32::new([
+            [scale[0], 0.0, 0.0, 3.0],
+            [0.0, scale[1], 0.0, -2.0],
+            [0.0, 0.0, scale[2], 1.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    #[test]
+    fn test_inverse_and_determinant() {
+        let mut rng = StdRng::seed_from_u64(96);
+        let mut m = Matrix3F32::identity();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                m[(i, j)] = rng.gen_range(-1.0, 1.0) + if i == j { 3.0 } else { 0.0 };
+            }
+        }
+
+        assert_mat_eq!(m * m.inverse().unwrap(), Matrix3F32::identity());
+
+        let m = Matrix3F32::new([[2.0, 0.0, 1.0], [1.0, 3.0, 2.0], [1.0, 1.0, 2.0]]);
+
+        assert_eq!(m.determinant(), 6.0);
+        assert_eq!(m.transpose().determinant(), 6.0);
+        assert_eq!(
+            Matrix3F32::new([[1.0, 2.0, 3.0], [2.0, 4.0, 6.0], [0.0, 1.0, 0.0]]).inverse(),
+            None
+        );
+        assert_eq!(Matrix4F32::identity().upper3x3(), Matrix3F32::identity());
+    }
+
+    #[test]
+    fn test_normal_matrix_uniform_scale() {
+        let normal = normal_matrix(&scale_rotate_z([2.0, 2.0, 2.0], 0.7)).unwrap();
+        // the inverse transpose of the rotation scaled by 2 is the rotation halved
+        let rotation = Matrix3F32::new([
+            [
+                normal[(0, 0)] * 2.0,
+                normal[(0, 1)] * 2.0,
+                normal[(0, 2)] * 2.0,
+            ],
+            [
+                normal[(1, 0)] * 2.0,
+                normal[(1, 1)] * 2.0,
+                normal[(1, 2)] * 2.0,
+            ],
+            [
+                normal[(2, 0)] * 2.0,
+                normal[(2, 1)] * 2.0,
+                normal[(2, 2)] * 2.0,
+            ],
+        ]);
+        let product = rotation * rotation.transpose();
+
+        assert!((rotation.determinant() - 1.0).abs() < 1e-5);
+        assert_mat_eq!(product, Matrix3F32::identity());
+    }
+
+    #[test]
+    fn test_normal_matrix_non_uniform_scale() {
+        let model = scale_rotate_z([2.0, 1.0, 1.0], 0.0);
+        let naive = model.upper3x3();
+        let normal = normal_matrix(&model).unwrap();
+        let x = Vector3F32::new(1.0, 0.0, 0.0);
+
+        // a normal along the scaled axis keeps its direction, but is shrunk
+        // instead of stretched
+        assert_eq!((naive * x).as_array(), [2.0, 0.0, 0.0]);
+        assert_eq!((normal * x).as_array(), [0.5, 0.0, 0.0]);
+
+        // the plane x = y is stretched into the plane x = 2y
+        let tangent = naive * Vector3F32::new(1.0, 1.0, 0.0);
+        let plane_normal = Vector3F32::new(1.0, -1.0, 0.0);
+
+        assert_eq!(tangent.as_array(), [2.0, 1.0, 0.0]);
+        assert_eq!((normal * plane_normal).dot(&tangent), 0.0);
+        assert_eq!((naive * plane_normal).dot(&tangent), 3.0);
+        assert_eq!(normal_matrix(&scale_rotate_z([1.0, 0.0, 1.0], 0.0)), None);
+    }
+
+    #[test]
+    fn test_normal_matrix_small_scale() {
+        let model = scale_rotate_z([0.001, 0.002, 0.004], 0.7);
+        let normal = normal_matrix(&model).unwrap();
+
+        assert!(model.upper3x3().determinant() < 1e-8);
+        assert_mat_eq!(
+            normal.transpose() * model.upper3x3(),
+            Matrix3F32::identity()
+        );
+        assert_eq!(
+            normal_matrix(&scale_rotate_z([0.001, 0.0, 0.001], 0.7)),
+            None
+        );
+        assert_eq!(
+            Matrix3F32::new([[1e-3, 2e-3, 3e-3], [2e-3, 4e-3, 6e-3], [0.0, 1e-3, 0.0]]).inverse(),
+            None
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_matrix4 {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::geometry::{
+        Matrix3F32, Matrix4F32, UVMapF32, Vector3F32, Vector3Int, Vector4F32, WAxis, XAxis, YAxis,
+        ZAxis,
+    };
+
+    fn assert_point(p: Vector3F32, expected: (f32, f32, f32)) {
+        assert!(
+            (p.get_x() - expected.0).abs() < 1e-5
+                && (p.get_y() - expected.1).abs() < 1e-5
+                && (p.get_z() - expected.2).abs() < 1e-5,
+            "{} != {:?}",
+            p,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_inverse() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut m = Matrix4F32::identity();
+
+        for i in 0..4 {
+            for j in 0..4 {
+                m[(i, j)] = rng.gen_range(-1.0, 1.0) + if i == j { 4.0 } else { 0.0 };
+            }
+        }
+
+        let identity = Matrix4F32::identity();
+
+        assert_mat_eq!(m * m.inverse().unwrap(), identity);
+        assert_eq!(identity.inverse(), Some(identity));
+
+        let mut small = identity;
+
+        for i in 0..3 {
+            small[(i, i)] = 0.001 * (i + 1) as f32;
+        }
+
+        assert_mat_eq!(small * small.inverse().unwrap(), identity);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut m = Matrix4F32::identity();
+
+        m[(0, 3)] = -2.5;
+        m[(3, 2)] = 0.25;
+
+        assert_eq!(
+            m.to_string(),
+            "[1, 0, 0, -2.5]\n[0, 1, 0, 0]\n[0, 0, 1, 0]\n[0, 0, 0.25, 1]"
+        );
+        assert_eq!(
+            Matrix3F32::new([[1.0, 2.0, 3.0], [4.0, 5.0, 6.0], [7.0, 8.0, 9.5]]).to_string(),
+            "[1, 2, 3]\n[4, 5, 6]\n[7, 8, 9.5]"
+        );
+        assert_eq!(Vector3Int::new(-1, 0, 7).to_string(), "(-1, 0, 7)");
+        assert_eq!(
+            Vector4F32::new(1.0, 2.0, 3.0, 0.5).to_string(),
+            "(1, 2, 3, 0.5)"
+        );
+        assert_eq!(
+            UVMapF32 {
+                u: 0.5,
+                v: 1.0,
+                w: 0.0
+            }
+            .to_string(),
+            "(0.5, 1, 0)"
+        );
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let mut m = Matrix4F32::identity();
+
+        m[(1, 2)] = 1e-3;
+
+        assert!(m.approx_eq(&Matrix4F32::identity(), 1e-3));
+        assert!(!m.approx_eq(&Matrix4F32::identity(), 1e-4));
+        assert!(!m.upper3x3().approx_eq(&Matrix3F32::identity(), 1e-4));
+        assert_mat_eq!(m, Matrix4F32::identity(), 1e-3);
+    }
+
+    #[test]
+    #[should_panic(expected = "within")]
+    fn test_assert_mat_eq_fails() {
+        let mut m = Matrix4F32::identity();
+
+        m[(2, 2)] = 1.1;
+
+        assert_mat_eq!(m, Matrix4F32::identity());
+    }
+
+    #[test]
+    fn test_singular_inverse() {
+        let mut m = Matrix4F32::identity();
+
+        m[(2, 2)] = 0.0;
+        assert_eq!(m.inverse(), None);
+
+        let repeated_row = Matrix4F32::new([
+            [1.0, 2.0, 3.0, 4.0],
+            [2.0, 4.0, 6.0, 8.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        assert_eq!(repeated_row.inverse(), None);
+
+        let mut m = Matrix4F32::identity();
+
+        m[(0, 0)] = f32::NAN;
+        assert_eq!(m.inverse(), None);
+    }
+
+    #[test]
+    fn test_transform_point() {
+        let p = Vector3F32::new(1.0, 2.0, 3.0);
+        let translation = Matrix4F32::new([
+            [1.0, 0.0, 0.0, 5.0],
+            [0.0, 1.0, 0.0, -1.0],
+            [0.0, 0.0, 1.0, 0.5],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let scale = Matrix4F32::new([
+            [2.0, 0.0, 0.0, 0.0],
+            [0.0, 3.0, 0.0, 0.0],
+            [0.0, 0.0, 4.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        // 90 degrees around Z
+        let rotation = Matrix4F32::new([
+            [0.0, -1.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+        let mut perspective = Matrix4F32::identity();
+
+        perspective[(3, 3)] = 2.0;
+
+        assert_point(translation.transform_point(p), (6.0, 1.0, 3.5));
+        assert_point(scale.transform_point(p), (2.0, 6.0, 12.0));
+        assert_point(rotation.transform_point(p), (-2.0, 1.0, 3.0));
+        assert_point((translation * scale).transform_point(p), (7.0, 5.0, 12.5));
+        assert_point(perspective.transform_point(p), (0.5, 1.0, 1.5));
+        assert_point(
+            translation.inverse().unwrap().transform_point(p),
+            (-4.0, 3.0, 2.5),
+        );
+        assert_eq!(rotation.transpose(), rotation.inverse().unwrap());
+
+        // Directions ignore the translation column
+        let direction = translation * p.to_homogeneous(0.0);
+
+        assert_eq!(
+            (
+                direction.get_x(),
+                direction.get_y(),
+                direction.get_z(),
+                direction.get_w()
+            ),
+            (1.0, 2.0, 3.0, 0.0)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test_serde {
+    use crate::geometry::{Matrix4F32, Vector2Int, Vector3F32, Vector4F32};
+    use crate::point::Point;
+    use crate::quaternion::Quaternion;
+
+    #[test]
+    fn test_vector_round_trip() {
+        let v = Vector3F32::new(0.5, -1.25, 3e-3);
+        let json = serde_json::to_string(&v).unwrap();
+
+        assert_eq!(json, r#"{"x":0.5,"y":-1.25,"z":0.003}"#);
+        assert_eq!(
+            serde_json::from_str::<Vector3F32>(&json)
+                .unwrap()
+                .as_array(),
+            v.as_array()
+        );
+
+        let v = Vector2Int::new(-7, 2);
+        let json = serde_json::to_string(&v).unwrap();
+
+        assert_eq!(json, r#"{"x":-7,"y":2}"#);
+        assert_eq!(
+            serde_json::from_str::<Vector2Int>(&json)
+                .unwrap()
+                .as_array(),
+            [-7, 2]
+        );
+
+        let v: Vector4F32 = serde_json::from_str(r#"{"x":1,"y":2,"z":3,"w":0.5}"#).unwrap();
+
+        assert_eq!(v.to_string(), "(1, 2, 3, 0.5)");
+
+        let p: Point = serde_json::from_str(r#"{"x":3,"y":-4}"#).unwrap();
+
+        assert_eq!((p.x, p.y), (3, -4));
+    }
+
+    #[test]
+    fn test_matrix_and_quaternion_round_trip() {
+        let mut m = Matrix4F32::identity();
+
+        m[(0, 3)] = 2.5;
+        m[(2, 1)] = -1.0;
+
+        let json = serde_json::to_string(&m).unwrap();
+
+        assert_eq!(
+            json,
+            "[1.0,0.0,0.0,2.5,0.0,1.0,0.0,0.0,0.0,-1.0,1.0,0.0,0.0,0.0,0.0,1.0]"
+        );
+        assert_eq!(serde_json::from_str::<Matrix4F32>(&json).unwrap(), m);
+        assert!(serde_json::from_str::<Matrix4F32>("[1.0,0.0]").is_err());
+
+        let q = Quaternion::new(0.5, -0.5, 0.5, 0.5);
+        let json = serde_json::to_string(&q).unwrap();
+
+        assert_eq!(json, r#"{"w":0.5,"x":-0.5,"y":0.5,"z":0.5}"#);
+        assert_eq!(serde_json::from_str::<Quaternion>(&json).unwrap(), q);
     }
 }
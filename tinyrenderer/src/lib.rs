@@ -1,74 +1,344 @@
-use std::ops::Neg;
+use std::io;
+use std::ops::{Add, AddAssign, Mul, Neg, RangeInclusive};
+use std::time::{Duration, Instant};
 
-use num::{One, Signed, Zero};
+use num::Signed;
+use num_traits::AsPrimitive;
 
-use tgaimage::{TGAColor, TGAImage};
+use tgaimage::colors::WHITE;
+use tgaimage::{Origin, TGAColor, TGAError, TGAImage, TGAImageFormat, TextureFilter};
 
+use crate::camera::Projection;
 use crate::geometry::{
-    NumMinMax, Vector2, Vector2Int, Vector3Int, VectorTrait, XAxis, XYAxis, YAxis, ZAxis,
+    is_back_facing, normal_matrix, Matrix3F32, Matrix4F32, NumMinMax, Vector2, Vector2F32,
+    Vector2Int, Vector3F32, Vector3Int, Vector4F32, VectorTrait, WAxis, XAxis, XYAxis, YAxis,
+    ZAxis,
 };
+use crate::lighting::{Light, LightingParams};
 use crate::line::Line;
 use crate::model::Model;
 use crate::point::Point;
+use crate::quaternion::Quaternion;
+use crate::raster_util::sort_by_y;
+use crate::zbuffer::ZBuffer;
 
+pub mod aabb;
+pub mod camera;
+pub mod framebuffer;
 pub mod geometry;
+pub mod lighting;
 pub mod line;
 pub mod model;
+pub mod mtl;
 pub mod point;
+pub mod quaternion;
+pub mod raster_util;
+pub mod zbuffer;
 
 pub struct TriangleDef(pub Vector3Int, pub Vector3Int, pub Vector3Int);
-pub struct TextureDef(pub Vector2Int, pub Vector2Int, pub Vector2Int);
+/// Triangle vertices in homogeneous screen coordinates, i.e. with the viewport and
+/// projection applied but not yet divided by `w`
+pub struct TriangleDefF32(pub Vector4F32, pub Vector4F32, pub Vector4F32);
+/// Normalized texture coordinates of triangle vertices
+pub struct TextureDef(pub Vector2F32, pub Vector2F32, pub Vector2F32);
 
+/// Source of the surface color for shaded triangles
+pub enum Diffuse<'a> {
+    /// Single color for the whole triangle
+    Color(TGAColor),
+    /// Diffuse texture of a model sampled at the interpolated texture coordinates
+    Texture {
+        model: &'a Model,
+        texture_def: TextureDef,
+        filter: TextureFilter,
+    },
+}
+
+/// Triangles skipped before rasterization depending on their screen winding, see
+/// [`geometry::is_back_facing`]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CullMode {
+    #[default]
+    None,
+    /// Skip clockwise triangles facing away from the viewer
+    Back,
+    /// Skip counter-clockwise triangles facing the viewer
+    Front,
+}
+
+/// Normals used to light every pixel in [`render_model`]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalMapping {
+    /// Light whole faces with their normal
+    #[default]
+    None,
+    /// Normal map of the model with normals in the model coordinates
+    Model,
+    /// Normal map of the model with normals relative to the surface, see
+    /// [`Model::compute_tangents`]
+    Tangent,
+}
+
+impl CullMode {
+    /// Whether a triangle in screen coordinates should be skipped
+    pub fn culls(&self, screen_tri: &[Vector3F32; 3]) -> bool {
+        match self {
+            CullMode::None => false,
+            CullMode::Back => is_back_facing(screen_tri),
+            CullMode::Front => !is_back_facing(screen_tri),
+        }
+    }
+}
+
+/// Counters collected while rendering
+///
+/// Returned by [`render_model`] and accumulated by the z-buffered triangle
+/// functions given a `stats` to update.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct RenderStats {
+    /// Triangles submitted for rendering
+    pub submitted: usize,
+    /// Triangles passed to the rasterizer
+    pub drawn: usize,
+    /// Triangles skipped by the cull mode
+    pub culled: usize,
+    /// Triangles skipped because their bounding box has no pixels in the image
+    pub empty: usize,
+    /// Bounding box pixels tested against the triangles
+    pub pixels_tested: u64,
+    /// Tested pixels inside of the triangles
+    pub pixels_inside: u64,
+    /// Pixels inside of the triangles that passed the depth test and were drawn
+    pub pixels_drawn: u64,
+    /// Time spent transforming, culling and lighting the faces
+    pub setup_time: Duration,
+    /// Time spent rasterizing the triangles
+    pub raster_time: Duration,
+}
+
+impl AddAssign for RenderStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.submitted += rhs.submitted;
+        self.drawn += rhs.drawn;
+        self.culled += rhs.culled;
+        self.empty += rhs.empty;
+        self.pixels_tested += rhs.pixels_tested;
+        self.pixels_inside += rhs.pixels_inside;
+        self.pixels_drawn += rhs.pixels_drawn;
+        self.setup_time += rhs.setup_time;
+        self.raster_time += rhs.raster_time;
+    }
+}
+
+/// Barycentric coordinates of a point with one weight per triangle vertex
+///
+/// `lambdaN` belongs to the `N`-th vertex passed to [`barycentric`], and the
+/// weights sum up to one.
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct PointBarycentricCoords {
-    pub u: f32,
-    pub v: f32,
-    pub w: f32,
+    pub lambda0: f32,
+    pub lambda1: f32,
+    pub lambda2: f32,
+}
+
+impl PointBarycentricCoords {
+    /// Blend per-vertex values with the vertex weights
+    pub fn interpolate<T: Mul<f32, Output = T> + Add<Output = T>>(&self, values: [T; 3]) -> T {
+        let [a, b, c] = values;
+
+        a * self.lambda0 + b * self.lambda1 + c * self.lambda2
+    }
+}
+
+pub fn line(x0: i32, y0: i32, x1: i32, y1: i32, color: &TGAColor, image: &mut TGAImage) {
+    let (start, end) = (Point::new(x0, y0), Point::new(x1, y1));
+    let delta = (end - start).abs();
+    // walk along the growing major axis, so swapped endpoints draw the same pixels
+    let reversed = if delta.x < delta.y { y0 > y1 } else { x0 > x1 };
+    let line = if reversed {
+        Line::new(end, start)
+    } else {
+        Line::new(start, end)
+    };
+    let visible_steps =
+        match visible_steps(line.start, line.end, image.get_width(), image.get_height()) {
+            Some(visible_steps) => visible_steps,
+            None => return,
+        };
+    // skipping the invisible points does not walk them
+    let points = line
+        .points()
+        .skip(*visible_steps.start() as usize)
+        .take((visible_steps.end() - visible_steps.start() + 1) as usize);
+
+    for p in points {
+        image.set(p.x as u32, p.y as u32, color);
+    }
+}
+
+/// Line of `width` pixels with round caps
+///
+/// A disc of the line width is stamped at every point of the one pixel line, so
+/// width 1 draws the plain line and width 0 draws nothing.
+pub fn line_thick(p0: Point, p1: Point, width: u32, color: &TGAColor, image: &mut TGAImage) {
+    stroke(Line::new(p0, p1).points(), width, color, image);
+}
+
+/// Connected line segments of `width` pixels with round joins
+///
+/// A single point is drawn as a disc.
+pub fn polyline(points: &[Point], width: u32, color: &TGAColor, image: &mut TGAImage) {
+    match points {
+        [] => {}
+        [point] => stroke(std::iter::once(*point), width, color, image),
+        _ => stroke(
+            points
+                .windows(2)
+                .flat_map(|segment| Line::new(segment[0], segment[1]).points()),
+            width,
+            color,
+            image,
+        ),
+    }
 }
 
-pub fn line(
-    mut x0: i32,
-    mut y0: i32,
-    mut x1: i32,
-    mut y1: i32,
+fn stroke<I: Iterator<Item = Point>>(
+    points: I,
+    width: u32,
     color: &TGAColor,
     image: &mut TGAImage,
 ) {
-    let steep = if (x0 - x1).abs() < (y0 - y1).abs() {
-        std::mem::swap(&mut x0, &mut y0);
-        std::mem::swap(&mut x1, &mut y1);
-        true
-    } else {
-        false
-    };
+    // pixel centers of the disc are measured from the disc center, which is
+    // between pixels for even widths
+    let center = (width as f32 - 1.0) / 2.0;
+    let radius = width as f32 / 2.0;
+    let disc = (0..width as i32)
+        .flat_map(|dy| (0..width as i32).map(move |dx| (dx, dy)))
+        .filter(|&(dx, dy)| {
+            (dx as f32 - center).powi(2) + (dy as f32 - center).powi(2) <= radius * radius
+        })
+        .map(|(dx, dy)| Point::new(dx - width as i32 / 2, dy - width as i32 / 2))
+        .collect::<Vec<_>>();
 
-    if x0 > x1 {
-        std::mem::swap(&mut x0, &mut x1);
-        std::mem::swap(&mut y0, &mut y1);
+    for point in points {
+        for offset in &disc {
+            let p = point + *offset;
+
+            image.set(p.x as u32, p.y as u32, color);
+        }
     }
+}
 
-    let dx = x1 - x0;
-    let dy = y1 - y0;
-    let derror2 = dy.abs() * 2;
-    let mut error2 = 0;
-    let mut y = y0;
-    let y_step = if dy > 0 { 1 } else { -1 };
+/// Clip a line to a `width` x `height` image with the Cohen-Sutherland algorithm
+///
+/// Returns `None` for lines outside of the image. Endpoints moved onto the image
+/// border are rounded to the closest pixel, so the returned line may deviate
+/// from the original one by half a pixel.
+pub fn clip_line(p0: Point, p1: Point, width: u32, height: u32) -> Option<(Point, Point)> {
+    const INSIDE: u8 = 0;
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 2;
+    const BOTTOM: u8 = 4;
+    const TOP: u8 = 8;
 
-    for x in x0..=x1 {
-        if steep {
-            image.set(y as u32, x as u32, color);
-        } else {
-            image.set(x as u32, y as u32, color);
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let (x_max, y_max) = (width as i64 - 1, height as i64 - 1);
+    let outcode = |(x, y): (i64, i64)| {
+        let mut code = INSIDE;
+
+        if x < 0 {
+            code |= LEFT;
+        } else if x > x_max {
+            code |= RIGHT;
+        }
+
+        if y < 0 {
+            code |= BOTTOM;
+        } else if y > y_max {
+            code |= TOP;
         }
 
-        error2 += derror2;
+        code
+    };
+    let mut points = [(p0.x as i64, p0.y as i64), (p1.x as i64, p1.y as i64)];
+    let (start, end) = (points[0], points[1]);
+    // intersection with a border is computed on the original line to avoid
+    // accumulating the rounding errors
+    let at_x = |x: i64| {
+        let t = (x - start.0) as f64 / (end.0 - start.0) as f64;
+
+        (
+            x,
+            (start.1 as f64 + t * (end.1 - start.1) as f64).round() as i64,
+        )
+    };
+    let at_y = |y: i64| {
+        let t = (y - start.1) as f64 / (end.1 - start.1) as f64;
+
+        (
+            (start.0 as f64 + t * (end.0 - start.0) as f64).round() as i64,
+            y,
+        )
+    };
+    let mut codes = [outcode(points[0]), outcode(points[1])];
+
+    loop {
+        if codes[0] | codes[1] == INSIDE {
+            let [(x0, y0), (x1, y1)] = points;
+
+            return Some((
+                Point::new(x0 as i32, y0 as i32),
+                Point::new(x1 as i32, y1 as i32),
+            ));
+        }
 
-        if error2 > dx {
-            y += y_step;
-            error2 -= dx * 2;
+        if codes[0] & codes[1] != INSIDE {
+            return None;
         }
+
+        let i = if codes[0] != INSIDE { 0 } else { 1 };
+        let code = codes[i];
+
+        points[i] = if code & TOP != INSIDE {
+            at_y(y_max)
+        } else if code & BOTTOM != INSIDE {
+            at_y(0)
+        } else if code & RIGHT != INSIDE {
+            at_x(x_max)
+        } else {
+            at_x(0)
+        };
+        codes[i] = outcode(points[i]);
     }
 }
 
+/// Steps along the major axis of the line from `start` to `end` that may be
+/// inside of the image, `None` when the whole line is outside
+fn visible_steps(start: Point, end: Point, width: u32, height: u32) -> Option<RangeInclusive<i64>> {
+    // pixels next to the image border may be rounded onto it, so the line is
+    // clipped to the image extended by a pixel on every side
+    let margin = Point::new(1, 1);
+    let (clip_start, clip_end) = clip_line(start + margin, end + margin, width + 2, height + 2)?;
+    let (clip_start, clip_end) = (clip_start - margin, clip_end - margin);
+    let length = (end - start).abs();
+    let step = |p: Point| {
+        if length.x < length.y {
+            (p.y - start.y).abs() as i64
+        } else {
+            (p.x - start.x).abs() as i64
+        }
+    };
+    let (a, b) = (step(clip_start), step(clip_end));
+
+    // clipped endpoints are rounded, so keep a step of margin on both sides
+    Some((a.min(b) - 1).max(0)..=(a.max(b) + 1).min(length.x.max(length.y) as i64))
+}
+
 pub fn barycentric<T: VectorTrait<T> + Signed + Neg, U: XYAxis<T>, V: XYAxis<T>>(
     triangle_points: &[U; 3],
     point: V,
@@ -97,12 +367,16 @@ pub fn barycentric<T: VectorTrait<T> + Signed + Neg, U: XYAxis<T>, V: XYAxis<T>>
         adj_coeffs.1 .0 * point_vec_x + adj_coeffs.1 .1 * point_vec_y,
     );
     let det = det.to_f32().unwrap();
-    let u = result.0.to_f32().unwrap() / det;
-    let v = result.1.to_f32().unwrap() / det;
-    let w = 1.0 - u - v;
+    // the solution is the weight of the second and the third vertex
+    let lambda1 = result.0.to_f32().unwrap() / det;
+    let lambda2 = result.1.to_f32().unwrap() / det;
 
-    if u >= 0.0 && v >= 0.0 && u + v <= 1.0 {
-        Some(PointBarycentricCoords { u, v, w })
+    if lambda1 >= 0.0 && lambda2 >= 0.0 && lambda1 + lambda2 <= 1.0 {
+        Some(PointBarycentricCoords {
+            lambda0: 1.0 - lambda1 - lambda2,
+            lambda1,
+            lambda2,
+        })
     } else {
         None
     }
@@ -110,26 +384,36 @@ pub fn barycentric<T: VectorTrait<T> + Signed + Neg, U: XYAxis<T>, V: XYAxis<T>>
 
 fn boundary_box_setup<T>(points: &[Vector2<T>; 3], width: T, height: T) -> (Vector2<T>, Vector2<T>)
 where
-    T: VectorTrait<T> + NumMinMax<Output = T> + Ord + Zero + One,
+    T: VectorTrait<T> + NumMinMax<Output = T>,
 {
-    let mut boundary_box_min = Vector2::new(T::max_value(), T::max_value());
-    let mut boundary_box_max = Vector2::new(T::min_value(), T::min_value());
-    let clamp = Vector2::<T>::new((width - T::one()).as_(), (height - T::one()).as_());
+    let [a, b, c] = *points;
 
-    for point in points {
-        *boundary_box_min.x_as_mut_ref() =
-            T::zero().max(boundary_box_min.get_x().min(point.get_x()));
-        *boundary_box_min.y_as_mut_ref() =
-            T::zero().max(boundary_box_min.get_y().min(point.get_y()));
-        *boundary_box_max.x_as_mut_ref() = clamp
-            .get_x()
-            .min(boundary_box_max.get_x().max(point.get_x()));
-        *boundary_box_max.y_as_mut_ref() = clamp
-            .get_y()
-            .min(boundary_box_max.get_y().max(point.get_y()));
-    }
+    (
+        a.min(b).min(c).max(Vector2::new(T::zero(), T::zero())),
+        a.max(b)
+            .max(c)
+            .min(Vector2::new(width - T::one(), height - T::one())),
+    )
+}
+
+/// Pixel bounding box of a triangle with floating-point vertices clamped to the image
+///
+/// The box is empty, with the minimum greater than the maximum, for triangles
+/// outside of the image.
+fn boundary_box_setup_f32(
+    points: &[Vector2F32; 3],
+    width: i32,
+    height: i32,
+) -> (Vector2Int, Vector2Int) {
+    let [a, b, c] = *points;
 
-    (boundary_box_min, boundary_box_max)
+    (
+        a.min(b).min(c).to_int_floor().max(Vector2Int::new(0, 0)),
+        a.max(b)
+            .max(c)
+            .to_int_ceil()
+            .min(Vector2Int::new(width - 1, height - 1)),
+    )
 }
 
 /// Fill triangle with calculating barycentric coordinates
@@ -153,9 +437,97 @@ pub fn triangle_barycentric(
 
     for x in boundary_box_min.get_x()..=boundary_box_max.get_x() {
         for y in boundary_box_min.get_y()..=boundary_box_max.get_y() {
-            if let Some(_) = barycentric(points, Vector2::new(x, y)) {
-                image.set(x as u32, y as u32, color);
+            if barycentric(points, Vector2::new(x, y)).is_some() {
+                image.set_unchecked(x as u32, y as u32, color);
+            }
+        }
+    }
+}
+
+/// Fill a simple polygon with scanlines
+///
+/// Pixels on the polygon border are filled the same way as in
+/// [`triangle_barycentric`], and concave polygons are filled with the even-odd
+/// rule. Polygons with less than three points or without area are not drawn.
+pub fn polygon_filled(points: &[Vector2Int], color: &TGAColor, image: &mut TGAImage) {
+    let doubled_area: i64 = (0..points.len())
+        .map(|i| {
+            let (p, q) = (points[i], points[(i + 1) % points.len()]);
+
+            p.get_x() as i64 * q.get_y() as i64 - q.get_x() as i64 * p.get_y() as i64
+        })
+        .sum();
+
+    if points.len() < 3 || doubled_area == 0 {
+        return;
+    }
+
+    // edges are sorted from the bottom and cover rows `start.y..end.y`, so
+    // vertices shared by two edges are crossed once
+    let mut edges = (0..points.len())
+        .map(|i| (points[i], points[(i + 1) % points.len()]))
+        .filter(|(p, q)| p.get_y() != q.get_y())
+        .map(|(p, q)| {
+            if p.get_y() < q.get_y() {
+                (p, q)
+            } else {
+                (q, p)
             }
+        })
+        .collect::<Vec<_>>();
+    let horizontal = (0..points.len())
+        .map(|i| (points[i], points[(i + 1) % points.len()]))
+        .filter(|(p, q)| p.get_y() == q.get_y())
+        .collect::<Vec<_>>();
+
+    edges.sort_by_key(|(start, _)| start.get_y());
+
+    let (width, height) = (image.get_width() as i32, image.get_height() as i32);
+    let y_min = points.iter().map(|p| p.get_y()).min().unwrap().max(0);
+    let y_max = points
+        .iter()
+        .map(|p| p.get_y())
+        .max()
+        .unwrap()
+        .min(height - 1);
+    let mut fill_span = |x0: i32, x1: i32, y: i32| {
+        for x in x0.max(0)..=x1.min(width - 1) {
+            image.set_unchecked(x as u32, y as u32, color);
+        }
+    };
+    let mut active: Vec<(Vector2Int, Vector2Int)> = Vec::new();
+    let mut next_edge = 0;
+
+    for y in y_min..=y_max {
+        while next_edge < edges.len() && edges[next_edge].0.get_y() <= y {
+            active.push(edges[next_edge]);
+            next_edge += 1;
+        }
+
+        active.retain(|(_, end)| end.get_y() > y);
+
+        let mut crossings = active
+            .iter()
+            .map(|(start, end)| {
+                start.get_x() as f64
+                    + (y - start.get_y()) as f64 * (end.get_x() - start.get_x()) as f64
+                        / (end.get_y() - start.get_y()) as f64
+            })
+            .collect::<Vec<_>>();
+
+        crossings.sort_by(f64::total_cmp);
+
+        for span in crossings.chunks_exact(2) {
+            fill_span(span[0].ceil() as i32, span[1].floor() as i32, y);
+        }
+
+        // top vertices and horizontal edges are on the border, but not crossed
+        for p in points.iter().filter(|p| p.get_y() == y) {
+            fill_span(p.get_x(), p.get_x(), y);
+        }
+
+        for (p, q) in horizontal.iter().filter(|(p, _)| p.get_y() == y) {
+            fill_span(p.get_x().min(q.get_x()), p.get_x().max(q.get_x()), y);
         }
     }
 }
@@ -164,46 +536,321 @@ pub fn triangle_barycentric_zbuf(
     v1: Vector3Int,
     v2: Vector3Int,
     v3: Vector3Int,
-    zbuf: &mut [f32],
+    zbuf: &mut ZBuffer,
     color: &TGAColor,
     image: &mut TGAImage,
+    stats: Option<&mut RenderStats>,
 ) {
-    let points_2d = &[
-        Vector2::new(v1.get_x(), v1.get_y()),
-        Vector2::new(v2.get_x(), v2.get_y()),
-        Vector2::new(v3.get_x(), v3.get_y()),
-    ];
-    let points = [v1, v2, v3];
-    let (boundary_box_min, boundary_box_max) = boundary_box_setup(
-        points_2d,
+    assert_zbuffer_matches(zbuf, image);
+    fill_triangle_with_stats(&[v1, v2, v3], &mut FrameMut { image, zbuf }, stats, |_| {
+        *color
+    });
+}
+
+/// Pixels and depth written by the rasterizers
+trait RasterTarget {
+    /// Size of the whole image
+    fn size(&self) -> (i32, i32);
+    /// First and last image row the target covers
+    fn rows(&self) -> (i32, i32);
+    fn test_and_set(&mut self, x: u32, y: u32, z: f32) -> bool;
+    fn set(&mut self, x: u32, y: u32, color: &TGAColor);
+}
+
+/// Whole image with its z-buffer
+struct FrameMut<'a> {
+    image: &'a mut TGAImage,
+    zbuf: &'a mut ZBuffer,
+}
+
+/// Band of image rows with the matching z-buffer rows
+#[cfg(feature = "parallel")]
+struct TileMut<'a> {
+    image: tgaimage::RowBandMut<'a>,
+    zbuf: crate::zbuffer::ZBufferBandMut<'a>,
+    size: (i32, i32),
+}
+
+#[cfg(feature = "parallel")]
+impl RasterTarget for TileMut<'_> {
+    fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    fn rows(&self) -> (i32, i32) {
+        let first = self.image.y_offset() as i32;
+
+        (first, first + self.image.height() as i32 - 1)
+    }
+
+    fn test_and_set(&mut self, x: u32, y: u32, z: f32) -> bool {
+        self.zbuf.test_and_set(x, y - self.zbuf.y_offset(), z)
+    }
+
+    fn set(&mut self, x: u32, y: u32, color: &TGAColor) {
+        self.image.set(x, y - self.image.y_offset(), color);
+    }
+}
+
+impl RasterTarget for FrameMut<'_> {
+    fn size(&self) -> (i32, i32) {
+        (
+            self.image.get_width() as i32,
+            self.image.get_height() as i32,
+        )
+    }
+
+    fn rows(&self) -> (i32, i32) {
+        (0, self.image.get_height() as i32 - 1)
+    }
+
+    fn test_and_set(&mut self, x: u32, y: u32, z: f32) -> bool {
+        self.zbuf.test_and_set(x, y, z)
+    }
+
+    fn set(&mut self, x: u32, y: u32, color: &TGAColor) {
+        self.image.set_unchecked(x, y, color);
+    }
+}
+
+/// Z-buffer without a color image
+struct DepthMut<'a> {
+    zbuf: &'a mut ZBuffer,
+}
+
+impl RasterTarget for DepthMut<'_> {
+    fn size(&self) -> (i32, i32) {
+        (self.zbuf.width() as i32, self.zbuf.height() as i32)
+    }
+
+    fn rows(&self) -> (i32, i32) {
+        (0, self.zbuf.height() as i32 - 1)
+    }
+
+    fn test_and_set(&mut self, x: u32, y: u32, z: f32) -> bool {
+        self.zbuf.test_and_set(x, y, z)
+    }
+
+    fn set(&mut self, _x: u32, _y: u32, _color: &TGAColor) {}
+}
+
+/// Fill pixels inside of a triangle that pass the depth test
+///
+/// `shade` is called only for the pixels that are drawn. Only the pixel counters
+/// of the returned stats are set and only when `COUNT` is set, otherwise the
+/// counting is compiled out and the stats are zero.
+fn fill_triangle<
+    const COUNT: bool,
+    T: RasterTarget,
+    F: FnMut(&PointBarycentricCoords) -> TGAColor,
+>(
+    points: &[Vector3Int; 3],
+    target: &mut T,
+    mut shade: F,
+) -> RenderStats {
+    let points_2d = &points.map(|p| Vector2::new(p.get_x(), p.get_y()));
+    let (width, height) = target.size();
+    let (first_row, last_row) = target.rows();
+    let (boundary_box_min, boundary_box_max) = boundary_box_setup(points_2d, width, height);
+    let (mut tested, mut inside, mut drawn) = (0, 0, 0);
+
+    for x in boundary_box_min.get_x()..=boundary_box_max.get_x() {
+        for y in boundary_box_min.get_y().max(first_row)..=boundary_box_max.get_y().min(last_row) {
+            if COUNT {
+                tested += 1;
+            }
+
+            if let Some(bc_screen) = barycentric(points, Vector2Int::new(x, y)) {
+                let z = bc_screen.interpolate(points.map(|p| p.get_z() as f32));
+
+                if COUNT {
+                    inside += 1;
+                }
+
+                if target.test_and_set(x as u32, y as u32, z) {
+                    if COUNT {
+                        drawn += 1;
+                    }
+
+                    target.set(x as u32, y as u32, &shade(&bc_screen));
+                }
+            }
+        }
+    }
+
+    RenderStats {
+        pixels_tested: tested,
+        pixels_inside: inside,
+        pixels_drawn: drawn,
+        ..RenderStats::default()
+    }
+}
+
+/// Same as [`fill_triangle`] but counts the triangle and its pixels into `stats`
+///
+/// Nothing is timed or counted without `stats`.
+fn fill_triangle_with_stats<T: RasterTarget, F: FnMut(&PointBarycentricCoords) -> TGAColor>(
+    points: &[Vector3Int; 3],
+    target: &mut T,
+    stats: Option<&mut RenderStats>,
+    shade: F,
+) {
+    let stats = match stats {
+        Some(stats) => stats,
+        None => {
+            fill_triangle::<false, _, _>(points, target, shade);
+            return;
+        }
+    };
+    let start = Instant::now();
+    let pixels = fill_triangle::<true, _, _>(points, target, shade);
+
+    stats.submitted += 1;
+
+    if pixels.pixels_tested == 0 {
+        stats.empty += 1;
+    } else {
+        stats.drawn += 1;
+    }
+
+    *stats += pixels;
+    stats.raster_time += start.elapsed();
+}
+
+/// Z-buffered triangle with vertex colors blended by the barycentric weights
+pub fn triangle_interp_color(
+    v: [Vector3Int; 3],
+    colors: [TGAColor; 3],
+    zbuf: &mut ZBuffer,
+    image: &mut TGAImage,
+    stats: Option<&mut RenderStats>,
+) {
+    assert_zbuffer_matches(zbuf, image);
+    fill_triangle_with_stats(&v, &mut FrameMut { image, zbuf }, stats, |bc_screen| {
+        TGAColor::weighted_sum(&[
+            (colors[0], bc_screen.lambda0),
+            (colors[1], bc_screen.lambda1),
+            (colors[2], bc_screen.lambda2),
+        ])
+    });
+}
+
+/// Z-buffered triangle with floating-point screen coordinates
+///
+/// Vertices are not snapped to the pixel grid, so the covered area changes
+/// gradually with sub-pixel movements. A pixel is filled when its center is
+/// inside of the triangle.
+pub fn triangle_barycentric_f32(
+    v: [Vector3F32; 3],
+    zbuf: &mut ZBuffer,
+    color: &TGAColor,
+    image: &mut TGAImage,
+) {
+    let points_2d = v.map(|p| Vector2F32::new(p.get_x(), p.get_y()));
+    assert_zbuffer_matches(zbuf, image);
+    let (boundary_box_min, boundary_box_max) = boundary_box_setup_f32(
+        &points_2d,
         image.get_width() as i32,
         image.get_height() as i32,
     );
-    let mut z = 0.0;
 
     for x in boundary_box_min.get_x()..=boundary_box_max.get_x() {
         for y in boundary_box_min.get_y()..=boundary_box_max.get_y() {
-            if let Some(bc_screen) = barycentric(&[v1, v2, v3], Vector3Int::new(x, y, z as i32)) {
-                z = (points[0].get_z() as f32 * bc_screen.w
-                    + points[1].get_z() as f32 * bc_screen.u
-                    + points[2].get_z() as f32 * bc_screen.v) as f32;
+            let center = Vector2F32::new(x as f32 + 0.5, y as f32 + 0.5);
 
-                if zbuf[(x as u32 + y as u32 * image.get_width() as u32) as usize] < z {
-                    zbuf[(x as u32 + y as u32 * image.get_width() as u32) as usize] = z;
-                    image.set(x as u32, y as u32, color);
+            if let Some(bc_screen) = barycentric(&points_2d, center) {
+                let z = bc_screen.interpolate(v.map(|p| p.get_z()));
+
+                if zbuf.test_and_set(x as u32, y as u32, z) {
+                    image.set_unchecked(x as u32, y as u32, color);
                 }
             }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn triangle_barycentric_zbuf_with_texture(
     triangle_def: TriangleDef,
     texture_def: TextureDef,
-    zbuf: &mut [f32],
+    zbuf: &mut ZBuffer,
+    image: &mut TGAImage,
+    model: &Model,
+    filter: TextureFilter,
+    intensity: f32,
+    stats: Option<&mut RenderStats>,
+) {
+    assert_zbuffer_matches(zbuf, image);
+    fill_textured_triangle(
+        triangle_def,
+        texture_def,
+        &mut FrameMut { image, zbuf },
+        model,
+        filter,
+        stats,
+        |color| color * intensity,
+    );
+}
+
+/// Same as [`triangle_barycentric_zbuf_with_texture`] but scales texture colors in
+/// linear space, so lighting of sRGB encoded textures does not look too dark
+#[allow(clippy::too_many_arguments)]
+pub fn triangle_barycentric_zbuf_with_texture_srgb(
+    triangle_def: TriangleDef,
+    texture_def: TextureDef,
+    zbuf: &mut ZBuffer,
     image: &mut TGAImage,
     model: &Model,
+    filter: TextureFilter,
     intensity: f32,
+    stats: Option<&mut RenderStats>,
+) {
+    assert_zbuffer_matches(zbuf, image);
+    fill_textured_triangle(
+        triangle_def,
+        texture_def,
+        &mut FrameMut { image, zbuf },
+        model,
+        filter,
+        stats,
+        |color| color.scale_srgb(intensity),
+    );
+}
+
+fn fill_textured_triangle<T: RasterTarget, F: Fn(TGAColor) -> TGAColor>(
+    triangle_def: TriangleDef,
+    texture_def: TextureDef,
+    target: &mut T,
+    model: &Model,
+    filter: TextureFilter,
+    stats: Option<&mut RenderStats>,
+    shade: F,
+) {
+    let points = [triangle_def.0, triangle_def.1, triangle_def.2];
+    let points_2d = &points.map(|p| Vector2::new(p.get_x(), p.get_y()));
+    let lod = model
+        .diffuse_size()
+        .map_or(0.0, |size| texture_lod(points_2d, &texture_def, size));
+
+    fill_triangle_with_stats(&points, target, stats, |bc_screen| {
+        let uv_p = bc_screen.interpolate([texture_def.0, texture_def.1, texture_def.2]);
+        let color = model.diffuse_lod(uv_p.get_x(), uv_p.get_y(), lod, filter);
+
+        shade(color.unwrap())
+    });
+}
+
+/// Gouraud shaded triangle
+///
+/// Light `intensities` of the vertices are interpolated with the barycentric
+/// coordinates and scale the diffuse color of every pixel. Negative intensities
+/// of unlit areas result in black pixels.
+pub fn triangle_gouraud(
+    triangle_def: TriangleDef,
+    intensities: [f32; 3],
+    zbuf: &mut ZBuffer,
+    diffuse: Diffuse,
+    image: &mut TGAImage,
 ) {
     let points_2d = &[
         Vector2::new(triangle_def.0.get_x(), triangle_def.0.get_y()),
@@ -211,122 +858,1428 @@ pub fn triangle_barycentric_zbuf_with_texture(
         Vector2::new(triangle_def.2.get_x(), triangle_def.2.get_y()),
     ];
     let points = [triangle_def.0, triangle_def.1, triangle_def.2];
+    assert_zbuffer_matches(zbuf, image);
     let (boundary_box_min, boundary_box_max) = boundary_box_setup(
         points_2d,
         image.get_width() as i32,
         image.get_height() as i32,
     );
+    let lod = match diffuse {
+        Diffuse::Texture {
+            model,
+            ref texture_def,
+            ..
+        } => model
+            .diffuse_size()
+            .map_or(0.0, |size| texture_lod(points_2d, texture_def, size)),
+        Diffuse::Color(_) => 0.0,
+    };
 
     for x in boundary_box_min.get_x()..=boundary_box_max.get_x() {
         for y in boundary_box_min.get_y()..=boundary_box_max.get_y() {
             if let Some(bc_screen) = barycentric(&points, Vector2Int::new(x, y)) {
-                let z = (points[0].get_z() as f32 * bc_screen.w
-                    + points[1].get_z() as f32 * bc_screen.u
-                    + points[2].get_z() as f32 * bc_screen.v) as f32;
-
-                let index = (x + y * image.get_width() as i32) as usize;
-                if zbuf[index] < z {
-                    zbuf[index] = z;
-                    let uv_p = texture_def.0 * bc_screen.w
-                        + texture_def.1 * bc_screen.u
-                        + texture_def.2 * bc_screen.v;
-                    let color = model.diffuse(uv_p);
-                    image.set(x as u32, y as u32, &(color.unwrap() * intensity));
+                let z = bc_screen.interpolate(points.map(|p| p.get_z() as f32));
+
+                if zbuf.test_and_set(x as u32, y as u32, z) {
+                    let intensity = bc_screen.interpolate(intensities);
+                    let color = match diffuse {
+                        Diffuse::Color(color) => color,
+                        Diffuse::Texture {
+                            model,
+                            ref texture_def,
+                            filter,
+                        } => {
+                            let uv_p = bc_screen.interpolate([
+                                texture_def.0,
+                                texture_def.1,
+                                texture_def.2,
+                            ]);
+
+                            model
+                                .diffuse_lod(uv_p.get_x(), uv_p.get_y(), lod, filter)
+                                .unwrap()
+                        }
+                    };
+
+                    image.set_unchecked(x as u32, y as u32, &(color * intensity.max(0.0)));
                 }
             }
         }
     }
 }
 
-fn triangle_vertices_sort(v1: &mut Vector2Int, v2: &mut Vector2Int, v3: &mut Vector2Int) {
-    if v1.get_y() > v2.get_y() {
-        v1.swap(v2);
-    }
+/// Textured triangle with perspective-correct texture coordinates
+///
+/// Texture coordinates divided by `w` and `1 / w` change linearly across the screen,
+/// so they are interpolated with the screen barycentric coordinates and divided back
+/// for every pixel. Depth is compared after the division by `w`, which is already
+/// linear in screen space. Pixel centers are tested against the triangle.
+pub fn triangle_perspective_with_texture(
+    triangle_def: TriangleDefF32,
+    texture_def: TextureDef,
+    zbuf: &mut ZBuffer,
+    image: &mut TGAImage,
+    model: &Model,
+    filter: TextureFilter,
+    intensity: f32,
+) {
+    let vertices = [triangle_def.0, triangle_def.1, triangle_def.2];
+    let points = vertices.map(|v| v.project());
+    let points_2d = points.map(|p| Vector2F32::new(p.get_x(), p.get_y()));
+    let inv_w = vertices.map(|v| 1.0 / v.get_w());
+    assert_zbuffer_matches(zbuf, image);
+    let (boundary_box_min, boundary_box_max) = boundary_box_setup_f32(
+        &points_2d,
+        image.get_width() as i32,
+        image.get_height() as i32,
+    );
+    let lod = model
+        .diffuse_size()
+        .map_or(0.0, |size| texture_lod(&points_2d, &texture_def, size));
 
-    if v1.get_y() > v3.get_y() {
-        v1.swap(v3);
-    }
+    for x in boundary_box_min.get_x()..=boundary_box_max.get_x() {
+        for y in boundary_box_min.get_y()..=boundary_box_max.get_y() {
+            let center = Vector2F32::new(x as f32 + 0.5, y as f32 + 0.5);
+
+            if let Some(bc_screen) = barycentric(&points_2d, center) {
+                let z = bc_screen.interpolate(points.map(|p| p.get_z()));
+
+                if zbuf.test_and_set(x as u32, y as u32, z) {
+                    let weights = [
+                        bc_screen.lambda0 * inv_w[0],
+                        bc_screen.lambda1 * inv_w[1],
+                        bc_screen.lambda2 * inv_w[2],
+                    ];
+                    let norm = 1.0 / (weights[0] + weights[1] + weights[2]);
+                    let uv_p = texture_def.0 * (weights[0] * norm)
+                        + texture_def.1 * (weights[1] * norm)
+                        + texture_def.2 * (weights[2] * norm);
+                    let color = model.diffuse_lod(uv_p.get_x(), uv_p.get_y(), lod, filter);
 
-    if v2.get_y() > v3.get_y() {
-        v2.swap(v3);
+                    image.set_unchecked(x as u32, y as u32, &(color.unwrap() * intensity));
+                }
+            }
+        }
     }
 }
 
-pub fn triangle(
-    mut v1: Vector2Int,
-    mut v2: Vector2Int,
-    mut v3: Vector2Int,
-    color: &TGAColor,
-    image: &mut TGAImage,
-) {
-    triangle_vertices_sort(&mut v1, &mut v2, &mut v3);
+fn assert_zbuffer_matches(zbuf: &ZBuffer, image: &TGAImage) {
+    assert!(
+        zbuf.matches(image),
+        "z-buffer {}x{} does not match the image {}x{}",
+        zbuf.width(),
+        zbuf.height(),
+        image.get_width(),
+        image.get_height()
+    );
+}
 
-    if v2.get_y() == v3.get_y() {
-        fill_flat_triangle(v1, v2, v3, color, image);
-    } else if v1.get_y() == v2.get_y() {
-        // fill top flat triangle
-        fill_flat_triangle(v3, v1, v2, color, image);
-    } else {
-        // split to bottom and flat triangles and fill
-        let v4 = Vector2::new(
-            (v1.get_x() as f32
-                + ((v2.get_y() - v1.get_y()) as f32 / (v3.get_y() - v1.get_y()) as f32)
-                    * (v3.get_x() - v1.get_x()) as f32) as i32,
-            v2.get_y(),
-        );
+/// Convert a z-buffer into a 16-bit grayscale image
+///
+/// Finite depth values are normalized from their `min..=max` range into `0..=65535`,
+/// so the closest points are white. Untouched (infinite) entries stay black.
+///
+/// # Panics
+///
+/// Panics if the buffer length does not match the image dimensions or if either
+/// of the dimensions is zero.
+pub fn zbuffer_to_image(zbuf: &[f32], width: u32, height: u32) -> TGAImage {
+    assert_eq!(
+        zbuf.len(),
+        width as usize * height as usize,
+        "z-buffer does not match the image dimensions"
+    );
+    let (min, max) = zbuf
+        .iter()
+        .filter(|z| z.is_finite())
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), z| {
+            (min.min(*z), max.max(*z))
+        });
+    let range = max - min;
+    let mut image = TGAImage::try_new(width, height, TGAImageFormat::Grayscale16)
+        .unwrap_or_else(|e| panic!("Cannot create image: {}", e));
 
-        fill_flat_triangle(v1, v2, v4, color, image);
-        fill_flat_triangle(v3, v2, v4, color, image);
+    for (i, z) in zbuf.iter().enumerate() {
+        if !z.is_finite() {
+            continue;
+        }
+
+        let depth = if range > 0.0 { (z - min) / range } else { 1.0 };
+
+        image.set_gray16(
+            i as u32 % width,
+            i as u32 / width,
+            (depth * u16::MAX as f32).round() as u16,
+        );
     }
+
+    image
 }
 
-fn fill_flat_triangle(
-    v1: Vector2Int,
-    v2: Vector2Int,
-    v3: Vector2Int,
-    color: &TGAColor,
-    image: &mut TGAImage,
-) {
-    let slope1 = Line::new(
-        Point::new(v1.get_x(), v1.get_y()),
-        Point::new(v2.get_x(), v2.get_y()),
-    );
-    let slope2 = Line::new(
-        Point::new(v1.get_x(), v1.get_y()),
-        Point::new(v3.get_x(), v3.get_y()),
+/// Mipmap level of detail of a triangle estimated from the ratio of covered texels
+/// to covered pixels
+fn texture_lod<T: VectorTrait<T> + AsPrimitive<f32>>(
+    points: &[Vector2<T>; 3],
+    texture_def: &TextureDef,
+    (width, height): (u32, u32),
+) -> f32 {
+    let area = |ax: f32, ay: f32, bx: f32, by: f32| (ax * by - ay * bx).abs();
+    let (p1, p2) = (points[1] - points[0], points[2] - points[0]);
+    let (t1, t2) = (texture_def.1 - texture_def.0, texture_def.2 - texture_def.0);
+    let screen_area = area(
+        p1.get_x().as_(),
+        p1.get_y().as_(),
+        p2.get_x().as_(),
+        p2.get_y().as_(),
     );
+    let texture_area =
+        area(t1.get_x(), t1.get_y(), t2.get_x(), t2.get_y()) * width as f32 * height as f32;
 
-    let y_range = if v1.get_y() < v2.get_y() {
-        v1.get_y()..=v2.get_y()
-    } else {
-        v2.get_y()..=v1.get_y()
-    };
+    // Every mipmap level halves the texture in both dimensions
+    (0.5 * (texture_area / screen_area).log2()).max(0.0)
+}
 
-    for y in y_range {
-        let mut min_p: i32 = i32::MAX;
-        let mut max_p: i32 = i32::MIN;
+/// Color of untextured faces rendered with [`render_model`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FaceColor {
+    /// The same color for every face scaled by the face light intensity
+    Flat(TGAColor),
+    /// Unlit pseudo-random color for every face, stable between renders
+    Random,
+}
 
-        for slope in &[slope1, slope2] {
-            slope
+/// Shadow test of [`render_model`] against the scene depth seen from the light
+#[derive(Debug, Copy, Clone)]
+pub struct Shadow<'a> {
+    /// Depth rendered with [`render_depth`]
+    pub depth: &'a ZBuffer,
+    /// World coordinates to the light screen coordinates used for `depth`
+    pub transform: Matrix4F32,
+    /// Points up to `bias` behind the light depth are still lit, which hides the
+    /// self-shadowing caused by the limited depth precision
+    pub bias: f32,
+    /// Scale of the shadowed pixel colors
+    pub intensity: f32,
+}
+
+impl Shadow<'_> {
+    /// Whether a point in world coordinates is hidden from the light
+    ///
+    /// Points outside of the light depth buffer are lit.
+    pub fn occludes(&self, point: Vector3F32) -> bool {
+        let p = self.transform.transform_point(point);
+        let (x, y) = (p.get_x().floor(), p.get_y().floor());
+
+        x >= 0.0
+            && y >= 0.0
+            && x < self.depth.width() as f32
+            && y < self.depth.height() as f32
+            && p.get_z() + self.bias < self.depth.get(x as u32, y as u32)
+    }
+}
+
+/// Settings of [`render_model`]
+///
+/// With the `serde` feature the options are serialized without the shadow map.
+/// They cannot be deserialized as they borrow the lights.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RenderOptions<'a> {
+    /// Direction from the light to the model
+    pub light_dir: Vector3F32,
+    /// Use the diffuse texture of the model instead of `color`
+    pub textured: bool,
+    /// Light textures in linear space, see [`triangle_barycentric_zbuf_with_texture_srgb`]
+    pub srgb: bool,
+    pub filter: TextureFilter,
+    pub color: FaceColor,
+    pub cull_mode: CullMode,
+    /// World vertices to screen coordinates, see [`camera`]
+    pub transform: Matrix4F32,
+    /// Size of the image `transform` maps onto, used where the renderer
+    /// allocates the image itself like [`render_turntable`]
+    pub width: u32,
+    pub height: u32,
+    /// Model vertices to world coordinates, where the lights and shadows are
+    ///
+    /// Normals are moved with [`normal_matrix`], so they stay perpendicular to
+    /// the faces under non-uniform scales. A [`Quaternion`] rotation is converted
+    /// with `into()`.
+    pub model_transform: Matrix4F32,
+    /// Light every pixel with the normal map of the model instead of the face normal
+    pub normal_map: NormalMapping,
+    /// Darken pixels hidden from the light
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub shadow: Option<Shadow<'a>>,
+    /// Light textured pixels with ambient, diffuse and specular terms instead of
+    /// `light_dir`, see [`Model::specular`]
+    pub phong: Option<LightingParams>,
+    /// Light sources of the Phong shading used instead of its single light
+    /// direction when not empty, see [`LightingParams::shade_lights`]
+    pub lights: &'a [Light],
+    /// Render only the faces of a model group, see [`Model::group_faces`]
+    ///
+    /// Used by [`render_model`] and [`render_depth`], [`wireframe`] always draws
+    /// every face.
+    pub group: Option<usize>,
+}
+
+impl RenderOptions<'_> {
+    /// Flat shaded white faces lit from the viewer with model coordinates `-1..1`
+    /// mapped onto the whole `width` x `height` image and depth `0..depth`
+    pub fn new(width: u32, height: u32, depth: u32) -> Self {
+        RenderOptions {
+            light_dir: Vector3F32::new(0.0, 0.0, -1.0),
+            textured: false,
+            srgb: false,
+            filter: TextureFilter::Trilinear,
+            color: FaceColor::Flat(WHITE),
+            cull_mode: CullMode::None,
+            transform: camera::viewport(0, 0, width, height, depth),
+            width,
+            height,
+            model_transform: Matrix4F32::identity(),
+            normal_map: NormalMapping::None,
+            shadow: None,
+            phong: None,
+            lights: &[],
+            group: None,
+        }
+    }
+
+    /// Same as [`RenderOptions::new`] but vertices are moved by `view` and
+    /// projected with `projection` first, see [`camera::screen_transform`]
+    pub fn with_projection(
+        width: u32,
+        height: u32,
+        depth: u32,
+        projection: &Projection,
+        view: &Matrix4F32,
+    ) -> Self {
+        let viewport = camera::viewport(0, 0, width, height, depth);
+
+        RenderOptions {
+            transform: camera::screen_transform(&viewport, projection, view),
+            ..RenderOptions::new(width, height, depth)
+        }
+    }
+}
+
+/// Render every face of a model, or of the group selected by `opts.group`
+///
+/// Faces are flat shaded with the intensity of the light falling onto them, so
+/// faces turned away from the light are black.
+///
+/// # Panics
+///
+/// Panics if the z-buffer does not match the image dimensions, a face has neither
+/// a material nor a diffuse texture while `opts.textured` is set or the model
+/// tangents are not computed for [`NormalMapping::Tangent`].
+pub fn render_model(
+    model: &Model,
+    target: &mut TGAImage,
+    zbuf: &mut ZBuffer,
+    opts: &RenderOptions,
+) -> RenderStats {
+    assert_zbuffer_matches(zbuf, target);
+    let size = (target.get_width() as i32, target.get_height() as i32);
+    let (faces, mut stats) = screen_faces(model, opts, size);
+    let start = Instant::now();
+    let mut frame = FrameMut {
+        image: target,
+        zbuf,
+    };
+
+    for face in &faces {
+        stats += draw_face(model, face, opts, &mut frame);
+    }
+
+    stats.raster_time = start.elapsed();
+
+    stats
+}
+
+/// Render the depth of every model face without colors
+///
+/// Vertices are moved by `opts.model_transform` and then by `opts.transform`.
+/// With `opts.transform` mapping the world to the light screen coordinates the
+/// result is a shadow map for [`Shadow`] with the same transform. Only the faces
+/// of `opts.group` are rendered when it is set. Faces are rasterized the same way
+/// as in [`render_model`] and none of them is culled, the other options are not
+/// used.
+pub fn render_depth(model: &Model, opts: &RenderOptions, shadow_zbuf: &mut ZBuffer) {
+    let mut target = DepthMut { zbuf: shadow_zbuf };
+    let range = opts
+        .group
+        .map_or(0..model.n_faces(), |group| model.group_faces(group));
+
+    for i in range {
+        let vertices = model.face(i).map(|vert| {
+            let world = opts
+                .model_transform
+                .transform_point(*model.vert(vert as usize));
+
+            opts.transform.transform_point(world).to_int_round()
+        });
+
+        // the color is never written
+        fill_triangle::<false, _, _>(&vertices, &mut target, |_| WHITE);
+    }
+}
+
+/// Render frames of the camera orbiting the model around the Y axis and write
+/// them to numbered TGA files
+///
+/// Every frame turns the camera by `360° / frames` around the model origin with
+/// [`camera::orbit`] applied before `opts.transform`, so the first frame is the
+/// same as [`render_model`]. Lights stay in place relative to the model. Every
+/// frame is rendered into a new black `opts.width` x `opts.height` RGB image
+/// with a new z-buffer.
+///
+/// `out_pattern` has a single `{}` placeholder for the frame index, optionally
+/// zero padded like in `frame_{:03}.tga`.
+///
+/// # Panics
+///
+/// Panics in the same cases as [`render_model`].
+pub fn render_turntable(
+    model: &Model,
+    frames: u32,
+    opts: &RenderOptions,
+    out_pattern: &str,
+) -> io::Result<()> {
+    for frame in 0..frames {
+        let angle = 2.0 * std::f32::consts::PI * frame as f32 / frames as f32;
+        let view = camera::orbit(
+            Vector3F32::new(0.0, 0.0, 0.0),
+            &Quaternion::from_axis_angle(Vector3F32::new(0.0, 1.0, 0.0), angle),
+        );
+        let filename = frame_filename(out_pattern, frame)?;
+        let mut image = TGAImage::try_new(opts.width, opts.height, TGAImageFormat::RGB)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mut zbuf = ZBuffer::new(opts.width, opts.height);
+
+        render_model(
+            model,
+            &mut image,
+            &mut zbuf,
+            &RenderOptions {
+                transform: opts.transform * view,
+                ..*opts
+            },
+        );
+        image
+            .write_tga_file_with_origin(&filename, Origin::BottomLeft, true)
+            .map_err(|e| match e {
+                TGAError::Io(e) => e,
+                e => io::Error::other(e),
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Substitute the frame index for the `{}` or `{:0N}` placeholder of a pattern
+fn frame_filename(pattern: &str, frame: u32) -> io::Result<String> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid frame placeholder in {:?}", pattern),
+        )
+    };
+    let start = pattern.find('{').ok_or_else(invalid)?;
+    let end = start + pattern[start..].find('}').ok_or_else(invalid)?;
+    let index = match &pattern[start + 1..end] {
+        "" => frame.to_string(),
+        spec => {
+            let width = spec
+                .strip_prefix(":0")
+                .and_then(|width| width.parse::<usize>().ok())
+                .ok_or_else(invalid)?;
+
+            format!("{:0width$}", frame, width = width)
+        }
+    };
+
+    Ok(format!(
+        "{}{}{}",
+        &pattern[..start],
+        index,
+        &pattern[end + 1..]
+    ))
+}
+
+/// Rows of the tiles rendered by a single [`render_model_parallel`] worker
+#[cfg(feature = "parallel")]
+pub const TILE_ROWS: u32 = 64;
+
+/// Same as [`render_model`] but rasterizes image tiles on the rayon thread pool
+///
+/// Image rows are contiguous in memory, so tiles are bands of [`TILE_ROWS`]
+/// full-width rows. Faces are binned to the tiles they overlap and every tile
+/// draws its faces in the model order, so the result is identical to
+/// [`render_model`].
+///
+/// # Panics
+///
+/// Panics if the z-buffer does not match the image dimensions, a face has neither
+/// a material nor a diffuse texture while `opts.textured` is set or the model
+/// tangents are not computed for [`NormalMapping::Tangent`].
+#[cfg(feature = "parallel")]
+pub fn render_model_parallel(
+    model: &Model,
+    target: &mut TGAImage,
+    zbuf: &mut ZBuffer,
+    opts: &RenderOptions,
+) -> RenderStats {
+    use rayon::prelude::*;
+
+    assert_zbuffer_matches(zbuf, target);
+    let height = target.get_height();
+    let size = (target.get_width() as i32, height as i32);
+    let (faces, mut stats) = screen_faces(model, opts, size);
+    let start = Instant::now();
+    let tiles = height.div_ceil(TILE_ROWS) as usize;
+    let rows_per_tile = height.div_ceil(tiles.max(1) as u32).max(1) as i32;
+    let mut bins = vec![Vec::new(); tiles];
+
+    for face in &faces {
+        let rows = face.vertices.map(|v| v.get_y());
+        let first = rows.iter().min().unwrap().clamp(&0, &(size.1 - 1)) / rows_per_tile;
+        let last = rows.iter().max().unwrap().clamp(&0, &(size.1 - 1)) / rows_per_tile;
+
+        for bin in &mut bins[first as usize..=last as usize] {
+            bin.push(face);
+        }
+    }
+
+    stats += target
+        .split_rows_mut(tiles)
+        .into_par_iter()
+        .zip(zbuf.split_rows_mut(tiles))
+        .zip(bins)
+        .map(|((image, zbuf), faces)| {
+            let mut tile = TileMut { image, zbuf, size };
+            let mut pixels = RenderStats::default();
+
+            for face in faces {
+                pixels += draw_face(model, face, opts, &mut tile);
+            }
+
+            pixels
+        })
+        .reduce(RenderStats::default, |mut a, b| {
+            a += b;
+            a
+        });
+    stats.raster_time = start.elapsed();
+
+    stats
+}
+
+/// Model face in screen coordinates
+struct ScreenFace {
+    index: usize,
+    world: [Vector3F32; 3],
+    vertices: [Vector3Int; 3],
+    intensity: f32,
+}
+
+/// Transform, cull and light the model faces for an image of `size`
+fn screen_faces(
+    model: &Model,
+    opts: &RenderOptions,
+    size: (i32, i32),
+) -> (Vec<ScreenFace>, RenderStats) {
+    let start = Instant::now();
+    let range = opts
+        .group
+        .map_or(0..model.n_faces(), |group| model.group_faces(group));
+    let mut stats = RenderStats {
+        submitted: range.len(),
+        ..RenderStats::default()
+    };
+    let mut faces = Vec::with_capacity(range.len());
+
+    for i in range {
+        let face = model.face(i);
+        let world_coords = face.map(|vert| {
+            opts.model_transform
+                .transform_point(*model.vert(vert as usize))
+        });
+        let screen_coords = world_coords.map(|v| opts.transform.transform_point(v));
+
+        if opts.cull_mode.culls(&screen_coords) {
+            stats.culled += 1;
+            continue;
+        }
+
+        let vertices = screen_coords.map(|v| v.to_int_round());
+        let (boundary_box_min, boundary_box_max) = boundary_box_setup(
+            &vertices.map(|v| Vector2::new(v.get_x(), v.get_y())),
+            size.0,
+            size.1,
+        );
+
+        if boundary_box_min.get_x() > boundary_box_max.get_x()
+            || boundary_box_min.get_y() > boundary_box_max.get_y()
+        {
+            stats.empty += 1;
+            continue;
+        }
+
+        let mut n = (world_coords[2] - world_coords[0]).cross(&(world_coords[1] - world_coords[0]));
+
+        n.normalize_default();
+
+        faces.push(ScreenFace {
+            index: i,
+            world: world_coords,
+            vertices,
+            intensity: n.dot(&opts.light_dir).max(0.0),
+        });
+        stats.drawn += 1;
+    }
+
+    stats.setup_time = start.elapsed();
+
+    (faces, stats)
+}
+
+/// Rasterize a face, only the pixel counters of the returned stats are set
+fn draw_face<T: RasterTarget>(
+    model: &Model,
+    face: &ScreenFace,
+    opts: &RenderOptions,
+    target: &mut T,
+) -> RenderStats {
+    let i = face.index;
+    let texture_def = TextureDef(model.uv_f32(i, 0), model.uv_f32(i, 1), model.uv_f32(i, 2));
+    let lod = match model.face_diffuse_size(i) {
+        Some(size) if opts.textured => texture_lod(
+            &face.vertices.map(|v| Vector2::new(v.get_x(), v.get_y())),
+            &texture_def,
+            size,
+        ),
+        _ => 0.0,
+    };
+    let flat_color = match opts.color {
+        FaceColor::Flat(color) => color,
+        FaceColor::Random => random_face_color(i),
+    };
+    let linear = opts.model_transform.upper3x3();
+    // a singular transform flattens the model, its normals are kept as they are
+    let to_world = normal_matrix(&opts.model_transform).unwrap_or_else(Matrix3F32::identity);
+    let tangent = match opts.normal_map {
+        NormalMapping::Tangent => {
+            let (tangent, bitangent) = model.tangent(i).expect("Model tangents are not computed");
+
+            // tangents lie in the face, so they move like the vertices
+            Some((linear * tangent, linear * bitangent))
+        }
+        _ => None,
+    };
+    let vertex_normal = |bc_screen: &PointBarycentricCoords| {
+        to_world
+            * bc_screen.interpolate([model.normal(i, 0), model.normal(i, 1), model.normal(i, 2)])
+    };
+
+    fill_triangle::<true, _, _>(&face.vertices, target, |bc_screen| {
+        let uv = bc_screen.interpolate([texture_def.0, texture_def.1, texture_def.2]);
+        let normal = match opts.normal_map {
+            NormalMapping::None => None,
+            NormalMapping::Model => model.normal_from_map(uv).map(|mapped| {
+                let mut normal = to_world * mapped;
+
+                normal.normalize_default();
+
+                normal
+            }),
+            NormalMapping::Tangent => model.normal_from_map(uv).map(|mapped| {
+                let (tangent, bitangent) = tangent.unwrap();
+
+                tangent_to_model(mapped, vertex_normal(bc_screen), tangent, bitangent)
+            }),
+        };
+        // normals of the map point out of the model, against the light
+        let intensity = normal.map_or(face.intensity, |n| (-n.dot(&opts.light_dir)).max(0.0));
+        let color = if opts.textured {
+            let diffuse = model
+                .face_diffuse_lod(i, uv.get_x(), uv.get_y(), lod, opts.filter)
+                .unwrap();
+
+            if let Some(ref params) = opts.phong {
+                let normal = normal.unwrap_or_else(|| {
+                    let mut n = vertex_normal(bc_screen);
+
+                    n.normalize_default();
+
+                    n
+                });
+
+                let shininess = model.specular(uv);
+
+                if opts.lights.is_empty() {
+                    params.shade(diffuse, normal, shininess)
+                } else {
+                    let point = bc_screen.interpolate(face.world);
+
+                    params.shade_lights(opts.lights, diffuse, normal, point, shininess)
+                }
+            } else if opts.srgb {
+                diffuse.scale_srgb(intensity)
+            } else {
+                diffuse * intensity
+            }
+        } else {
+            match opts.color {
+                FaceColor::Flat(_) => flat_color.scaled(intensity, intensity, intensity),
+                FaceColor::Random => flat_color,
+            }
+        };
+
+        match opts.shadow {
+            Some(ref shadow) if shadow.occludes(bc_screen.interpolate(face.world)) => {
+                color * shadow.intensity
+            }
+            _ => color,
+        }
+    })
+}
+
+/// Transform a tangent space normal to the model coordinates
+///
+/// The face tangent and bitangent are made orthogonal to the interpolated vertex
+/// normal, so the frame follows smooth shading across faces.
+fn tangent_to_model(
+    mapped: Vector3F32,
+    mut normal: Vector3F32,
+    tangent: Vector3F32,
+    bitangent: Vector3F32,
+) -> Vector3F32 {
+    normal.normalize_default();
+
+    let mut t = tangent - normal * normal.dot(&tangent);
+
+    t.normalize_default();
+
+    let mut b = bitangent - normal * normal.dot(&bitangent) - t * t.dot(&bitangent);
+
+    b.normalize_default();
+
+    let mut result = t * mapped.get_x() + b * mapped.get_y() + normal * mapped.get_z();
+
+    result.normalize_default();
+
+    result
+}
+
+/// Draw the edges of every model face
+///
+/// `transform` maps model vertices to screen coordinates, faces skipped by
+/// `cull_mode` are not drawn. There is no group selection like
+/// [`RenderOptions::group`], so all groups are drawn.
+pub fn wireframe(
+    model: &Model,
+    color: &TGAColor,
+    image: &mut TGAImage,
+    transform: &Matrix4F32,
+    cull_mode: CullMode,
+) {
+    draw_edges(model, color, None, image, transform, cull_mode);
+}
+
+/// Same as [`wireframe`] but edges hidden behind the z-buffer are not drawn
+///
+/// Edge pixels up to `depth_bias` behind the z-buffer stay visible. Rasterized
+/// faces snap their vertices to whole pixels, so the depth of their own edges may
+/// be off by the depth change over a pixel, which is large for faces seen at a
+/// grazing angle. The z-buffer is not updated.
+///
+/// # Panics
+///
+/// Panics if the z-buffer does not match the image dimensions.
+pub fn wireframe_zbuf(
+    model: &Model,
+    color: &TGAColor,
+    zbuf: &ZBuffer,
+    depth_bias: f32,
+    image: &mut TGAImage,
+    transform: &Matrix4F32,
+    cull_mode: CullMode,
+) {
+    assert_zbuffer_matches(zbuf, image);
+    draw_edges(
+        model,
+        color,
+        Some((zbuf, depth_bias)),
+        image,
+        transform,
+        cull_mode,
+    );
+}
+
+fn draw_edges(
+    model: &Model,
+    color: &TGAColor,
+    depth_test: Option<(&ZBuffer, f32)>,
+    image: &mut TGAImage,
+    transform: &Matrix4F32,
+    cull_mode: CullMode,
+) {
+    for i in 0..model.n_faces() {
+        let face = model.face(i);
+        let screen_coords = face.map(|vert| transform.transform_point(*model.vert(vert as usize)));
+
+        if cull_mode.culls(&screen_coords) {
+            continue;
+        }
+
+        for j in 0..3 {
+            let (start, end) = (screen_coords[j], screen_coords[(j + 1) % 3]);
+            let point = |v: Vector3F32| {
+                let v = v.to_int_round();
+
+                Point::new(v.get_x(), v.get_y())
+            };
+            let edge = Line::new(point(start), point(end));
+            let length = (edge.end - edge.start).abs();
+            let steps = length.x.max(length.y).max(1) as f32;
+            let visible_steps =
+                match visible_steps(edge.start, edge.end, image.get_width(), image.get_height()) {
+                    Some(visible_steps) => visible_steps,
+                    None => continue,
+                };
+            let points = edge
                 .points()
-                .skip_while(|p| p.y != y)
-                .take_while(|p| p.y == y)
-                .for_each(|p| {
-                    min_p = min_p.min(p.x);
-                    max_p = max_p.max(p.x);
+                .enumerate()
+                .skip(*visible_steps.start() as usize)
+                .take((visible_steps.end() - visible_steps.start() + 1) as usize);
+
+            for (k, point) in points {
+                let (x, y) = (point.x as u32, point.y as u32);
+                let z = start.get_z() + (end.get_z() - start.get_z()) * k as f32 / steps;
+                let visible = depth_test.is_none_or(|(zbuf, depth_bias)| {
+                    x < zbuf.width() && y < zbuf.height() && z >= zbuf.get(x, y) - depth_bias
                 });
+
+                if visible {
+                    image.set(x, y, color);
+                }
+            }
+        }
+    }
+}
+
+/// Color derived from a hash of the face index
+fn random_face_color(face_index: usize) -> TGAColor {
+    // 64-bit finalizer of the MurmurHash3
+    let mut hash = face_index as u64 ^ 0x9e37_79b9_7f4a_7c15;
+
+    hash = (hash ^ (hash >> 33)).wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash = (hash ^ (hash >> 33)).wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^= hash >> 33;
+
+    let [r, g, b, ..] = hash.to_le_bytes();
+
+    TGAColor::new_rgb(r, g, b)
+}
+
+pub fn triangle(
+    v1: Vector2Int,
+    v2: Vector2Int,
+    v3: Vector2Int,
+    color: &TGAColor,
+    image: &mut TGAImage,
+) {
+    let mut verts = [v1, v2, v3];
+
+    sort_by_y(&mut verts);
+
+    let [v1, v2, v3] = verts;
+
+    if v2.get_y() == v3.get_y() {
+        fill_flat_triangle(v1, v2, v3, color, image);
+    } else if v1.get_y() == v2.get_y() {
+        // fill top flat triangle
+        fill_flat_triangle(v3, v1, v2, color, image);
+    } else {
+        // split to bottom and flat triangles and fill
+        let v4 = Vector2::new(
+            (v1.get_x() as f32
+                + ((v2.get_y() - v1.get_y()) as f32 / (v3.get_y() - v1.get_y()) as f32)
+                    * (v3.get_x() - v1.get_x()) as f32) as i32,
+            v2.get_y(),
+        );
+
+        fill_flat_triangle(v1, v2, v4, color, image);
+        fill_flat_triangle(v3, v2, v4, color, image);
+    }
+}
+
+fn fill_flat_triangle(
+    v1: Vector2Int,
+    v2: Vector2Int,
+    v3: Vector2Int,
+    color: &TGAColor,
+    image: &mut TGAImage,
+) {
+    let slope1 = Line::new(
+        Point::new(v1.get_x(), v1.get_y()),
+        Point::new(v2.get_x(), v2.get_y()),
+    );
+    let slope2 = Line::new(
+        Point::new(v1.get_x(), v1.get_y()),
+        Point::new(v3.get_x(), v3.get_y()),
+    );
+
+    let y_min = v1.get_y().min(v2.get_y());
+    let y_max = v1.get_y().max(v2.get_y());
+    // horizontal span of the edge points on every row, walking each edge once
+    let mut spans = vec![(i32::MAX, i32::MIN); (y_max - y_min + 1) as usize];
+
+    for slope in &[slope1, slope2] {
+        for p in slope.points().filter(|p| (y_min..=y_max).contains(&p.y)) {
+            let span = &mut spans[(p.y - y_min) as usize];
+
+            span.0 = span.0.min(p.x);
+            span.1 = span.1.max(p.x);
+        }
+    }
+
+    for (y, (min_p, max_p)) in (y_min..).zip(spans) {
+        for x in min_p..=max_p {
+            image.set(x as u32, y as u32, color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_renderer_lib {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use tgaimage::colors::{BLUE, GREEN, RED, WHITE};
+    use tgaimage::{TGAColor, TGAImage, TGAImageFormat, TextureFilter};
+
+    use crate::camera::{lookat, projection, viewport};
+    use crate::geometry::{
+        Vector2, Vector2F32, Vector2Int, Vector3F32, Vector3Int, Vector4F32, XAxis, YAxis, ZAxis,
+    };
+    use crate::line::{Line, LineF32};
+    use crate::model::Model;
+    use crate::point::Point;
+    use crate::raster_util::sort_by_y;
+    use crate::zbuffer::ZBuffer;
+    use crate::{
+        barycentric, clip_line, fill_triangle, frame_filename, line, line_thick, polygon_filled,
+        polyline, render_model, texture_lod, triangle, triangle_barycentric,
+        triangle_barycentric_f32, triangle_barycentric_zbuf, triangle_gouraud,
+        triangle_interp_color, triangle_perspective_with_texture, wireframe, wireframe_zbuf,
+        zbuffer_to_image, CullMode, Diffuse, FaceColor, FrameMut, PointBarycentricCoords,
+        RenderOptions, RenderStats, TextureDef, TriangleDef, TriangleDefF32,
+    };
+
+    fn filled(image: &TGAImage) -> Vec<(u32, u32)> {
+        image
+            .pixels()
+            .filter(|(_, _, color)| color.r() != 0)
+            .map(|(x, y, _)| (x, y))
+            .collect()
+    }
+
+    fn rainbow_triangle(width: u32, height: u32, v: [Vector3Int; 3]) -> TGAImage {
+        let mut image = TGAImage::try_new(width, height, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(width, height);
+
+        triangle_interp_color(v, [RED, GREEN, BLUE], &mut zbuf, &mut image, None);
+
+        image
+    }
+
+    #[test]
+    fn test_interp_color_center() {
+        let image = rainbow_triangle(
+            64,
+            64,
+            [
+                Vector3Int::new(2, 2, 0),
+                Vector3Int::new(62, 2, 0),
+                Vector3Int::new(32, 54, 0),
+            ],
+        );
+        let center = image.get(32, 19);
+
+        for channel in &[center.r(), center.g(), center.b()] {
+            assert!((80..=90).contains(channel), "{:?}", center);
+        }
+
+        assert_eq!(image[(2, 2)], RED.as_slice()[..3]);
+        assert_eq!(image[(62, 2)], GREEN.as_slice()[..3]);
+        assert_eq!(image[(32, 54)], BLUE.as_slice()[..3]);
+    }
+
+    #[test]
+    fn test_interp_color_golden() {
+        let image = rainbow_triangle(
+            32,
+            32,
+            [
+                Vector3Int::new(1, 1, 0),
+                Vector3Int::new(30, 4, 0),
+                Vector3Int::new(12, 29, 0),
+            ],
+        );
+        let golden = TGAImage::read_tga_file(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/rainbow_triangle.tga"
+        ))
+        .unwrap();
+
+        assert!(image.diff_with_tolerance(&golden, 1).is_none());
+    }
+
+    #[test]
+    fn test_polygon_square_matches_triangles() {
+        let corners = [
+            Vector2Int::new(10, 10),
+            Vector2Int::new(30, 10),
+            Vector2Int::new(30, 30),
+            Vector2Int::new(10, 30),
+        ];
+        let mut polygon = TGAImage::try_new(40, 40, TGAImageFormat::RGB).unwrap();
+        let mut triangles = TGAImage::try_new(40, 40, TGAImageFormat::RGB).unwrap();
+
+        polygon_filled(&corners, &WHITE, &mut polygon);
+        triangle_barycentric(corners[0], corners[1], corners[2], &WHITE, &mut triangles);
+        triangle_barycentric(corners[0], corners[2], corners[3], &WHITE, &mut triangles);
+
+        assert_eq!(polygon.as_bytes(), triangles.as_bytes());
+        assert_eq!(filled(&polygon).len(), 21 * 21);
+
+        let diamond = [
+            Vector2Int::new(20, 2),
+            Vector2Int::new(37, 20),
+            Vector2Int::new(20, 38),
+            Vector2Int::new(3, 20),
+        ];
+
+        polygon.clear();
+        triangles.clear();
+        polygon_filled(&diamond, &WHITE, &mut polygon);
+        triangle_barycentric(diamond[0], diamond[1], diamond[2], &WHITE, &mut triangles);
+        triangle_barycentric(diamond[0], diamond[2], diamond[3], &WHITE, &mut triangles);
+
+        assert_eq!(polygon.as_bytes(), triangles.as_bytes());
+    }
+
+    #[test]
+    fn test_polygon_concave() {
+        let mut image = TGAImage::try_new(50, 50, TGAImageFormat::RGB).unwrap();
+        let l_shape = [
+            Vector2Int::new(10, 10),
+            Vector2Int::new(40, 10),
+            Vector2Int::new(40, 20),
+            Vector2Int::new(20, 20),
+            Vector2Int::new(20, 40),
+            Vector2Int::new(10, 40),
+        ];
+
+        polygon_filled(&l_shape, &WHITE, &mut image);
+
+        let pixels = filled(&image);
+
+        // 31x11 pixels of the bottom bar and 11x20 pixels of the vertical bar
+        assert_eq!(pixels.len(), 31 * 11 + 11 * 20);
+        assert!(pixels.iter().all(|&(x, y)| !(x > 20 && y > 20)));
+        assert!(pixels.contains(&(40, 20)) && pixels.contains(&(20, 40)));
+    }
+
+    #[test]
+    fn test_polygon_degenerate() {
+        let mut image = TGAImage::try_new(20, 20, TGAImageFormat::RGB).unwrap();
+        let (a, b, c) = (
+            Vector2Int::new(2, 2),
+            Vector2Int::new(15, 2),
+            Vector2Int::new(15, 15),
+        );
+
+        polygon_filled(&[], &WHITE, &mut image);
+        polygon_filled(&[a, b], &WHITE, &mut image);
+        polygon_filled(&[a, b, Vector2Int::new(8, 2)], &WHITE, &mut image);
+        polygon_filled(&[a, c, Vector2Int::new(6, 6)], &WHITE, &mut image);
+        assert!(filled(&image).is_empty());
+
+        let mut reference = TGAImage::try_new(20, 20, TGAImageFormat::RGB).unwrap();
+
+        polygon_filled(&[a, a, b, b, c, c, a], &WHITE, &mut image);
+        polygon_filled(&[a, b, c], &WHITE, &mut reference);
+        assert_eq!(image.as_bytes(), reference.as_bytes());
+
+        // Partially outside of the image
+        image.clear();
+        polygon_filled(
+            &[
+                Vector2Int::new(-30, -30),
+                Vector2Int::new(80, -30),
+                Vector2Int::new(-30, 80),
+            ],
+            &WHITE,
+            &mut image,
+        );
+        assert_eq!(filled(&image).len(), 20 * 20);
+    }
+
+    #[test]
+    fn test_clip_line() {
+        let clip = |x0, y0, x1, y1| {
+            clip_line(Point::new(x0, y0), Point::new(x1, y1), 20, 20)
+                .map(|(p0, p1)| ((p0.x, p0.y), (p1.x, p1.y)))
+        };
+
+        assert_eq!(clip(-10, -10, 50, 50), Some(((0, 0), (19, 19))));
+        assert_eq!(clip(-5, 10, 10, -5), Some(((0, 5), (5, 0))));
+        // Crosses the corner region without entering the image
+        assert_eq!(clip(15, 30, 30, 15), None);
+        assert_eq!(clip(3, 4, 17, 12), Some(((3, 4), (17, 12))));
+        assert_eq!(clip(0, 0, 19, 19), Some(((0, 0), (19, 19))));
+        assert_eq!(clip(-500, -500, -1, 50), None);
+        assert_eq!(clip(-5, 25, 25, -5), Some(((1, 19), (19, 1))));
+        assert_eq!(clip(5, 5, 5, 5), Some(((5, 5), (5, 5))));
+        assert_eq!(
+            clip_line(Point::new(0, 0), Point::new(1, 1), 0, 10).map(|_| ()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_line_clipped_matches_unclipped() {
+        let mut rng = StdRng::seed_from_u64(71);
+        let offset = 600;
+
+        for _ in 0..500 {
+            let mut random_coord = || rng.gen_range(-500, 560);
+            let (x0, y0, x1, y1) = (
+                random_coord(),
+                random_coord(),
+                random_coord(),
+                random_coord(),
+            );
+            let mut clipped = TGAImage::try_new(64, 48, TGAImageFormat::Grayscale).unwrap();
+            let mut reference = TGAImage::try_new(1200, 1200, TGAImageFormat::Grayscale).unwrap();
+
+            line(x0, y0, x1, y1, &WHITE, &mut clipped);
+            line(
+                x0 + offset,
+                y0 + offset,
+                x1 + offset,
+                y1 + offset,
+                &WHITE,
+                &mut reference,
+            );
+
+            for (x, y, color) in clipped.pixels() {
+                let expected =
+                    reference.get((x as i32 + offset) as u32, (y as i32 + offset) as u32);
+
+                assert_eq!(
+                    color.as_slice()[0],
+                    expected.as_slice()[0],
+                    "pixel ({}, {}) of ({}, {}) - ({}, {})",
+                    x,
+                    y,
+                    x0,
+                    y0,
+                    x1,
+                    y1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_points_nth() {
+        for &(x1, y1) in &[(13, 5), (-7, 30), (30, 30), (-25, -4), (0, 0), (0, 9)] {
+            let line = Line::new(Point::new(2, 3), Point::new(x1, y1));
+            let points = line.points().collect::<Vec<_>>();
+
+            for n in 0..=points.len() {
+                let mut skipped = line.points();
+
+                assert_eq!(
+                    skipped.nth(n).map(|p| (p.x, p.y)),
+                    points.get(n).map(|p| (p.x, p.y))
+                );
+                assert_eq!(
+                    skipped.map(|p| (p.x, p.y)).collect::<Vec<_>>(),
+                    points
+                        .iter()
+                        .skip(n + 1)
+                        .map(|p| (p.x, p.y))
+                        .collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    /// Bresenham loop `line` used before it walked `Line::points`
+    fn line_reference(
+        mut x0: i32,
+        mut y0: i32,
+        mut x1: i32,
+        mut y1: i32,
+        color: &TGAColor,
+        image: &mut TGAImage,
+    ) {
+        let steep = (x0 - x1).abs() < (y0 - y1).abs();
+
+        if steep {
+            std::mem::swap(&mut x0, &mut y0);
+            std::mem::swap(&mut x1, &mut y1);
+        }
+
+        if x0 > x1 {
+            std::mem::swap(&mut x0, &mut x1);
+            std::mem::swap(&mut y0, &mut y1);
+        }
+
+        let dx = x1 - x0;
+        let derror2 = (y1 - y0).abs() * 2;
+        let y_step = if y1 > y0 { 1 } else { -1 };
+        let mut error2 = 0;
+        let mut y = y0;
+
+        for x in x0..=x1 {
+            if steep {
+                image.set(y as u32, x as u32, color);
+            } else {
+                image.set(x as u32, y as u32, color);
+            }
+
+            error2 += derror2;
+
+            if error2 > dx {
+                y += y_step;
+                error2 -= dx * 2;
+            }
         }
+    }
 
-        for x in min_p..=max_p {
-            image.set(x as u32, y as u32, color);
+    #[test]
+    fn test_line_matches_reference() {
+        // every line between the points around an 8x8 image covers all the
+        // octants, vertical, horizontal and single pixel lines in both directions
+        let coords = -3..=10;
+
+        for x0 in coords.clone() {
+            for y0 in coords.clone() {
+                for x1 in coords.clone() {
+                    for y1 in coords.clone() {
+                        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::Grayscale).unwrap();
+                        let mut reference =
+                            TGAImage::try_new(8, 8, TGAImageFormat::Grayscale).unwrap();
+
+                        line(x0, y0, x1, y1, &WHITE, &mut image);
+                        line_reference(x0, y0, x1, y1, &WHITE, &mut reference);
+
+                        assert_eq!(
+                            image.as_bytes(),
+                            reference.as_bytes(),
+                            "({}, {}) - ({}, {})",
+                            x0,
+                            y0,
+                            x1,
+                            y1
+                        );
+                    }
+                }
+            }
         }
     }
-}
 
-#[cfg(test)]
-mod test_renderer_lib {
-    use crate::barycentric;
-    use crate::geometry::Vector3Int;
+    #[test]
+    fn test_line_points_both_ends() {
+        for &(x1, y1) in &[
+            (13, 5),
+            (-7, 30),
+            (30, 30),
+            (-25, -4),
+            (0, 0),
+            (0, 9),
+            (2, -6),
+        ] {
+            let line = Line::new(Point::new(2, 3), Point::new(x1, y1));
+            let points = line.points().map(|p| (p.x, p.y)).collect::<Vec<_>>();
+            let mut iter = line.points();
+
+            assert_eq!(
+                points.len(),
+                (x1 - 2).abs().max((y1 - 3).abs()) as usize + 1
+            );
+            assert_eq!(points.first(), Some(&(2, 3)));
+            assert_eq!(points.last(), Some(&(x1, y1)));
+            assert_eq!(
+                line.points().rev().map(|p| (p.x, p.y)).collect::<Vec<_>>(),
+                points.iter().rev().copied().collect::<Vec<_>>()
+            );
+
+            // alternate the ends until they meet
+            let (mut front, mut back) = (0, points.len());
+
+            while front < back {
+                assert_eq!(iter.len(), back - front);
+
+                if (front + back) % 2 == 0 {
+                    assert_eq!(iter.next().map(|p| (p.x, p.y)), Some(points[front]));
+                    front += 1;
+                } else {
+                    back -= 1;
+                    assert_eq!(iter.next_back().map(|p| (p.x, p.y)), Some(points[back]));
+                }
+            }
+
+            assert_eq!(iter.len(), 0);
+            assert!(iter.next().is_none() && iter.next_back().is_none());
+        }
+    }
+
+    #[test]
+    fn test_line_f32_matches_line() {
+        for x1 in -4..=4 {
+            for y1 in -4..=4 {
+                let line = Line::new(Point::new(1, -2), Point::new(x1, y1));
+                let line_f32 = LineF32::new(
+                    Vector2F32::new(1.0, -2.0),
+                    Vector2F32::new(x1 as f32, y1 as f32),
+                );
+                let points = line_f32.points().collect::<Vec<_>>();
+
+                assert_eq!(
+                    points.iter().map(|p| (p.0, p.1)).collect::<Vec<_>>(),
+                    line.points().map(|p| (p.x, p.y)).collect::<Vec<_>>(),
+                    "(1, -2) - ({}, {})",
+                    x1,
+                    y1
+                );
+                assert_eq!(points[0].2, 0.0);
+
+                if points.len() > 1 {
+                    assert_eq!(points[points.len() - 1].2, 1.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_line_f32_sub_pixel() {
+        let point = Vector2F32::new(10.3, 20.7);
+
+        assert_eq!(
+            LineF32::new(point, point).points().collect::<Vec<_>>(),
+            vec![(10, 21, 0.0)]
+        );
+
+        let points = LineF32::new(point, Vector2F32::new(80.1, 40.2))
+            .points()
+            .collect::<Vec<_>>();
+
+        assert_eq!(points.len(), 71);
+        assert_eq!((points[0].0, points[0].1), (10, 21));
+        assert_eq!((points[70].0, points[70].1), (80, 40));
+        assert!(points
+            .windows(2)
+            .all(|w| w[1].0 == w[0].0 + 1 && w[1].1 - w[0].1 <= 1 && w[0].2 <= w[1].2));
+    }
+
+    #[test]
+    fn test_line_thick_area() {
+        let mut image = TGAImage::try_new(64, 32, TGAImageFormat::RGB).unwrap();
+
+        line_thick(
+            Point::new(10, 10),
+            Point::new(40, 10),
+            5,
+            &WHITE,
+            &mut image,
+        );
+
+        let pixels = filled(&image);
+
+        // 31 columns of 5 pixels with round caps of 5 and 3 pixels on both ends
+        assert_eq!(pixels.len(), 31 * 5 + 2 * (5 + 3));
+        assert_eq!(pixels.iter().map(|p| p.1).min(), Some(8));
+        assert_eq!(pixels.iter().map(|p| p.1).max(), Some(12));
+        assert_eq!(pixels.iter().map(|p| p.0).min(), Some(8));
+        assert_eq!(pixels.iter().map(|p| p.0).max(), Some(42));
+        assert!((10..=40).all(|x| (8..=12).all(|y| pixels.contains(&(x, y)))));
+
+        image.clear();
+        line_thick(
+            Point::new(10, 10),
+            Point::new(40, 10),
+            0,
+            &WHITE,
+            &mut image,
+        );
+        assert!(filled(&image).is_empty());
+    }
+
+    #[test]
+    fn test_line_thick_width_one() {
+        for &(x1, y1) in &[(50, 3), (50, 20), (20, 50), (3, 50), (45, 45)] {
+            let mut thin = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+            let mut thick = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+
+            line(5, 5, x1, y1, &WHITE, &mut thin);
+            line_thick(Point::new(5, 5), Point::new(x1, y1), 1, &WHITE, &mut thick);
+
+            let (thin, thick) = (filled(&thin), filled(&thick));
+
+            assert_eq!(thin.len(), thick.len());
+            assert!(thick
+                .iter()
+                .all(|&(x, y)| thin
+                    .iter()
+                    .any(|&(tx, ty)| (x as i32 - tx as i32).abs() <= 1
+                        && (y as i32 - ty as i32).abs() <= 1)));
+        }
+    }
+
+    #[test]
+    fn test_polyline_joints() {
+        let mut image = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+        let joint = Point::new(30, 40);
+
+        polyline(
+            &[Point::new(5, 5), joint, Point::new(55, 5)],
+            5,
+            &WHITE,
+            &mut image,
+        );
+
+        let pixels = filled(&image);
+
+        // The round join covers the whole disc around the joint
+        for dy in -2..=2i32 {
+            for dx in -2..=2i32 {
+                if dx * dx + dy * dy <= 6 {
+                    let p = ((joint.x + dx) as u32, (joint.y + dy) as u32);
+
+                    assert!(pixels.contains(&p), "{:?} is not filled", p);
+                }
+            }
+        }
+
+        assert!(!pixels.contains(&(30, 43)));
+
+        image.clear();
+        polyline(&[joint], 5, &WHITE, &mut image);
+        assert_eq!(filled(&image).len(), 21);
+
+        image.clear();
+        polyline(&[], 5, &WHITE, &mut image);
+        assert!(filled(&image).is_empty());
+    }
 
     #[test]
     fn test_barycentric() {
@@ -339,34 +2292,854 @@ mod test_renderer_lib {
         let p4 = Vector3Int::new(0, 2, 0);
         let p5 = Vector3Int::new(2, 0, 0);
 
-        if let Some(bc) = barycentric(&[v1, v2, v3], p1) {
-            assert_eq!(bc.w == 0.5 && bc.u == 0., bc.v == 0.5);
-        } else {
-            panic!("Invalid barycentric calculation");
+        let bc = |p| barycentric(&[v1, v2, v3], p).expect("point is inside the triangle");
+        let coords = |lambda0, lambda1, lambda2| PointBarycentricCoords {
+            lambda0,
+            lambda1,
+            lambda2,
+        };
+
+        assert_eq!(bc(p1), coords(0.5, 0., 0.5));
+        assert_eq!(bc(p2), coords(0.5, 0.5, 0.));
+        assert_eq!(bc(p3), coords(1., 0., 0.));
+        assert_eq!(bc(p4), coords(0., 1., 0.));
+        assert_eq!(bc(p5), coords(0., 0., 1.));
+        assert!(barycentric(&[v1, v2, v3], Vector3Int::new(2, 2, 0)).is_none());
+        assert!(barycentric(&[v1, v1, v3], p1).is_none());
+    }
+
+    #[test]
+    fn test_barycentric_asymmetric() {
+        let triangle = [
+            Vector2Int::new(1, 1),
+            Vector2Int::new(9, 2),
+            Vector2Int::new(3, 7),
+        ];
+
+        for (i, vertex) in triangle.iter().enumerate() {
+            let bc = barycentric(&triangle, *vertex).unwrap();
+            let weights = [bc.lambda0, bc.lambda1, bc.lambda2];
+
+            for (j, weight) in weights.iter().enumerate() {
+                assert_eq!(*weight, if i == j { 1. } else { 0. }, "vertex {}", i);
+            }
         }
 
-        if let Some(bc) = barycentric(&[v1, v2, v3], p2) {
-            assert_eq!(bc.w == 0.5 && bc.u == 0.5, bc.v == 0.);
-        } else {
-            panic!("Invalid barycentric calculation");
+        let bc = barycentric(&triangle, Vector2Int::new(4, 3)).unwrap();
+
+        assert!((bc.interpolate([1., 9., 3.]) - 4.).abs() < 1e-6);
+        assert!((bc.interpolate([1., 2., 7.]) - 3.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_barycentric_reconstructs_point() {
+        let mut rng = StdRng::seed_from_u64(63);
+        let random_point =
+            |rng: &mut StdRng| Vector2F32::new(rng.gen_range(-1.0, 1.0), rng.gen_range(-1.0, 1.0));
+
+        for _ in 0..1000 {
+            let triangle = [
+                random_point(&mut rng),
+                random_point(&mut rng),
+                random_point(&mut rng),
+            ];
+            let (s, t) = (rng.gen_range(0.0, 1.0f32), rng.gen_range(0.0, 1.0f32));
+            let (s, t) = if s + t > 1.0 {
+                (1.0 - s, 1.0 - t)
+            } else {
+                (s, t)
+            };
+            let point = triangle[0] * (1.0 - s - t) + triangle[1] * s + triangle[2] * t;
+            let (side_one, side_two) = (triangle[1] - triangle[0], triangle[2] - triangle[0]);
+            let area = side_one.get_x() * side_two.get_y() - side_one.get_y() * side_two.get_x();
+
+            // nearly degenerate triangles are too sensitive to rounding
+            if area.abs() < 1e-2 {
+                continue;
+            }
+
+            let bc = barycentric(&triangle, point);
+            let bc = match bc {
+                Some(bc) => bc,
+                // rounding can push points on an edge slightly outside
+                None if s.min(t).min(1.0 - s - t) < 1e-3 => continue,
+                None => panic!("{} is outside of {:?}", point, triangle),
+            };
+            let restored = bc.interpolate(triangle);
+            let error = (restored.get_x() - point.get_x())
+                .abs()
+                .max((restored.get_y() - point.get_y()).abs());
+
+            assert!((bc.lambda0 + bc.lambda1 + bc.lambda2 - 1.0).abs() < 1e-4);
+            assert!(error < 1e-4, "{} restored as {}", point, restored);
         }
+    }
 
-        if let Some(bc) = barycentric(&[v1, v2, v3], p3) {
-            assert_eq!(bc.w == 1. && bc.u == 0., bc.v == 0.);
-        } else {
-            panic!("Invalid barycentric calculation");
+    #[test]
+    fn test_triangle_f32_subpixel_translation() {
+        let triangle = [
+            Vector3F32::new(2.0, 3.0, 0.0),
+            Vector3F32::new(30.0, 8.0, 0.0),
+            Vector3F32::new(10.0, 27.0, 0.0),
+        ];
+        let coverage = (0..10)
+            .map(|i| {
+                let offset = i as f32 * 0.1;
+                let mut image = TGAImage::try_new(40, 40, TGAImageFormat::RGB).unwrap();
+                let mut zbuf = ZBuffer::new(40, 40);
+                let moved =
+                    triangle.map(|p| Vector3F32::new(p.get_x() + offset, p.get_y(), p.get_z()));
+
+                triangle_barycentric_f32(moved, &mut zbuf, &WHITE, &mut image);
+
+                let filled = image
+                    .pixels()
+                    .filter(|(_, _, color)| color.r() != 0)
+                    .map(|(x, _, _)| x as f32 + 0.5)
+                    .collect::<Vec<_>>();
+
+                (
+                    filled.len() as i32,
+                    filled.iter().sum::<f32>() / filled.len() as f32 - offset,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // The triangle area is 316 pixels with the centroid at x = 14, only pixels
+        // along the edges may change between the offsets. Snapped vertices would
+        // keep the coverage in place and move the centroid away by up to a half pixel.
+        for pair in coverage.windows(2) {
+            assert!((pair[0].0 - pair[1].0).abs() <= 8, "{:?}", coverage);
         }
 
-        if let Some(bc) = barycentric(&[v1, v2, v3], p4) {
-            assert_eq!(bc.w == 0. && bc.u == 1., bc.v == 0.);
-        } else {
-            panic!("Invalid barycentric calculation");
+        for (count, centroid) in &coverage {
+            assert!((count - 316).abs() <= 8, "{:?}", coverage);
+            assert!((centroid - 14.0).abs() < 0.3, "{:?}", coverage);
+        }
+    }
+
+    #[test]
+    fn test_triangle_f32_outside_of_image() {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+        let triangle = [
+            Vector3F32::new(-10.0, -10.0, 0.0),
+            Vector3F32::new(-2.0, -10.0, 0.0),
+            Vector3F32::new(-10.0, -2.0, 0.0),
+        ];
+
+        triangle_barycentric_f32(triangle, &mut zbuf, &WHITE, &mut image);
+
+        assert!(image.pixels().all(|(_, _, color)| color.r() == 0));
+    }
+
+    #[test]
+    fn test_texture_lod() {
+        let points = [Vector2::new(0, 0), Vector2::new(10, 0), Vector2::new(0, 10)];
+        let texture = TextureDef(
+            Vector2F32::new(0., 0.),
+            Vector2F32::new(1., 0.),
+            Vector2F32::new(0., 1.),
+        );
+
+        assert_eq!(texture_lod(&points, &texture, (40, 40)), 2.0);
+        assert_eq!(texture_lod(&points, &texture, (10, 10)), 0.0);
+        assert_eq!(texture_lod(&points, &texture, (5, 5)), 0.0);
+    }
+
+    #[test]
+    fn test_gouraud_gradient() {
+        let mut image = TGAImage::try_new(16, 16, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(16, 16);
+
+        triangle_gouraud(
+            TriangleDef(
+                Vector3Int::new(0, 0, 0),
+                Vector3Int::new(0, 10, 0),
+                Vector3Int::new(10, 0, 0),
+            ),
+            [0.0, 0.0, 1.0],
+            &mut zbuf,
+            Diffuse::Color(WHITE),
+            &mut image,
+        );
+
+        // Along the bottom edge the weight of the lit vertex grows from 0 to 1
+        let edge = (0..=10).map(|x| image[(x, 0)][0]).collect::<Vec<_>>();
+
+        assert_eq!(edge[0], 0);
+        assert_eq!(edge[5], 127);
+        assert_eq!(edge[10], 255);
+        assert!(edge.windows(2).all(|pair| pair[0] < pair[1]));
+        // The opposite edge only blends the two unlit vertices
+        assert!((0..=10).all(|y| image[(0, y)][0] == 0));
+    }
+
+    #[test]
+    fn test_gouraud_negative_intensity() {
+        let mut image = TGAImage::try_new(16, 16, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(16, 16);
+
+        triangle_gouraud(
+            TriangleDef(
+                Vector3Int::new(0, 0, 0),
+                Vector3Int::new(0, 10, 0),
+                Vector3Int::new(10, 0, 0),
+            ),
+            [-1.0, -1.0, 1.0],
+            &mut zbuf,
+            Diffuse::Color(WHITE),
+            &mut image,
+        );
+
+        assert_eq!(image[(0, 0)][0], 0);
+        assert_eq!(image[(5, 0)][0], 0);
+        assert_eq!(image[(10, 0)][0], 255);
+        assert_eq!(zbuf.get(0, 0), 0.0);
+    }
+
+    #[test]
+    fn test_perspective_correct_texture() {
+        const SIZE: u32 = 64;
+        const CHECKS: u32 = 8;
+        let mut checkerboard = TGAImage::try_new(CHECKS, CHECKS, TGAImageFormat::RGB).unwrap();
+        let mut model = Model::default();
+        let mut image = TGAImage::try_new(SIZE, SIZE, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(SIZE, SIZE);
+
+        checkerboard.for_each_pixel(|x, y, pixel| {
+            pixel
+                .iter_mut()
+                .for_each(|v| *v = ((x + y) % 2) as u8 * 255)
+        });
+        model.set_texture(checkerboard).unwrap();
+
+        // A rectangle receding from w = 1 at the bottom of the screen to w = 4 at the
+        // top, so its far edge is projected four times shorter
+        let vertex = |x: f32, y: f32, w: f32| Vector4F32::new(x * w, y * w, 0.0, w);
+        let (near_left, near_right) = (vertex(0.0, 0.0, 1.0), vertex(64.0, 0.0, 1.0));
+        let (far_right, far_left) = (vertex(40.0, 64.0, 4.0), vertex(24.0, 64.0, 4.0));
+        let uv = Vector2F32::new;
+
+        for (triangle, texture) in [
+            (
+                TriangleDefF32(near_left, near_right, far_right),
+                TextureDef(uv(0.0, 0.0), uv(1.0, 0.0), uv(1.0, 1.0)),
+            ),
+            (
+                TriangleDefF32(near_left, far_right, far_left),
+                TextureDef(uv(0.0, 0.0), uv(1.0, 1.0), uv(0.0, 1.0)),
+            ),
+        ] {
+            triangle_perspective_with_texture(
+                triangle,
+                texture,
+                &mut zbuf,
+                &mut image,
+                &model,
+                TextureFilter::Nearest,
+                1.0,
+            );
+        }
+
+        // Along the screen 1 / w changes linearly from 1 to 1/4 and v / w from 0 to 1/4
+        let (x, y) = (SIZE / 2, SIZE / 2);
+        let t = (y as f32 + 0.5) / SIZE as f32;
+        let inv_w = 1.0 - 0.75 * t;
+        let v = 0.25 * t / inv_w;
+        let half_width = 32.0 * inv_w;
+        let u = 0.5 + (x as f32 + 0.5 - 32.0) / (2.0 * half_width);
+        let texel = |c: f32| (c * CHECKS as f32) as u32;
+        let expected = ((texel(u) + texel(v)) % 2) as u8 * 255;
+
+        // Linear interpolation would pick v = t instead
+        assert!((v - 0.205).abs() < 1e-3);
+        assert_ne!(texel(v), texel(t));
+        assert_eq!(&image[(x, y)], &[expected; 3]);
+        // Far texels are smaller on the screen
+        assert_eq!(image[(32, 5)], image[(32, 0)]);
+        assert_ne!(image[(32, 60)], image[(32, 56)]);
+    }
+
+    #[test]
+    fn test_zbuffer_overlapping_triangles() {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+        let (far, near) = (TGAColor::new_rgb(0, 0, 255), TGAColor::new_rgb(255, 0, 0));
+
+        // The near triangle is drawn first and must not be covered by the far one
+        triangle_barycentric_zbuf(
+            Vector3Int::new(0, 0, 20),
+            Vector3Int::new(0, 4, 20),
+            Vector3Int::new(4, 0, 20),
+            &mut zbuf,
+            &near,
+            &mut image,
+            None,
+        );
+        triangle_barycentric_zbuf(
+            Vector3Int::new(0, 0, 10),
+            Vector3Int::new(0, 7, 10),
+            Vector3Int::new(7, 0, 10),
+            &mut zbuf,
+            &far,
+            &mut image,
+            None,
+        );
+
+        assert_eq!(image.get(1, 1).r(), 255);
+        assert_eq!(image.get(5, 1).b(), 255);
+        assert_eq!((zbuf.get(1, 1), zbuf.get(5, 1)), (20.0, 10.0));
+        assert_eq!(zbuf.get(7, 7), f32::NEG_INFINITY);
+
+        let depth = zbuf.to_image();
+
+        assert_eq!(depth.get_bytespp(), TGAImageFormat::Grayscale16);
+        assert_eq!(depth.get_gray16(1, 1), u16::MAX);
+        assert_eq!(depth.get_gray16(5, 1), 0);
+        assert_eq!(depth.get_gray16(7, 7), 0);
+    }
+
+    #[test]
+    fn test_zbuffer_interpenetrating_triangles() {
+        // Both triangles cover the same pixels, their depths go in opposite
+        // directions along X and cross between columns 9 and 10 of every row
+        let rising = [
+            Vector3Int::new(0, 0, 0),
+            Vector3Int::new(19, 0, 20),
+            Vector3Int::new(0, 19, 0),
+        ];
+        let falling = [
+            Vector3Int::new(0, 0, 20),
+            Vector3Int::new(19, 0, 0),
+            Vector3Int::new(0, 19, 20),
+        ];
+        let (red, blue) = (TGAColor::new_rgb(255, 0, 0), TGAColor::new_rgb(0, 0, 255));
+        let render = |order: [(&[Vector3Int; 3], &TGAColor); 2]| {
+            let mut image = TGAImage::try_new(20, 20, TGAImageFormat::RGB).unwrap();
+            let mut zbuf = ZBuffer::new(20, 20);
+
+            for (points, color) in order.iter() {
+                triangle_barycentric_zbuf(
+                    points[0], points[1], points[2], &mut zbuf, color, &mut image, None,
+                );
+            }
+
+            image
+        };
+        let image = render([(&rising, &red), (&falling, &blue)]);
+
+        for y in [1, 5] {
+            for x in 0..(19 - y) {
+                let expected = if x < 10 { &blue } else { &red };
+
+                assert_eq!(
+                    image.get(x, y).channels(),
+                    &expected.channels()[..3],
+                    "pixel ({}, {})",
+                    x,
+                    y
+                );
+            }
+        }
+
+        assert_eq!(
+            image.as_bytes(),
+            render([(&falling, &blue), (&rising, &red)]).as_bytes()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match the image")]
+    fn test_zbuffer_size_mismatch() {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 4);
+
+        triangle_barycentric_zbuf(
+            Vector3Int::new(0, 0, 0),
+            Vector3Int::new(0, 7, 0),
+            Vector3Int::new(7, 0, 0),
+            &mut zbuf,
+            &WHITE,
+            &mut image,
+            None,
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_render_model_parallel_matches_serial() {
+        use crate::render_model_parallel;
+
+        let mut model = Model::unit_cube();
+        let mut texture = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+
+        texture.for_each_pixel(|x, y, pixel| pixel.fill(((x + y) % 2 * 200) as u8));
+        model.set_texture(texture).unwrap();
+
+        let (width, height) = (200, 150);
+        let eye = Vector3F32::new(1.0, 1.0, 3.0);
+        let center = Vector3F32::new(0.0, 0.0, 0.0);
+        let transform = viewport(0, 0, width, height, 255)
+            * projection((eye - center).norm_f32())
+            * lookat(eye, center, Vector3F32::new(0.0, 1.0, 0.0));
+
+        for &(textured, color, format) in &[
+            (false, FaceColor::Flat(WHITE), TGAImageFormat::RGB),
+            (false, FaceColor::Random, TGAImageFormat::RGB),
+            (true, FaceColor::Random, TGAImageFormat::RGB),
+            (false, FaceColor::Random, TGAImageFormat::Grayscale16),
+            (true, FaceColor::Random, TGAImageFormat::Grayscale16),
+        ] {
+            let opts = RenderOptions {
+                textured,
+                color,
+                transform,
+                ..RenderOptions::new(width, height, 255)
+            };
+            let render = |parallel: bool| {
+                let mut image = TGAImage::try_new(width, height, format).unwrap();
+                let mut zbuf = ZBuffer::new(width, height);
+                let stats = if parallel {
+                    render_model_parallel(&model, &mut image, &mut zbuf, &opts)
+                } else {
+                    render_model(&model, &mut image, &mut zbuf, &opts)
+                };
+
+                (image, zbuf, stats)
+            };
+            let (serial, serial_zbuf, serial_stats) = render(false);
+            let (parallel, parallel_zbuf, parallel_stats) = render(true);
+
+            assert_eq!(serial.as_bytes(), parallel.as_bytes());
+            assert_eq!(serial_zbuf.as_slice(), parallel_zbuf.as_slice());
+            // everything but the wall times
+            assert_eq!(
+                RenderStats {
+                    setup_time: parallel_stats.setup_time,
+                    raster_time: parallel_stats.raster_time,
+                    ..serial_stats
+                },
+                parallel_stats
+            );
+            assert!(serial
+                .pixels()
+                .any(|(_, y, color)| y > 64 && color.channels().iter().any(|c| *c != 0)));
+        }
+    }
+
+    #[test]
+    fn test_render_model_culling() {
+        let model = Model::unit_cube();
+        // Front and back faces have the same depth on the silhouette, the view
+        // keeps every silhouette pixel closer to a front face
+        let eye = Vector3F32::new(1.0, 1.5, 3.0);
+        let center = Vector3F32::new(0.0, 0.0, 0.0);
+        let transform = viewport(0, 0, 64, 64, 255)
+            * projection((eye - center).norm_f32())
+            * lookat(eye, center, Vector3F32::new(0.0, 1.0, 0.0));
+        let render = |cull_mode| {
+            let mut image = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+            let mut zbuf = ZBuffer::new(64, 64);
+            let opts = RenderOptions {
+                cull_mode,
+                transform,
+                ..RenderOptions::new(64, 64, 255)
+            };
+            let stats = render_model(&model, &mut image, &mut zbuf, &opts);
+
+            (image, stats)
+        };
+        let (all, all_stats) = render(CullMode::None);
+        let (front, front_stats) = render(CullMode::Back);
+        let (back, back_stats) = render(CullMode::Front);
+
+        // Three of the six cube sides face the camera
+        assert_eq!(
+            (all_stats.submitted, all_stats.drawn, all_stats.culled),
+            (12, 12, 0)
+        );
+        assert_eq!(
+            (front_stats.submitted, front_stats.drawn, front_stats.culled),
+            (12, 6, 6)
+        );
+        assert_eq!(
+            (back_stats.submitted, back_stats.drawn, back_stats.culled),
+            (12, 6, 6)
+        );
+        assert_eq!(all.as_bytes(), front.as_bytes());
+        assert_ne!(all.as_bytes(), back.as_bytes());
+        assert!(all.as_bytes().iter().any(|v| *v != 0));
+    }
+
+    #[test]
+    fn test_render_cube_face_formats() {
+        let load = |name: &str| {
+            Model::new(&format!(
+                "{}/tests/data/{}",
+                env!("CARGO_MANIFEST_DIR"),
+                name
+            ))
+            .unwrap()
+        };
+        let eye = Vector3F32::new(1.0, 1.0, 3.0);
+        let center = Vector3F32::new(0.0, 0.0, 0.0);
+        let transform = viewport(0, 0, 64, 64, 255)
+            * projection((eye - center).norm_f32())
+            * lookat(eye, center, Vector3F32::new(0.0, 1.0, 0.0));
+        let draw = |model: &Model| {
+            let mut image = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+            let mut shaded = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+            let mut zbuf = ZBuffer::new(64, 64);
+            let opts = RenderOptions {
+                transform,
+                ..RenderOptions::new(64, 64, 255)
+            };
+
+            wireframe(model, &WHITE, &mut image, &transform, CullMode::Back);
+            render_model(model, &mut shaded, &mut zbuf, &opts);
+
+            (image, shaded)
+        };
+        let (expected_image, expected_shaded) = draw(&load("cube.obj"));
+
+        assert!(expected_image.pixels().any(|(_, _, p)| p.r() == 255));
+
+        // Without texture coordinates and normals, and with quads split into triangles
+        for name in &["cube_v.obj", "cube_quads.obj"] {
+            let (image, shaded) = draw(&load(name));
+
+            assert_eq!(image.as_bytes(), expected_image.as_bytes(), "{}", name);
+            assert_eq!(shaded.as_bytes(), expected_shaded.as_bytes(), "{}", name);
         }
+    }
+
+    #[test]
+    fn test_gouraud_computed_normals() {
+        let mut model = Model::new(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/cube_v.obj"
+        ))
+        .unwrap();
+        let eye = Vector3F32::new(1.0, 1.0, 3.0);
+        let center = Vector3F32::new(0.0, 0.0, 0.0);
+        let transform = viewport(0, 0, 64, 64, 255)
+            * projection((eye - center).norm_f32())
+            * lookat(eye, center, Vector3F32::new(0.0, 1.0, 0.0));
+        let mut light_dir = center - eye;
+
+        light_dir.normalize_default();
+
+        let shades = |model: &Model| {
+            let mut image = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+            let mut zbuf = ZBuffer::new(64, 64);
+
+            for i in 0..model.n_faces() {
+                let face = model.face(i);
+                let screen = face.map(|v| {
+                    transform
+                        .transform_point(*model.vert(v as usize))
+                        .to_int_round()
+                });
+                let intensities = [0, 1, 2].map(|j| -model.normal(i, j).dot(&light_dir));
+
+                triangle_gouraud(
+                    TriangleDef(screen[0], screen[1], screen[2]),
+                    intensities,
+                    &mut zbuf,
+                    Diffuse::Color(WHITE),
+                    &mut image,
+                );
+            }
+
+            let mut shades = image.pixels().map(|(_, _, p)| p.r()).collect::<Vec<_>>();
+
+            shades.sort_unstable();
+            shades.dedup();
+            shades.len()
+        };
 
-        if let Some(bc) = barycentric(&[v1, v2, v3], p5) {
-            assert_eq!(bc.w == 0. && bc.u == 0., bc.v == 1.);
+        // Background and the three visible sides lit with the face normals
+        assert!(shades(&model) <= 4, "{}", shades(&model));
+        model.compute_vertex_normals();
+        // Smooth gradients over the sides with the vertex normals
+        assert!(shades(&model) > 20, "{}", shades(&model));
+    }
+
+    #[test]
+    fn test_wireframe_cube() {
+        let model = Model::unit_cube();
+        let eye = Vector3F32::new(1.0, 1.0, 3.0);
+        let center = Vector3F32::new(0.0, 0.0, 0.0);
+        let transform = viewport(0, 0, 64, 64, 255)
+            * projection((eye - center).norm_f32())
+            * lookat(eye, center, Vector3F32::new(0.0, 1.0, 0.0));
+        let count = |image: &TGAImage| image.pixels().filter(|(_, _, p)| p.r() == 255).count();
+        let draw = |cull_mode| {
+            let mut image = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+
+            wireframe(&model, &WHITE, &mut image, &transform, cull_mode);
+
+            image
+        };
+        let all = draw(CullMode::None);
+        let front = draw(CullMode::Back);
+
+        // 18 edges of about 20 to 30 pixels, 12 of them on the visible sides
+        assert!((400..480).contains(&count(&all)), "{}", count(&all));
+        assert!((290..360).contains(&count(&front)), "{}", count(&front));
+
+        let mut image = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(64, 64);
+        let opts = RenderOptions {
+            transform,
+            color: FaceColor::Flat(BLUE),
+            ..RenderOptions::new(64, 64, 255)
+        };
+
+        render_model(&model, &mut image, &mut zbuf, &opts);
+        wireframe_zbuf(
+            &model,
+            &WHITE,
+            &zbuf,
+            16.0,
+            &mut image,
+            &transform,
+            CullMode::None,
+        );
+
+        // Every edge of the visible sides is drawn over the shaded cube, while the
+        // hidden ones only touch it where they meet the visible sides
+        assert!(front
+            .pixels()
+            .all(|(x, y, p)| p.r() == 0 || image.get(x, y).r() == 255));
+        assert!(count(&image) < count(&front) + 16, "{}", count(&image));
+    }
+
+    #[test]
+    fn test_zbuffer_to_image() {
+        let zbuf = [f32::NEG_INFINITY, -2.0, 0.5, 3.0, 3.0, f32::NAN];
+        let image = zbuffer_to_image(&zbuf, 3, 2);
+
+        assert_eq!(image.get_gray16(0, 0), 0);
+        assert_eq!(image.get_gray16(1, 0), 0);
+        assert_eq!(image.get_gray16(2, 0), 32768);
+        assert_eq!(image.get_gray16(0, 1), 65535);
+        assert_eq!(image.get_gray16(1, 1), 65535);
+        assert_eq!(image.get_gray16(2, 1), 0);
+
+        let flat = zbuffer_to_image(&[7.0; 4], 2, 2);
+
+        assert!((0..2).all(|x| flat.get_gray16(x, 1) == u16::MAX));
+    }
+
+    #[test]
+    fn test_frame_filename() {
+        assert_eq!(frame_filename("frame_{}.tga", 7).unwrap(), "frame_7.tga");
+        assert_eq!(
+            frame_filename("frame_{:03}.tga", 7).unwrap(),
+            "frame_007.tga"
+        );
+        assert_eq!(frame_filename("{:02}", 123).unwrap(), "123");
+        assert!(frame_filename("frame.tga", 0).is_err());
+        assert!(frame_filename("frame_{:x}.tga", 0).is_err());
+        assert!(frame_filename("frame_{.tga", 0).is_err());
+    }
+
+    #[test]
+    fn test_fill_triangle_counting() {
+        let v = [
+            Vector3Int::new(0, 0, 10),
+            Vector3Int::new(4, 0, 10),
+            Vector3Int::new(0, 4, 10),
+        ];
+        let fill = |count: bool| {
+            let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+            let mut zbuf = ZBuffer::new(8, 8);
+            let mut frame = FrameMut {
+                image: &mut image,
+                zbuf: &mut zbuf,
+            };
+            let stats = if count {
+                fill_triangle::<true, _, _>(&v, &mut frame, |_| RED)
+            } else {
+                fill_triangle::<false, _, _>(&v, &mut frame, |_| RED)
+            };
+
+            (stats, filled(&image).len())
+        };
+        let (counted, counted_pixels) = fill(true);
+        let (uncounted, uncounted_pixels) = fill(false);
+
+        assert_eq!(
+            (
+                counted.pixels_tested,
+                counted.pixels_inside,
+                counted.pixels_drawn
+            ),
+            (25, 15, 15)
+        );
+        assert_eq!(
+            (
+                uncounted.pixels_tested,
+                uncounted.pixels_inside,
+                uncounted.pixels_drawn
+            ),
+            (0, 0, 0)
+        );
+        assert_eq!((counted_pixels, uncounted_pixels), (15, 15));
+    }
+
+    #[test]
+    fn test_render_stats_single_triangle() {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+        let mut stats = RenderStats::default();
+        let v = [
+            Vector3Int::new(0, 0, 10),
+            Vector3Int::new(4, 0, 10),
+            Vector3Int::new(0, 4, 10),
+        ];
+
+        triangle_barycentric_zbuf(
+            v[0],
+            v[1],
+            v[2],
+            &mut zbuf,
+            &RED,
+            &mut image,
+            Some(&mut stats),
+        );
+
+        // 5x5 bounding box with 15 pixels on and under the x + y = 4 diagonal
+        assert_eq!((stats.submitted, stats.drawn, stats.empty), (1, 1, 0));
+        assert_eq!(
+            (stats.pixels_tested, stats.pixels_inside, stats.pixels_drawn),
+            (25, 15, 15)
+        );
+        assert_eq!(filled(&image).len(), 15);
+
+        // The same depth fails the depth test, the triangle off the image is empty
+        triangle_barycentric_zbuf(
+            v[0],
+            v[1],
+            v[2],
+            &mut zbuf,
+            &RED,
+            &mut image,
+            Some(&mut stats),
+        );
+        triangle_barycentric_zbuf(
+            Vector3Int::new(-9, 0, 10),
+            Vector3Int::new(-5, 0, 10),
+            Vector3Int::new(-9, 4, 10),
+            &mut zbuf,
+            &RED,
+            &mut image,
+            Some(&mut stats),
+        );
+
+        assert_eq!((stats.submitted, stats.drawn, stats.empty), (3, 2, 1));
+        assert_eq!(
+            (stats.pixels_tested, stats.pixels_inside, stats.pixels_drawn),
+            (50, 30, 15)
+        );
+    }
+
+    /// Scanline fill walking the edges from the start for every row, the way
+    /// [`triangle`] used to work
+    fn triangle_reference(
+        v1: Vector2Int,
+        v2: Vector2Int,
+        v3: Vector2Int,
+        color: &TGAColor,
+        image: &mut TGAImage,
+    ) {
+        let fill_flat = |v1: Vector2Int, v2: Vector2Int, v3: Vector2Int, image: &mut TGAImage| {
+            let slope1 = Line::new(
+                Point::new(v1.get_x(), v1.get_y()),
+                Point::new(v2.get_x(), v2.get_y()),
+            );
+            let slope2 = Line::new(
+                Point::new(v1.get_x(), v1.get_y()),
+                Point::new(v3.get_x(), v3.get_y()),
+            );
+
+            for y in v1.get_y().min(v2.get_y())..=v1.get_y().max(v2.get_y()) {
+                let mut min_p: i32 = i32::MAX;
+                let mut max_p: i32 = i32::MIN;
+
+                for slope in &[slope1, slope2] {
+                    slope
+                        .points()
+                        .skip_while(|p| p.y != y)
+                        .take_while(|p| p.y == y)
+                        .for_each(|p| {
+                            min_p = min_p.min(p.x);
+                            max_p = max_p.max(p.x);
+                        });
+                }
+
+                for x in min_p..=max_p {
+                    image.set(x as u32, y as u32, color);
+                }
+            }
+        };
+
+        let mut verts = [v1, v2, v3];
+
+        sort_by_y(&mut verts);
+
+        let [v1, v2, v3] = verts;
+
+        if v2.get_y() == v3.get_y() {
+            fill_flat(v1, v2, v3, image);
+        } else if v1.get_y() == v2.get_y() {
+            fill_flat(v3, v1, v2, image);
         } else {
-            panic!("Invalid barycentric calculation");
+            let v4 = Vector2::new(
+                (v1.get_x() as f32
+                    + ((v2.get_y() - v1.get_y()) as f32 / (v3.get_y() - v1.get_y()) as f32)
+                        * (v3.get_x() - v1.get_x()) as f32) as i32,
+                v2.get_y(),
+            );
+
+            fill_flat(v1, v2, v4, image);
+            fill_flat(v3, v2, v4, image);
+        }
+    }
+
+    #[test]
+    fn test_triangle_matches_reference() {
+        let mut rng = StdRng::seed_from_u64(87);
+
+        for _ in 0..300 {
+            let mut point = || Vector2Int::new(rng.gen_range(0, 64), rng.gen_range(0, 64));
+            let v = [point(), point(), point()];
+            let mut image = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+            let mut expected = TGAImage::try_new(64, 64, TGAImageFormat::RGB).unwrap();
+
+            triangle(v[0], v[1], v[2], &WHITE, &mut image);
+            triangle_reference(v[0], v[1], v[2], &WHITE, &mut expected);
+
+            assert!(!filled(&image).is_empty());
+            assert_eq!(filled(&image), filled(&expected), "{:?}", v);
+        }
+
+        // Slivers one pixel wide
+        for &(x0, x1) in &[(0, 0), (0, 1), (5, 3)] {
+            let mut image = TGAImage::try_new(8, 64, TGAImageFormat::RGB).unwrap();
+            let mut expected = TGAImage::try_new(8, 64, TGAImageFormat::RGB).unwrap();
+            let v = [
+                Vector2Int::new(x0, 0),
+                Vector2Int::new(x1, 63),
+                Vector2Int::new(x0, 40),
+            ];
+
+            triangle(v[0], v[1], v[2], &WHITE, &mut image);
+            triangle_reference(v[0], v[1], v[2], &WHITE, &mut expected);
+
+            assert_eq!(filled(&image), filled(&expected), "{:?}", v);
         }
     }
 }
@@ -0,0 +1,336 @@
+use tgaimage::colors::WHITE;
+use tgaimage::{ColorChannel, TGAColor};
+
+use crate::geometry::Vector3F32;
+
+/// Reflect a direction off a surface with the unit `normal`
+///
+/// `dir` points towards the surface and the result points away from it.
+pub fn reflect(dir: Vector3F32, normal: Vector3F32) -> Vector3F32 {
+    dir.reflect(&normal)
+}
+
+/// Shape of the light emitted by a [`Light`]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LightKind {
+    /// Parallel rays along the unit direction `dir` from the light to the model
+    Directional { dir: Vector3F32 },
+    /// Rays from `pos` dimmed by `1 / (1 + attenuation * d²)` at distance `d`
+    Point { pos: Vector3F32, attenuation: f32 },
+}
+
+/// Colored light source in the model coordinates
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Light {
+    pub kind: LightKind,
+    pub color: TGAColor,
+    pub intensity: f32,
+}
+
+impl Light {
+    /// Unit direction from the light to `point` and the attenuation of the light
+    /// reaching it
+    pub fn direction_at(&self, point: Vector3F32) -> (Vector3F32, f32) {
+        match self.kind {
+            LightKind::Directional { dir } => (dir, 1.0),
+            LightKind::Point { pos, attenuation } => {
+                let mut dir = point - pos;
+                let distance_squared = dir.length_squared();
+
+                if distance_squared > 0.0 {
+                    dir.normalize_default();
+                }
+
+                (dir, 1.0 / (1.0 + attenuation * distance_squared))
+            }
+        }
+    }
+}
+
+/// Phong lighting of a surface by a single light
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LightingParams {
+    /// Unit direction from the light to the model
+    pub light_dir: Vector3F32,
+    /// Unit direction from the model to the viewer
+    pub view_dir: Vector3F32,
+    /// Light added to every pixel regardless of its normal, in `0.0..=1.0`
+    pub ambient: f32,
+    pub diffuse: f32,
+    pub specular: f32,
+}
+
+impl LightingParams {
+    /// Viewer on the positive Z axis and a dim ambient light
+    pub fn new(light_dir: Vector3F32) -> Self {
+        LightingParams {
+            light_dir,
+            view_dir: Vector3F32::new(0.0, 0.0, 1.0),
+            ambient: 0.02,
+            diffuse: 1.0,
+            specular: 0.6,
+        }
+    }
+
+    /// Diffuse and specular terms for a unit surface `normal`
+    ///
+    /// The specular term is `max(0, r·v)^shininess` with the reflected light
+    /// direction `r`. Surfaces facing away from the light and a zero `shininess`
+    /// get no highlight.
+    pub fn terms(&self, normal: Vector3F32, shininess: f32) -> (f32, f32) {
+        self.terms_from(self.light_dir, normal, shininess)
+    }
+
+    fn terms_from(&self, light_dir: Vector3F32, normal: Vector3F32, shininess: f32) -> (f32, f32) {
+        let diffuse = (-normal.dot(&light_dir)).max(0.0);
+        let specular = if diffuse > 0.0 && shininess > 0.0 {
+            reflect(light_dir, normal)
+                .dot(&self.view_dir)
+                .max(0.0)
+                .powf(shininess)
+        } else {
+            0.0
+        };
+
+        (diffuse, specular)
+    }
+
+    /// Light a surface color by a white light along `light_dir`, every channel
+    /// is clamped to 255
+    pub fn shade(&self, color: TGAColor, normal: Vector3F32, shininess: f32) -> TGAColor {
+        let light = Light {
+            kind: LightKind::Directional {
+                dir: self.light_dir,
+            },
+            color: WHITE,
+            intensity: 1.0,
+        };
+
+        self.shade_lights(&[light], color, normal, Vector3F32::default(), shininess)
+    }
+
+    /// Light a surface color at `point` by every light, `light_dir` is ignored
+    ///
+    /// Diffuse and specular contributions of the lights are added up and every
+    /// channel is clamped to 255. The ambient light is added once.
+    pub fn shade_lights(
+        &self,
+        lights: &[Light],
+        color: TGAColor,
+        normal: Vector3F32,
+        point: Vector3F32,
+        shininess: f32,
+    ) -> TGAColor {
+        let mut k = [0f32; 3];
+
+        for light in lights {
+            let (dir, attenuation) = light.direction_at(point);
+            let (diffuse, specular) = self.terms_from(dir, normal, shininess);
+            let energy =
+                (self.diffuse * diffuse + self.specular * specular) * light.intensity * attenuation;
+
+            k[0] += energy * light.color.r() as f32 / 255.0;
+            k[1] += energy * light.color.g() as f32 / 255.0;
+            k[2] += energy * light.color.b() as f32 / 255.0;
+        }
+
+        let ambient = (self.ambient * 255.0).round().clamp(0.0, 255.0) as u8;
+        let mut lit = color.scaled(k[0], k[1], k[2]);
+
+        for &channel in &[ColorChannel::R, ColorChannel::G, ColorChannel::B] {
+            lit[channel] = lit[channel].saturating_add(ambient);
+        }
+
+        lit
+    }
+}
+
+#[cfg(test)]
+mod test_lighting {
+    use tgaimage::colors::WHITE;
+    use tgaimage::TGAColor;
+
+    use crate::assert_vec_eq;
+    use crate::geometry::{Vector3F32, XAxis, ZAxis};
+    use crate::lighting::{reflect, Light, LightKind, LightingParams};
+
+    #[test]
+    fn reflect_off_surface() {
+        let up = Vector3F32::new(0.0, 0.0, 1.0);
+        let mut dir = Vector3F32::new(1.0, 0.0, -1.0);
+
+        dir.normalize_default();
+
+        let r = reflect(dir, up);
+
+        assert_vec_eq!(r, Vector3F32::new(dir.get_x(), 0.0, -dir.get_z()));
+        assert!((r.dot(&r) - 1.0).abs() < 1e-5);
+        assert_vec_eq!(reflect(Vector3F32::new(0.0, 0.0, -1.0), up), up);
+        assert_vec_eq!(
+            reflect(Vector3F32::new(0.0, 1.0, 0.0), up),
+            Vector3F32::new(0.0, 1.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn phong_terms() {
+        let normal = Vector3F32::new(0.0, 0.0, 1.0);
+        let params = LightingParams::new(Vector3F32::new(0.0, 0.0, -1.0));
+
+        assert_eq!(params.terms(normal, 10.0), (1.0, 1.0));
+        assert_eq!(params.terms(normal, 0.0), (1.0, 0.0));
+
+        let mut light_dir = Vector3F32::new(1.0, 0.0, -1.0);
+
+        light_dir.normalize_default();
+
+        let (diffuse, specular) = LightingParams::new(light_dir).terms(normal, 2.0);
+
+        assert!((diffuse - 0.5f32.sqrt()).abs() < 1e-5);
+        assert!((specular - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    fn phong_facing_away_is_ambient() {
+        let params = LightingParams {
+            ambient: 0.1,
+            ..LightingParams::new(Vector3F32::new(0.0, 0.0, -1.0))
+        };
+        let color = params.shade(WHITE, Vector3F32::new(0.0, 0.0, -1.0), 10.0);
+
+        assert_eq!(
+            params.terms(Vector3F32::new(0.0, 0.0, -1.0), 10.0),
+            (0.0, 0.0)
+        );
+        assert_eq!((color.r(), color.g(), color.b()), (26, 26, 26));
+
+        // diffuse, specular and ambient light add up above 255
+        let color = params.shade(
+            TGAColor::new_rgb(200, 100, 0),
+            Vector3F32::new(0.0, 0.0, 1.0),
+            10.0,
+        );
+
+        assert_eq!((color.r(), color.g(), color.b()), (255, 186, 26));
+    }
+
+    #[test]
+    fn point_light_direction_and_attenuation() {
+        let light = Light {
+            kind: LightKind::Point {
+                pos: Vector3F32::new(0.0, 0.0, 2.0),
+                attenuation: 0.5,
+            },
+            color: WHITE,
+            intensity: 1.0,
+        };
+        let (dir, attenuation) = light.direction_at(Vector3F32::new(0.0, 0.0, 0.0));
+
+        assert_vec_eq!(dir, Vector3F32::new(0.0, 0.0, -1.0));
+        assert!((attenuation - 1.0 / 3.0).abs() < 1e-5);
+        assert_eq!(light.direction_at(Vector3F32::new(0.0, 0.0, 2.0)).1, 1.0);
+    }
+
+    #[test]
+    fn half_intensity_lights_add_up() {
+        let params = LightingParams::new(Vector3F32::new(0.0, 0.0, -1.0));
+        let color = TGAColor::new_rgb(180, 120, 60);
+        let point = Vector3F32::new(0.2, -0.1, 0.0);
+        let mut normal = Vector3F32::new(0.3, 0.2, 1.0);
+
+        normal.normalize_default();
+
+        for &kind in &[
+            LightKind::Directional {
+                dir: Vector3F32::new(0.0, 0.0, -1.0),
+            },
+            LightKind::Point {
+                pos: Vector3F32::new(-1.0, 1.0, 3.0),
+                attenuation: 0.1,
+            },
+        ] {
+            let light = |intensity| Light {
+                kind,
+                color: TGAColor::new_rgb(255, 200, 150),
+                intensity,
+            };
+            let full = params.shade_lights(&[light(1.0)], color, normal, point, 8.0);
+            let halves = params.shade_lights(&[light(0.5), light(0.5)], color, normal, point, 8.0);
+
+            for (a, b) in full.as_slice()[..3]
+                .iter()
+                .zip(halves.as_slice()[..3].iter())
+            {
+                assert!(
+                    (*a as i32 - *b as i32).abs() <= 1,
+                    "{:?} != {:?}",
+                    full,
+                    halves
+                );
+            }
+        }
+
+        // a single white light along the light direction is the plain shading
+        assert_eq!(
+            params.shade(color, normal, 8.0),
+            params.shade_lights(
+                &[Light {
+                    kind: LightKind::Directional {
+                        dir: params.light_dir
+                    },
+                    color: WHITE,
+                    intensity: 1.0,
+                }],
+                color,
+                normal,
+                point,
+                8.0
+            )
+        );
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test_lighting_serde {
+    use tgaimage::colors::RED;
+
+    use crate::geometry::Vector3F32;
+    use crate::lighting::{Light, LightKind};
+    use crate::RenderOptions;
+
+    #[test]
+    fn light_round_trip() {
+        let light = Light {
+            kind: LightKind::Point {
+                pos: Vector3F32::new(0.0, 1.0, 2.0),
+                attenuation: 0.5,
+            },
+            color: RED,
+            intensity: 0.75,
+        };
+        let json = serde_json::to_string(&light).unwrap();
+        let back: Light = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+        assert_eq!(back.color, RED);
+        assert!(json.contains(r#""pos":{"x":0.0,"y":1.0,"z":2.0}"#));
+
+        // the options serialize their lights, but not the shadow map
+        let lights = [light];
+        let opts = RenderOptions {
+            lights: &lights,
+            ..RenderOptions::new(8, 8, 255)
+        };
+        let value = serde_json::to_value(opts).unwrap();
+
+        assert_eq!(
+            value["lights"][0],
+            serde_json::from_str::<serde_json::Value>(&json).unwrap()
+        );
+        assert!(value.get("shadow").is_none());
+        assert_eq!(value["transform"].as_array().unwrap().len(), 16);
+    }
+}
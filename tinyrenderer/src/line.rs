@@ -1,3 +1,4 @@
+use crate::geometry::{Vector2F32, XAxis, YAxis};
 use crate::point::Point;
 
 #[derive(Copy, Clone, Debug)]
@@ -14,7 +15,6 @@ impl<T> MajorMinor<T> {
 
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct SlopeParameters {
-    is_steep: bool,
     error_threshold: i32,
     derror_step: MajorMinor<i32>,
     position_step: MajorMinor<Point>,
@@ -23,14 +23,12 @@ pub(crate) struct SlopeParameters {
 
 impl SlopeParameters {
     pub(crate) const fn new(
-        is_steep: bool,
         error_threshold: i32,
         derror_step: MajorMinor<i32>,
         position_step: MajorMinor<Point>,
         length: u32,
     ) -> Self {
         SlopeParameters {
-            is_steep,
             error_threshold,
             derror_step,
             position_step,
@@ -47,24 +45,21 @@ impl From<&Line> for SlopeParameters {
         let dx_abs = dx.abs();
         let dy_abs = dy.abs();
 
-        let (delta, step, is_steep, length) = if dx_abs < dy_abs {
+        let (delta, step, length) = if dx_abs < dy_abs {
             (
                 MajorMinor::new(dy_abs, dx_abs),
                 MajorMinor::new(Point::new(0, dy.signum()), Point::new(dx.signum(), 0)),
-                true,
                 dy_abs as u32,
             )
         } else {
             (
                 MajorMinor::new(dx_abs, dy_abs),
                 MajorMinor::new(Point::new(dx.signum(), 0), Point::new(0, dy.signum())),
-                false,
                 dx_abs as u32,
             )
         };
 
         SlopeParameters::new(
-            is_steep,
             delta.major,
             MajorMinor::new(2 * delta.minor, 2 * delta.major),
             step,
@@ -100,8 +95,38 @@ impl BresenhamWrapper {
 
         ret
     }
+
+    /// Skip `steps` points without walking them
+    fn advance(&mut self, parameters: &SlopeParameters, steps: u32) {
+        if steps == 0 {
+            return;
+        }
+
+        // the error before the last skipped check if no minor steps were taken,
+        // every minor step taken so far subtracts one `derror_step.minor` from it
+        let threshold = parameters.error_threshold as i64;
+        let (added, subtracted) = (
+            parameters.derror_step.major as i64,
+            parameters.derror_step.minor as i64,
+        );
+        let accumulated = self.error as i64 + (steps as i64 - 1) * added;
+        let minor_steps = if accumulated > threshold {
+            (accumulated - threshold - 1) / subtracted + 1
+        } else {
+            0
+        };
+        let major = parameters.position_step.major;
+        let minor = parameters.position_step.minor;
+
+        self.current_point += Point::new(
+            major.x * steps as i32 + minor.x * minor_steps as i32,
+            major.y * steps as i32 + minor.y * minor_steps as i32,
+        );
+        self.error = (self.error as i64 + steps as i64 * added - minor_steps * subtracted) as i32;
+    }
 }
 
+/// Pixels of a [`Line`] from its start to its end, both included
 #[derive(Copy, Clone, Debug)]
 pub struct Points {
     parameters: SlopeParameters,
@@ -135,10 +160,48 @@ impl Iterator for Points {
             None
         }
     }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.points_remaining as usize {
+            self.points_remaining = 0;
+
+            return None;
+        }
+
+        self.bresenham.advance(&self.parameters, n as u32);
+        self.points_remaining -= n as u32;
+
+        self.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.points_remaining as usize;
+
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Points {}
+
+impl DoubleEndedIterator for Points {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.points_remaining > 0 {
+            self.points_remaining -= 1;
+
+            let mut last = self.bresenham;
+
+            last.advance(&self.parameters, self.points_remaining);
+
+            Some(last.next(&self.parameters))
+        } else {
+            None
+        }
+    }
 }
 
+/// Segment between two pixels rasterized with the Bresenham algorithm
 #[derive(Copy, Clone, Debug, Default)]
-pub(crate) struct Line {
+pub struct Line {
     pub start: Point,
     pub end: Point,
 }
@@ -148,7 +211,128 @@ impl Line {
         Line { start, end }
     }
 
+    /// Pixels of the line walking from `start` to `end`
+    ///
+    /// A line and its reverse may differ in the pixels where the line passes
+    /// exactly halfway between two rows or columns.
     pub fn points(&self) -> Points {
         Points::new(self)
     }
 }
+
+/// Segment between two sub-pixel positions rasterized with a DDA
+#[derive(Copy, Clone, Debug, Default)]
+pub struct LineF32 {
+    pub start: Vector2F32,
+    pub end: Vector2F32,
+}
+
+impl LineF32 {
+    pub const fn new(start: Vector2F32, end: Vector2F32) -> Self {
+        LineF32 { start, end }
+    }
+
+    /// Pixels of the line from `start` to `end` with the line parameter `t` in
+    /// `0.0..=1.0` at every pixel
+    ///
+    /// Whole number endpoints give the pixels of the integer [`Line`].
+    pub fn points(&self) -> PointsF32 {
+        PointsF32::new(self)
+    }
+}
+
+/// Pixels of a [`LineF32`] stepping one pixel along its major axis
+#[derive(Copy, Clone, Debug)]
+pub struct PointsF32 {
+    start: MajorMinor<f32>,
+    delta: MajorMinor<f32>,
+    is_steep: bool,
+    major: i32,
+    major_step: i32,
+    points_remaining: u32,
+}
+
+/// Round `value` to a pixel, halves go back against `delta` like in [`Line`]
+fn round_from(value: f32, delta: f32) -> i32 {
+    if delta < 0.0 {
+        (value + 0.5).floor() as i32
+    } else {
+        (value - 0.5).ceil() as i32
+    }
+}
+
+impl PointsF32 {
+    fn new(line: &LineF32) -> Self {
+        let (dx, dy) = (
+            line.end.get_x() - line.start.get_x(),
+            line.end.get_y() - line.start.get_y(),
+        );
+        let is_steep = dx.abs() < dy.abs();
+        let (start, end) = if is_steep {
+            (
+                MajorMinor::new(line.start.get_y(), line.start.get_x()),
+                MajorMinor::new(line.end.get_y(), line.end.get_x()),
+            )
+        } else {
+            (
+                MajorMinor::new(line.start.get_x(), line.start.get_y()),
+                MajorMinor::new(line.end.get_x(), line.end.get_y()),
+            )
+        };
+        let delta = MajorMinor::new(end.major - start.major, end.minor - start.minor);
+        let first = round_from(start.major, delta.major);
+        let last = round_from(end.major, -delta.major);
+
+        Self {
+            start,
+            delta,
+            is_steep,
+            major: first,
+            major_step: if delta.major < 0.0 { -1 } else { 1 },
+            points_remaining: (last - first).unsigned_abs() + 1,
+        }
+    }
+}
+
+impl Iterator for PointsF32 {
+    type Item = (i32, i32, f32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.points_remaining == 0 {
+            return None;
+        }
+
+        self.points_remaining -= 1;
+
+        let offset = self.major as f32 - self.start.major;
+        let t = if self.delta.major != 0.0 {
+            (offset / self.delta.major).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        // the product is divided last to keep exact halves of whole number lines
+        let minor = if self.delta.major != 0.0 {
+            self.start.minor + offset * self.delta.minor / self.delta.major
+        } else {
+            self.start.minor
+        };
+        let minor = round_from(minor, self.delta.minor);
+        let major = self.major;
+
+        self.major += self.major_step;
+
+        if self.is_steep {
+            Some((minor, major, t))
+        } else {
+            Some((major, minor, t))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.points_remaining as usize;
+
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for PointsF32 {}
@@ -1,126 +1,496 @@
 use std::{
+    fmt::{Display, Formatter},
     fs::File,
     io,
-    io::{BufRead, BufReader},
+    io::{BufRead, BufReader, Write},
+    ops::{Range, RangeInclusive},
+    path::Path,
     str::{FromStr, SplitWhitespace},
 };
 
-use tgaimage::{TGAColor, TGAImage};
+use tgaimage::{MipmapSampler, Sampler, TGAColor, TGAError, TGAImage, TextureFilter, WrapMode};
 
-use crate::geometry::{UVMapF32, Vector2Int, Vector3F32, XAxis, YAxis};
+use crate::aabb::Aabb;
+use crate::geometry::{
+    Matrix4F32, UVMapF32, Vector2F32, Vector2Int, Vector3F32, XAxis, YAxis, ZAxis,
+};
+use crate::mtl::{self, Material};
 
+/// Triangle with indices of its vertices and, if the model file lists them,
+/// of their texture coordinates and normals
 #[derive(Default)]
 struct ModelFace {
     verts_index: [u32; 3],
-    uv_index: [u32; 3],
-    norm_index: [u32; 3],
+    uv_index: Option<[u32; 3]>,
+    norm_index: Option<[u32; 3]>,
+    material: Option<usize>,
+}
+
+/// Read a map image, errors that are not I/O failures are reported as invalid data
+fn read_map(filename: &str) -> io::Result<TGAImage> {
+    TGAImage::read_tga_file(filename).map_err(|e| match e {
+        TGAError::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    })
+}
+
+/// Texture lookups clamp to the edge texels, so faces mapped onto the whole texture
+/// do not pick up texels of the opposite edge at `u = 1` or `v = 1`
+const TEXTURE_WRAP: WrapMode = WrapMode::Clamp;
+
+/// Material used by faces with its diffuse map, if it could be loaded
+struct ModelMaterial {
+    material: Material,
+    diffusemap: Option<TGAImage>,
+    diffuse_mipmaps: Vec<TGAImage>,
+}
+
+impl ModelMaterial {
+    fn new(material: Material) -> Self {
+        ModelMaterial {
+            material,
+            diffusemap: None,
+            diffuse_mipmaps: vec![],
+        }
+    }
+}
+
+/// Error reported while loading a model, see [`Model::new`]
+#[derive(Debug)]
+pub struct ModelError {
+    /// 1-based number of the offending line, 0 if the file cannot be opened or
+    /// the model is assembled by a [`ModelBuilder`]
+    pub line_number: usize,
+    pub kind: ModelErrorKind,
+}
+
+/// Reason of a [`ModelError`]
+#[derive(Debug)]
+pub enum ModelErrorKind {
+    /// Underlying I/O failure
+    Io(io::Error),
+    /// Word that is not a floating point number
+    BadFloat(String),
+    /// Word that is not a 1-based index of an already defined element
+    BadIndex(String),
+    /// Statement has an unexpected number of values
+    WrongArity {
+        expected: RangeInclusive<usize>,
+        got: usize,
+    },
+    /// Statement has fewer than `min` values and takes any number above it
+    TooFewValues { min: usize, got: usize },
+    /// Word that does not have the expected form, like a face vertex without slashes
+    UnexpectedToken(String),
+    /// Face of a [`ModelBuilder`] refers to one of the `count` vertices, texture
+    /// coordinates or normals that does not exist
+    IndexOutOfRange {
+        face: usize,
+        element: &'static str,
+        index: u32,
+        count: usize,
+    },
+}
+
+impl Display for ModelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.line_number > 0 {
+            write!(f, "line {}: ", self.line_number)?;
+        }
+
+        match &self.kind {
+            ModelErrorKind::Io(e) => write!(f, "I/O error: {}", e),
+            ModelErrorKind::BadFloat(word) => write!(f, "Invalid number '{}'", word),
+            ModelErrorKind::BadIndex(word) => write!(f, "Invalid index '{}'", word),
+            ModelErrorKind::WrongArity { expected, got } if expected.start() == expected.end() => {
+                write!(f, "Expected {} values, got {}", expected.start(), got)
+            }
+            ModelErrorKind::WrongArity { expected, got } => write!(
+                f,
+                "Expected {} to {} values, got {}",
+                expected.start(),
+                expected.end(),
+                got
+            ),
+            ModelErrorKind::TooFewValues { min, got } => {
+                write!(f, "Expected at least {} values, got {}", min, got)
+            }
+            ModelErrorKind::UnexpectedToken(word) => write!(f, "Unexpected token '{}'", word),
+            ModelErrorKind::IndexOutOfRange {
+                face,
+                element,
+                index,
+                count,
+            } => write!(
+                f,
+                "Face {} refers to {} {} of {}",
+                face, element, index, count
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ModelErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Named run of consecutive faces started by a `g` or `o` statement
+struct ModelGroup {
+    name: String,
+    material: Option<String>,
+    first_face: usize,
+}
+
+/// Name of the group of faces listed before any group statement
+const DEFAULT_GROUP: &str = "default";
+
+impl ModelGroup {
+    fn new(name: &str, first_face: usize) -> Self {
+        ModelGroup {
+            name: name.to_string(),
+            material: None,
+            first_face,
+        }
+    }
 }
 
+/// Triangle mesh with an optional diffuse texture
+///
+/// The default model has no geometry and no texture.
+#[derive(Default)]
 pub struct Model {
     verts: Vec<Vector3F32>,
     faces: Vec<ModelFace>,
+    groups: Vec<ModelGroup>,
+    material_libraries: Vec<String>,
+    materials: Vec<ModelMaterial>,
     normals: Vec<Vector3F32>,
     uvs: Vec<UVMapF32>,
     diffusemap: Option<TGAImage>,
+    diffuse_mipmaps: Vec<TGAImage>,
+    normalmap: Option<TGAImage>,
+    specularmap: Option<TGAImage>,
+    tangents: Vec<Vector3F32>,
+    bitangents: Vec<Vector3F32>,
 }
 
 impl Model {
-    pub fn new(filename: &str) -> io::Result<Self> {
-        let model_file = File::open(filename)?;
-        let reader = BufReader::new(model_file);
+    /// Load a triangle mesh from a Wavefront OBJ file, see [`Model::from_reader`]
+    ///
+    /// Material libraries are loaded from the directory of the file, see
+    /// [`Model::load_material_libraries`].
+    pub fn new(filename: &str) -> Result<Self, ModelError> {
+        let model_file = File::open(filename).map_err(|e| ModelError {
+            line_number: 0,
+            kind: ModelErrorKind::Io(e),
+        })?;
+        let mut model = Model::from_reader(BufReader::new(model_file))?;
+
+        model.load_material_libraries(Path::new(filename).parent().unwrap_or(Path::new("")))?;
+
+        Ok(model)
+    }
+
+    /// Load the materials of the `mtllib` files, which are looked up in `dir`
+    ///
+    /// Missing libraries and unused materials are skipped. A material keeps its
+    /// `Kd` color when its diffuse map cannot be loaded. Errors report lines of
+    /// the material library.
+    pub fn load_material_libraries(&mut self, dir: &Path) -> Result<(), ModelError> {
+        for library in &self.material_libraries {
+            let file = match File::open(dir.join(library)) {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(ModelError {
+                        line_number: 0,
+                        kind: ModelErrorKind::Io(e),
+                    })
+                }
+            };
+
+            for material in mtl::parse(BufReader::new(file))? {
+                let used = self
+                    .materials
+                    .iter_mut()
+                    .find(|used| used.material.name == material.name);
+
+                if let Some(used) = used {
+                    let diffusemap = material
+                        .diffuse_map
+                        .as_ref()
+                        .and_then(|map| TGAImage::read_tga_file(dir.join(map).to_str()?).ok());
+
+                    used.diffuse_mipmaps = diffusemap
+                        .as_ref()
+                        .map_or_else(Vec::new, TGAImage::generate_mipmaps);
+                    used.diffusemap = diffusemap;
+                    used.material = material;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Parse a triangle mesh in the Wavefront OBJ format
+    ///
+    /// Faces are split into groups by the `g` and `o` statements, see
+    /// [`Model::group_faces`]. Faces following `usemtl` use a white material of
+    /// the given name until [`Model::load_material_libraries`] loads the `mtllib`
+    /// files, and faces following a `usemtl` without a name have no material.
+    /// Other statements are skipped and textures are not loaded.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, ModelError> {
         let mut verts = vec![];
         let mut faces = vec![];
+        let mut groups = vec![];
+        let mut material_libraries = vec![];
+        let mut materials: Vec<ModelMaterial> = vec![];
+        let mut material = None;
         let mut normals = vec![];
         let mut uvs = vec![];
         let diffusemap = None;
 
-        for line in reader.lines() {
-            let (_line, mut words) = match line {
-                Ok(ref line) => (&line[..], line[..].split_whitespace()),
-                Err(_e) => return Err(_e),
-            };
+        for (index, line) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |kind| ModelError { line_number, kind };
+            let line = line.map_err(|e| error(ModelErrorKind::Io(e)))?;
+            let mut words = line.split_whitespace();
 
             match words.next() {
                 Some("v") => Model::process_vertice(&mut words, &mut verts),
-                Some("f") => Model::process_face(&mut words, &mut faces),
+                Some("f") => Model::process_face(
+                    &mut words,
+                    &mut faces,
+                    (verts.len(), uvs.len(), normals.len()),
+                    material,
+                ),
                 Some("vn") => Model::process_normal(&mut words, &mut normals),
                 Some("vt") => Model::process_texture(&mut words, &mut uvs),
+                Some("g") | Some("o") => {
+                    Model::process_group(&mut words, faces.len(), &mut groups);
+                    continue;
+                }
+                Some("usemtl") => {
+                    let name =
+                        Some(words.collect::<Vec<_>>().join(" ")).filter(|name| !name.is_empty());
+
+                    material = name.as_ref().map(|name| {
+                        match materials.iter().position(|m| &m.material.name == name) {
+                            Some(index) => index,
+                            None => {
+                                materials.push(ModelMaterial::new(Material::new(name)));
+                                materials.len() - 1
+                            }
+                        }
+                    });
+                    Model::process_material(name, faces.len(), &mut groups);
+                    continue;
+                }
+                Some("mtllib") => {
+                    material_libraries.extend(words.map(String::from));
+                    continue;
+                }
                 Some("#") | None | Some(_) => continue,
             }
+            .map_err(error)?;
+        }
+
+        // Faces before the first group statement
+        if !faces.is_empty() && groups.first().is_none_or(|group| group.first_face > 0) {
+            groups.insert(0, ModelGroup::new(DEFAULT_GROUP, 0));
         }
 
         Ok(Model {
             verts,
             faces,
+            groups,
+            material_libraries,
+            materials,
             normals,
             uvs,
             diffusemap,
+            diffuse_mipmaps: vec![],
+            normalmap: None,
+            specularmap: None,
+            tangents: vec![],
+            bitangents: vec![],
         })
     }
 
-    fn process_vertice(words: &mut SplitWhitespace, vertices: &mut Vec<Vector3F32>) {
-        let mut coords = [f32::default(); 3];
-        coords
-            .iter_mut()
-            .zip(words.into_iter())
-            .enumerate()
-            .for_each(|(i, (c, w))| {
-                *c = f32::from_str(w).unwrap();
-                assert!(i < 3);
-            });
-        vertices.push(Vector3F32::new_from_array(&coords));
+    /// Start a group of the faces following `first_face`
+    ///
+    /// A group without faces yet, like an object directly followed by a group,
+    /// is renamed instead.
+    fn process_group(words: &mut SplitWhitespace, first_face: usize, groups: &mut Vec<ModelGroup>) {
+        let name = words.collect::<Vec<_>>().join(" ");
+        let name = if name.is_empty() {
+            DEFAULT_GROUP
+        } else {
+            &name
+        };
+
+        match groups.last_mut() {
+            Some(group) if group.first_face == first_face => group.name = name.to_string(),
+            _ => groups.push(ModelGroup::new(name, first_face)),
+        }
     }
 
-    fn process_face(words: &mut SplitWhitespace, faces: &mut Vec<ModelFace>) {
-        let mut model_face = ModelFace::default();
+    /// Record the material name of the current group
+    fn process_material(name: Option<String>, first_face: usize, groups: &mut Vec<ModelGroup>) {
+        if groups.is_empty() {
+            groups.push(ModelGroup::new(DEFAULT_GROUP, first_face));
+        }
 
-        words.into_iter().enumerate().for_each(|(i, word)| {
-            let mut it = word
-                .split("/")
-                .map(|n| u32::from_str(n).unwrap() - 1)
-                .into_iter();
+        groups.last_mut().unwrap().material = name;
+    }
 
-            model_face.verts_index[i] = it.next().unwrap();
-            model_face.uv_index[i] = it.next().unwrap();
-            model_face.norm_index[i] = it.next().unwrap();
-            assert!(i < 3);
-        });
+    /// Parse the remaining words of a statement as a number of floats within `arity`
+    pub(crate) fn parse_floats(
+        words: &mut SplitWhitespace,
+        arity: RangeInclusive<usize>,
+    ) -> Result<Vec<f32>, ModelErrorKind> {
+        let values = words
+            .map(|w| f32::from_str(w).map_err(|_| ModelErrorKind::BadFloat(w.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if arity.contains(&values.len()) {
+            Ok(values)
+        } else {
+            Err(ModelErrorKind::WrongArity {
+                expected: arity,
+                got: values.len(),
+            })
+        }
+    }
+
+    /// Convert a 1-based OBJ index into an index of one of `count` elements
+    fn parse_index(word: &str, count: usize) -> Result<u32, ModelErrorKind> {
+        match u32::from_str(word) {
+            Ok(index) if index >= 1 && index as usize <= count => Ok(index - 1),
+            _ => Err(ModelErrorKind::BadIndex(word.to_string())),
+        }
+    }
 
-        faces.push(model_face);
+    /// Same as [`Model::parse_index`] but a missing or empty word means no index
+    fn parse_optional_index(
+        word: Option<&str>,
+        count: usize,
+    ) -> Result<Option<u32>, ModelErrorKind> {
+        match word {
+            Some("") | None => Ok(None),
+            Some(index) => Model::parse_index(index, count).map(Some),
+        }
     }
 
-    fn process_normal(words: &mut SplitWhitespace, normals: &mut Vec<Vector3F32>) {
-        let mut normal_vals = [0.0f32; 3];
+    fn process_vertice(
+        words: &mut SplitWhitespace,
+        vertices: &mut Vec<Vector3F32>,
+    ) -> Result<(), ModelErrorKind> {
+        let coords = Model::parse_floats(words, 3..=3)?;
 
-        words
-            .into_iter()
-            .zip(normal_vals.iter_mut())
-            .enumerate()
-            .for_each(|(i, (w, c))| {
-                *c = f32::from_str(w).unwrap();
-                assert!(i < 3);
-            });
+        vertices.push(Vector3F32::new(coords[0], coords[1], coords[2]));
 
-        normals.push(Vector3F32::new_from_array(&normal_vals))
+        Ok(())
     }
 
-    fn process_texture(words: &mut SplitWhitespace, uv: &mut Vec<UVMapF32>) {
-        let mut texture_uv = [0.0f32; 3];
+    /// Parse a polygon of `v`, `v/vt`, `v//vn` or `v/vt/vn` references to already
+    /// defined elements, `counts` holds the number of vertices, texture coordinates
+    /// and normals
+    ///
+    /// Every vertex of a face has to use the same form. Polygons with more than
+    /// three vertices are split into a fan of triangles sharing the first vertex,
+    /// which keeps the winding of the polygon.
+    fn process_face(
+        words: &mut SplitWhitespace,
+        faces: &mut Vec<ModelFace>,
+        counts: (usize, usize, usize),
+        material: Option<usize>,
+    ) -> Result<(), ModelErrorKind> {
+        let references: Vec<&str> = words.collect();
+
+        if references.len() < 3 {
+            return Err(ModelErrorKind::TooFewValues {
+                min: 3,
+                got: references.len(),
+            });
+        }
+
+        let mut verts_index = Vec::with_capacity(references.len());
+        let mut uv_index = Vec::with_capacity(references.len());
+        let mut norm_index = Vec::with_capacity(references.len());
+
+        for word in &references {
+            let mut indices = word.split('/');
+
+            verts_index.push(Model::parse_index(
+                indices.next().unwrap_or_default(),
+                counts.0,
+            )?);
+            uv_index.push(Model::parse_optional_index(indices.next(), counts.1)?);
+            norm_index.push(Model::parse_optional_index(indices.next(), counts.2)?);
+
+            if indices.next().is_some() {
+                return Err(ModelErrorKind::UnexpectedToken(word.to_string()));
+            }
+        }
+
+        // Keep the indices of a face only if every vertex has them
+        let all_or_none = |indices: Vec<Option<u32>>| {
+            if indices.iter().all(Option::is_some) {
+                Ok(indices.into_iter().collect::<Option<Vec<_>>>())
+            } else if indices.iter().all(Option::is_none) {
+                Ok(None)
+            } else {
+                Err(ModelErrorKind::UnexpectedToken(references.join(" ")))
+            }
+        };
+        let uv_index = all_or_none(uv_index)?;
+        let norm_index = all_or_none(norm_index)?;
+        let fan = |indices: &[u32], i: usize| [indices[0], indices[i], indices[i + 1]];
 
-        words
-            .into_iter()
-            .zip(texture_uv.iter_mut())
-            .enumerate()
-            .for_each(|(i, (w, c))| {
-                *c = f32::from_str(w).unwrap();
-                assert!(i < 3);
+        for i in 1..verts_index.len() - 1 {
+            faces.push(ModelFace {
+                verts_index: fan(&verts_index, i),
+                uv_index: uv_index.as_ref().map(|uv_index| fan(uv_index, i)),
+                norm_index: norm_index.as_ref().map(|norm_index| fan(norm_index, i)),
+                material,
             });
+        }
+
+        Ok(())
+    }
+
+    fn process_normal(
+        words: &mut SplitWhitespace,
+        normals: &mut Vec<Vector3F32>,
+    ) -> Result<(), ModelErrorKind> {
+        let normal = Model::parse_floats(words, 3..=3)?;
+
+        normals.push(Vector3F32::new(normal[0], normal[1], normal[2]));
+
+        Ok(())
+    }
+
+    /// Parse texture coordinates with an optional `w` component
+    fn process_texture(
+        words: &mut SplitWhitespace,
+        uv: &mut Vec<UVMapF32>,
+    ) -> Result<(), ModelErrorKind> {
+        let texture_uv = Model::parse_floats(words, 2..=3)?;
 
         uv.push(UVMapF32 {
             u: texture_uv[0],
             v: texture_uv[1],
-            w: texture_uv[2],
-        })
+            w: texture_uv.get(2).copied().unwrap_or(0.0),
+        });
+
+        Ok(())
     }
 
     pub fn load_texture(&mut self, filename: &str) -> io::Result<()> {
@@ -128,11 +498,202 @@ impl Model {
             return Err(io::Error::from(io::ErrorKind::AlreadyExists));
         }
 
-        self.diffusemap = Some(TGAImage::read_tga_file(filename).expect("Unable to read file"));
+        let diffusemap = TGAImage::read_tga_file(filename).expect("Unable to read file");
+
+        self.set_texture(diffusemap)
+    }
+
+    /// Use an already loaded image as the diffuse texture
+    pub fn set_texture(&mut self, diffusemap: TGAImage) -> io::Result<()> {
+        if self.diffusemap.is_some() {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+
+        self.diffuse_mipmaps = diffusemap.generate_mipmaps();
+        self.diffusemap = Some(diffusemap);
+
+        Ok(())
+    }
+
+    /// Load a normal map with normals in the model coordinates
+    pub fn load_normal_map(&mut self, filename: &str) -> io::Result<()> {
+        if self.normalmap.is_some() {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+
+        let normalmap = read_map(filename)?;
+
+        self.set_normal_map(normalmap)
+    }
+
+    /// Use an already loaded image as the normal map, see [`Model::normal_from_map`]
+    pub fn set_normal_map(&mut self, normalmap: TGAImage) -> io::Result<()> {
+        if self.normalmap.is_some() {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+
+        self.normalmap = Some(normalmap);
+
+        Ok(())
+    }
+
+    /// Unit normal stored in the normal map at normalized texture coordinates
+    ///
+    /// Red, green and blue channels map `0..=255` onto `-1..=1` along the x, y
+    /// and z axes. Texture coordinates address the map like the diffuse texture,
+    /// so `v = 0` is the bottom image row.
+    pub fn normal_from_map(&self, uv: Vector2F32) -> Option<Vector3F32> {
+        self.normalmap.as_ref().map(|normalmap| {
+            let texel = Sampler::new(normalmap, TEXTURE_WRAP).sample(uv.get_x(), uv.get_y());
+            let decode = |c: u8| c as f32 / 255.0 * 2.0 - 1.0;
+            let mut normal =
+                Vector3F32::new(decode(texel.r()), decode(texel.g()), decode(texel.b()));
+
+            normal.normalize_default();
+
+            normal
+        })
+    }
+
+    /// Load a grayscale map of the specular exponent
+    pub fn load_specular_map(&mut self, filename: &str) -> io::Result<()> {
+        if self.specularmap.is_some() {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+
+        let specularmap = read_map(filename)?;
+
+        self.set_specular_map(specularmap)
+    }
+
+    /// Use an already loaded image as the specular map, see [`Model::specular`]
+    pub fn set_specular_map(&mut self, specularmap: TGAImage) -> io::Result<()> {
+        if self.specularmap.is_some() {
+            return Err(io::Error::from(io::ErrorKind::AlreadyExists));
+        }
+
+        self.specularmap = Some(specularmap);
 
         Ok(())
     }
 
+    /// Specular exponent stored in the specular map at normalized texture
+    /// coordinates
+    ///
+    /// The brightness of the map in `0..=255` is the exponent, models without
+    /// a specular map have no highlights and return 0.
+    pub fn specular(&self, uv: Vector2F32) -> f32 {
+        self.specularmap.as_ref().map_or(0.0, |specularmap| {
+            Sampler::new(specularmap, TEXTURE_WRAP)
+                .sample(uv.get_x(), uv.get_y())
+                .luma() as f32
+        })
+    }
+
+    /// Compute the tangent and the bitangent of every face for tangent space
+    /// normal maps
+    ///
+    /// The tangent follows the direction of the growing `u` texture coordinate
+    /// and the bitangent the growing `v`. Faces without texture area get an
+    /// arbitrary tangent frame perpendicular to the face normal.
+    pub fn compute_tangents(&mut self) {
+        let (tangents, bitangents) = (0..self.n_faces())
+            .map(|i| {
+                let p = self.faces[i].verts_index.map(|v| self.verts[v as usize]);
+                let uv = [0, 1, 2].map(|j| self.uv_f32(i, j));
+                let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+                let (duv1, duv2) = (uv[1] - uv[0], uv[2] - uv[0]);
+                let r = duv1.get_x() * duv2.get_y() - duv2.get_x() * duv1.get_y();
+
+                let (mut tangent, mut bitangent) = if r.abs() > f32::EPSILON {
+                    (
+                        (e1 * duv2.get_y() - e2 * duv1.get_y()) * (1.0 / r),
+                        (e2 * duv1.get_x() - e1 * duv2.get_x()) * (1.0 / r),
+                    )
+                } else {
+                    let normal = e1.cross(&e2);
+
+                    (e1, normal.cross(&e1))
+                };
+
+                tangent.normalize_default();
+                bitangent.normalize_default();
+
+                (tangent, bitangent)
+            })
+            .unzip();
+
+        self.tangents = tangents;
+        self.bitangents = bitangents;
+    }
+
+    /// Compute a normal of every vertex from the faces around it and let every
+    /// face use them
+    ///
+    /// Models without normals in the file are lit with whole face normals until
+    /// this is called, normals listed in the file are replaced. Face normals are
+    /// weighted by the face area, so large faces bend the normal more than small
+    /// ones next to them.
+    pub fn compute_vertex_normals(&mut self) {
+        let mut normals = vec![Vector3F32::new(0.0, 0.0, 0.0); self.verts.len()];
+
+        for face in &self.faces {
+            let p = face.verts_index.map(|v| self.verts[v as usize]);
+            // the cross product length is twice the face area, degenerate faces
+            // add nothing
+            let normal = (p[1] - p[0]).cross(&(p[2] - p[0]));
+
+            for &vert in &face.verts_index {
+                normals[vert as usize] = normals[vert as usize] + normal;
+            }
+        }
+
+        for normal in &mut normals {
+            if normal.norm_f32() > 0.0 {
+                normal.normalize_default();
+            }
+        }
+
+        self.normals = normals;
+
+        for face in &mut self.faces {
+            face.norm_index = Some(face.verts_index);
+        }
+    }
+
+    /// Tangent and bitangent of a face, `None` until [`Model::compute_tangents`]
+    /// is called
+    pub fn tangent(&self, face_index: usize) -> Option<(Vector3F32, Vector3F32)> {
+        self.tangents
+            .get(face_index)
+            .map(|tangent| (*tangent, self.bitangents[face_index]))
+    }
+
+    /// Box around every vertex, `None` for a model without vertices
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        Aabb::from_points(self.verts.iter().copied())
+    }
+
+    /// Transform of [`Model::normalize_to_unit`], the identity for a model without
+    /// vertices
+    pub fn normalization_transform(&self) -> Matrix4F32 {
+        self.bounding_box()
+            .map_or_else(Matrix4F32::identity, |aabb| aabb.fit_transform())
+    }
+
+    /// Center the model in the origin and scale it uniformly to fit `-1..1`
+    /// along every axis, see [`Aabb::fit_transform`]
+    ///
+    /// Only the vertices are moved, the directions of the normals do not change
+    /// under a uniform scale.
+    pub fn normalize_to_unit(&mut self) {
+        let transform = self.normalization_transform();
+
+        for vert in &mut self.verts {
+            *vert = transform.transform_point(*vert);
+        }
+    }
+
     pub fn n_verts(&self) -> usize {
         self.verts.len()
     }
@@ -141,6 +702,33 @@ impl Model {
         self.faces.len()
     }
 
+    /// Number of face groups, models without group statements have a single
+    /// `default` group of all faces
+    pub fn n_groups(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Names given to a group by its `g` or `o` statement, `default` for the
+    /// faces listed before the first one
+    pub fn group_name(&self, index: usize) -> &str {
+        &self.groups[index].name
+    }
+
+    /// Material name given by `usemtl` to a group
+    pub fn group_material(&self, index: usize) -> Option<&str> {
+        self.groups[index].material.as_deref()
+    }
+
+    /// Indices of the faces of a group
+    pub fn group_faces(&self, index: usize) -> Range<usize> {
+        let end = self
+            .groups
+            .get(index + 1)
+            .map_or(self.faces.len(), |next| next.first_face);
+
+        self.groups[index].first_face..end
+    }
+
     pub fn n_textures(&self) -> usize {
         self.uvs.len()
     }
@@ -173,16 +761,1142 @@ impl Model {
         None
     }
 
-    pub fn uv(&self, face_index: usize, vert_index: usize) -> Vector2Int {
-        if let Some(ref diffusemap) = self.diffusemap {
-            let index = self.faces[face_index].uv_index[vert_index] as usize;
+    /// Diffuse texture color at normalized texture coordinates
+    pub fn diffuse_uv(&self, u: f32, v: f32) -> Option<TGAColor> {
+        self.diffuse_filtered(u, v, TextureFilter::Nearest)
+    }
+
+    /// Diffuse texture color at normalized texture coordinates using the given filter
+    pub fn diffuse_filtered(&self, u: f32, v: f32, filter: TextureFilter) -> Option<TGAColor> {
+        self.diffusemap
+            .as_ref()
+            .map(|diffusemap| Sampler::new(diffusemap, TEXTURE_WRAP).sample_filtered(u, v, filter))
+    }
+
+    /// Diffuse texture color at normalized texture coordinates and a mipmap level of detail
+    pub fn diffuse_lod(
+        &self,
+        u: f32,
+        v: f32,
+        level: f32,
+        filter: TextureFilter,
+    ) -> Option<TGAColor> {
+        self.diffusemap.as_ref().map(|diffusemap| {
+            MipmapSampler::new(diffusemap, &self.diffuse_mipmaps, TEXTURE_WRAP, filter)
+                .sample_lod(u, v, level)
+        })
+    }
+
+    /// Width and height of the diffuse texture
+    pub fn diffuse_size(&self) -> Option<(u32, u32)> {
+        self.diffusemap
+            .as_ref()
+            .map(|diffusemap| (diffusemap.get_width(), diffusemap.get_height()))
+    }
+
+    /// Write the model in the Wavefront OBJ format
+    ///
+    /// Faces refer to their vertices with the `v/vt/vn`, `v/vt`, `v//vn` or `v`
+    /// form depending on the indices they have. Groups, material libraries and
+    /// material names are kept, except for empty groups at the end of the model
+    /// which have no face to precede. Faces without a material following faces
+    /// with one are preceded by a `usemtl` without a name. Numbers are written with the shortest form
+    /// that reads back to the same value.
+    pub fn write_obj<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        for library in &self.material_libraries {
+            writeln!(out, "mtllib {}", library)?;
+        }
+
+        for vert in &self.verts {
+            writeln!(out, "v {} {} {}", vert.get_x(), vert.get_y(), vert.get_z())?;
+        }
+
+        for uv in &self.uvs {
+            writeln!(out, "vt {} {} {}", uv.u, uv.v, uv.w)?;
+        }
+
+        for normal in &self.normals {
+            writeln!(
+                out,
+                "vn {} {} {}",
+                normal.get_x(),
+                normal.get_y(),
+                normal.get_z()
+            )?;
+        }
+
+        let mut groups = self.groups.iter().peekable();
+        let mut material = None;
+
+        for (i, face) in self.faces.iter().enumerate() {
+            while let Some(group) = groups.next_if(|group| group.first_face == i) {
+                writeln!(out, "g {}", group.name)?;
+            }
+
+            if face.material != material {
+                match face.material {
+                    Some(index) => writeln!(out, "usemtl {}", self.materials[index].material.name),
+                    None => writeln!(out, "usemtl"),
+                }?;
+                material = face.material;
+            }
+
+            write!(out, "f")?;
+
+            for j in 0..3 {
+                // OBJ indices are 1-based
+                let vert = face.verts_index[j] + 1;
+
+                match (face.uv_index, face.norm_index) {
+                    (Some(uv), Some(norm)) => {
+                        write!(out, " {}/{}/{}", vert, uv[j] + 1, norm[j] + 1)
+                    }
+                    (Some(uv), None) => write!(out, " {}/{}", vert, uv[j] + 1),
+                    (None, Some(norm)) => write!(out, " {}//{}", vert, norm[j] + 1),
+                    (None, None) => write!(out, " {}", vert),
+                }?;
+            }
+
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+
+    /// Number of materials used by the faces
+    pub fn n_materials(&self) -> usize {
+        self.materials.len()
+    }
+
+    /// Material below [`Model::n_materials`] read from the material libraries
+    pub fn material(&self, index: usize) -> &Material {
+        &self.materials[index].material
+    }
+
+    /// Index of the material used by a face, see [`Model::material`]
+    pub fn face_material(&self, face_index: usize) -> Option<usize> {
+        self.faces[face_index].material
+    }
+
+    /// Diffuse color of a face at normalized texture coordinates and a mipmap
+    /// level of detail
+    ///
+    /// Faces with a material sample its diffuse map or use its `Kd` color if it
+    /// has no map. Other faces use the diffuse texture of the model, see
+    /// [`Model::diffuse_lod`].
+    pub fn face_diffuse_lod(
+        &self,
+        face_index: usize,
+        u: f32,
+        v: f32,
+        level: f32,
+        filter: TextureFilter,
+    ) -> Option<TGAColor> {
+        match self.faces[face_index].material.map(|m| &self.materials[m]) {
+            Some(ModelMaterial {
+                diffusemap: Some(diffusemap),
+                diffuse_mipmaps,
+                ..
+            }) => Some(
+                MipmapSampler::new(diffusemap, diffuse_mipmaps, TEXTURE_WRAP, filter)
+                    .sample_lod(u, v, level),
+            ),
+            Some(used) => Some(used.material.diffuse_color()),
+            None => self.diffuse_lod(u, v, level, filter),
+        }
+    }
+
+    /// Width and height of the texture sampled by [`Model::face_diffuse_lod`]
+    pub fn face_diffuse_size(&self, face_index: usize) -> Option<(u32, u32)> {
+        match self.faces[face_index].material.map(|m| &self.materials[m]) {
+            Some(used) => used
+                .diffusemap
+                .as_ref()
+                .map(|diffusemap| (diffusemap.get_width(), diffusemap.get_height())),
+            None => self.diffuse_size(),
+        }
+    }
+
+    /// Texture coordinates of a face vertex in diffuse texture pixels, `None`
+    /// without a diffuse texture
+    ///
+    /// Same as [`Model::uv_f32`] scaled by the texture size.
+    pub fn uv(&self, face_index: usize, vert_index: usize) -> Option<Vector2Int> {
+        self.diffuse_size().map(|(width, height)| {
+            let uv = self.uv_f32(face_index, vert_index);
+
+            Vector2Int::new(
+                (uv.get_x() * width as f32) as i32,
+                (uv.get_y() * height as f32) as i32,
+            )
+        })
+    }
+
+    /// Whether a face lists texture coordinates of its vertices
+    pub fn has_uv(&self, face_index: usize) -> bool {
+        self.faces[face_index].uv_index.is_some()
+    }
+
+    /// Whether a face lists normals of its vertices
+    pub fn has_normals(&self, face_index: usize) -> bool {
+        self.faces[face_index].norm_index.is_some()
+    }
+
+    /// Normal of a face vertex as stored in the model file, the normal of the
+    /// whole face if the file does not list one
+    pub fn normal(&self, face_index: usize, vert_index: usize) -> Vector3F32 {
+        match self.faces[face_index].norm_index {
+            Some(norm_index) => self.normals[norm_index[vert_index] as usize],
+            None => self.face_normal(face_index),
+        }
+    }
+
+    /// Unit normal of a face facing the side its vertices wind counter-clockwise
+    pub fn face_normal(&self, face_index: usize) -> Vector3F32 {
+        let p = self.faces[face_index]
+            .verts_index
+            .map(|v| self.verts[v as usize]);
+        let mut normal = (p[1] - p[0]).cross(&(p[2] - p[0]));
+
+        normal.normalize_default();
+
+        normal
+    }
+
+    /// Normalized texture coordinates of a face vertex, `(0, 0)` for faces
+    /// without texture coordinates
+    pub fn uv_f32(&self, face_index: usize, vert_index: usize) -> Vector2F32 {
+        match self.faces[face_index].uv_index {
+            Some(uv_index) => {
+                let uv = &self.uvs[uv_index[vert_index] as usize];
+
+                Vector2F32::new(uv.u, uv.v)
+            }
+            None => Vector2F32::new(0.0, 0.0),
+        }
+    }
+}
+
+impl FromStr for Model {
+    type Err = ModelError;
+
+    /// Parse a model from the contents of an OBJ file, see [`Model::from_reader`]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Model::from_reader(s.as_bytes())
+    }
+}
 
-            return Vector2Int::new(
-                (self.uvs[index].u * diffusemap.get_width() as f32) as i32,
-                (self.uvs[index].v * diffusemap.get_height() as f32) as i32,
+/// Model assembled in code instead of loaded from a file
+///
+/// Indices of the faces are 0-based and checked by [`ModelBuilder::build`].
+#[derive(Default)]
+pub struct ModelBuilder {
+    verts: Vec<Vector3F32>,
+    uvs: Vec<UVMapF32>,
+    normals: Vec<Vector3F32>,
+    faces: Vec<ModelFace>,
+}
+
+impl ModelBuilder {
+    pub fn new() -> Self {
+        ModelBuilder::default()
+    }
+
+    /// Add a vertex and return its index
+    pub fn add_vertex(&mut self, vertex: Vector3F32) -> u32 {
+        self.verts.push(vertex);
+
+        self.verts.len() as u32 - 1
+    }
+
+    /// Add normalized texture coordinates and return their index
+    pub fn add_uv(&mut self, uv: Vector2F32) -> u32 {
+        self.uvs.push(UVMapF32 {
+            u: uv.get_x(),
+            v: uv.get_y(),
+            w: 0.0,
+        });
+
+        self.uvs.len() as u32 - 1
+    }
+
+    /// Add a normal and return its index
+    pub fn add_normal(&mut self, normal: Vector3F32) -> u32 {
+        self.normals.push(normal);
+
+        self.normals.len() as u32 - 1
+    }
+
+    /// Add a triangle wound counter-clockwise seen from its front side
+    pub fn add_face(&mut self, verts: [u32; 3], uvs: Option<[u32; 3]>, normals: Option<[u32; 3]>) {
+        self.faces.push(ModelFace {
+            verts_index: verts,
+            uv_index: uvs,
+            norm_index: normals,
+            material: None,
+        });
+    }
+
+    /// Create the model with every face in a single `default` group
+    pub fn build(self) -> Result<Model, ModelError> {
+        for (face, model_face) in self.faces.iter().enumerate() {
+            let check = |element, indices: Option<[u32; 3]>, count| match indices
+                .and_then(|indices| indices.iter().copied().find(|&i| i as usize >= count))
+            {
+                Some(index) => Err(ModelError {
+                    line_number: 0,
+                    kind: ModelErrorKind::IndexOutOfRange {
+                        face,
+                        element,
+                        index,
+                        count,
+                    },
+                }),
+                None => Ok(()),
+            };
+
+            check("vertex", Some(model_face.verts_index), self.verts.len())?;
+            check("texture coordinates", model_face.uv_index, self.uvs.len())?;
+            check("normal", model_face.norm_index, self.normals.len())?;
+        }
+
+        let groups = if self.faces.is_empty() {
+            vec![]
+        } else {
+            vec![ModelGroup::new(DEFAULT_GROUP, 0)]
+        };
+
+        Ok(Model {
+            verts: self.verts,
+            faces: self.faces,
+            groups,
+            normals: self.normals,
+            uvs: self.uvs,
+            ..Model::default()
+        })
+    }
+}
+
+impl Model {
+    /// Cube of the size 1 centered at the origin with a normal for every side
+    /// and every side textured with the whole texture
+    pub fn unit_cube() -> Self {
+        // vertices of every side counter-clockwise seen from the outside
+        const SIDES: [([u32; 4], [f32; 3]); 6] = [
+            ([4, 5, 6, 7], [0.0, 0.0, 1.0]),
+            ([1, 0, 3, 2], [0.0, 0.0, -1.0]),
+            ([5, 1, 2, 6], [1.0, 0.0, 0.0]),
+            ([0, 4, 7, 3], [-1.0, 0.0, 0.0]),
+            ([7, 6, 2, 3], [0.0, 1.0, 0.0]),
+            ([0, 1, 5, 4], [0.0, -1.0, 0.0]),
+        ];
+        let mut builder = ModelBuilder::new();
+
+        for &(x, y, z) in &[
+            (-0.5, -0.5, -0.5),
+            (0.5, -0.5, -0.5),
+            (0.5, 0.5, -0.5),
+            (-0.5, 0.5, -0.5),
+            (-0.5, -0.5, 0.5),
+            (0.5, -0.5, 0.5),
+            (0.5, 0.5, 0.5),
+            (-0.5, 0.5, 0.5),
+        ] {
+            builder.add_vertex(Vector3F32::new(x, y, z));
+        }
+
+        for &(u, v) in &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)] {
+            builder.add_uv(Vector2F32::new(u, v));
+        }
+
+        for (side, normal) in SIDES {
+            let n = builder.add_normal(Vector3F32::from(normal));
+
+            builder.add_face([side[0], side[1], side[2]], Some([0, 1, 2]), Some([n; 3]));
+            builder.add_face([side[0], side[2], side[3]], Some([0, 2, 3]), Some([n; 3]));
+        }
+
+        builder.build().unwrap()
+    }
+
+    /// Sphere of the radius 1 centered at the origin
+    ///
+    /// Vertices lie on `stacks + 1` circles of latitude from the north pole on the
+    /// y axis and `slices + 1` meridians, where the first and the last one meet
+    /// at the `u = 0` seam of the texture facing the z axis.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stacks < 2` or `slices < 3`.
+    pub fn uv_sphere(stacks: u32, slices: u32) -> Self {
+        assert!(stacks >= 2 && slices >= 3, "Sphere is too coarse");
+
+        let mut builder = ModelBuilder::new();
+        let index = |stack: u32, slice: u32| stack * (slices + 1) + slice;
+
+        for stack in 0..=stacks {
+            let phi = std::f32::consts::PI * stack as f32 / stacks as f32;
+
+            for slice in 0..=slices {
+                let theta = 2.0 * std::f32::consts::PI * slice as f32 / slices as f32;
+                let point =
+                    Vector3F32::new(phi.sin() * theta.sin(), phi.cos(), phi.sin() * theta.cos());
+
+                builder.add_vertex(point);
+                builder.add_normal(point);
+                builder.add_uv(Vector2F32::new(
+                    slice as f32 / slices as f32,
+                    1.0 - stack as f32 / stacks as f32,
+                ));
+            }
+        }
+
+        for stack in 0..stacks {
+            for slice in 0..slices {
+                let top_left = index(stack, slice);
+                let bottom_left = index(stack + 1, slice);
+                let bottom_right = index(stack + 1, slice + 1);
+                let top_right = index(stack, slice + 1);
+                let mut add = |face: [u32; 3]| builder.add_face(face, Some(face), Some(face));
+
+                // triangles touching the poles would be degenerate
+                if stack + 1 < stacks {
+                    add([top_left, bottom_left, bottom_right]);
+                }
+
+                if stack > 0 {
+                    add([top_left, bottom_right, top_right]);
+                }
+            }
+        }
+
+        builder.build().unwrap()
+    }
+
+    /// Square of the size 1 centered at the origin in the `z = 0` plane facing
+    /// the z axis, split into `subdivisions` x `subdivisions` cells of two
+    /// triangles
+    ///
+    /// The texture covers the whole square. Cells are split along the diagonal
+    /// from their bottom left to their top right corner.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `subdivisions` is 0.
+    pub fn plane(subdivisions: u32) -> Self {
+        assert!(subdivisions > 0, "Plane needs at least one cell");
+
+        let mut builder = ModelBuilder::new();
+        let normal = builder.add_normal(Vector3F32::new(0.0, 0.0, 1.0));
+        let index = |row: u32, column: u32| row * (subdivisions + 1) + column;
+
+        for row in 0..=subdivisions {
+            for column in 0..=subdivisions {
+                let (u, v) = (
+                    column as f32 / subdivisions as f32,
+                    row as f32 / subdivisions as f32,
+                );
+
+                builder.add_vertex(Vector3F32::new(u - 0.5, v - 0.5, 0.0));
+                builder.add_uv(Vector2F32::new(u, v));
+            }
+        }
+
+        for row in 0..subdivisions {
+            for column in 0..subdivisions {
+                let bottom_left = index(row, column);
+                let bottom_right = index(row, column + 1);
+                let top_right = index(row + 1, column + 1);
+                let top_left = index(row + 1, column);
+                let mut add =
+                    |face: [u32; 3]| builder.add_face(face, Some(face), Some([normal; 3]));
+
+                add([bottom_left, bottom_right, top_right]);
+                add([bottom_left, top_right, top_left]);
+            }
+        }
+
+        builder.build().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test_model {
+    use std::io;
+
+    use tgaimage::{TGAColor, TGAImage, TGAImageFormat};
+
+    use crate::assert_vec_eq;
+    use crate::geometry::{UVMapF32, Vector2F32, Vector3F32, XAxis, YAxis};
+    use crate::model::{Model, ModelBuilder, ModelError, ModelErrorKind, ModelFace};
+
+    fn load_data(name: &str) -> Result<Model, ModelError> {
+        Model::new(&format!(
+            "{}/tests/data/{}",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        ))
+    }
+
+    /// Unit quad in the `z = 0` plane with texture coordinates laid out by `uv`
+    fn quad(uv: [(f32, f32); 4]) -> Model {
+        let face = |verts_index: [u32; 3]| ModelFace {
+            verts_index,
+            uv_index: Some(verts_index),
+            norm_index: Some([0; 3]),
+            material: None,
+        };
+
+        Model {
+            verts: vec![
+                Vector3F32::new(-0.5, -0.5, 0.0),
+                Vector3F32::new(0.5, -0.5, 0.0),
+                Vector3F32::new(0.5, 0.5, 0.0),
+                Vector3F32::new(-0.5, 0.5, 0.0),
+            ],
+            faces: vec![face([0, 1, 2]), face([0, 2, 3])],
+            normals: vec![Vector3F32::new(0.0, 0.0, 1.0)],
+            uvs: uv.iter().map(|&(u, v)| UVMapF32 { u, v, w: 0.0 }).collect(),
+            ..Model::default()
+        }
+    }
+
+    #[test]
+    fn normal_from_map_decoding() {
+        let mut model = Model::default();
+        let mut normalmap = TGAImage::try_new(2, 2, TGAImageFormat::RGB).unwrap();
+
+        assert!(model.normal_from_map(Vector2F32::new(0.5, 0.5)).is_none());
+
+        // the bottom row is stored first
+        normalmap.set(0, 0, &TGAColor::new_rgb(128, 128, 255));
+        normalmap.set(1, 0, &TGAColor::new_rgb(0, 0, 0));
+        normalmap.set(0, 1, &TGAColor::new_rgb(255, 128, 128));
+        normalmap.set(1, 1, &TGAColor::new_rgb(128, 0, 128));
+        model.set_normal_map(normalmap).unwrap();
+        assert!(model
+            .set_normal_map(TGAImage::try_new(1, 1, TGAImageFormat::RGB).unwrap())
+            .is_err());
+
+        let normal = |u, v| model.normal_from_map(Vector2F32::new(u, v)).unwrap();
+        let diagonal = -1.0 / 3.0f32.sqrt();
+
+        assert_vec_eq!(normal(0.25, 0.25), Vector3F32::new(0.0, 0.0, 1.0), 1e-2);
+        assert_vec_eq!(
+            normal(0.75, 0.25),
+            Vector3F32::new(diagonal, diagonal, diagonal),
+            1e-2
+        );
+        assert_vec_eq!(normal(0.25, 0.75), Vector3F32::new(1.0, 0.0, 0.0), 1e-2);
+        assert_vec_eq!(normal(0.75, 0.75), Vector3F32::new(0.0, -1.0, 0.0), 1e-2);
+        // coordinates past the edge are clamped
+        assert_vec_eq!(
+            normal(1.25, 0.25),
+            Vector3F32::new(diagonal, diagonal, diagonal),
+            1e-2
+        );
+    }
+
+    #[test]
+    fn load_normal_map_errors() {
+        let mut model = Model::default();
+        let missing = std::env::temp_dir().join("tinyrenderer_missing_normal_map.tga");
+        let corrupt = std::env::temp_dir().join("tinyrenderer_corrupt_normal_map.tga");
+
+        std::fs::write(&corrupt, b"not a TGA file").unwrap();
+
+        let error = model
+            .load_normal_map(missing.to_str().unwrap())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        let error = model
+            .load_normal_map(corrupt.to_str().unwrap())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(model.normal_from_map(Vector2F32::new(0.5, 0.5)).is_none());
+
+        std::fs::remove_file(corrupt).unwrap();
+    }
+
+    #[test]
+    fn specular_from_map() {
+        let mut model = Model::default();
+        let mut specularmap = TGAImage::try_new(2, 1, TGAImageFormat::RGB).unwrap();
+
+        assert_eq!(model.specular(Vector2F32::new(0.5, 0.5)), 0.0);
+
+        specularmap.set(1, 0, &TGAColor::new_rgb(20, 20, 20));
+        model.set_specular_map(specularmap).unwrap();
+        assert!(model
+            .set_specular_map(TGAImage::try_new(1, 1, TGAImageFormat::Grayscale).unwrap())
+            .is_err());
+        assert_eq!(model.specular(Vector2F32::new(0.25, 0.5)), 0.0);
+        assert_eq!(model.specular(Vector2F32::new(0.75, 0.5)), 20.0);
+    }
+
+    #[test]
+    fn load_specular_map_errors() {
+        let mut model = Model::default();
+        let missing = std::env::temp_dir().join("tinyrenderer_missing_specular_map.tga");
+        let corrupt = std::env::temp_dir().join("tinyrenderer_corrupt_specular_map.tga");
+
+        std::fs::write(&corrupt, b"not a TGA file").unwrap();
+
+        let error = model
+            .load_specular_map(missing.to_str().unwrap())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        let error = model
+            .load_specular_map(corrupt.to_str().unwrap())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(model.specular(Vector2F32::new(0.5, 0.5)), 0.0);
+
+        std::fs::remove_file(corrupt).unwrap();
+    }
+
+    #[test]
+    fn compute_tangents_follow_uv() {
+        let mut model = quad([(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+
+        assert!(model.tangent(0).is_none());
+        model.compute_tangents();
+
+        for face in 0..2 {
+            let (tangent, bitangent) = model.tangent(face).unwrap();
+
+            assert_vec_eq!(tangent, Vector3F32::new(1.0, 0.0, 0.0), 1e-2);
+            assert_vec_eq!(bitangent, Vector3F32::new(0.0, 1.0, 0.0), 1e-2);
+        }
+
+        // texture mirrored and rotated by 90 degrees
+        let mut model = quad([(1.0, 1.0), (1.0, 0.0), (0.0, 0.0), (0.0, 1.0)]);
+
+        model.compute_tangents();
+
+        let (tangent, bitangent) = model.tangent(1).unwrap();
+
+        assert_vec_eq!(tangent, Vector3F32::new(0.0, -1.0, 0.0), 1e-2);
+        assert_vec_eq!(bitangent, Vector3F32::new(-1.0, 0.0, 0.0), 1e-2);
+    }
+
+    #[test]
+    fn compute_tangents_degenerate_uv() {
+        let mut model = quad([(0.5, 0.5); 4]);
+
+        model.compute_tangents();
+
+        let (tangent, bitangent) = model.tangent(0).unwrap();
+        let normal = Vector3F32::new(0.0, 0.0, 1.0);
+
+        assert!((tangent.dot(&tangent) - 1.0).abs() < 1e-5);
+        assert!((bitangent.dot(&bitangent) - 1.0).abs() < 1e-5);
+        assert!(tangent.dot(&normal).abs() < 1e-5);
+        assert!(bitangent.dot(&normal).abs() < 1e-5);
+        assert!(tangent.dot(&bitangent).abs() < 1e-5);
+    }
+
+    /// Triangle with a texture coordinate for every vertex and a shared normal
+    const TRIANGLE: &str = "\
+v 0.0 0.0 0.0
+v 1.0 0.0 0.0
+v 0.0 1.0 0.0
+vt 0.0 0.0 0.0
+vt 1.0 0.0 0.0
+vt 0.0 1.0 0.0
+vn 0.0 0.0 1.0
+";
+
+    fn parse_error(source: &str) -> ModelError {
+        source.parse::<Model>().err().unwrap()
+    }
+
+    #[test]
+    fn load_with_whitespace_and_blank_lines() {
+        let source = "# Triangle\n\nv 0.0 0.0 0.0   \nv\t1.0 0.0 0.0\t\n   v 0.0 1.0 0.0\r\n\n\n\
+                      vt 0.0 0.0\nvn 0.0 0.0 1.0 \n\ng triangle\nf 1/1/1  2/1/1 3/1/1   \n\n\n";
+        let model: Model = source.parse().unwrap();
+
+        assert_eq!(
+            (model.n_verts(), model.n_textures(), model.n_normals()),
+            (3, 1, 1)
+        );
+        assert_eq!(model.n_faces(), 1);
+        assert_eq!(model.face(0), &[0, 1, 2]);
+        assert_vec_eq!(*model.vert(1), Vector3F32::new(1.0, 0.0, 0.0));
+        assert_vec_eq!(model.normal(0, 2), Vector3F32::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn load_face_formats() {
+        for &(face, uv, normals) in &[
+            ("f 1 2 3", false, false),
+            ("f 1/1 2/2 3/3", true, false),
+            ("f 1//1 2//1 3//1", false, true),
+            ("f 1/1/1 2/2/1 3/3/1", true, true),
+        ] {
+            let model: Model = format!("{}{}\n", TRIANGLE, face).parse().unwrap();
+
+            assert_eq!(model.n_faces(), 1, "{}", face);
+            assert_eq!(model.face(0), &[0, 1, 2], "{}", face);
+            assert_eq!(model.has_uv(0), uv, "{}", face);
+            assert_eq!(model.has_normals(0), normals, "{}", face);
+
+            for j in 0..3 {
+                // The triangle is flat, so its face normal matches the listed one
+                assert_vec_eq!(model.normal(0, j), Vector3F32::new(0.0, 0.0, 1.0));
+            }
+
+            if uv {
+                assert_vec_eq!(model.uv_f32(0, 1), Vector2F32::new(1.0, 0.0));
+                assert_vec_eq!(model.uv_f32(0, 2), Vector2F32::new(0.0, 1.0));
+            } else {
+                assert_vec_eq!(model.uv_f32(0, 1), Vector2F32::new(0.0, 0.0));
+            }
+        }
+    }
+
+    #[test]
+    fn load_polygons() {
+        let full = load_data("cube.obj").unwrap();
+        let quads = load_data("cube_quads.obj").unwrap();
+
+        assert_eq!(quads.n_faces(), 12);
+
+        for i in 0..quads.n_faces() {
+            assert_eq!(quads.face(i), full.face(i));
+
+            for j in 0..3 {
+                assert_vec_eq!(quads.uv_f32(i, j), full.uv_f32(i, j));
+                assert_vec_eq!(quads.normal(i, j), full.normal(i, j));
+            }
+        }
+
+        // Convex pentagon wound counter-clockwise
+        let pentagon: Model =
+            "v 0.0 -1.0 0.0\nv 1.0 -0.3 0.0\nv 0.6 0.8 0.0\nv -0.6 0.8 0.0\nv -1.0 -0.3 0.0\n\
+             f 1 2 3 4 5\n"
+                .parse()
+                .unwrap();
+
+        assert_eq!(pentagon.n_faces(), 3);
+        assert_eq!(pentagon.face(0), &[0, 1, 2]);
+        assert_eq!(pentagon.face(1), &[0, 2, 3]);
+        assert_eq!(pentagon.face(2), &[0, 3, 4]);
+
+        for i in 0..pentagon.n_faces() {
+            assert_vec_eq!(pentagon.face_normal(i), Vector3F32::new(0.0, 0.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn compute_vertex_normals_cube() {
+        // The diagonal of every side joins the two corners with an even number of
+        // positive coordinates, so every corner has the same area on its sides
+        let mut model: Model = "\
+v -0.5 -0.5 -0.5
+v 0.5 -0.5 -0.5
+v 0.5 0.5 -0.5
+v -0.5 0.5 -0.5
+v -0.5 -0.5 0.5
+v 0.5 -0.5 0.5
+v 0.5 0.5 0.5
+v -0.5 0.5 0.5
+f 5 6 8
+f 6 7 8
+f 2 1 3
+f 1 4 3
+f 6 2 3
+f 6 3 7
+f 1 5 8
+f 1 8 4
+f 8 7 3
+f 8 3 4
+f 1 2 6
+f 1 6 5
+"
+        .parse()
+        .unwrap();
+
+        model.compute_vertex_normals();
+
+        assert_eq!(model.n_normals(), 8);
+
+        for i in 0..model.n_faces() {
+            assert!(model.has_normals(i));
+
+            for j in 0..3 {
+                // Sum of the three side normals points away from the center
+                let mut expected = *model.vert(model.face(i)[j] as usize);
+
+                expected.normalize_default();
+                assert_vec_eq!(model.normal(i, j), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn compute_vertex_normals_area_weighted() {
+        // Perpendicular faces of areas 2 and 0.5 meet at the origin
+        let mut model: Model = "\
+v 0.0 0.0 0.0
+v 2.0 0.0 0.0
+v 0.0 2.0 0.0
+v 0.0 1.0 0.0
+v 0.0 0.0 1.0
+f 1 2 3
+f 1 4 5
+"
+        .parse()
+        .unwrap();
+
+        model.compute_vertex_normals();
+
+        let mut expected = Vector3F32::new(0.5, 0.0, 2.0);
+
+        expected.normalize_default();
+        assert_vec_eq!(model.normal(0, 0), expected);
+        assert_vec_eq!(model.normal(0, 1), Vector3F32::new(0.0, 0.0, 1.0));
+        assert_vec_eq!(model.normal(1, 2), Vector3F32::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn load_groups() {
+        let model: Model = format!(
+            "{}f 1 2 3\no head\ng head skin\nusemtl skin\nf 1 2 3\nf 1 3 2\n\
+             g eyes\nusemtl white\nf 1 2 3 1\ng\n",
+            TRIANGLE
+        )
+        .parse()
+        .unwrap();
+
+        assert_eq!(model.n_groups(), 4);
+        assert_eq!(
+            (0..model.n_groups())
+                .map(|i| (
+                    model.group_name(i),
+                    model.group_faces(i),
+                    model.group_material(i)
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("default", 0..1, None),
+                ("head skin", 1..3, Some("skin")),
+                ("eyes", 3..5, Some("white")),
+                ("default", 5..5, None),
+            ]
+        );
+
+        let model: Model = TRIANGLE.parse().unwrap();
+
+        assert_eq!(model.n_groups(), 0);
+
+        let model: Model = format!("{}f 1 2 3\n", TRIANGLE).parse().unwrap();
+
+        assert_eq!(model.n_groups(), 1);
+        assert_eq!(model.group_faces(0), 0..1);
+    }
+
+    #[test]
+    fn builder_checks_indices() {
+        let mut builder = ModelBuilder::new();
+
+        for &(x, y) in &[(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)] {
+            builder.add_vertex(Vector3F32::new(x, y, 0.0));
+        }
+
+        let uv = builder.add_uv(Vector2F32::new(0.5, 0.5));
+        let normal = builder.add_normal(Vector3F32::new(0.0, 0.0, 1.0));
+
+        builder.add_face([0, 1, 2], Some([uv; 3]), Some([normal; 3]));
+        builder.add_face([2, 1, 0], None, None);
+
+        let model = builder.build().unwrap();
+
+        assert_eq!((model.n_faces(), model.n_groups()), (2, 1));
+        assert_vec_eq!(model.uv_f32(0, 2), Vector2F32::new(0.5, 0.5));
+        assert!(!model.has_normals(1));
+
+        let mut builder = ModelBuilder::new();
+
+        builder.add_vertex(Vector3F32::new(0.0, 0.0, 0.0));
+        builder.add_face([0, 0, 0], None, None);
+        builder.add_face([0, 3, 0], None, None);
+
+        let error = builder.build().err().unwrap();
+
+        assert!(matches!(
+            error.kind,
+            ModelErrorKind::IndexOutOfRange {
+                face: 1,
+                element: "vertex",
+                index: 3,
+                count: 1
+            }
+        ));
+        assert_eq!(error.to_string(), "Face 1 refers to vertex 3 of 1");
+
+        let mut builder = ModelBuilder::new();
+
+        builder.add_vertex(Vector3F32::new(0.0, 0.0, 0.0));
+        builder.add_face([0, 0, 0], None, Some([0; 3]));
+
+        assert_eq!(
+            builder.build().err().unwrap().to_string(),
+            "Face 0 refers to normal 0 of 0"
+        );
+    }
+
+    #[test]
+    fn unit_cube_matches_obj() {
+        let cube = Model::unit_cube();
+        let obj = load_data("cube.obj").unwrap();
+
+        assert_eq!(
+            (cube.n_verts(), cube.n_textures(), cube.n_normals()),
+            (obj.n_verts(), obj.n_textures(), obj.n_normals())
+        );
+        assert_eq!(cube.n_faces(), obj.n_faces());
+
+        for i in 0..cube.n_verts() {
+            assert_vec_eq!(*cube.vert(i), *obj.vert(i));
+        }
+
+        for i in 0..cube.n_faces() {
+            assert_eq!(cube.face(i), obj.face(i));
+
+            for j in 0..3 {
+                assert_vec_eq!(cube.uv_f32(i, j), obj.uv_f32(i, j));
+                assert_vec_eq!(cube.normal(i, j), obj.normal(i, j));
+            }
+        }
+    }
+
+    #[test]
+    fn write_obj_face_forms() {
+        let source = "\
+mtllib scene.mtl
+v 0 0 0
+v 1 0 0.5
+v 0 1 -0.25
+vt 0 0 0
+vt 1 0 0
+vt 0 1 0
+vn 0 0 1
+f 1 2 3
+g textured
+usemtl skin
+f 1/1 2/2 3/3
+f 1//1 2//1 3//1
+g full
+f 1/1/1 2/2/1 3/3/1
+";
+        let model: Model = source.parse().unwrap();
+        let mut out = vec![];
+
+        model.write_obj(&mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            source.replace("f 1 2 3", "g default\nf 1 2 3")
+        );
+    }
+
+    #[test]
+    fn write_obj_round_trip() {
+        // Numbers without an exact binary form, a quad split into triangles, a
+        // face going back to no material and an empty group at the end
+        let model: Model = "\
+v -0.1 0.2 0.3
+v 0.7 -0.000123456789 1e-7
+v 0.333333333 0.6666667 -12345.678
+v -1.1 1.9 0.05
+vt 0.1 0.9 0
+vt 0.7 0.3 0.2
+vt 0.5 0.5 0
+vn 0.57735026 0.57735026 0.57735026
+vn -0.1 0.99 0.0
+g body
+usemtl skin
+f 1/1/1 2/2/1 3/3/2 4/1/2
+g arm
+usemtl
+f 4/3/2 3/2/1 2/1/1
+g tail
+"
+        .parse()
+        .unwrap();
+        let mut out = vec![];
+
+        model.write_obj(&mut out).unwrap();
+
+        let copy = Model::from_reader(out.as_slice()).unwrap();
+        let bits = |v: &Vector3F32| v.as_array().map(f32::to_bits);
+
+        assert_eq!(
+            (copy.n_verts(), copy.n_textures(), copy.n_normals()),
+            (model.n_verts(), model.n_textures(), model.n_normals())
+        );
+        assert_eq!(copy.n_faces(), model.n_faces());
+
+        for i in 0..model.n_verts() {
+            assert_eq!(bits(copy.vert(i)), bits(model.vert(i)));
+        }
+
+        for i in 0..model.n_normals() {
+            assert_eq!(bits(&copy.normals[i]), bits(&model.normals[i]));
+        }
+
+        for (a, b) in copy.uvs.iter().zip(&model.uvs) {
+            assert_eq!(
+                [a.u.to_bits(), a.v.to_bits(), a.w.to_bits()],
+                [b.u.to_bits(), b.v.to_bits(), b.w.to_bits()]
+            );
+        }
+
+        for (a, b) in copy.faces.iter().zip(&model.faces) {
+            assert_eq!(
+                (a.verts_index, a.uv_index, a.norm_index),
+                (b.verts_index, b.uv_index, b.norm_index)
             );
         }
 
-        panic!("Invalid access to UV buffer");
+        assert_eq!(
+            (0..model.n_faces())
+                .map(|i| model.face_material(i))
+                .collect::<Vec<_>>(),
+            vec![Some(0), Some(0), None]
+        );
+        assert_eq!(
+            (0..copy.n_faces())
+                .map(|i| copy.face_material(i))
+                .collect::<Vec<_>>(),
+            vec![Some(0), Some(0), None]
+        );
+        assert_eq!(copy.material(0).name, "skin");
+
+        // The trailing group has no faces to write
+        assert_eq!(model.n_groups(), 3);
+        assert_eq!(
+            (0..copy.n_groups())
+                .map(|i| (
+                    copy.group_name(i),
+                    copy.group_faces(i),
+                    copy.group_material(i)
+                ))
+                .collect::<Vec<_>>(),
+            vec![("body", 0..2, Some("skin")), ("arm", 2..3, None)]
+        );
+    }
+
+    #[test]
+    fn generated_meshes() {
+        let plane = Model::plane(3);
+
+        assert_eq!((plane.n_verts(), plane.n_faces()), (16, 18));
+
+        let bounds = plane.bounding_box().unwrap();
+
+        assert_vec_eq!(bounds.min, Vector3F32::new(-0.5, -0.5, 0.0));
+        assert_vec_eq!(bounds.max, Vector3F32::new(0.5, 0.5, 0.0));
+
+        for i in 0..plane.n_faces() {
+            assert_vec_eq!(plane.face_normal(i), Vector3F32::new(0.0, 0.0, 1.0));
+
+            for j in 0..3 {
+                // texture coordinates follow the position on the plane
+                let vert = *plane.vert(plane.face(i)[j] as usize);
+                let uv = plane.uv_f32(i, j);
+
+                assert_vec_eq!(uv, Vector2F32::new(vert.get_x() + 0.5, vert.get_y() + 0.5));
+            }
+        }
+
+        let sphere = Model::uv_sphere(6, 8);
+
+        // slices of the pole stacks have one triangle
+        assert_eq!(sphere.n_faces(), 2 * 8 * (6 - 1));
+
+        for i in 0..sphere.n_faces() {
+            let face = sphere.face(i).map(|v| *sphere.vert(v as usize));
+            let center = (face[0] + face[1] + face[2]) * (1.0 / 3.0);
+
+            // faces are wound counter-clockwise seen from the outside
+            assert!(sphere.face_normal(i).dot(&center) > 0.0, "{}", i);
+
+            for (j, vert) in face.iter().enumerate() {
+                assert!((vert.norm_f32() - 1.0).abs() < 1e-5);
+                assert_vec_eq!(sphere.normal(i, j), *vert);
+            }
+        }
+    }
+
+    #[test]
+    fn load_errors() {
+        let error = parse_error("v 0.0 0.0 0.0\nv 1.0 O.5 0.0\n");
+
+        assert_eq!(error.line_number, 2);
+        assert!(matches!(error.kind, ModelErrorKind::BadFloat(ref w) if w == "O.5"));
+        assert_eq!(error.to_string(), "line 2: Invalid number 'O.5'");
+
+        let error = parse_error(&format!("{}\nf 1/1/1 2/2/1 4/3/1\n", TRIANGLE));
+
+        assert_eq!(error.line_number, 9);
+        assert!(matches!(error.kind, ModelErrorKind::BadIndex(ref w) if w == "4"));
+
+        let error = parse_error(&format!("{}f 1/1/1 2/2/1 3/3/2\n", TRIANGLE));
+
+        assert_eq!(error.line_number, 8);
+        assert_eq!(error.to_string(), "line 8: Invalid index '2'");
+
+        let error = parse_error(&format!("{}f 0 1 2\n", TRIANGLE));
+
+        assert!(matches!(error.kind, ModelErrorKind::BadIndex(ref w) if w == "0"));
+
+        let error = parse_error("v 0.0 0.0 0.0\nvn 0.0 1.0\n");
+
+        assert_eq!(error.line_number, 2);
+        assert!(matches!(
+            error.kind,
+            ModelErrorKind::WrongArity { ref expected, got: 2 } if *expected == (3..=3)
+        ));
+        assert_eq!(error.to_string(), "line 2: Expected 3 values, got 2");
+
+        let error = parse_error(&format!("{}f 1 2\n", TRIANGLE));
+
+        assert!(matches!(
+            error.kind,
+            ModelErrorKind::TooFewValues { min: 3, got: 2 }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "line 8: Expected at least 3 values, got 2"
+        );
+
+        let error = parse_error(&format!("{}f 1/1/1/1 2/2/1 3/3/1\n", TRIANGLE));
+
+        assert_eq!(error.line_number, 8);
+        assert!(matches!(error.kind, ModelErrorKind::UnexpectedToken(ref w) if w == "1/1/1/1"));
+
+        let error = parse_error(&format!("{}f 1//1 2 3//1\n", TRIANGLE));
+
+        assert_eq!(error.to_string(), "line 8: Unexpected token '1//1 2 3//1'");
+
+        // Lines that are not valid UTF-8 fail to read
+        let error = Model::from_reader(&b"v 0.0 0.0 0.0\n\xff\n"[..])
+            .err()
+            .unwrap();
+
+        assert_eq!(error.line_number, 2);
+        assert!(
+            matches!(error.kind, ModelErrorKind::Io(ref e) if e.kind() == io::ErrorKind::InvalidData)
+        );
+
+        let error = load_data("missing.obj").err().unwrap();
+
+        assert_eq!(error.line_number, 0);
+        assert!(
+            matches!(error.kind, ModelErrorKind::Io(ref e) if e.kind() == io::ErrorKind::NotFound)
+        );
     }
 }
@@ -0,0 +1,168 @@
+use std::{io::BufRead, str::SplitWhitespace};
+
+use tgaimage::TGAColor;
+
+use crate::geometry::{Vector3F32, XAxis, YAxis, ZAxis};
+use crate::model::{Model, ModelError, ModelErrorKind};
+
+/// Surface of a `newmtl` entry of a Wavefront MTL file
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub name: String,
+    /// Diffuse color `Kd` with components in `0..=1`
+    pub diffuse: Vector3F32,
+    /// Specular color `Ks` with components in `0..=1`
+    pub specular: Vector3F32,
+    /// Specular exponent `Ns`
+    pub shininess: f32,
+    /// Texture files of `map_Kd`, `map_Ks` and `map_Bump` relative to the MTL file
+    pub diffuse_map: Option<String>,
+    pub specular_map: Option<String>,
+    pub bump_map: Option<String>,
+}
+
+impl Material {
+    /// White material without specular highlights and textures
+    pub fn new(name: &str) -> Self {
+        Material {
+            name: name.to_string(),
+            diffuse: Vector3F32::new(1.0, 1.0, 1.0),
+            specular: Vector3F32::new(0.0, 0.0, 0.0),
+            shininess: 1.0,
+            diffuse_map: None,
+            specular_map: None,
+            bump_map: None,
+        }
+    }
+
+    /// Diffuse color `Kd` as an RGB color
+    pub fn diffuse_color(&self) -> TGAColor {
+        let channel = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        TGAColor::new_rgb(
+            channel(self.diffuse.get_x()),
+            channel(self.diffuse.get_y()),
+            channel(self.diffuse.get_z()),
+        )
+    }
+}
+
+/// Parse the materials of an MTL file
+///
+/// Statements other than `newmtl`, `Kd`, `Ks`, `Ns`, `map_Kd`, `map_Ks` and
+/// `map_Bump` are skipped. Texture statements keep only the file name, their
+/// options are dropped.
+pub fn parse<R: BufRead>(reader: R) -> Result<Vec<Material>, ModelError> {
+    let mut materials: Vec<Material> = vec![];
+
+    for (index, line) in reader.lines().enumerate() {
+        let line_number = index + 1;
+        let error = |kind| ModelError { line_number, kind };
+        let line = line.map_err(|e| error(ModelErrorKind::Io(e)))?;
+        let mut words = line.split_whitespace();
+        let keyword = match words.next() {
+            Some("newmtl") => {
+                let name = words.collect::<Vec<_>>().join(" ");
+
+                materials.push(Material::new(&name));
+                continue;
+            }
+            Some(keyword @ ("Kd" | "Ks" | "Ns" | "map_Kd" | "map_Ks" | "map_Bump")) => keyword,
+            Some(_) | None => continue,
+        };
+        let material = materials
+            .last_mut()
+            .ok_or_else(|| error(ModelErrorKind::UnexpectedToken(keyword.to_string())))?;
+        let color =
+            |words| Model::parse_floats(words, 3..=3).map(|c| Vector3F32::new(c[0], c[1], c[2]));
+        let texture = |words: SplitWhitespace| {
+            words
+                .last()
+                .map(String::from)
+                .ok_or(ModelErrorKind::TooFewValues { min: 1, got: 0 })
+        };
+
+        match keyword {
+            "Kd" => material.diffuse = color(&mut words).map_err(error)?,
+            "Ks" => material.specular = color(&mut words).map_err(error)?,
+            "Ns" => material.shininess = Model::parse_floats(&mut words, 1..=1).map_err(error)?[0],
+            "map_Kd" => material.diffuse_map = Some(texture(words).map_err(error)?),
+            "map_Ks" => material.specular_map = Some(texture(words).map_err(error)?),
+            _ => material.bump_map = Some(texture(words).map_err(error)?),
+        }
+    }
+
+    Ok(materials)
+}
+
+#[cfg(test)]
+mod test_mtl {
+    use tgaimage::TGAColor;
+
+    use crate::assert_vec_eq;
+    use crate::geometry::Vector3F32;
+    use crate::model::ModelErrorKind;
+    use crate::mtl::parse;
+
+    #[test]
+    fn parse_materials() {
+        let source = "\
+# two materials
+newmtl skin
+Ka 0.1 0.1 0.1
+Kd 0.8 0.5 0.25
+Ks 0.5 0.5 0.5
+Ns 32
+map_Kd skin diffuse.tga
+map_Ks skin_spec.tga
+map_Bump -bm 0.5 skin_nm.tga
+
+newmtl plain
+Kd 0.0 1.0 0.0
+";
+        let materials = parse(source.as_bytes()).unwrap();
+
+        assert_eq!(materials.len(), 2);
+
+        let skin = &materials[0];
+
+        assert_eq!(skin.name, "skin");
+        assert_vec_eq!(skin.diffuse, Vector3F32::new(0.8, 0.5, 0.25));
+        assert_vec_eq!(skin.specular, Vector3F32::new(0.5, 0.5, 0.5));
+        assert_eq!(skin.shininess, 32.0);
+        assert_eq!(skin.diffuse_map.as_deref(), Some("diffuse.tga"));
+        assert_eq!(skin.specular_map.as_deref(), Some("skin_spec.tga"));
+        assert_eq!(skin.bump_map.as_deref(), Some("skin_nm.tga"));
+        assert_eq!(skin.diffuse_color(), TGAColor::new_rgb(204, 128, 64));
+
+        let plain = &materials[1];
+
+        assert_eq!(plain.name, "plain");
+        assert_eq!(plain.diffuse_color(), TGAColor::new_rgb(0, 255, 0));
+        assert!(plain.diffuse_map.is_none());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = parse("Kd 1.0 0.0 0.0\n".as_bytes()).err().unwrap();
+
+        assert_eq!(error.line_number, 1);
+        assert!(matches!(error.kind, ModelErrorKind::UnexpectedToken(ref w) if w == "Kd"));
+
+        let error = parse("newmtl red\nKd 1.0 0.0\n".as_bytes()).err().unwrap();
+
+        assert_eq!(error.to_string(), "line 2: Expected 3 values, got 2");
+
+        let error = parse("newmtl red\nNs high\n".as_bytes()).err().unwrap();
+
+        assert_eq!(error.to_string(), "line 2: Invalid number 'high'");
+
+        let error = parse("newmtl red\nmap_Kd\n".as_bytes()).err().unwrap();
+
+        assert_eq!(error.line_number, 2);
+        assert_eq!(
+            error.to_string(),
+            "line 2: Expected at least 1 values, got 0"
+        );
+    }
+}
@@ -1,6 +1,8 @@
+use core::fmt::{Display, Formatter, Result};
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 
 #[derive(Copy, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     pub x: i32,
     pub y: i32,
@@ -19,6 +21,12 @@ impl Point {
     }
 }
 
+impl Display for Point {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
 impl Add for Point {
     type Output = Self;
 
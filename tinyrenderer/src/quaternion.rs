@@ -0,0 +1,275 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Mul;
+
+use crate::geometry::{Matrix3F32, Matrix4F32, Vector3F32, XAxis, YAxis, ZAxis};
+
+/// Rotation stored as a unit quaternion `w + xi + yj + zk`
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quaternion {
+    w: f32,
+    x: f32,
+    y: f32,
+    z: f32,
+}
+
+impl Quaternion {
+    pub const fn new(w: f32, x: f32, y: f32, z: f32) -> Self {
+        Quaternion { w, x, y, z }
+    }
+
+    /// No rotation
+    pub const fn identity() -> Self {
+        Quaternion::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// Counter-clockwise rotation by `radians` looking from the end of `axis`
+    /// towards the origin, `axis` does not have to be unit
+    pub fn from_axis_angle(mut axis: Vector3F32, radians: f32) -> Self {
+        axis.normalize_default();
+
+        let (sin, cos) = (radians / 2.0).sin_cos();
+
+        Quaternion::new(
+            cos,
+            axis.get_x() * sin,
+            axis.get_y() * sin,
+            axis.get_z() * sin,
+        )
+    }
+
+    pub fn w(&self) -> f32 {
+        self.w
+    }
+
+    /// Vector part of the quaternion
+    pub fn xyz(&self) -> Vector3F32 {
+        Vector3F32::new(self.x, self.y, self.z)
+    }
+
+    /// Every component differs by at most `eps`, `q` and `-q` are not equal
+    pub fn approx_eq(&self, other: &Self, eps: f32) -> bool {
+        (self.w - other.w).abs() <= eps && self.xyz().approx_eq(&other.xyz(), eps)
+    }
+
+    pub fn dot(&self, rhs: &Self) -> f32 {
+        self.w * rhs.w + self.x * rhs.x + self.y * rhs.y + self.z * rhs.z
+    }
+
+    pub fn norm(&self) -> f32 {
+        self.dot(self).sqrt()
+    }
+
+    /// Scale to the unit length, products of many rotations drift away from it
+    pub fn normalize(&mut self) {
+        let norm = self.norm();
+
+        *self = Quaternion::new(self.w / norm, self.x / norm, self.y / norm, self.z / norm);
+    }
+
+    /// Inverse rotation of a unit quaternion
+    pub fn conjugate(&self) -> Self {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Rotate a vector, the quaternion has to be unit
+    pub fn rotate_vector(&self, v: Vector3F32) -> Vector3F32 {
+        let u = self.xyz();
+        let t = u.cross(&v) * 2.0;
+
+        v + t * self.w + u.cross(&t)
+    }
+
+    /// Rotation at `t` of the way from `self` to `other` with a constant speed
+    ///
+    /// The rotation takes the shorter way around, nearly equal rotations are
+    /// interpolated linearly.
+    pub fn slerp(&self, other: &Self, t: f32) -> Self {
+        let mut cos = self.dot(other);
+        let mut other = *other;
+
+        // q and -q are the same rotation
+        if cos < 0.0 {
+            cos = -cos;
+            other = Quaternion::new(-other.w, -other.x, -other.y, -other.z);
+        }
+
+        let (a, b) = if cos > 0.9995 {
+            (1.0 - t, t)
+        } else {
+            let angle = cos.acos();
+            let sin = angle.sin();
+
+            (((1.0 - t) * angle).sin() / sin, (t * angle).sin() / sin)
+        };
+        let mut result = Quaternion::new(
+            a * self.w + b * other.w,
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+        );
+
+        result.normalize();
+
+        result
+    }
+
+    /// Rotation matrix of a unit quaternion
+    pub fn to_matrix3(&self) -> Matrix3F32 {
+        let Quaternion { w, x, y, z } = *self;
+
+        Matrix3F32::new([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ])
+    }
+
+    /// Rotation of the homogeneous coordinates, see [`Quaternion::to_matrix3`]
+    pub fn to_matrix4(&self) -> Matrix4F32 {
+        let rotation = self.to_matrix3();
+        let mut m = Matrix4F32::identity();
+
+        for i in 0..3 {
+            for j in 0..3 {
+                m[(i, j)] = rotation[(i, j)];
+            }
+        }
+
+        m
+    }
+}
+
+impl Display for Quaternion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {}, {}, {})", self.w, self.x, self.y, self.z)
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Quaternion::identity()
+    }
+}
+
+/// Composition, `a * b` rotates by `b` first and by `a` then
+impl Mul for Quaternion {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Quaternion::new(
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        )
+    }
+}
+
+impl From<Quaternion> for Matrix3F32 {
+    fn from(q: Quaternion) -> Self {
+        q.to_matrix3()
+    }
+}
+
+impl From<Quaternion> for Matrix4F32 {
+    fn from(q: Quaternion) -> Self {
+        q.to_matrix4()
+    }
+}
+
+#[cfg(test)]
+mod test_quaternion {
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+
+    use crate::assert_vec_eq;
+    use crate::geometry::{Matrix4F32, Vector3F32, YAxis};
+    use crate::quaternion::Quaternion;
+
+    #[test]
+    fn rotate_about_z() {
+        let q = Quaternion::from_axis_angle(Vector3F32::new(0.0, 0.0, 2.0), FRAC_PI_2);
+
+        assert_vec_eq!(
+            q.rotate_vector(Vector3F32::new(1.0, 0.0, 0.0)),
+            Vector3F32::new(0.0, 1.0, 0.0),
+        );
+        assert_vec_eq!(
+            q.rotate_vector(Vector3F32::new(0.0, 0.0, 3.0)),
+            Vector3F32::new(0.0, 0.0, 3.0),
+        );
+        assert_vec_eq!(
+            q.conjugate().rotate_vector(Vector3F32::new(0.0, 1.0, 0.0)),
+            Vector3F32::new(1.0, 0.0, 0.0),
+        );
+    }
+
+    #[test]
+    fn compose_rotations() {
+        let z = Quaternion::from_axis_angle(Vector3F32::new(0.0, 0.0, 1.0), FRAC_PI_2);
+        let x = Quaternion::from_axis_angle(Vector3F32::new(1.0, 0.0, 0.0), FRAC_PI_2);
+        let v = Vector3F32::new(1.0, 2.0, 3.0);
+
+        assert_vec_eq!(
+            (x * z).rotate_vector(v),
+            x.rotate_vector(z.rotate_vector(v)),
+        );
+        // (1, 0, 0) goes to (0, 1, 0) around Z and then to (0, 0, 1) around X
+        assert_vec_eq!(
+            (x * z).rotate_vector(Vector3F32::new(1.0, 0.0, 0.0)),
+            Vector3F32::new(0.0, 0.0, 1.0),
+        );
+
+        let mut twice = Quaternion::new(2.0, 0.0, 0.0, 0.0);
+
+        twice.normalize();
+        assert_eq!(twice, Quaternion::identity());
+        assert_eq!(twice.to_string(), "(1, 0, 0, 0)");
+    }
+
+    #[test]
+    fn slerp_halfway() {
+        let axis = Vector3F32::new(0.0, 1.0, 0.0);
+        let quarter = Quaternion::from_axis_angle(axis, FRAC_PI_2);
+        let half = Quaternion::identity().slerp(&quarter, 0.5);
+        let eighth = Quaternion::from_axis_angle(axis, FRAC_PI_4);
+
+        assert!((half.dot(&eighth) - 1.0).abs() < 1e-5);
+        assert_eq!(
+            Quaternion::identity().slerp(&quarter, 0.0),
+            Quaternion::identity()
+        );
+        assert!((Quaternion::identity().slerp(&quarter, 1.0).dot(&quarter) - 1.0).abs() < 1e-5);
+
+        // the negated rotation is the same, so the shorter way is taken
+        let negated = Quaternion::new(-quarter.w(), 0.0, -quarter.xyz().get_y(), 0.0);
+
+        assert!((Quaternion::identity().slerp(&negated, 0.5).dot(&eighth) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn matrix_matches_rotate_vector() {
+        let mut q = Quaternion::from_axis_angle(Vector3F32::new(1.0, -2.0, 0.5), 0.3 * PI);
+
+        q = q * Quaternion::from_axis_angle(Vector3F32::new(0.0, 1.0, 1.0), 1.1);
+
+        let v = Vector3F32::new(0.7, -1.3, 2.0);
+
+        assert_vec_eq!(q.to_matrix3() * v, q.rotate_vector(v));
+        assert_vec_eq!(q.to_matrix4().transform_point(v), q.rotate_vector(v));
+        assert_vec_eq!(Matrix4F32::from(q).transform_point(v), q.rotate_vector(v));
+        assert!((q.to_matrix3().determinant() - 1.0).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,122 @@
+use num::Num;
+
+use crate::geometry::{XYAxis, YAxis};
+
+/// Order triangle vertices by the growing Y coordinate
+pub fn sort_by_y<T: PartialOrd, V: YAxis<T>>(verts: &mut [V; 3]) {
+    if verts[0].get_y() > verts[1].get_y() {
+        verts.swap(0, 1);
+    }
+
+    if verts[0].get_y() > verts[2].get_y() {
+        verts.swap(0, 2);
+    }
+
+    if verts[1].get_y() > verts[2].get_y() {
+        verts.swap(1, 2);
+    }
+}
+
+/// Twice the signed area of a triangle, positive for counter-clockwise vertices
+pub fn signed_area<T: Num + Copy, V: XYAxis<T>>(verts: &[V; 3]) -> T {
+    let (ax, ay) = (verts[0].get_x(), verts[0].get_y());
+
+    (verts[1].get_x() - ax) * (verts[2].get_y() - ay)
+        - (verts[2].get_x() - ax) * (verts[1].get_y() - ay)
+}
+
+/// Order triangle vertices counter-clockwise by swapping the last two of the
+/// clockwise ones, degenerate triangles are left as is
+pub fn sort_ccw<T: Num + Copy + PartialOrd, V: XYAxis<T>>(verts: &mut [V; 3]) {
+    if signed_area(verts) < T::zero() {
+        verts.swap(1, 2);
+    }
+}
+
+#[cfg(test)]
+mod test_raster_util {
+    use crate::geometry::{Vector2F32, Vector2Int, Vector3Int, XAxis, YAxis};
+    use crate::raster_util::{signed_area, sort_by_y, sort_ccw};
+
+    fn xy<V: XAxis<i32> + YAxis<i32>>(verts: &[V; 3]) -> [(i32, i32); 3] {
+        [
+            (verts[0].get_x(), verts[0].get_y()),
+            (verts[1].get_x(), verts[1].get_y()),
+            (verts[2].get_x(), verts[2].get_y()),
+        ]
+    }
+
+    #[test]
+    fn sort_by_y_orders() {
+        let sorted = [(5, 0), (1, 3), (4, 7)];
+
+        for input in &[
+            sorted,
+            [(4, 7), (1, 3), (5, 0)],
+            [(1, 3), (4, 7), (5, 0)],
+            [(4, 7), (5, 0), (1, 3)],
+        ] {
+            let mut verts = [
+                Vector2Int::new(input[0].0, input[0].1),
+                Vector2Int::new(input[1].0, input[1].1),
+                Vector2Int::new(input[2].0, input[2].1),
+            ];
+
+            sort_by_y(&mut verts);
+            assert_eq!(xy(&verts), sorted);
+        }
+    }
+
+    #[test]
+    fn sort_by_y_equal_y() {
+        let mut verts = [
+            Vector3Int::new(8, 4, 1),
+            Vector3Int::new(2, 9, 2),
+            Vector3Int::new(3, 4, 3),
+        ];
+
+        sort_by_y(&mut verts);
+        assert_eq!(xy(&verts), [(8, 4), (3, 4), (2, 9)]);
+
+        let mut verts = [
+            Vector2F32::new(0.0, 2.5),
+            Vector2F32::new(1.0, 2.5),
+            Vector2F32::new(2.0, -1.0),
+        ];
+
+        sort_by_y(&mut verts);
+        assert_eq!(
+            verts.iter().map(|v| v.get_y()).collect::<Vec<_>>(),
+            vec![-1.0, 2.5, 2.5]
+        );
+        assert_eq!(verts[0].get_x(), 2.0);
+    }
+
+    #[test]
+    fn sort_ccw_flips_one_pair() {
+        let ccw = [(0, 0), (4, 0), (0, 3)];
+        let mut verts = [
+            Vector2Int::new(0, 0),
+            Vector2Int::new(0, 3),
+            Vector2Int::new(4, 0),
+        ];
+
+        assert_eq!(signed_area(&verts), -12);
+        sort_ccw(&mut verts);
+        assert_eq!(xy(&verts), ccw);
+        assert_eq!(signed_area(&verts), 12);
+
+        // counter-clockwise and degenerate triangles are kept
+        sort_ccw(&mut verts);
+        assert_eq!(xy(&verts), ccw);
+
+        let mut line = [
+            Vector2Int::new(0, 0),
+            Vector2Int::new(2, 2),
+            Vector2Int::new(1, 1),
+        ];
+
+        sort_ccw(&mut line);
+        assert_eq!(xy(&line), [(0, 0), (2, 2), (1, 1)]);
+    }
+}
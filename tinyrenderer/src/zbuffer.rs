@@ -0,0 +1,200 @@
+use tgaimage::TGAImage;
+
+use crate::zbuffer_to_image;
+
+/// Depth of the closest rendered point for every pixel
+///
+/// Bigger values are closer to the viewer.
+#[derive(Debug, Clone)]
+pub struct ZBuffer {
+    data: Vec<f32>,
+    width: u32,
+    height: u32,
+}
+
+impl ZBuffer {
+    /// Create a buffer where every pixel is infinitely far away
+    pub fn new(width: u32, height: u32) -> Self {
+        ZBuffer {
+            data: vec![f32::NEG_INFINITY; width as usize * height as usize],
+            width,
+            height,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Depth stored for a pixel
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside of the buffer.
+    pub fn get(&self, x: u32, y: u32) -> f32 {
+        self.data[self.index(x, y)]
+    }
+
+    /// Store `z` for a pixel if it is closer than the current depth
+    ///
+    /// Returns `true` when the depth was updated, so the pixel should be drawn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside of the buffer.
+    pub fn test_and_set(&mut self, x: u32, y: u32, z: f32) -> bool {
+        let index = self.index(x, y);
+
+        if self.data[index] < z {
+            self.data[index] = z;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Split the buffer into at most `bands` disjoint bands of rows
+    ///
+    /// Rows are distributed the same way as in [`TGAImage::split_rows_mut`], so
+    /// the bands line up with the image bands of the same count.
+    pub fn split_rows_mut(&mut self, bands: usize) -> Vec<ZBufferBandMut<'_>> {
+        let rows_per_band = (self.height as usize).div_ceil(bands.max(1));
+        let width = self.width;
+
+        self.data
+            .chunks_mut((rows_per_band * width as usize).max(1))
+            .enumerate()
+            .map(|(i, data)| ZBufferBandMut {
+                y_offset: (i * rows_per_band) as u32,
+                height: (data.len() / width as usize) as u32,
+                data,
+                width,
+            })
+            .collect()
+    }
+
+    /// Reset every pixel to be infinitely far away
+    pub fn clear(&mut self) {
+        self.data.fill(f32::NEG_INFINITY);
+    }
+
+    pub fn as_slice(&self) -> &[f32] {
+        &self.data
+    }
+
+    /// Depth as a 16-bit grayscale image, see [`zbuffer_to_image`]
+    pub fn to_image(&self) -> TGAImage {
+        zbuffer_to_image(&self.data, self.width, self.height)
+    }
+
+    /// Whether the buffer covers every pixel of the image
+    pub fn matches(&self, image: &TGAImage) -> bool {
+        self.width == image.get_width() && self.height == image.get_height()
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        assert!(
+            x < self.width && y < self.height,
+            "pixel ({}, {}) is outside of the z-buffer",
+            x,
+            y
+        );
+
+        x as usize + y as usize * self.width as usize
+    }
+}
+
+/// Mutable band of consecutive z-buffer rows, see [`ZBuffer::split_rows_mut`]
+pub struct ZBufferBandMut<'a> {
+    data: &'a mut [f32],
+    y_offset: u32,
+    width: u32,
+    height: u32,
+}
+
+impl<'a> ZBufferBandMut<'a> {
+    /// Buffer `y` coordinate of the first band row
+    pub fn y_offset(&self) -> u32 {
+        self.y_offset
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Number of rows in the band
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Same as [`ZBuffer::test_and_set`] for the band row `y_local`
+    ///
+    /// # Panics
+    ///
+    /// Panics if the pixel is outside of the band.
+    pub fn test_and_set(&mut self, x: u32, y_local: u32, z: f32) -> bool {
+        assert!(
+            x < self.width && y_local < self.height,
+            "pixel ({}, {}) is outside of the band",
+            x,
+            y_local
+        );
+        let value = &mut self.data[x as usize + y_local as usize * self.width as usize];
+
+        if *value < z {
+            *value = z;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_zbuffer {
+    use crate::zbuffer::ZBuffer;
+
+    #[test]
+    fn test_and_set() {
+        let mut zbuf = ZBuffer::new(3, 2);
+
+        assert_eq!(zbuf.get(2, 1), f32::NEG_INFINITY);
+        assert!(zbuf.test_and_set(2, 1, -5.0));
+        assert!(!zbuf.test_and_set(2, 1, -5.0));
+        assert!(!zbuf.test_and_set(2, 1, -6.0));
+        assert!(zbuf.test_and_set(2, 1, 1.0));
+        assert_eq!(zbuf.get(2, 1), 1.0);
+        assert_eq!(zbuf.as_slice()[5], 1.0);
+        assert!(!zbuf.test_and_set(0, 0, f32::NAN));
+
+        zbuf.clear();
+        assert!(zbuf.as_slice().iter().all(|z| *z == f32::NEG_INFINITY));
+    }
+
+    #[test]
+    fn split_rows_mut() {
+        let mut zbuf = ZBuffer::new(2, 5);
+        let mut bands = zbuf.split_rows_mut(2);
+
+        assert_eq!(bands.len(), 2);
+        assert_eq!((bands[1].y_offset(), bands[1].height()), (3, 2));
+        assert!(bands[1].test_and_set(1, 0, 2.0));
+        assert!(!bands[1].test_and_set(1, 0, 1.0));
+        drop(bands);
+        assert_eq!(zbuf.get(1, 3), 2.0);
+        assert_eq!(zbuf.split_rows_mut(0).len(), 1);
+        assert_eq!(zbuf.split_rows_mut(9).len(), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the z-buffer")]
+    fn test_out_of_bounds() {
+        let zbuf = ZBuffer::new(3, 2);
+
+        zbuf.get(0, 2);
+    }
+}
@@ -0,0 +1,822 @@
+use std::fs;
+
+use tgaimage::colors::{BLACK, BLUE, RED, WHITE};
+use tgaimage::{TGAColor, TGAImage, TGAImageFormat, TextureFilter};
+use tinyrenderer::camera::{lookat, viewport, Projection};
+use tinyrenderer::geometry::{Matrix4F32, Vector3F32};
+use tinyrenderer::lighting::{Light, LightKind, LightingParams};
+use tinyrenderer::model::Model;
+use tinyrenderer::zbuffer::ZBuffer;
+use tinyrenderer::{
+    render_depth, render_model, render_turntable, triangle_gouraud, CullMode, Diffuse, FaceColor,
+    NormalMapping, RenderOptions, Shadow, TriangleDef,
+};
+
+const QUAD_OBJ: &str = "\
+v -0.5 -0.5 0.0
+v 0.5 -0.5 0.0
+v 0.5 0.5 0.0
+v -0.5 0.5 0.0
+vt 0.0 0.0 0.0
+vt 1.0 0.0 0.0
+vt 1.0 1.0 0.0
+vt 0.0 1.0 0.0
+vn 0.0 0.0 1.0
+f 1/1/1 2/2/1 3/3/1
+f 1/1/1 3/3/1 4/4/1
+";
+
+/// Ground quad in the `z = 0` plane with a triangle standing on it along the x axis
+const SHADOW_OBJ: &str = "\
+v -1.0 -1.0 0.0
+v 1.0 -1.0 0.0
+v 1.0 1.0 0.0
+v -1.0 1.0 0.0
+v -0.3 0.0 0.0
+v 0.3 0.0 0.0
+v 0.0 0.0 0.8
+vt 0.0 0.0 0.0
+vn 0.0 0.0 1.0
+f 1/1/1 2/1/1 3/1/1
+f 1/1/1 3/1/1 4/1/1
+f 5/1/1 6/1/1 7/1/1
+";
+
+/// Quad in the `z = x` plane with its vertex normals
+const TILTED_QUAD_OBJ: &str = "\
+v -0.5 -0.5 -0.5
+v 0.5 -0.5 0.5
+v 0.5 0.5 0.5
+v -0.5 0.5 -0.5
+vt 0.0 0.0 0.0
+vn -0.70710678 0.0 0.70710678
+f 1/1/1 2/1/1 3/1/1
+f 1/1/1 3/1/1 4/1/1
+";
+
+/// Left and right halves of the unit quad in separate groups
+const GROUPS_OBJ: &str = "\
+v -1.0 -1.0 0.0
+v 0.0 -1.0 0.0
+v 0.0 1.0 0.0
+v -1.0 1.0 0.0
+v 1.0 -1.0 0.0
+v 1.0 1.0 0.0
+g left
+f 1 2 3 4
+g right
+usemtl blue
+f 2 5 6 3
+";
+
+/// Quad of the size 10 centered at (100, 50, 2)
+const FAR_QUAD_OBJ: &str = "\
+v 95.0 45.0 2.0
+v 105.0 45.0 2.0
+v 105.0 55.0 2.0
+v 95.0 55.0 2.0
+vt 0.0 0.0 0.0
+vn 0.0 0.0 1.0
+f 1/1/1 2/1/1 3/1/1
+f 1/1/1 3/1/1 4/1/1
+";
+
+fn load_obj(source: &str) -> Model {
+    source.parse().unwrap()
+}
+
+/// Render an image as text, one character per pixel with the top row first
+fn to_text(image: &TGAImage, legend: &[(char, TGAColor)]) -> Vec<String> {
+    (0..image.get_height())
+        .rev()
+        .map(|y| {
+            (0..image.get_width())
+                .map(|x| {
+                    legend
+                        .iter()
+                        .find(|(_, color)| image[(x, y)] == color.as_slice()[..3])
+                        .map_or('?', |(c, _)| *c)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn render_quad_flat() {
+    let model = Model::plane(1);
+    let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+    let mut zbuf = ZBuffer::new(8, 8);
+    let stats = render_model(
+        &model,
+        &mut image,
+        &mut zbuf,
+        &RenderOptions::new(8, 8, 255),
+    );
+
+    assert_eq!((stats.drawn, stats.culled), (2, 0));
+    // Pixels on the shared diagonal fail the depth test for the second face
+    assert_eq!(
+        (stats.submitted, stats.empty, stats.pixels_drawn),
+        (2, 0, 25)
+    );
+    assert_eq!(
+        to_text(&image, &[('.', BLACK), ('#', WHITE)]),
+        vec![
+            "........", "..#####.", "..#####.", "..#####.", "..#####.", "..#####.", "........",
+            "........",
+        ]
+    );
+}
+
+#[test]
+fn render_framed_by_bounding_box() {
+    let quad = Model::plane(1);
+    let bounds = quad.bounding_box().unwrap();
+
+    assert_eq!(bounds.min.as_array(), [-0.5, -0.5, 0.0]);
+    assert_eq!(bounds.max.as_array(), [0.5, 0.5, 0.0]);
+    assert!(Model::default().bounding_box().is_none());
+
+    let model = load_obj(FAR_QUAD_OBJ);
+    let render = |model_transform| {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+
+        render_model(
+            &model,
+            &mut image,
+            &mut zbuf,
+            &RenderOptions {
+                model_transform,
+                ..RenderOptions::new(8, 8, 255)
+            },
+        );
+
+        to_text(&image, &[('.', BLACK), ('#', WHITE)])
+    };
+
+    // The quad is far outside of the viewport until it is framed
+    assert!(render(Matrix4F32::identity())
+        .iter()
+        .all(|row| row == "........"));
+    assert_eq!(
+        render(model.bounding_box().unwrap().fit_transform()),
+        vec!["########"; 8]
+    );
+}
+
+#[test]
+fn render_normalized_to_unit() {
+    let model = load_obj(SHADOW_OBJ);
+    // The same scene scaled by 10 and moved to (100, 50, 2)
+    let far_obj: String = SHADOW_OBJ
+        .lines()
+        .map(|line| match line.strip_prefix("v ") {
+            Some(coords) => {
+                let v: Vec<f32> = coords.split(' ').map(|c| c.parse().unwrap()).collect();
+
+                format!(
+                    "v {} {} {}\n",
+                    v[0] * 10.0 + 100.0,
+                    v[1] * 10.0 + 50.0,
+                    v[2] * 10.0 + 2.0
+                )
+            }
+            None => format!("{}\n", line),
+        })
+        .collect();
+    let mut far_model = load_obj(&far_obj);
+
+    far_model.normalize_to_unit();
+
+    let bounds = far_model.bounding_box().unwrap();
+
+    assert!(bounds.min.as_array().iter().all(|&c| c >= -1.0 - 1e-5));
+    assert!(bounds.max.as_array().iter().all(|&c| c <= 1.0 + 1e-5));
+    assert_eq!(
+        far_model.normal(2, 2).as_array(),
+        model.normal(2, 2).as_array()
+    );
+
+    let render = |model: &Model| {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+
+        render_model(model, &mut image, &mut zbuf, &RenderOptions::new(8, 8, 255));
+
+        to_text(&image, &[('.', BLACK), ('#', WHITE)])
+    };
+
+    assert_eq!(render(&far_model), render(&model));
+    assert_eq!(
+        Model::default().normalization_transform(),
+        Matrix4F32::identity()
+    );
+}
+
+#[test]
+fn quad_uvs_without_texture() {
+    let mut model = load_obj(QUAD_OBJ);
+    let expected = [
+        [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)],
+        [(0.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+    ];
+
+    for (i, face) in expected.iter().enumerate() {
+        for (j, &(u, v)) in face.iter().enumerate() {
+            assert_eq!(model.uv_f32(i, j).as_array(), [u, v]);
+            assert!(model.uv(i, j).is_none());
+        }
+    }
+
+    model
+        .set_texture(TGAImage::try_new(4, 2, TGAImageFormat::RGB).unwrap())
+        .unwrap();
+
+    assert_eq!(model.uv(0, 0).unwrap().as_array(), [0, 0]);
+    assert_eq!(model.uv(0, 2).unwrap().as_array(), [4, 2]);
+    assert_eq!(model.uv(1, 2).unwrap().as_array(), [0, 2]);
+}
+
+#[test]
+fn render_groups() {
+    let model = load_obj(GROUPS_OBJ);
+
+    assert_eq!(model.n_groups(), 2);
+    assert_eq!((model.group_name(0), model.group_faces(0)), ("left", 0..2));
+    assert_eq!((model.group_name(1), model.group_faces(1)), ("right", 2..4));
+    assert_eq!(model.group_material(1), Some("blue"));
+
+    let render = |group, submitted| {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+        let stats = render_model(
+            &model,
+            &mut image,
+            &mut zbuf,
+            &RenderOptions {
+                group,
+                ..RenderOptions::new(8, 8, 255)
+            },
+        );
+
+        assert_eq!(stats.submitted, submitted);
+
+        to_text(&image, &[('.', BLACK), ('#', WHITE)])
+    };
+
+    assert_eq!(render(None, 4), vec!["########"; 8]);
+    // Pixels of the other group stay untouched, the shared edge column belongs to
+    // the left half only
+    assert_eq!(render(Some(0), 2), vec!["#####..."; 8]);
+    assert_eq!(render(Some(1), 2), vec!["....####"; 8]);
+
+    // The depth pass selects the same faces
+    let mut depth = ZBuffer::new(8, 8);
+
+    render_depth(
+        &model,
+        &RenderOptions {
+            group: Some(1),
+            ..RenderOptions::new(8, 8, 255)
+        },
+        &mut depth,
+    );
+    assert_eq!(depth.get(2, 4), f32::NEG_INFINITY);
+    assert!(depth.get(6, 4).is_finite());
+}
+
+#[test]
+fn render_two_materials() {
+    let model = Model::new(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/tests/data/two_materials.obj"
+    ))
+    .unwrap();
+
+    assert_eq!(model.n_materials(), 2);
+    assert_eq!(model.material(0).name, "red");
+    assert_eq!(
+        model.material(0).diffuse_map.as_deref(),
+        Some("missing_red.tga")
+    );
+    assert_eq!(
+        model.material(1).diffuse_color(),
+        TGAColor::new_rgb(0, 255, 0)
+    );
+    assert_eq!(
+        (0..model.n_faces())
+            .map(|i| model.face_material(i).unwrap())
+            .collect::<Vec<_>>(),
+        [0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1]
+    );
+    // Without the library the materials are white
+    assert_eq!(
+        load_obj("usemtl red\nv 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n")
+            .material(0)
+            .diffuse_color(),
+        WHITE
+    );
+
+    // Looking at the +z, +x and +y sides
+    let eye = Vector3F32::new(1.0, 1.5, 2.0);
+    let mut light_dir = eye * -1.0;
+
+    light_dir.normalize_default();
+
+    let mut image = TGAImage::try_new(32, 32, TGAImageFormat::RGB).unwrap();
+    let mut zbuf = ZBuffer::new(32, 32);
+    let projection = Projection::orthographic(-1.0, 1.0, -1.0, 1.0, 0.5, 10.0);
+    let view = lookat(
+        eye,
+        Vector3F32::new(0.0, 0.0, 0.0),
+        Vector3F32::new(0.0, 1.0, 0.0),
+    );
+
+    render_model(
+        &model,
+        &mut image,
+        &mut zbuf,
+        &RenderOptions {
+            light_dir,
+            textured: true,
+            cull_mode: CullMode::Back,
+            ..RenderOptions::with_projection(32, 32, 255, &projection, &view)
+        },
+    );
+
+    let count = |f: fn(&TGAColor) -> bool| image.pixels().filter(|(_, _, p)| f(p)).count();
+    let red = count(|p| p.r() > 0 && p.g() == 0 && p.b() == 0);
+    let green = count(|p| p.g() > 0 && p.r() == 0 && p.b() == 0);
+    let lit = count(|p| p.r() > 0 || p.g() > 0 || p.b() > 0);
+
+    // The red +z and +x sides cover about twice as much as the green top
+    assert!(green > 50, "{}", green);
+    assert!(red > green, "{} {}", red, green);
+    assert_eq!(red + green, lit);
+}
+
+#[test]
+fn render_sphere_gouraud() {
+    let model = Model::uv_sphere(12, 24);
+    let transform = RenderOptions::new(32, 32, 255).transform;
+    let light_dir = Vector3F32::new(0.0, 0.0, -1.0);
+    let mut image = TGAImage::try_new(32, 32, TGAImageFormat::RGB).unwrap();
+    let mut zbuf = ZBuffer::new(32, 32);
+
+    for i in 0..model.n_faces() {
+        let screen = model.face(i).map(|v| {
+            transform
+                .transform_point(*model.vert(v as usize))
+                .to_int_round()
+        });
+        let intensities = [0, 1, 2].map(|j| -model.normal(i, j).dot(&light_dir));
+
+        triangle_gouraud(
+            TriangleDef(screen[0], screen[1], screen[2]),
+            intensities,
+            &mut zbuf,
+            Diffuse::Color(WHITE),
+            &mut image,
+        );
+    }
+
+    let shade = |x: u32, y: u32| image[(x, y)][0] as i32;
+
+    // Brightest in the middle and darker towards the rim
+    assert!(shade(16, 16) > 245, "{}", shade(16, 16));
+    assert!(shade(16, 16) > shade(16, 24) && shade(16, 24) > shade(16, 30));
+    assert!(shade(16, 16) > shade(24, 16) && shade(24, 16) > shade(30, 16));
+    assert_eq!(image[(0, 0)], [0, 0, 0]);
+
+    // Without visible face edges neighbors differ little away from the rim
+    for y in 6..26 {
+        for x in 6..25 {
+            assert!(
+                (shade(x, y) - shade(x + 1, y)).abs() < 24,
+                "{} {}: {} {}",
+                x,
+                y,
+                shade(x, y),
+                shade(x + 1, y)
+            );
+        }
+    }
+}
+
+#[test]
+fn render_quad_projections() {
+    let model = Model::plane(1);
+    let render = |opts: &RenderOptions| {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+
+        render_model(&model, &mut image, &mut zbuf, opts);
+
+        to_text(&image, &[('.', BLACK), ('#', WHITE)])
+    };
+    let ortho = Projection::orthographic(-1.0, 1.0, -1.0, 1.0, -1.0, 1.0);
+
+    assert_eq!(
+        render(&RenderOptions::with_projection(
+            8,
+            8,
+            255,
+            &ortho,
+            &Matrix4F32::identity()
+        )),
+        render(&RenderOptions::new(8, 8, 255))
+    );
+
+    // The camera two units away from the quad sees twice as much with a
+    // 90 degrees field of view, the same as the orthographic box of that size
+    let mut view = Matrix4F32::identity();
+
+    view[(2, 3)] = -2.0;
+
+    let perspective = Projection::perspective(std::f32::consts::FRAC_PI_2, 1.0, 0.5, 10.0);
+    let wide_ortho = Projection::orthographic(-2.0, 2.0, -2.0, 2.0, 0.5, 10.0);
+    let far = render(&RenderOptions::with_projection(
+        8,
+        8,
+        255,
+        &perspective,
+        &view,
+    ));
+
+    assert_eq!(
+        far,
+        render(&RenderOptions::with_projection(
+            8,
+            8,
+            255,
+            &wide_ortho,
+            &view
+        ))
+    );
+    assert_eq!(
+        far,
+        vec![
+            "........", "........", "...###..", "...###..", "...###..", "........", "........",
+            "........",
+        ]
+    );
+}
+
+#[test]
+fn render_turntable_frames() {
+    // Two triangles in perpendicular planes, so no frame looks like another
+    let model = load_obj(
+        "\
+v -0.8 -0.8 0.0
+v 0.6 -0.4 0.0
+v -0.2 0.8 0.0
+v 0.0 -0.8 -0.7
+v 0.0 -0.3 0.6
+v 0.0 0.7 -0.1
+vt 0.0 0.0 0.0
+vn 0.0 0.0 1.0
+f 1/1/1 2/1/1 3/1/1
+f 4/1/1 5/1/1 6/1/1
+",
+    );
+    let pattern = std::env::temp_dir().join("tinyrenderer_turntable_{:02}.tga");
+    let opts = RenderOptions {
+        color: FaceColor::Random,
+        ..RenderOptions::new(16, 16, 255)
+    };
+
+    render_turntable(&model, 4, &opts, pattern.to_str().unwrap()).unwrap();
+
+    let frames: Vec<_> = (0..4)
+        .map(|i| {
+            let path = std::env::temp_dir().join(format!("tinyrenderer_turntable_{:02}.tga", i));
+            let frame = TGAImage::read_tga_file(path.to_str().unwrap()).unwrap();
+
+            fs::remove_file(&path).unwrap();
+
+            frame.as_bytes().to_vec()
+        })
+        .collect();
+
+    for (i, a) in frames.iter().enumerate() {
+        assert!(a.iter().any(|v| *v != 0), "frame {} is empty", i);
+
+        for b in &frames[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+    assert!(render_turntable(&model, 1, &opts, "frame.tga").is_err());
+}
+
+#[test]
+fn render_quad_textured() {
+    let mut model = Model::plane(1);
+    let mut texture = TGAImage::try_new(2, 2, TGAImageFormat::RGB).unwrap();
+    let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+    let mut zbuf = ZBuffer::new(8, 8);
+
+    texture.set(0, 0, &RED);
+    texture.set(1, 0, &BLUE);
+    texture.set(0, 1, &BLUE);
+    texture.set(1, 1, &RED);
+    model.set_texture(texture).unwrap();
+
+    let opts = RenderOptions {
+        textured: true,
+        filter: TextureFilter::Nearest,
+        cull_mode: CullMode::Front,
+        ..RenderOptions::new(8, 8, 255)
+    };
+
+    // The quad faces the viewer, so culling front faces leaves nothing
+    assert_eq!(render_model(&model, &mut image, &mut zbuf, &opts).drawn, 0);
+
+    let opts = RenderOptions {
+        cull_mode: CullMode::Back,
+        ..opts
+    };
+    let stats = render_model(&model, &mut image, &mut zbuf, &opts);
+
+    assert_eq!((stats.drawn, stats.culled), (2, 0));
+    // The right and top edges are sampled at u = 1 and v = 1, which are clamped
+    // to the last texel instead of wrapping around to the first one
+    assert_eq!(
+        to_text(&image, &[('.', BLACK), ('R', RED), ('B', BLUE)]),
+        vec![
+            "........", "..BBRRR.", "..BBRRR.", "..BBRRR.", "..RRBBB.", "..RRBBB.", "........",
+            "........",
+        ]
+    );
+}
+
+#[test]
+fn render_quad_random_colors() {
+    let model = Model::plane(1);
+    let render = || {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+        let opts = RenderOptions {
+            color: FaceColor::Random,
+            ..RenderOptions::new(8, 8, 255)
+        };
+
+        render_model(&model, &mut image, &mut zbuf, &opts);
+
+        image
+    };
+    let image = render();
+
+    assert_eq!(image.as_bytes(), render().as_bytes());
+    assert_ne!(image[(6, 2)], image[(2, 6)]);
+}
+
+#[test]
+fn render_shadow() {
+    let model = load_obj(SHADOW_OBJ);
+    // The light shines at 45 degrees from the negative y side, so the triangle
+    // casts its shadow onto the positive y half of the ground
+    let light_transform = viewport(0, 0, 128, 128, 255)
+        * lookat(
+            Vector3F32::new(0.0, -1.0, 1.0),
+            Vector3F32::new(0.0, 0.0, 0.0),
+            Vector3F32::new(0.0, 0.0, 1.0),
+        );
+    let render = |bias, model_transform| {
+        let mut shadow_zbuf = ZBuffer::new(128, 128);
+        let mut image = TGAImage::try_new(128, 128, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(128, 128);
+        let opts = RenderOptions {
+            model_transform,
+            ..RenderOptions::new(128, 128, 255)
+        };
+
+        render_depth(
+            &model,
+            &RenderOptions {
+                transform: light_transform,
+                ..opts
+            },
+            &mut shadow_zbuf,
+        );
+
+        let opts = RenderOptions {
+            shadow: Some(Shadow {
+                depth: &shadow_zbuf,
+                transform: light_transform,
+                bias,
+                intensity: 0.25,
+            }),
+            ..opts
+        };
+
+        render_model(&model, &mut image, &mut zbuf, &opts);
+
+        // (count in the shadow half, count in the lit half)
+        image.pixels().filter(|(_, _, color)| color.r() < 128).fold(
+            (0, 0),
+            |(back, front), (_, y, _)| {
+                if y >= 64 {
+                    (back + 1, front)
+                } else {
+                    (back, front + 1)
+                }
+            },
+        )
+    };
+    let (shadow, acne) = render(4.0, Matrix4F32::identity());
+    let (_, acne_without_bias) = render(0.0, Matrix4F32::identity());
+    // Halving the triangle height halves its shadow, the depth is rendered from
+    // the same squashed world as the image
+    let (half_shadow, half_acne) = render(
+        4.0,
+        Matrix4F32::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 0.5, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]),
+    );
+
+    // The shadow is the triangle laid onto the ground, 0.6 wide and 0.8 long
+    // with 64 pixels per unit
+    let expected = (0.5 * 0.6 * 0.8 * 64.0 * 64.0) as i32;
+
+    assert!((shadow - expected).abs() < expected / 10, "{}", shadow);
+    assert_eq!(acne, 0);
+    assert!(acne_without_bias > 100, "{}", acne_without_bias);
+    assert!(
+        (half_shadow - expected / 2).abs() < expected / 20,
+        "{}",
+        half_shadow
+    );
+    assert_eq!(half_acne, 0);
+}
+
+#[test]
+fn render_quad_normal_mapped() {
+    let mut model = Model::plane(1);
+    let mut normalmap = TGAImage::try_new(2, 1, TGAImageFormat::RGB).unwrap();
+    let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+    let mut zbuf = ZBuffer::new(8, 8);
+
+    // The left half faces the viewer, the right half is turned away from it
+    normalmap.set(0, 0, &TGAColor::new_rgb(128, 128, 255));
+    normalmap.set(1, 0, &TGAColor::new_rgb(255, 128, 0));
+    model.set_normal_map(normalmap).unwrap();
+    model.compute_tangents();
+
+    // The quad tangent frame matches the model axes, so both spaces agree
+    for &normal_map in &[NormalMapping::Model, NormalMapping::Tangent] {
+        let opts = RenderOptions {
+            normal_map,
+            ..RenderOptions::new(8, 8, 255)
+        };
+
+        image.clear();
+        zbuf.clear();
+        render_model(&model, &mut image, &mut zbuf, &opts);
+
+        assert_eq!(
+            to_text(&image, &[('.', BLACK), ('#', WHITE)]),
+            vec![
+                "........", "..##....", "..##....", "..##....", "..##....", "..##....", "........",
+                "........",
+            ],
+            "{:?}",
+            normal_map
+        );
+    }
+}
+
+#[test]
+fn render_quad_phong() {
+    let mut model = Model::plane(1);
+    let mut texture = TGAImage::try_new(1, 1, TGAImageFormat::RGB).unwrap();
+    let mut specularmap = TGAImage::try_new(2, 1, TGAImageFormat::RGB).unwrap();
+    let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+    let mut zbuf = ZBuffer::new(8, 8);
+
+    // Only the right half is shiny
+    texture.set(0, 0, &TGAColor::new_rgb(100, 100, 100));
+    specularmap.set(1, 0, &TGAColor::new_rgb(1, 1, 1));
+    model.set_texture(texture).unwrap();
+    model.set_specular_map(specularmap).unwrap();
+
+    let light_dir = Vector3F32::new(0.0, 0.0, -1.0);
+    let opts = RenderOptions {
+        textured: true,
+        phong: Some(LightingParams::new(light_dir)),
+        ..RenderOptions::new(8, 8, 255)
+    };
+
+    render_model(&model, &mut image, &mut zbuf, &opts);
+
+    // Ambient 5 plus the diffuse 100 and the specular 60 on the right half
+    assert_eq!(
+        to_text(
+            &image,
+            &[
+                ('.', BLACK),
+                ('d', TGAColor::new_rgb(105, 105, 105)),
+                ('s', TGAColor::new_rgb(165, 165, 165)),
+            ]
+        ),
+        vec![
+            "........", "..ddsss.", "..ddsss.", "..ddsss.", "..ddsss.", "..ddsss.", "........",
+            "........",
+        ]
+    );
+}
+
+#[test]
+fn render_scaled_normals() {
+    let mut model = load_obj(TILTED_QUAD_OBJ);
+    let mut texture = TGAImage::try_new(1, 1, TGAImageFormat::RGB).unwrap();
+
+    texture.set(0, 0, &WHITE);
+    model.set_texture(texture).unwrap();
+
+    let center = |model_transform: Matrix4F32| {
+        let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+        let mut zbuf = ZBuffer::new(8, 8);
+        let opts = RenderOptions {
+            textured: true,
+            phong: Some(LightingParams::new(Vector3F32::new(0.0, 0.0, -1.0))),
+            model_transform,
+            ..RenderOptions::new(8, 8, 255)
+        };
+
+        render_model(&model, &mut image, &mut zbuf, &opts);
+
+        image.get(4, 4).r()
+    };
+    let squash = Matrix4F32::new([
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 0.5, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]);
+
+    // Ambient 5 plus the diffuse light of the normal (-1, 0, 1) / √2 and of the
+    // squashed plane `z = x / 2` normal (-1, 0, 2) / √5
+    assert_eq!(center(Matrix4F32::identity()), 180 + 5);
+    assert_eq!(center(squash), 228 + 5);
+}
+
+#[test]
+fn render_quad_point_light() {
+    let mut model = Model::plane(1);
+    let mut texture = TGAImage::try_new(1, 1, TGAImageFormat::RGB).unwrap();
+    let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+    let mut zbuf = ZBuffer::new(8, 8);
+
+    texture.set(0, 0, &WHITE);
+    model.set_texture(texture).unwrap();
+
+    // Red light right above the quad center
+    let lights = [Light {
+        kind: LightKind::Point {
+            pos: Vector3F32::new(0.0, 0.0, 0.3),
+            attenuation: 4.0,
+        },
+        color: RED,
+        intensity: 1.0,
+    }];
+    let opts = RenderOptions {
+        textured: true,
+        phong: Some(LightingParams::new(Vector3F32::new(0.0, 0.0, -1.0))),
+        lights: &lights,
+        ..RenderOptions::new(8, 8, 255)
+    };
+
+    render_model(&model, &mut image, &mut zbuf, &opts);
+
+    let (center, corner) = (image.get(4, 4), image.get(2, 2));
+
+    assert_eq!((center.g(), center.b()), (5, 5));
+    assert_eq!((corner.g(), corner.b()), (5, 5));
+    assert!(center.r() > corner.r() + 50, "{:?} {:?}", center, corner);
+}
+
+#[test]
+#[should_panic(expected = "tangents")]
+fn render_tangent_space_without_tangents() {
+    let mut model = Model::plane(1);
+    let mut image = TGAImage::try_new(8, 8, TGAImageFormat::RGB).unwrap();
+    let mut zbuf = ZBuffer::new(8, 8);
+
+    model
+        .set_normal_map(TGAImage::try_new(1, 1, TGAImageFormat::RGB).unwrap())
+        .unwrap();
+    render_model(
+        &model,
+        &mut image,
+        &mut zbuf,
+        &RenderOptions {
+            normal_map: NormalMapping::Tangent,
+            ..RenderOptions::new(8, 8, 255)
+        },
+    );
+}